@@ -9,14 +9,22 @@ from .native import receive as receive
 
 InputBase = player.InputBase
 AudioInput = player.AudioInput
+ClipInput = player.ClipInput
+ConcatInput = player.ConcatInput
+DcaInput = player.DcaInput
+LazyInput = player.LazyInput
+MixInput = player.MixInput
 OpusPacketInput = player.OpusPacketInput
 OpusPacketStreamInput = player.OpusPacketStreamInput
 RawPCMInput = player.RawPCMInput
+RelayInput = player.RelayInput
 StreamInput = player.StreamInput
 Track = player.Track
 TrackHandle = player.TrackHandle
+TrackState = player.TrackState
 Queue = player.Queue
 supported_codecs = player.supported_codecs
+encode_opus = player.encode_opus
 
 PySongbirdError = error.PySongbirdError
 PyPlayerError = error.PyPlayerError
@@ -32,10 +40,18 @@ __all__ = (
     "Queue",
     "Track",
     "TrackHandle",
+    "TrackState",
     "AudioInput",
+    "ClipInput",
+    "ConcatInput",
+    "DcaInput",
+    "LazyInput",
+    "MixInput",
     "OpusPacketInput",
     "OpusPacketStreamInput",
     "RawPCMInput",
+    "RelayInput",
     "StreamInput",
     "supported_codecs",
+    "encode_opus",
 )
@@ -210,6 +210,61 @@ class SongbirdImpl:
         bool
             Whether this account is deafened.
         """
+    def set_ducking(
+        self,
+        depth_db: typing.Optional[builtins.float] = 12.0,
+        *,
+        attack_ms: builtins.float = 150.0,
+        release_ms: builtins.float = 600.0,
+        on_speech: builtins.bool = False,
+    ) -> None:
+        r"""
+        Configure ducking of background tracks.
+
+        Parameters
+        ----------
+        depth_db : float | None
+            How far background tracks are lowered, in dB. None disables
+            ducking.
+        attack_ms : float, optional
+            Time taken to lower background tracks.
+        release_ms : float, optional
+            Time taken to restore background tracks.
+        on_speech : bool, optional
+            Also duck while received voice shows users speaking.
+
+        Returns
+        -------
+        None
+
+        Raises
+        ------
+        ValueError
+            If a value is negative or not finite.
+
+        Notes
+        -----
+        Tracks take part through `Track.role`. Background tracks are lowered
+        while any primary track is playing, and with `on_speech` while users
+        are heard. Settings apply to tracks that are already playing.
+
+        Examples
+        --------
+        ```python
+        vc.set_ducking(12.0, on_speech=True)
+        await vc.play(player.Track(music).role("background"))
+        await vc.play(player.Track(announcement).role("primary"))
+        ```
+        """
+    def is_ducking(self) -> builtins.bool:
+        r"""
+        Check if background tracks are currently ducked.
+
+        Returns
+        -------
+        bool
+            Whether ducking is enabled and a primary track or speech is active.
+        """
     def move_to(self, channel: discord.abc.Snowflake) -> typing.Coroutine[typing.Any, typing.Any, None]:
         r"""
         |coro|
@@ -251,6 +306,35 @@ class SongbirdImpl:
         vc.listen(sink)
         ```
         """
+    def voice_events(
+        self, *, max_events: typing.Optional[builtins.int] = None
+    ) -> model.PyAsyncIterator[receive.VoiceEvent]:
+        r"""
+        Return an async iterator over speaking and membership events.
+
+        Speaking starts and stops are derived per SSRC from consecutive voice
+        ticks, so no sink or PCM decoding is needed on the Python side. Each
+        call subscribes independently; iteration ends when the bot leaves the
+        channel.
+
+        Parameters
+        ----------
+        max_events : int | None, optional
+            Maximum number of unread events to keep. The oldest events are
+            dropped once full. If None, unbounded.
+
+        Returns
+        -------
+        PyAsyncIterator[VoiceEvent]
+
+        Examples
+        --------
+        ```python
+        async for event in vc.voice_events():
+            if isinstance(event, receive.VoiceEvent.SpeakingStarted):
+                print(event.user_id, "is talking")
+        ```
+        """
     def play(self, track: player.Track) -> typing.Coroutine[typing.Any, typing.Any, player.TrackHandle]:
         r"""
         |coro|
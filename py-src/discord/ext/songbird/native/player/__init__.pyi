@@ -3,57 +3,868 @@
 
 import asyncio
 import builtins
+import collections.abc
 import datetime
+import os
 import typing
 
 import pyarrow
+from discord.ext.songbird import native
+from discord.ext.songbird.native import model
+from discord.ext.songbird.native import receive
+
+from . import dca
 
 __all__ = [
     "AudioInput",
+    "ClipInput",
+    "Compressor",
+    "ConcatInput",
+    "DcaInput",
+    "Effect",
+    "Equalizer",
+    "FadeIn",
+    "FadeOut",
+    "HighPass",
     "InputBase",
+    "LazyInput",
+    "Limiter",
+    "LowPass",
+    "MixInput",
     "OpusPacketInput",
     "OpusPacketStreamInput",
     "Queue",
     "RawPCMInput",
+    "RelayInput",
+    "SegmentChange",
     "StreamInput",
     "Track",
     "TrackHandle",
+    "TrackState",
+    "dca",
+    "encode_opus",
     "supported_codecs",
 ]
 
 @typing.final
-class AudioInput(InputBase):
+class AudioInput(InputBase):
+    r"""
+    Encoded audio input backed by a pyarrow array.
+
+    Notes
+    -----
+    The payload format is detected by Songbird/Symphonia.
+    """
+    def __new__(cls, array: pyarrow.Array) -> typing.Self:
+        r"""
+        Create an encoded audio input.
+
+        Parameters
+        ----------
+        array : pyarrow.Array
+            Encoded audio payload.
+
+        Returns
+        -------
+        AudioInput
+        """
+
+@typing.final
+class ClipInput(InputBase):
+    r"""
+    Input that plays a time range of another input.
+
+    Notes
+    -----
+    The inner input is decoded to 48 kHz stereo. Playback starts exactly at
+    `start` and the track ends exactly at `end`, so no audio outside the range
+    is heard.
+
+    Examples
+    --------
+    ```python
+    import datetime
+
+    clip = player.ClipInput(
+        player.AudioInput(recording),
+        start=datetime.timedelta(seconds=12),
+        end=datetime.timedelta(seconds=14, milliseconds=500),
+    )
+    await vc.play(player.Track(clip))
+    ```
+    """
+    @property
+    def start(self) -> datetime.timedelta:
+        r"""
+        Start of the clip within the inner input.
+
+        Returns
+        -------
+        datetime.timedelta
+        """
+    @property
+    def end(self) -> typing.Optional[datetime.timedelta]:
+        r"""
+        End of the clip within the inner input.
+
+        Returns
+        -------
+        datetime.timedelta | None
+        """
+    def __new__(
+        cls,
+        input: InputBase,
+        *,
+        start: typing.Optional[datetime.timedelta] = None,
+        end: typing.Optional[datetime.timedelta] = None,
+    ) -> typing.Self:
+        r"""
+        Create a clipped input.
+
+        Parameters
+        ----------
+        input : InputBase
+            The input to clip.
+        start : datetime.timedelta | None, optional
+            Position to start playback from. Defaults to the beginning.
+        end : datetime.timedelta | None, optional
+            Position to end playback at. Defaults to the end of `input`.
+
+        Returns
+        -------
+        ClipInput
+
+        Raises
+        ------
+        ValueError
+            If `end` is not after `start`.
+        """
+
+@typing.final
+class Compressor(Effect):
+    r"""
+    Dynamic range compressor.
+    """
+    @property
+    def threshold_db(self) -> builtins.float:
+        r"""
+        Threshold in dBFS.
+
+        Returns
+        -------
+        float
+        """
+    @threshold_db.setter
+    def threshold_db(self, value: builtins.float) -> None: ...
+    @property
+    def ratio(self) -> builtins.float:
+        r"""
+        Compression ratio.
+
+        Returns
+        -------
+        float
+        """
+    @ratio.setter
+    def ratio(self, value: builtins.float) -> None: ...
+    @property
+    def attack_ms(self) -> builtins.float:
+        r"""
+        Attack time in milliseconds.
+
+        Returns
+        -------
+        float
+        """
+    @attack_ms.setter
+    def attack_ms(self, value: builtins.float) -> None: ...
+    @property
+    def release_ms(self) -> builtins.float:
+        r"""
+        Release time in milliseconds.
+
+        Returns
+        -------
+        float
+        """
+    @release_ms.setter
+    def release_ms(self, value: builtins.float) -> None: ...
+    @property
+    def makeup_db(self) -> builtins.float:
+        r"""
+        Makeup gain in decibels.
+
+        Returns
+        -------
+        float
+        """
+    @makeup_db.setter
+    def makeup_db(self, value: builtins.float) -> None: ...
+    def __new__(
+        cls,
+        *,
+        threshold_db: builtins.float = -18.0,
+        ratio: builtins.float = 4.0,
+        attack_ms: builtins.float = 10.0,
+        release_ms: builtins.float = 100.0,
+        makeup_db: builtins.float = 0.0,
+    ) -> typing.Self:
+        r"""
+        Create a compressor.
+
+        Parameters
+        ----------
+        threshold_db : float, optional
+            Level above which gain reduction starts, in dBFS.
+        ratio : float, optional
+            Input to output ratio above the threshold.
+        attack_ms : float, optional
+            Time constant for reacting to rising levels.
+        release_ms : float, optional
+            Time constant for recovering after levels fall.
+        makeup_db : float, optional
+            Gain applied after compression.
+
+        Returns
+        -------
+        Compressor
+
+        Raises
+        ------
+        ValueError
+            If `ratio` is below 1 or a time constant is negative.
+        """
+
+@typing.final
+class ConcatInput(InputBase):
+    r"""
+    Input that plays several inputs back to back as one track.
+
+    Notes
+    -----
+    Segments are decoded to 48 kHz stereo and joined without gaps. Only the
+    first segment is opened when the track starts; each later segment is
+    opened in the background once the one before it starts playing. If a
+    segment is still opening when playback reaches it, silence plays until it
+    is ready. The combined track is seekable, including across segment
+    boundaries, when every segment is seekable and has a known length.
+
+    Examples
+    --------
+    ```python
+    playlist = player.ConcatInput([intro, song, outro])
+    await vc.enqueue(player.Track(playlist))
+
+    async for change in playlist.segment_changes():
+        print("now playing segment", change.index)
+    ```
+    """
+    @property
+    def duration(self) -> typing.Optional[datetime.timedelta]:
+        r"""
+        Total duration of all segments.
+
+        Returns
+        -------
+        datetime.timedelta | None
+            None until every segment has been opened or has reported its
+            duration, or if any segment length is unknown.
+        """
+    @property
+    def current_segment(self) -> typing.Optional[builtins.int]:
+        r"""
+        Index of the segment currently playing.
+
+        Returns
+        -------
+        int | None
+            None before playback starts.
+        """
+    def __new__(cls, inputs: collections.abc.Sequence[InputBase]) -> typing.Self:
+        r"""
+        Create a concatenated input.
+
+        Parameters
+        ----------
+        inputs : Sequence[InputBase]
+            Segments in playback order.
+
+        Returns
+        -------
+        ConcatInput
+
+        Raises
+        ------
+        ValueError
+            If `inputs` is empty.
+        TypeError
+            If an entry is not an `InputBase`.
+        """
+    def segment_changes(self) -> model.PyAsyncIterator[SegmentChange]:
+        r"""
+        Return an async iterator over segment changes.
+
+        Returns
+        -------
+        PyAsyncIterator[SegmentChange]
+
+        Notes
+        -----
+        Only changes that happen after this call are yielded. Seeking into
+        another segment also produces a change.
+        """
+
+@typing.final
+class DcaInput(InputBase):
+    r"""
+    DCA (Discord Compressed Audio) input.
+
+    Notes
+    -----
+    DCA1 files carry a JSON metadata header followed by length-prefixed Opus
+    frames. Headerless DCA0 payloads are also accepted. Frames are played
+    through the same reader as `OpusPacketInput`, so passthrough applies.
+    """
+    @property
+    def title(self) -> typing.Optional[builtins.str]:
+        r"""
+        Title from the DCA1 metadata header, if present.
+
+        Returns
+        -------
+        str | None
+        """
+    @property
+    def artist(self) -> typing.Optional[builtins.str]:
+        r"""
+        Artist from the DCA1 metadata header, if present.
+
+        Returns
+        -------
+        str | None
+        """
+    @property
+    def album(self) -> typing.Optional[builtins.str]:
+        r"""
+        Album from the DCA1 metadata header, if present.
+
+        Returns
+        -------
+        str | None
+        """
+    @property
+    def genre(self) -> typing.Optional[builtins.str]:
+        r"""
+        Genre from the DCA1 metadata header, if present.
+
+        Returns
+        -------
+        str | None
+        """
+    @property
+    def comments(self) -> typing.Optional[builtins.str]:
+        r"""
+        Comments from the DCA1 metadata header, if present.
+
+        Returns
+        -------
+        str | None
+        """
+    @property
+    def url(self) -> typing.Optional[builtins.str]:
+        r"""
+        Origin URL from the DCA1 metadata header, if present.
+
+        Returns
+        -------
+        str | None
+        """
+    @property
+    def duration(self) -> datetime.timedelta:
+        r"""
+        Total playback duration of the contained frames.
+
+        Returns
+        -------
+        datetime.timedelta
+        """
+    def __new__(cls, source: bytes | str | os.PathLike[str]) -> typing.Self:
+        r"""
+        Create a DCA input.
+
+        Parameters
+        ----------
+        source : bytes | str | os.PathLike[str]
+            DCA payload, or a path to a DCA file.
+
+        Returns
+        -------
+        DcaInput
+        """
+
+class Effect:
+    r"""
+    Base class for audio effects attached with `Track.effects`.
+
+    Notes
+    -----
+    Effect parameters are shared with the running track, so assigning to an
+    effect's properties takes effect on the next 20 ms block. The effects of a
+    playing track are available from `TrackHandle.effects`.
+    """
+
+    ...
+
+@typing.final
+class Equalizer(Effect):
+    r"""
+    Parametric equalizer built from peaking filters.
+
+    Examples
+    --------
+    ```python
+    bass_boost = player.Equalizer([(60.0, 6.0), (120.0, 3.0)])
+    ```
+    """
+    @property
+    def bands(self) -> builtins.list[tuple[builtins.float, builtins.float, builtins.float]]:
+        r"""
+        Current bands as `(frequency_hz, gain_db, q)`.
+
+        Returns
+        -------
+        list[tuple[float, float, float]]
+        """
+    @bands.setter
+    def bands(self, value: collections.abc.Sequence[tuple[float, float] | tuple[float, float, float]]) -> None: ...
+    def __new__(cls, bands: collections.abc.Sequence[tuple[float, float] | tuple[float, float, float]]) -> typing.Self:
+        r"""
+        Create an equalizer.
+
+        Parameters
+        ----------
+        bands : Sequence[tuple[float, float] | tuple[float, float, float]]
+            `(frequency_hz, gain_db)` or `(frequency_hz, gain_db, q)` per band.
+            `q` defaults to 1.0.
+
+        Returns
+        -------
+        Equalizer
+
+        Raises
+        ------
+        ValueError
+            If a frequency or `q` is not positive.
+        """
+    def set_gain(self, index: builtins.int, gain_db: builtins.float) -> None:
+        r"""
+        Set the gain of one band.
+
+        Parameters
+        ----------
+        index : int
+            Band index.
+        gain_db : float
+            New gain in decibels.
+
+        Returns
+        -------
+        None
+
+        Raises
+        ------
+        IndexError
+            If `index` is out of range.
+        """
+
+@typing.final
+class FadeIn(Effect):
+    r"""
+    Fade the track in from silence.
+    """
+    @property
+    def duration_ms(self) -> builtins.float:
+        r"""
+        Length of the fade in milliseconds.
+
+        Returns
+        -------
+        float
+        """
+    @duration_ms.setter
+    def duration_ms(self, value: builtins.float) -> None: ...
+    def __new__(cls, duration_ms: builtins.float) -> typing.Self:
+        r"""
+        Create a fade in.
+
+        Parameters
+        ----------
+        duration_ms : float
+            Length of the fade in milliseconds.
+
+        Returns
+        -------
+        FadeIn
+
+        Raises
+        ------
+        ValueError
+            If `duration_ms` is negative.
+        """
+
+@typing.final
+class FadeOut(Effect):
+    r"""
+    Fade the end of the track out to silence.
+
+    Notes
+    -----
+    The fade needs the track length, so it has no effect on inputs whose
+    length is unknown, such as live streams.
+    """
+    @property
+    def duration_ms(self) -> builtins.float:
+        r"""
+        Length of the fade in milliseconds.
+
+        Returns
+        -------
+        float
+        """
+    @duration_ms.setter
+    def duration_ms(self, value: builtins.float) -> None: ...
+    def __new__(cls, duration_ms: builtins.float) -> typing.Self:
+        r"""
+        Create a fade out.
+
+        Parameters
+        ----------
+        duration_ms : float
+            Length of the fade in milliseconds, ending at the end of the track.
+
+        Returns
+        -------
+        FadeOut
+
+        Raises
+        ------
+        ValueError
+            If `duration_ms` is negative.
+        """
+
+@typing.final
+class HighPass(Effect):
+    r"""
+    Second-order Butterworth high-pass filter.
+    """
+    @property
+    def cutoff_hz(self) -> builtins.float:
+        r"""
+        Cutoff frequency in hertz.
+
+        Returns
+        -------
+        float
+        """
+    @cutoff_hz.setter
+    def cutoff_hz(self, value: builtins.float) -> None: ...
+    def __new__(cls, cutoff_hz: builtins.float) -> typing.Self:
+        r"""
+        Create a high-pass filter.
+
+        Parameters
+        ----------
+        cutoff_hz : float
+            Cutoff frequency in hertz.
+
+        Returns
+        -------
+        HighPass
+
+        Raises
+        ------
+        ValueError
+            If `cutoff_hz` is not positive.
+        """
+
+class InputBase:
+    r"""
+    Base class for player inputs.
+
+    Notes
+    -----
+    Concrete inputs are provided under `discord.ext.songbird.player.input`.
+
+    Python subclasses can provide their own source by implementing
+    `read(n) -> bytes`. Implement `seek(pos) -> int` to make the source
+    seekable and `byte_len() -> int | None` to report its total size. The
+    bytes are probed as an encoded container unless `pcm_format` declares
+    interleaved float32 PCM.
+
+    Examples
+    --------
+    ```python
+    class ToneInput(InputBase):
+        pcm_format = (48000, 2)
+
+        def __init__(self, data: bytes):
+            self.buffer = io.BytesIO(data)
+
+        def read(self, n: int) -> bytes:
+            return self.buffer.read(n)
+
+        def seek(self, pos: int) -> int:
+            return self.buffer.seek(pos)
+    ```
+    """
+    pcm_format: typing.Optional[tuple[builtins.int, builtins.int]]
+    r"""
+    Interleaved float32 PCM layout of a Python source.
+
+    Notes
+    -----
+    Override with `(sample_rate, channels)` when `read` returns raw PCM.
+    Leave as `None` for encoded containers, which are detected from the
+    stream contents.
+    """
+    def __new__(cls, *_args: typing.Any, **_kwargs: typing.Any) -> typing.Self:
+        r"""
+        Create an input base for a Python subclass.
+
+        Notes
+        -----
+        Arguments are accepted so subclasses can define their own `__init__`.
+        """
+
+@typing.final
+class LazyInput(InputBase):
+    r"""
+    Input created by an async factory when the track starts.
+
+    Notes
+    -----
+    The factory is awaited on the client's event loop only when playback of
+    the track begins, so queued tracks do not need to be downloaded up front.
+
+    The factory must return encoded bytes or an input Songbird can read as a
+    byte stream, such as `AudioInput`, `StreamInput` or `RawPCMInput`. Inputs
+    parsed natively up front (`OpusPacketInput`, `OpusPacketStreamInput` and
+    `DcaInput`) are rejected with a `TypeError` when the track starts.
+    """
+    @property
+    def title(self) -> typing.Optional[builtins.str]:
+        r"""
+        Title provided up front.
+
+        Returns
+        -------
+        str | None
+        """
+    @property
+    def artist(self) -> typing.Optional[builtins.str]:
+        r"""
+        Artist provided up front.
+
+        Returns
+        -------
+        str | None
+        """
+    @property
+    def album(self) -> typing.Optional[builtins.str]:
+        r"""
+        Album provided up front.
+
+        Returns
+        -------
+        str | None
+        """
+    @property
+    def duration(self) -> typing.Optional[datetime.timedelta]:
+        r"""
+        Duration provided up front.
+
+        Returns
+        -------
+        datetime.timedelta | None
+        """
+    @property
+    def source_url(self) -> typing.Optional[builtins.str]:
+        r"""
+        Origin URL provided up front.
+
+        Returns
+        -------
+        str | None
+        """
+    @property
+    def thumbnail(self) -> typing.Optional[builtins.str]:
+        r"""
+        Thumbnail URL provided up front.
+
+        Returns
+        -------
+        str | None
+        """
+    def __new__(
+        cls,
+        factory: collections.abc.Callable[[], collections.abc.Awaitable[InputBase | bytes]],
+        *,
+        title: typing.Optional[builtins.str] = None,
+        artist: typing.Optional[builtins.str] = None,
+        album: typing.Optional[builtins.str] = None,
+        duration: typing.Optional[datetime.timedelta] = None,
+        source_url: typing.Optional[builtins.str] = None,
+        thumbnail: typing.Optional[builtins.str] = None,
+    ) -> typing.Self:
+        r"""
+        Create a lazy input.
+
+        Parameters
+        ----------
+        factory : Callable[[], Awaitable[InputBase | bytes]]
+            Async callable returning another input or encoded audio bytes.
+            Natively parsed inputs such as `OpusPacketInput` and `DcaInput`
+            are not supported.
+        title : str | None, optional
+            Title known before the factory runs.
+        artist : str | None, optional
+            Artist known before the factory runs.
+        album : str | None, optional
+            Album known before the factory runs.
+        duration : datetime.timedelta | None, optional
+            Duration known before the factory runs.
+        source_url : str | None, optional
+            Origin URL of the audio.
+        thumbnail : str | None, optional
+            Thumbnail URL of the audio.
+
+        Returns
+        -------
+        LazyInput
+
+        Raises
+        ------
+        TypeError
+            If `factory` is not callable.
+        """
+
+@typing.final
+class Limiter(Effect):
     r"""
-    Encoded audio input backed by a pyarrow array.
+    Peak limiter that keeps the signal below a ceiling.
+    """
+    @property
+    def ceiling_db(self) -> builtins.float:
+        r"""
+        Ceiling in dBFS.
 
-    Notes
-    -----
-    The payload format is detected by Songbird/Symphonia.
+        Returns
+        -------
+        float
+        """
+    @ceiling_db.setter
+    def ceiling_db(self, value: builtins.float) -> None: ...
+    @property
+    def release_ms(self) -> builtins.float:
+        r"""
+        Release time in milliseconds.
+
+        Returns
+        -------
+        float
+        """
+    @release_ms.setter
+    def release_ms(self, value: builtins.float) -> None: ...
+    def __new__(cls, *, ceiling_db: builtins.float = -1.0, release_ms: builtins.float = 50.0) -> typing.Self:
+        r"""
+        Create a limiter.
+
+        Parameters
+        ----------
+        ceiling_db : float, optional
+            Maximum output peak in dBFS.
+        release_ms : float, optional
+            Time constant for recovering after a peak.
+
+        Returns
+        -------
+        Limiter
+
+        Raises
+        ------
+        ValueError
+            If `release_ms` is negative.
+        """
+
+@typing.final
+class LowPass(Effect):
+    r"""
+    Second-order Butterworth low-pass filter.
     """
-    def __new__(cls, array: pyarrow.Array) -> typing.Self:
+    @property
+    def cutoff_hz(self) -> builtins.float:
         r"""
-        Create an encoded audio input.
+        Cutoff frequency in hertz.
+
+        Returns
+        -------
+        float
+        """
+    @cutoff_hz.setter
+    def cutoff_hz(self, value: builtins.float) -> None: ...
+    def __new__(cls, cutoff_hz: builtins.float) -> typing.Self:
+        r"""
+        Create a low-pass filter.
 
         Parameters
         ----------
-        array : pyarrow.Array
-            Encoded audio payload.
+        cutoff_hz : float
+            Cutoff frequency in hertz.
 
         Returns
         -------
-        AudioInput
+        LowPass
+
+        Raises
+        ------
+        ValueError
+            If `cutoff_hz` is not positive.
         """
 
-class InputBase:
+@typing.final
+class MixInput(InputBase):
     r"""
-    Base class for player inputs.
+    Input that overlays several inputs into one track.
 
     Notes
     -----
-    Concrete inputs are provided under `discord.ext.songbird.player.input`.
+    Children are decoded to 48 kHz stereo, scaled by their gain, and summed
+    with a soft clipper to avoid harsh distortion. The mix ends when the last
+    child ends.
     """
+    def __new__(
+        cls,
+        inputs: collections.abc.Sequence[
+            InputBase | tuple[InputBase, float] | tuple[InputBase, float, datetime.timedelta]
+        ],
+    ) -> typing.Self:
+        r"""
+        Create a mixed input.
 
-    ...
+        Parameters
+        ----------
+        inputs : list[InputBase | tuple[InputBase, float] | tuple[InputBase, float, datetime.timedelta]]
+            Child inputs, optionally with a gain multiplier and a start offset.
+
+        Returns
+        -------
+        MixInput
+
+        Raises
+        ------
+        ValueError
+            If `inputs` is empty or a gain is negative.
+        """
 
 @typing.final
 class OpusPacketInput(InputBase):
@@ -255,6 +1066,103 @@ class RawPCMInput(InputBase):
         RawPCMInput
         """
 
+@typing.final
+class RelayInput(InputBase):
+    r"""
+    Live input that plays audio received on a voice connection.
+
+    Ticks from the source are mixed natively and played with bounded latency,
+    so one channel can be relayed into another, or a user echoed back for a
+    microphone test, without Python in the audio path.
+
+    Notes
+    -----
+    The input never ends on its own; stop its track to end the relay. When
+    the source falls behind, silence is played; when playback falls behind,
+    the oldest audio beyond `max_latency_ms` is dropped.
+
+    Examples
+    --------
+    ```python
+    source = await lobby.connect(cls=songbird.SongbirdClient)
+    target = await stage.connect(cls=songbird.SongbirdClient)
+    await target.play(songbird.Track(player.RelayInput(source)))
+    ```
+    """
+    def __new__(
+        cls,
+        source: native.SongbirdImpl | receive.StreamSink,
+        *,
+        keys: typing.Optional[typing.Sequence[receive.VoiceKey]] = None,
+        mixdown: builtins.bool = True,
+        max_latency_ms: builtins.int = 100,
+    ) -> typing.Self:
+        r"""
+        Create a relay input.
+
+        Parameters
+        ----------
+        source : SongbirdClient | receive.StreamSink
+            A connected voice client to receive from, or a `StreamSink` that
+            is already listening. A `StreamSink` must use the default 48 kHz
+            stereo format, and the relay holds one of its stream permits.
+        keys : list[receive.VoiceKey] | None, optional
+            Sources to relay. If None, every speaker is relayed.
+        mixdown : bool, optional
+            If True, the selected speakers are summed. If False, only the one
+            most likely to be speaking in each tick is played, which needs
+            voice activity scores: a `StreamSink` source must be created with
+            `vad=True`.
+        max_latency_ms : int, optional
+            Most audio, in milliseconds, buffered before the oldest is
+            dropped. Must be at least 20.
+
+        Returns
+        -------
+        RelayInput
+
+        Raises
+        ------
+        TypeError
+            If `source` is neither a voice client nor a `StreamSink`.
+        ValueError
+            If the client is not connected, the sink's format is not 48 kHz
+            stereo, `mixdown` is False for a sink without `vad=True`, or
+            `max_latency_ms` is below 20.
+        """
+
+@typing.final
+class SegmentChange:
+    r"""
+    Event emitted when a `ConcatInput` starts playing another segment.
+    """
+    @property
+    def index(self) -> builtins.int:
+        r"""
+        Index of the segment that started playing.
+
+        Returns
+        -------
+        int
+        """
+    @property
+    def start(self) -> datetime.timedelta:
+        r"""
+        Position of the segment start within the combined track.
+
+        Returns
+        -------
+        datetime.timedelta
+        """
+    def __repr__(self) -> builtins.str:
+        r"""
+        Return a debug representation.
+
+        Returns
+        -------
+        str
+        """
+
 @typing.final
 class StreamInput(InputBase):
     r"""
@@ -348,6 +1256,109 @@ class Track:
         Track
             This track.
         """
+    def effects(self, effects: collections.abc.Sequence[Effect]) -> Track:
+        r"""
+        Set the effects applied to this track.
+
+        Parameters
+        ----------
+        effects : Sequence[Effect]
+            Effects applied in order to the decoded 48 kHz stereo PCM.
+
+        Returns
+        -------
+        Track
+            This track.
+
+        Notes
+        -----
+        A track with effects is always decoded, so Opus passthrough does not
+        apply. Pass an empty list to remove effects.
+        """
+    def normalize(
+        self, mode: typing.Literal["r128", "replaygain"] | None, *, target_lufs: builtins.float = -18.0
+    ) -> Track:
+        r"""
+        Normalize the track loudness.
+
+        Parameters
+        ----------
+        mode : {"r128", "replaygain"} | None
+            `"r128"` measures EBU R128 integrated loudness. `"replaygain"` uses
+            ReplayGain track gain tags and falls back to measuring. None
+            disables normalization.
+        target_lufs : float, optional
+            Loudness to normalize to, in LUFS.
+
+        Returns
+        -------
+        Track
+            This track.
+
+        Raises
+        ------
+        ValueError
+            If `mode` is unknown or `target_lufs` is above 0.
+
+        Notes
+        -----
+        `AudioInput` and `RawPCMInput` are measured in full before playback
+        starts. Other inputs, such as streams, adjust their gain gradually
+        while playing.
+        """
+    def speed(self, speed: builtins.float = 1.0, *, preserve_pitch: builtins.bool = True) -> Track:
+        r"""
+        Enable speed control for this track.
+
+        Parameters
+        ----------
+        speed : float
+            Playback rate between 0.25 and 4.0; 1.0 is normal speed.
+        preserve_pitch : bool, optional
+            Time-stretch the audio so the pitch stays the same. When False the
+            audio is resampled, so pitch rises and falls with the speed.
+
+        Returns
+        -------
+        Track
+            This track.
+
+        Raises
+        ------
+        ValueError
+            If `speed` is out of range.
+
+        Notes
+        -----
+        Only tracks created with this option accept `TrackHandle.set_speed`.
+        Like effects, speed control decodes the track, so Opus passthrough
+        does not apply.
+        """
+    def role(self, role: typing.Literal["primary", "background"] | None) -> Track:
+        r"""
+        Tag this track for call-level ducking.
+
+        Parameters
+        ----------
+        role : {"primary", "background"} | None
+            Primary tracks, such as announcements, lower background tracks
+            while they play. None leaves the track unaffected.
+
+        Returns
+        -------
+        Track
+            This track.
+
+        Raises
+        ------
+        ValueError
+            If `role` is unknown.
+
+        Notes
+        -----
+        Ducking is configured per call with `SongbirdClient.set_ducking`.
+        Background tracks are decoded, so Opus passthrough does not apply.
+        """
 
 @typing.final
 class TrackHandle:
@@ -358,6 +1369,27 @@ class TrackHandle:
     -----
     Returned by `SongbirdImpl.play`.
     """
+    @property
+    def effects(self) -> builtins.list[Effect]:
+        r"""
+        Effects attached to this track.
+
+        Returns
+        -------
+        list[Effect]
+            The same objects passed to `Track.effects`; changing their
+            properties adjusts the playing track.
+        """
+    @property
+    def speed(self) -> builtins.float:
+        r"""
+        Current playback speed.
+
+        Returns
+        -------
+        float
+            1.0 for tracks created without `Track.speed`.
+        """
     def seek(self, position: datetime.timedelta) -> typing.Coroutine[typing.Any, typing.Any, datetime.timedelta]: ...
     def play(self) -> None:
         r"""
@@ -385,8 +1417,173 @@ class TrackHandle:
         """
     def enable_loop(self) -> None: ...
     def disable_loop(self) -> None: ...
+    def set_speed(self, speed: builtins.float, *, preserve_pitch: typing.Optional[builtins.bool] = None) -> None:
+        r"""
+        Change the playback speed.
+
+        Parameters
+        ----------
+        speed : float
+            Playback rate between 0.25 and 4.0; 1.0 is normal speed.
+        preserve_pitch : bool | None, optional
+            Time-stretch (True) or resample (False). None keeps the current
+            mode.
+
+        Returns
+        -------
+        None
+
+        Raises
+        ------
+        ValueError
+            If `speed` is out of range.
+        PyControlError
+            If the track was not created with `Track.speed`.
+
+        Examples
+        --------
+        ```python
+        handle = await vc.play(player.Track(source).speed())
+        handle.set_speed(1.25)
+        ```
+        """
+    def get_info(self) -> typing.Coroutine[typing.Any, typing.Any, TrackState]:
+        r"""
+        Fetch the current playback state.
+
+        Returns
+        -------
+        Future[TrackState]
+
+        Raises
+        ------
+        PyControlError
+            If the track has ended.
+
+        Notes
+        -----
+        `position` is measured in the source, so it advances faster or slower
+        than `play_time` when the speed is changed.
+        """
     def loop_for(self, times: builtins.int) -> None: ...
 
+@typing.final
+class TrackState:
+    r"""
+    Snapshot of a track's playback state.
+
+    Notes
+    -----
+    Returned by `TrackHandle.get_info`.
+    """
+    @property
+    def playing(self) -> typing.Literal["play", "pause", "stop", "end", "errored"]:
+        r"""
+        Play mode of the track.
+
+        Returns
+        -------
+        {"play", "pause", "stop", "end", "errored"}
+        """
+    @property
+    def volume(self) -> builtins.float:
+        r"""
+        Volume multiplier of the track.
+
+        Returns
+        -------
+        float
+        """
+    @property
+    def position(self) -> datetime.timedelta:
+        r"""
+        Playback position in the source, accounting for the speed factor.
+
+        Returns
+        -------
+        datetime.timedelta
+        """
+    @property
+    def play_time(self) -> datetime.timedelta:
+        r"""
+        Total time the track has been playing, unaffected by seeks or speed.
+
+        Returns
+        -------
+        datetime.timedelta
+        """
+    @property
+    def loops(self) -> typing.Optional[builtins.int]:
+        r"""
+        Remaining loops, or None when looping forever.
+
+        Returns
+        -------
+        int | None
+        """
+    @property
+    def speed(self) -> builtins.float:
+        r"""
+        Playback speed.
+
+        Returns
+        -------
+        float
+        """
+    def __repr__(self) -> builtins.str:
+        r"""
+        Return a debug representation.
+
+        Returns
+        -------
+        str
+        """
+
+def encode_opus(
+    pcm: pyarrow.FloatArray | pyarrow.Int16Array,
+    sample_rate: builtins.int,
+    channels: builtins.int,
+    *,
+    bitrate: builtins.int = 128000,
+    application: builtins.str = "audio",
+    fec: builtins.bool = False,
+) -> pyarrow.BinaryArray:
+    r"""
+    Encode interleaved PCM into 20 ms Opus frames.
+
+    Parameters
+    ----------
+    pcm : pyarrow.FloatArray | pyarrow.Int16Array
+        Interleaved PCM samples. Float samples are expected in `[-1.0, 1.0]`.
+    sample_rate : int
+        Sample rate of `pcm` in Hz. Audio is resampled to 48 kHz when needed.
+    channels : int
+        Channel count of `pcm`, either 1 or 2.
+    bitrate : int, optional
+        Target bitrate in bits per second.
+    application : {"audio", "voip"}, optional
+        Encoder tuning. Use `"voip"` for speech.
+    fec : bool, optional
+        Enable in-band forward error correction, tuned for 10% packet loss.
+
+    Returns
+    -------
+    pyarrow.BinaryArray
+        One Opus frame per row, ready for `OpusPacketInput` or `dca.encode`.
+
+    Notes
+    -----
+    The final frame is padded with silence up to 20 ms. Encoding releases the
+    GIL.
+
+    Examples
+    --------
+    ```python
+    frames = player.encode_opus(pcm, 44100, 2)
+    track = Track(OpusPacketInput(frames))
+    ```
+    """
+
 def supported_codecs() -> builtins.list[builtins.str]:
     r"""
     Return codec and format identifiers enabled in this native build.
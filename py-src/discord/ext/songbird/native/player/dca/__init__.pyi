@@ -0,0 +1,61 @@
+# This file is automatically generated by pyo3_stub_gen
+# ruff: noqa: E501, F401, F403, F405
+r"""
+DCA (Discord Compressed Audio) helpers.
+
+Use `encode` to write pre-encoded Opus frames as a DCA1 file and
+`player.DcaInput` to play DCA files back.
+"""
+
+import builtins
+import typing
+
+import pyarrow
+from discord.ext.songbird.native import player
+
+__all__ = [
+    "encode",
+]
+
+def encode(
+    frames: player.OpusPacketInput | pyarrow.BinaryArray | pyarrow.LargeBinaryArray,
+    *,
+    title: typing.Optional[builtins.str] = None,
+    artist: typing.Optional[builtins.str] = None,
+    album: typing.Optional[builtins.str] = None,
+    genre: typing.Optional[builtins.str] = None,
+    comments: typing.Optional[builtins.str] = None,
+    url: typing.Optional[builtins.str] = None,
+) -> bytes:
+    r"""
+    Encode 20 ms Opus frames as a DCA1 file.
+
+    Parameters
+    ----------
+    frames : OpusPacketInput | pyarrow.BinaryArray | pyarrow.LargeBinaryArray
+        Opus frames to write, one 20 ms frame per row.
+    title : str | None, optional
+        Title stored in the metadata header.
+    artist : str | None, optional
+        Artist stored in the metadata header.
+    album : str | None, optional
+        Album stored in the metadata header.
+    genre : str | None, optional
+        Genre stored in the metadata header.
+    comments : str | None, optional
+        Comments stored in the metadata header.
+    url : str | None, optional
+        Origin URL stored in the metadata header.
+
+    Returns
+    -------
+    bytes
+        The DCA1 payload, readable by `DcaInput`.
+
+    Examples
+    --------
+    ```python
+    data = player.dca.encode(player.OpusPacketInput(frames), title="Airhorn")
+    pathlib.Path("airhorn.dca").write_bytes(data)
+    ```
+    """
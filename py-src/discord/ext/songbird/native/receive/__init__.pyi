@@ -12,8 +12,24 @@ BufferSink
     Buffering sink that yields Arrow `RecordBatch` snapshots.
 StreamSink
     Streaming sink that yields Arrow `RecordBatch` snapshots.
+MixSink
+    Sink that mixes all speakers into a single PCM stream.
+FileSink
+    Sink that records WAV, FLAC, or Ogg Opus files.
+IpcSink
+    Sink that streams ticks to an Arrow IPC file.
+OpusSink
+    Sink that yields undecoded Opus packets.
+RtcpSink
+    Sink that yields received RTCP packets.
+CallbackSink
+    Sink that hands received voice to async Python callbacks.
 Stream
     Async stream handle returned by `StreamSink.stream()`.
+AudioChunk
+    Contiguous per-source PCM yielded by `user_stream()`.
+VoiceEvent
+    Speaking and membership event yielded by `voice_events()`.
 VoiceTick
     Compatibility wrapper for per-tick helpers.
 VoiceKey
@@ -38,31 +54,757 @@ async for batch in sink:
 Notes
 -----
 Receive iterators return `pyarrow.RecordBatch` values with `key_kind`,
-`key_id`, `speaking`, and `pcm` columns. Per-key convenience iterators still
-return `pyarrow.Int16Array | None`.
+`key_id`, `speaking`, `pcm`, `vad`, `tick`, `timestamp`, `rtp_sequence`,
+`rtp_timestamp`, `concealed`, and `lost_packets` columns. Per-key
+convenience iterators still return `pyarrow.Int16Array | None`.
 """
 
 import builtins
+import datetime
+import os
 import typing
 
 import pyarrow
 from discord.ext.songbird.native import model
 
 __all__ = [
+    "AudioChunk",
     "BufferSink",
+    "CallbackSink",
+    "FileSink",
+    "IpcSink",
+    "MixSink",
+    "OpusSink",
+    "RtcpSink",
     "SinkBase",
     "Stream",
     "StreamSink",
+    "VoiceEvent",
     "VoiceKey",
     "VoiceTick",
 ]
 
+@typing.final
+class AudioChunk:
+    r"""
+    Contiguous PCM for one voice source, yielded by `user_stream()`.
+
+    Each chunk covers `tick_count` consecutive 20 ms ticks starting at `tick`.
+    Ticks where the source sent no audio are zero-filled when the stream was
+    created with `fill_silence=True`.
+    """
+    @property
+    def key(self) -> VoiceKey:
+        r"""
+        Voice source this chunk belongs to.
+
+        Returns
+        -------
+        VoiceKey
+        """
+    @property
+    def pcm(self) -> pyarrow.Int16Array | pyarrow.FloatArray:
+        r"""
+        Interleaved PCM in the sink's format.
+
+        Returns
+        -------
+        pyarrow.Int16Array | pyarrow.FloatArray
+        """
+    @property
+    def tick(self) -> builtins.int:
+        r"""
+        Index of the first tick in this chunk, counted from sink creation.
+
+        Returns
+        -------
+        int
+        """
+    @property
+    def tick_count(self) -> builtins.int:
+        r"""
+        Number of ticks in this chunk.
+
+        Returns
+        -------
+        int
+        """
+    @property
+    def timestamp(self) -> datetime.datetime:
+        r"""
+        Wall-clock time the first tick was received.
+
+        Returns
+        -------
+        datetime.datetime
+        """
+    @property
+    def duration(self) -> datetime.timedelta:
+        r"""
+        Duration of audio in this chunk.
+
+        Returns
+        -------
+        datetime.timedelta
+        """
+    @property
+    def filled_ticks(self) -> builtins.int:
+        r"""
+        Number of ticks that were filled with silence.
+
+        Returns
+        -------
+        int
+        """
+    @property
+    def sequence_gaps(self) -> builtins.int:
+        r"""
+        Number of RTP packets missing from the sequence within this chunk.
+
+        Returns
+        -------
+        int
+        """
+    @property
+    def timestamp_gap(self) -> builtins.int:
+        r"""
+        RTP clock units (48 kHz samples) by which the sender's timestamps
+        diverged from the ticks they were played out on.
+
+        Returns
+        -------
+        int
+
+        Notes
+        -----
+        Positive values mean the sender skipped time, for example after a
+        transmission pause; negative values mean audio was played out later
+        than it was captured.
+        """
+    @property
+    def rtp_sequence(self) -> typing.Optional[builtins.int]:
+        r"""
+        RTP sequence number of the first packet in this chunk.
+
+        Returns
+        -------
+        int | None
+        """
+    @property
+    def rtp_timestamp(self) -> typing.Optional[builtins.int]:
+        r"""
+        RTP timestamp of the first packet in this chunk.
+
+        Returns
+        -------
+        int | None
+        """
+    def __repr__(self) -> builtins.str:
+        r"""
+        Return a debug representation.
+
+        Returns
+        -------
+        str
+        """
+
 @typing.final
 class BufferSink(SinkBase):
     r"""
-    Buffering sink for received voice data.
+    Buffering sink for received voice data.
+
+    Collects columnar Arrow record batches and exposes them via async iteration.
+
+    Examples
+    --------
+    ```python
+    from discord.ext import songbird
+    from discord.ext.songbird import receive
+
+    vc = await channel.connect(cls=songbird.SongbirdClient)
+    sink = receive.BufferSink(max_duration_secs=5)
+    vc.listen(sink)
+
+    async for batch in sink:
+        handle_batch(batch)
+    ```
+    """
+    @property
+    def duration(self) -> datetime.timedelta:
+        r"""
+        Duration of audio currently buffered, at 20 ms per tick.
+
+        Returns
+        -------
+        datetime.timedelta
+        """
+    def __new__(
+        cls,
+        *,
+        max_duration_secs: typing.Optional[builtins.int] = None,
+        drop_oldest: builtins.bool = True,
+        sample_rate: builtins.int = 48000,
+        channels: builtins.int = 2,
+        dtype: typing.Literal["int16", "float32"] = "int16",
+        keys: typing.Optional[typing.Sequence[VoiceKey]] = None,
+        exclude: typing.Optional[typing.Sequence[VoiceKey]] = None,
+        skip_silent: builtins.bool = False,
+        vad: builtins.bool = False,
+    ) -> typing.Self:
+        r"""
+        Create a new BufferSink.
+
+        Parameters
+        ----------
+        max_duration_secs : int | None
+            Maximum buffer size in seconds worth of ticks. If None, unbounded.
+        drop_oldest : bool, optional
+            If True, drop the oldest ticks when the buffer is full. If False,
+            drop new ticks instead.
+        sample_rate : int, optional
+            Sample rate of the `pcm` column, a multiple of 50 between 8000 and
+            192000. Rates other than 48000 are resampled per speaker.
+        channels : int, optional
+            1 downmixes to mono, 2 keeps stereo.
+        dtype : {"int16", "float32"}, optional
+            Sample type of the `pcm` column. `float32` samples are in [-1, 1].
+        keys : list[VoiceKey] | None, optional
+            Sources to record. If None, every source is recorded. Unmapped
+            `VoiceKey.Unknown` sources are kept until their user is known.
+        exclude : list[VoiceKey] | None, optional
+            Sources never to record, even if listed in `keys`.
+        skip_silent : bool, optional
+            If True, ticks in which no recorded source is speaking are dropped.
+        vad : bool, optional
+            If True, score voice activity into the `vad` column, as required
+            by `utterances()`. Otherwise the column is zero.
+
+        Notes
+        -----
+        Internally converted to a tick count based on 20 ms per tick (50 ticks/sec).
+        Parameters are keyword-only.
+        `max_duration_secs=0` is invalid.
+
+        Returns
+        -------
+        BufferSink
+        """
+    def stop(self) -> None:
+        r"""
+        Stop buffering new ticks.
+
+        Notes
+        -----
+        This does not unregister the sink.
+
+        Returns
+        -------
+        None
+        """
+    def set_filter(
+        self,
+        *,
+        keys: typing.Optional[typing.Sequence[VoiceKey]] = None,
+        exclude: typing.Optional[typing.Sequence[VoiceKey]] = None,
+        skip_silent: builtins.bool = False,
+    ) -> None:
+        r"""
+        Replace the source filter for ticks received from now on.
+
+        Parameters
+        ----------
+        keys : list[VoiceKey] | None, optional
+            Sources to record. If None, every source is recorded. Unmapped
+            `VoiceKey.Unknown` sources are kept until their user is known.
+        exclude : list[VoiceKey] | None, optional
+            Sources never to record, even if listed in `keys`.
+        skip_silent : bool, optional
+            If True, ticks in which no recorded source is speaking are dropped.
+
+        Returns
+        -------
+        None
+
+        Examples
+        --------
+        ```python
+        sink.set_filter(keys=[receive.VoiceKey.User(host_id)], skip_silent=True)
+        ```
+        """
+    def write_ipc(
+        self, target: str | os.PathLike[str] | typing.BinaryIO, *, format: typing.Literal["file", "stream"] = "file"
+    ) -> builtins.int:
+        r"""
+        Write the buffered ticks to an Arrow IPC file or stream.
+
+        The buffer is left unchanged. Ticks are coalesced into record batches
+        of one second each.
+
+        Parameters
+        ----------
+        target : str | os.PathLike[str] | typing.BinaryIO
+            Path to create, or a writable binary file object.
+        format : {"file", "stream"}, optional
+            IPC file format (Feather v2) or IPC streaming format.
+
+        Returns
+        -------
+        int
+            Number of ticks written.
+
+        Examples
+        --------
+        ```python
+        sink.write_ipc("session.arrow")
+        df = polars.read_ipc("session.arrow")
+        ```
+        """
+    def write_parquet(self, target: str | os.PathLike[str] | typing.BinaryIO) -> builtins.int:
+        r"""
+        Write the buffered ticks to a Parquet file.
+
+        The buffer is left unchanged. Encoding uses `pyarrow.parquet`.
+
+        Parameters
+        ----------
+        target : str | os.PathLike[str] | typing.BinaryIO
+            Path to create, or a writable binary file object.
+
+        Returns
+        -------
+        int
+            Number of ticks written.
+
+        Examples
+        --------
+        ```python
+        sink.write_parquet("session.parquet")
+        duckdb.sql("SELECT key_id, count(*) FROM 'session.parquet' GROUP BY 1")
+        ```
+        """
+    def drain(self) -> typing.Coroutine[typing.Any, typing.Any, pyarrow.RecordBatch]:
+        r"""
+        |coro|
+
+        Remove every buffered tick and return them as one record batch.
+
+        Returns
+        -------
+        pyarrow.RecordBatch
+            All buffered ticks in order, with the same columns as iteration.
+            Empty if nothing was buffered.
+
+        Examples
+        --------
+        ```python
+        batch = await sink.drain()
+        ```
+        """
+    def snapshot(self, *, last_secs: typing.Optional[builtins.int] = None) -> pyarrow.RecordBatch:
+        r"""
+        Return buffered ticks as one record batch, leaving the buffer intact.
+
+        Parameters
+        ----------
+        last_secs : int | None, optional
+            Only include ticks from the last `last_secs` seconds before the
+            newest tick. If None, every buffered tick is included.
+
+        Returns
+        -------
+        pyarrow.RecordBatch
+
+        Examples
+        --------
+        ```python
+        clip = sink.snapshot(last_secs=30)
+        ```
+        """
+    def window(self, start_tick: builtins.int, end_tick: builtins.int) -> pyarrow.RecordBatch:
+        r"""
+        Return buffered ticks within a tick range, leaving the buffer intact.
+
+        Parameters
+        ----------
+        start_tick : int
+            First tick index to include.
+        end_tick : int
+            Tick index to stop before.
+
+        Returns
+        -------
+        pyarrow.RecordBatch
+
+        Examples
+        --------
+        ```python
+        batch = sink.window(500, 1000)  # ticks 500..999, ten seconds
+        ```
+        """
+    def clear(self) -> None:
+        r"""
+        Remove every buffered tick.
+
+        Returns
+        -------
+        None
+        """
+    def __len__(self) -> builtins.int:
+        r"""
+        Return the number of buffered ticks.
+
+        Returns
+        -------
+        int
+        """
+    def __getitem__(
+        self, key: VoiceKey
+    ) -> model.PyAsyncIterator[typing.Optional[pyarrow.Int16Array | pyarrow.FloatArray]]:
+        r"""
+        Return an async iterator over PCM for a specific key.
+
+        Parameters
+        ----------
+        key : VoiceKey
+            The user/ssrc key to filter.
+
+        Returns
+        -------
+        PyAsyncIterator[pyarrow.Int16Array | pyarrow.FloatArray | None]
+
+        Examples
+        --------
+        ```python
+        key = receive.VoiceKey.User(user_id)
+        async for pcm in sink[key]:
+            if pcm is not None:
+                handle_pcm(pcm)
+        ```
+        """
+    def user_stream(
+        self, key: VoiceKey, *, fill_silence: builtins.bool = True, chunk_ms: builtins.int = 20
+    ) -> model.PyAsyncIterator[AudioChunk]:
+        r"""
+        Return an async iterator over contiguous PCM for a specific key.
+
+        Parameters
+        ----------
+        key : VoiceKey
+            The user/ssrc key to follow.
+        fill_silence : bool, optional
+            If True, ticks where the key sent no audio, and ticks the sink
+            dropped, are zero-filled once the key has first spoken, giving a
+            gapless timeline. If False, chunks only contain received audio and
+            end early at each silence.
+        chunk_ms : int, optional
+            Length of each chunk in milliseconds. Must be a positive multiple
+            of 20.
+
+        Returns
+        -------
+        PyAsyncIterator[AudioChunk]
+
+        Examples
+        --------
+        ```python
+        key = receive.VoiceKey.User(user_id)
+        async for chunk in sink.user_stream(key, chunk_ms=200):
+            transcriber.feed(chunk.pcm, chunk.timestamp)
+        ```
+        """
+    def utterances(
+        self,
+        *,
+        threshold: builtins.float = 0.5,
+        pre_padding_ms: builtins.int = 200,
+        post_padding_ms: builtins.int = 500,
+        min_duration_ms: builtins.int = 300,
+    ) -> model.PyAsyncIterator[tuple[VoiceKey, datetime.datetime, datetime.datetime, pyarrow.Int16Array | pyarrow.FloatArray]]:
+        r"""
+        Return an async iterator over speech segments from every source.
+
+        Segments are cut from the `vad` column: one starts when a source's
+        speech probability reaches `threshold` and ends once it has stayed
+        below it for `post_padding_ms`. The sink must be created with
+        `vad=True`.
+
+        Parameters
+        ----------
+        threshold : float, optional
+            Speech probability, between 0 and 1, that counts as speech.
+        pre_padding_ms : int, optional
+            Audio kept before the onset.
+        post_padding_ms : int, optional
+            Quiet audio kept after the last speech; also how long a pause must
+            last to end the segment.
+        min_duration_ms : int, optional
+            Segments with less speech than this, excluding padding, are dropped.
+
+        Returns
+        -------
+        PyAsyncIterator[tuple[VoiceKey, datetime.datetime, datetime.datetime, pyarrow.Int16Array | pyarrow.FloatArray]]
+            `(key, start, end, pcm)` for each segment, in the sink's PCM format.
+
+        Raises
+        ------
+        RuntimeError
+            If the sink was created without `vad=True`.
+
+        Examples
+        --------
+        ```python
+        sink = receive.BufferSink(vad=True)
+        ...
+        async for key, start, end, pcm in sink.utterances(min_duration_ms=500):
+            await transcribe(key, pcm)
+        ```
+        """
+    def __aiter__(self) -> model.PyAsyncIterator[pyarrow.RecordBatch]:
+        r"""
+        Return an async iterator over buffered Arrow record batches.
+
+        Returns
+        -------
+        PyAsyncIterator[pyarrow.RecordBatch]
+
+        Examples
+        --------
+        ```python
+        async for batch in sink:
+            ...
+        ```
+        """
+
+@typing.final
+class CallbackSink(SinkBase):
+    r"""
+    Sink that hands received voice to async Python callbacks.
+
+    This is the supported way to implement a custom sink in Python. Events are
+    queued natively without taking the GIL and delivered one at a time, in
+    order, on the event loop that created the sink. Each callback is awaited
+    before the next event is delivered, so a slow callback fills the queue
+    rather than delaying voice receive; once full, events are discarded
+    according to `overflow`.
+
+    Examples
+    --------
+    ```python
+    from discord.ext import songbird
+    from discord.ext.songbird import receive
+
+    async def on_tick(batch):
+        await transcriber.feed(batch)
+
+    async def on_speaking(key, speaking):
+        print(key, "started" if speaking else "stopped")
+
+    vc = await channel.connect(cls=songbird.SongbirdClient)
+    sink = receive.CallbackSink(on_tick=on_tick, on_speaking=on_speaking)
+    vc.listen(sink)
+    ```
+    """
+    @property
+    def dropped(self) -> builtins.int:
+        r"""
+        Number of events discarded because the queue was full.
+
+        Returns
+        -------
+        int
+        """
+    def __new__(
+        cls,
+        *,
+        on_tick: typing.Callable[[pyarrow.RecordBatch], typing.Awaitable[None]] | None = None,
+        on_speaking: typing.Callable[[VoiceKey, bool], typing.Awaitable[None]] | None = None,
+        on_disconnect: typing.Callable[[int], typing.Awaitable[None]] | None = None,
+        max_queue: builtins.int = 500,
+        overflow: typing.Literal["drop_oldest", "drop_newest"] = "drop_oldest",
+        sample_rate: builtins.int = 48000,
+        channels: builtins.int = 2,
+        dtype: typing.Literal["int16", "float32"] = "int16",
+        vad: builtins.bool = False,
+    ) -> typing.Self:
+        r"""
+        Create a new CallbackSink.
+
+        Must be called while an event loop is running; callbacks run on that
+        loop.
+
+        Parameters
+        ----------
+        on_tick : Callable[[pyarrow.RecordBatch], Awaitable[None]] | None, optional
+            Called with the record batch of every 20 ms tick.
+        on_speaking : Callable[[VoiceKey, bool], Awaitable[None]] | None, optional
+            Called when a source starts (True) or stops (False) speaking.
+        on_disconnect : Callable[[int], Awaitable[None]] | None, optional
+            Called with the user ID of a member who left the channel.
+        max_queue : int, optional
+            Maximum number of undelivered events. Must be greater than zero.
+        overflow : {"drop_oldest", "drop_newest"}, optional
+            Which events to discard when the queue is full.
+        sample_rate : int, optional
+            Sample rate of the `pcm` column, a multiple of 50 between 8000 and
+            192000. Rates other than 48000 are resampled per speaker.
+        channels : int, optional
+            1 downmixes to mono, 2 keeps stereo.
+        dtype : {"int16", "float32"}, optional
+            Sample type of the `pcm` column. `float32` samples are in [-1, 1].
+        vad : bool, optional
+            If True, score voice activity into the `vad` column. Otherwise the
+            column is zero.
+
+        Returns
+        -------
+        CallbackSink
+
+        Raises
+        ------
+        RuntimeError
+            If no event loop is running.
+        """
+    def stop(self) -> None:
+        r"""
+        Stop delivering events.
+
+        Events already queued are still delivered, then the callbacks are
+        released.
+
+        Notes
+        -----
+        This does not unregister the sink.
+
+        Returns
+        -------
+        None
+        """
+
+@typing.final
+class FileSink(SinkBase):
+    r"""
+    Sink that records received audio straight to files.
+
+    Encoding and file I/O run on a background thread, so recording never
+    blocks the voice connection or needs the GIL. Per-user files start at a
+    source's first speech and are filled with silence while it is quiet, so
+    their timing stays aligned; mixed files start with the first tick.
+
+    Examples
+    --------
+    ```python
+    from discord.ext import songbird
+    from discord.ext.songbird import receive
+
+    vc = await channel.connect(cls=songbird.SongbirdClient)
+    sink = receive.FileSink("recordings/{key}-{segment}.{ext}", format="flac", rotate_secs=600)
+    vc.listen(sink)
+
+    async for path in sink:
+        upload(path)
+    ```
+    """
+    @property
+    def dropped(self) -> builtins.int:
+        r"""
+        Number of ticks discarded because the writer fell behind.
+
+        Up to about 10 seconds of ticks are queued for the writer thread.
+
+        Returns
+        -------
+        int
+        """
+    def __new__(
+        cls,
+        path_template: builtins.str,
+        *,
+        format: typing.Literal["wav", "flac", "ogg-opus"] = "wav",
+        per_user: builtins.bool = True,
+        mixdown: builtins.bool = False,
+        rotate_secs: typing.Optional[builtins.int] = None,
+        sample_rate: builtins.int = 48000,
+        channels: builtins.int = 2,
+    ) -> typing.Self:
+        r"""
+        Create a new FileSink.
+
+        Parameters
+        ----------
+        path_template : str
+            Path of each file. `{key}` becomes the user ID, `ssrc-<n>` for
+            unmapped sources, or `mix`; `{segment}` the rotation index;
+            `{timestamp}` the segment start in Unix seconds; `{ext}` the
+            format's extension. Missing directories are created.
+        format : {"wav", "flac", "ogg-opus"}, optional
+            File format. `ogg-opus` needs a sample rate of 8000, 12000,
+            16000, 24000, or 48000.
+        per_user : bool, optional
+            Write one file per source. Requires `{key}` in `path_template`.
+        mixdown : bool, optional
+            Write one file with every source mixed together.
+        rotate_secs : int | None, optional
+            Close every file and start new ones after this many seconds.
+            Requires `{segment}` or `{timestamp}` in `path_template`.
+        sample_rate : int, optional
+            Sample rate of the files, a multiple of 50 between 8000 and 192000.
+        channels : int, optional
+            1 for mono files, 2 for stereo.
+
+        Returns
+        -------
+        FileSink
+        """
+    def stop(self) -> None:
+        r"""
+        Stop recording and finalise every open file.
+
+        Finalised paths are still reported by the iterator, which ends once
+        the last file is closed.
+
+        Notes
+        -----
+        This does not unregister the sink. Files left open when the process
+        exits without `stop()` may have incomplete headers.
+
+        Returns
+        -------
+        None
+        """
+    def __aiter__(self) -> model.PyAsyncIterator[builtins.str]:
+        r"""
+        Return an async iterator over the paths of finished files.
+
+        A path is yielded each time a file is finalised, on rotation or
+        `stop()`. Iteration ends after `stop()` once every file is closed.
+
+        Returns
+        -------
+        PyAsyncIterator[str]
+
+        Raises
+        ------
+        OSError
+            If a file could not be created or written. Recording of other
+            files continues, and the failed file is retried after rotation.
+
+        Examples
+        --------
+        ```python
+        async for path in sink:
+            print("recorded", path)
+        ```
+        """
+
+@typing.final
+class IpcSink(SinkBase):
+    r"""
+    Sink that streams every tick to an Arrow IPC file or stream.
 
-    Collects columnar Arrow record batches and exposes them via async iteration.
+    Rows use the same schema as `BufferSink` batches, including the `tick`
+    and `timestamp` columns, so recordings can be analysed offline with
+    pyarrow, Polars, or DuckDB. Ticks are written from a background thread in
+    record batches of one second each.
 
     Examples
     --------
@@ -71,40 +813,154 @@ class BufferSink(SinkBase):
     from discord.ext.songbird import receive
 
     vc = await channel.connect(cls=songbird.SongbirdClient)
-    sink = receive.BufferSink(max_duration_secs=5)
+    sink = receive.IpcSink("session.arrow")
     vc.listen(sink)
+    ...
+    sink.stop()
 
-    async for batch in sink:
-        handle_batch(batch)
+    df = polars.read_ipc("session.arrow")
     ```
     """
+    @property
+    def dropped(self) -> builtins.int:
+        r"""
+        Number of ticks discarded because the writer fell behind.
+
+        Up to about 10 seconds of ticks are queued for the writer thread.
+
+        Returns
+        -------
+        int
+        """
     def __new__(
-        cls, *, max_duration_secs: typing.Optional[builtins.int] = None, drop_oldest: builtins.bool = True
+        cls,
+        target: str | os.PathLike[str] | typing.BinaryIO,
+        *,
+        format: typing.Literal["file", "stream"] = "file",
+        sample_rate: builtins.int = 48000,
+        channels: builtins.int = 2,
+        dtype: typing.Literal["int16", "float32"] = "int16",
+        vad: builtins.bool = False,
     ) -> typing.Self:
         r"""
-        Create a new BufferSink.
+        Create a new IpcSink.
 
         Parameters
         ----------
-        max_duration_secs : int | None
-            Maximum buffer size in seconds worth of ticks. If None, unbounded.
-        drop_oldest : bool, optional
-            If True, drop the oldest ticks when the buffer is full. If False,
-            drop new ticks instead.
+        target : str | os.PathLike[str] | typing.BinaryIO
+            Path to create, or a writable binary file object. File objects are
+            written to from a background thread and are not closed.
+        format : {"file", "stream"}, optional
+            `file` writes the random-access IPC file format (Feather v2),
+            which is readable once `stop()` has written its footer. `stream`
+            writes the IPC streaming format, readable while recording.
+        sample_rate : int, optional
+            Sample rate of the `pcm` column, a multiple of 50 between 8000 and
+            192000.
+        channels : int, optional
+            1 downmixes to mono, 2 keeps stereo.
+        dtype : {"int16", "float32"}, optional
+            Sample type of the `pcm` column.
+        vad : bool, optional
+            If True, score voice activity into the `vad` column. Otherwise the
+            column is zero.
+
+        Returns
+        -------
+        IpcSink
+
+        Raises
+        ------
+        OSError
+            If `target` cannot be created or written.
+        """
+    def stop(self) -> None:
+        r"""
+        Stop recording, write any pending ticks, and finish the output.
+
+        Blocks, without holding the GIL, until the output is complete.
 
         Notes
         -----
-        Internally converted to a tick count based on 20 ms per tick (50 ticks/sec).
-        Parameters are keyword-only.
-        `max_duration_secs=0` is invalid.
+        This does not unregister the sink.
 
         Returns
         -------
-        BufferSink
+        None
+
+        Raises
+        ------
+        OSError
+            If writing failed at any point during recording.
+        """
+
+@typing.final
+class MixSink(SinkBase):
+    r"""
+    Sink that mixes every speaker into a single PCM stream.
+
+    Each tick, the PCM of all speaking sources is summed natively with soft
+    clipping, so a whole conversation can be recorded without per-row work in
+    Python. Ticks with nobody speaking produce silence, so the output is
+    continuous.
+
+    Examples
+    --------
+    ```python
+    from discord.ext import songbird
+    from discord.ext.songbird import receive
+
+    vc = await channel.connect(cls=songbird.SongbirdClient)
+    sink = receive.MixSink(chunk_ms=1000, exclude=[receive.VoiceKey.User(bot_id)])
+    vc.listen(sink)
+
+    async for pcm in sink:
+        recording.write(pcm)
+    ```
+    """
+    def __new__(
+        cls,
+        *,
+        chunk_ms: builtins.int = 20,
+        gains: typing.Optional[typing.Mapping[VoiceKey, builtins.float]] = None,
+        exclude: typing.Optional[typing.Sequence[VoiceKey]] = None,
+        max_duration_secs: typing.Optional[builtins.int] = None,
+        sample_rate: builtins.int = 48000,
+        channels: builtins.int = 2,
+        dtype: typing.Literal["int16", "float32"] = "int16",
+    ) -> typing.Self:
+        r"""
+        Create a new MixSink.
+
+        Parameters
+        ----------
+        chunk_ms : int, optional
+            Length of each mixed chunk in milliseconds. Must be a positive
+            multiple of 20.
+        gains : dict[VoiceKey, float] | None, optional
+            Linear gain per source. Sources not listed are mixed at 1.0.
+        exclude : list[VoiceKey] | None, optional
+            Sources left out of the mix.
+        max_duration_secs : int | None, optional
+            Maximum seconds of unread mix to keep. The oldest chunks are
+            dropped once full. If None, unbounded.
+        sample_rate : int, optional
+            Sample rate of the mix, a multiple of 50 between 8000 and 192000.
+        channels : int, optional
+            1 for a mono mix, 2 for stereo.
+        dtype : {"int16", "float32"}, optional
+            Sample type of the mix. `float32` samples are in [-1, 1].
+
+        Returns
+        -------
+        MixSink
         """
     def stop(self) -> None:
         r"""
-        Stop buffering new ticks.
+        Stop mixing new ticks.
+
+        Any partially filled chunk is emitted, and iteration ends once the
+        remaining chunks have been read.
 
         Notes
         -----
@@ -114,42 +970,143 @@ class BufferSink(SinkBase):
         -------
         None
         """
-    def __getitem__(self, key: VoiceKey) -> model.PyAsyncIterator[typing.Optional[pyarrow.Int16Array]]:
+    def __aiter__(self) -> model.PyAsyncIterator[pyarrow.Int16Array | pyarrow.FloatArray]:
         r"""
-        Return an async iterator over PCM for a specific key.
+        Return an async iterator over mixed PCM chunks.
 
-        Parameters
-        ----------
-        key : VoiceKey
-            The user/ssrc key to filter.
+        Unlike `BufferSink`, iteration waits for new audio and only ends after
+        `stop()`.
 
         Returns
         -------
-        PyAsyncIterator[pyarrow.Int16Array | None]
+        PyAsyncIterator[pyarrow.Int16Array | pyarrow.FloatArray]
 
         Examples
         --------
         ```python
-        key = receive.VoiceKey.User(user_id)
-        async for pcm in sink[key]:
-            if pcm is not None:
-                handle_pcm(pcm)
+        async for pcm in sink:
+            recording.write(pcm)
         ```
         """
+
+@typing.final
+class OpusSink(SinkBase):
+    r"""
+    Sink that yields the undecoded Opus packets of each tick.
+
+    Each tick becomes a record batch with one row per source whose packet
+    arrived, in playout order, with `key_kind`, `key_id`, `rtp_sequence`,
+    `rtp_timestamp`, `opus`, `tick`, and `timestamp` columns. `opus` holds
+    the Opus frame exactly as sent, so audio can be archived without decoding
+    and re-encoding. Lost packets have no row.
+
+    Examples
+    --------
+    ```python
+    from discord.ext import songbird
+    from discord.ext.songbird import receive
+
+    vc = await channel.connect(cls=songbird.SongbirdClient)
+    sink = receive.OpusSink()
+    vc.listen(sink)
+
+    async for batch in sink:
+        archive.write(batch)
+    ```
+    """
+    def __new__(cls, *, max_duration_secs: typing.Optional[builtins.int] = None) -> typing.Self:
+        r"""
+        Create a new OpusSink.
+
+        Parameters
+        ----------
+        max_duration_secs : int | None, optional
+            Maximum seconds of unread ticks to keep. The oldest ticks are
+            dropped once full. If None, unbounded.
+
+        Returns
+        -------
+        OpusSink
+        """
+    def stop(self) -> None:
+        r"""
+        Stop collecting packets.
+
+        Iteration ends once the remaining batches have been read.
+
+        Notes
+        -----
+        This does not unregister the sink.
+
+        Returns
+        -------
+        None
+        """
     def __aiter__(self) -> model.PyAsyncIterator[pyarrow.RecordBatch]:
         r"""
-        Return an async iterator over buffered Arrow record batches.
+        Return an async iterator over per-tick Opus batches.
 
         Returns
         -------
         PyAsyncIterator[pyarrow.RecordBatch]
+        """
 
-        Examples
-        --------
-        ```python
-        async for batch in sink:
-            ...
-        ```
+@typing.final
+class RtcpSink(SinkBase):
+    r"""
+    Sink that yields received RTCP packets.
+
+    Each packet becomes a one-row record batch with `key_kind` and `key_id`
+    for its sender, `packet_type` (200 for sender reports, 201 for receiver
+    reports), the decrypted `payload` following the 8-byte header, and
+    `timestamp`. Use it to inspect the network statistics reported by other
+    participants.
+
+    Examples
+    --------
+    ```python
+    sink = receive.RtcpSink()
+    vc.listen(sink)
+
+    async for batch in sink:
+        print(batch.to_pylist())
+    ```
+    """
+    def __new__(cls, *, max_packets: typing.Optional[builtins.int] = None) -> typing.Self:
+        r"""
+        Create a new RtcpSink.
+
+        Parameters
+        ----------
+        max_packets : int | None, optional
+            Maximum number of unread packets to keep. The oldest packets are
+            dropped once full. If None, unbounded.
+
+        Returns
+        -------
+        RtcpSink
+        """
+    def stop(self) -> None:
+        r"""
+        Stop collecting packets.
+
+        Iteration ends once the remaining packets have been read.
+
+        Notes
+        -----
+        This does not unregister the sink.
+
+        Returns
+        -------
+        None
+        """
+    def __aiter__(self) -> model.PyAsyncIterator[pyarrow.RecordBatch]:
+        r"""
+        Return an async iterator over RTCP packets.
+
+        Returns
+        -------
+        PyAsyncIterator[pyarrow.RecordBatch]
         """
 
 class SinkBase:
@@ -159,7 +1116,8 @@ class SinkBase:
     Notes
     -----
     This is an internal type exposed to Python for sink registration.
-    Custom sinks are not currently supported from Python.
+    Subclassing it from Python is not supported; implement custom sinks with
+    `CallbackSink` instead.
     """
 
     ...
@@ -205,7 +1163,9 @@ class Stream:
         -------
         None
         """
-    def __getitem__(self, key: VoiceKey) -> model.PyAsyncIterator[typing.Optional[pyarrow.Int16Array]]:
+    def __getitem__(
+        self, key: VoiceKey
+    ) -> model.PyAsyncIterator[typing.Optional[pyarrow.Int16Array | pyarrow.FloatArray]]:
         r"""
         Return an async iterator over PCM for a specific key.
 
@@ -216,7 +1176,7 @@ class Stream:
 
         Returns
         -------
-        PyAsyncIterator[pyarrow.Int16Array | None]
+        PyAsyncIterator[pyarrow.Int16Array | pyarrow.FloatArray | None]
 
         Examples
         --------
@@ -227,6 +1187,86 @@ class Stream:
                     handle_pcm(pcm)
         ```
         """
+    def user_stream(
+        self, key: VoiceKey, *, fill_silence: builtins.bool = True, chunk_ms: builtins.int = 20
+    ) -> model.PyAsyncIterator[AudioChunk]:
+        r"""
+        Return an async iterator over contiguous PCM for a specific key.
+
+        Parameters
+        ----------
+        key : VoiceKey
+            The user/ssrc key to follow.
+        fill_silence : bool, optional
+            If True, ticks where the key sent no audio, and ticks the sink
+            dropped, are zero-filled once the key has first spoken, giving a
+            gapless timeline. If False, chunks only contain received audio and
+            end early at each silence.
+        chunk_ms : int, optional
+            Length of each chunk in milliseconds. Must be a positive multiple
+            of 20.
+
+        Returns
+        -------
+        PyAsyncIterator[AudioChunk]
+
+        Examples
+        --------
+        ```python
+        key = receive.VoiceKey.User(user_id)
+        async with sink.stream() as stream:
+            async for chunk in stream.user_stream(key, chunk_ms=200):
+                transcriber.feed(chunk.pcm, chunk.timestamp)
+        ```
+        """
+    def utterances(
+        self,
+        *,
+        threshold: builtins.float = 0.5,
+        pre_padding_ms: builtins.int = 200,
+        post_padding_ms: builtins.int = 500,
+        min_duration_ms: builtins.int = 300,
+    ) -> model.PyAsyncIterator[tuple[VoiceKey, datetime.datetime, datetime.datetime, pyarrow.Int16Array | pyarrow.FloatArray]]:
+        r"""
+        Return an async iterator over speech segments from every source.
+
+        Segments are cut from the `vad` column: one starts when a source's
+        speech probability reaches `threshold` and ends once it has stayed
+        below it for `post_padding_ms`. The sink must be created with
+        `vad=True`.
+
+        Parameters
+        ----------
+        threshold : float, optional
+            Speech probability, between 0 and 1, that counts as speech.
+        pre_padding_ms : int, optional
+            Audio kept before the onset.
+        post_padding_ms : int, optional
+            Quiet audio kept after the last speech; also how long a pause must
+            last to end the segment.
+        min_duration_ms : int, optional
+            Segments with less speech than this, excluding padding, are dropped.
+
+        Returns
+        -------
+        PyAsyncIterator[tuple[VoiceKey, datetime.datetime, datetime.datetime, pyarrow.Int16Array | pyarrow.FloatArray]]
+            `(key, start, end, pcm)` for each segment, in the sink's PCM format.
+
+        Raises
+        ------
+        RuntimeError
+            If the sink was created without `vad=True`.
+
+        Examples
+        --------
+        ```python
+        sink = receive.StreamSink(vad=True)
+        ...
+        async with sink.stream() as stream:
+            async for key, start, end, pcm in stream.utterances():
+                await transcribe(key, pcm)
+        ```
+        """
 
 @typing.final
 class StreamSink(SinkBase):
@@ -253,7 +1293,18 @@ class StreamSink(SinkBase):
     ```
     """
     def __new__(
-        cls, *, retain: builtins.bool = False, retain_secs: builtins.int = 15, max_concurrent: builtins.int = 50
+        cls,
+        *,
+        retain: builtins.bool = False,
+        retain_secs: builtins.int = 15,
+        max_concurrent: builtins.int = 50,
+        sample_rate: builtins.int = 48000,
+        channels: builtins.int = 2,
+        dtype: typing.Literal["int16", "float32"] = "int16",
+        keys: typing.Optional[typing.Sequence[VoiceKey]] = None,
+        exclude: typing.Optional[typing.Sequence[VoiceKey]] = None,
+        skip_silent: builtins.bool = False,
+        vad: builtins.bool = False,
     ) -> typing.Self:
         r"""
         Create a new StreamSink.
@@ -269,6 +1320,24 @@ class StreamSink(SinkBase):
         max_concurrent : int, optional
             Maximum number of concurrent streams.
             Must be greater than zero.
+        sample_rate : int, optional
+            Sample rate of the `pcm` column, a multiple of 50 between 8000 and
+            192000. Rates other than 48000 are resampled per speaker.
+        channels : int, optional
+            1 downmixes to mono, 2 keeps stereo.
+        dtype : {"int16", "float32"}, optional
+            Sample type of the `pcm` column. `float32` samples are in [-1, 1].
+        keys : list[VoiceKey] | None, optional
+            Sources to stream. If None, every source is streamed. Unmapped
+            `VoiceKey.Unknown` sources are streamed as well, since they may
+            belong to a listed user.
+        exclude : list[VoiceKey] | None, optional
+            Sources never to stream, even if listed in `keys`.
+        skip_silent : bool, optional
+            If True, ticks in which no streamed source is speaking are dropped.
+        vad : bool, optional
+            If True, score voice activity into the `vad` column, as required
+            by `Stream.utterances()`. Otherwise the column is zero.
 
         Returns
         -------
@@ -292,6 +1361,128 @@ class StreamSink(SinkBase):
                 ...
         ```
         """
+    def set_filter(
+        self,
+        *,
+        keys: typing.Optional[typing.Sequence[VoiceKey]] = None,
+        exclude: typing.Optional[typing.Sequence[VoiceKey]] = None,
+        skip_silent: builtins.bool = False,
+    ) -> None:
+        r"""
+        Replace the source filter for ticks received from now on.
+
+        Parameters
+        ----------
+        keys : list[VoiceKey] | None, optional
+            Sources to stream. If None, every source is streamed. Unmapped
+            `VoiceKey.Unknown` sources are streamed as well, since they may
+            belong to a listed user.
+        exclude : list[VoiceKey] | None, optional
+            Sources never to stream, even if listed in `keys`.
+        skip_silent : bool, optional
+            If True, ticks in which no streamed source is speaking are dropped.
+
+        Returns
+        -------
+        None
+        """
+    def identity_changes(self) -> model.PyAsyncIterator[tuple[VoiceKey, VoiceKey]]:
+        r"""
+        Return an async iterator over newly learned SSRC to user mappings.
+
+        Batches keep the key they were built with, so audio received before
+        Discord announced a speaker's SSRC stays under `VoiceKey.Unknown`.
+        Use these `(old, new)` pairs to re-key such audio yourself. Only
+        mappings learned after this call are yielded.
+
+        Iteration ends, like the sink's streams, once the voice connection
+        and the sink have released its handler, typically after the sink is
+        garbage collected following a disconnect. Break out of the loop to
+        stop listening earlier.
+
+        Returns
+        -------
+        PyAsyncIterator[tuple[VoiceKey, VoiceKey]]
+
+        Examples
+        --------
+        ```python
+        async for old, new in sink.identity_changes():
+            transcripts[new] = transcripts.pop(old, "") + transcripts.get(new, "")
+        ```
+        """
+
+class VoiceEvent:
+    r"""
+    Speaking and membership event yielded by `SongbirdClient.voice_events()`.
+
+    `user_id` is None for speaking events from an SSRC whose user Discord has
+    not announced yet; an `SsrcMapped` event follows once it does.
+
+    Examples
+    --------
+    ```python
+    async for event in vc.voice_events():
+        match event:
+            case receive.VoiceEvent.SpeakingStarted(user_id=user_id):
+                indicators.on(user_id)
+            case receive.VoiceEvent.SpeakingStopped(user_id=user_id):
+                indicators.off(user_id)
+    ```
+    """
+    def __repr__(self) -> builtins.str:
+        r"""
+        Return a debug representation.
+
+        Returns
+        -------
+        str
+        """
+    @typing.final
+    class SpeakingStarted(VoiceEvent):
+        r"""
+        A source started sending audio.
+        """
+        __match_args__ = ("user_id", "ssrc",)
+        @property
+        def user_id(self) -> typing.Optional[builtins.int]: ...
+        @property
+        def ssrc(self) -> builtins.int: ...
+        def __new__(cls, user_id: typing.Optional[builtins.int], ssrc: builtins.int) -> VoiceEvent.SpeakingStarted: ...
+
+    @typing.final
+    class SpeakingStopped(VoiceEvent):
+        r"""
+        A source stopped sending audio.
+        """
+        __match_args__ = ("user_id", "ssrc",)
+        @property
+        def user_id(self) -> typing.Optional[builtins.int]: ...
+        @property
+        def ssrc(self) -> builtins.int: ...
+        def __new__(cls, user_id: typing.Optional[builtins.int], ssrc: builtins.int) -> VoiceEvent.SpeakingStopped: ...
+
+    @typing.final
+    class UserDisconnected(VoiceEvent):
+        r"""
+        A member left the voice channel.
+        """
+        __match_args__ = ("user_id",)
+        @property
+        def user_id(self) -> builtins.int: ...
+        def __new__(cls, user_id: builtins.int) -> VoiceEvent.UserDisconnected: ...
+
+    @typing.final
+    class SsrcMapped(VoiceEvent):
+        r"""
+        Discord announced which user sends an SSRC.
+        """
+        __match_args__ = ("ssrc", "user_id",)
+        @property
+        def ssrc(self) -> builtins.int: ...
+        @property
+        def user_id(self) -> builtins.int: ...
+        def __new__(cls, ssrc: builtins.int, user_id: builtins.int) -> VoiceEvent.SsrcMapped: ...
 
 class VoiceKey:
     r"""
@@ -388,7 +1579,7 @@ class VoiceTick:
     New receive iterators yield `pyarrow.RecordBatch` objects. This class remains
     available for Rust-side compatibility helpers.
     """
-    def get(self, key: VoiceKey) -> typing.Optional[pyarrow.Int16Array]:
+    def get(self, key: VoiceKey) -> typing.Optional[pyarrow.Int16Array | pyarrow.FloatArray]:
         r"""
         Get PCM audio for a key if it is speaking in this tick.
 
@@ -399,7 +1590,7 @@ class VoiceTick:
 
         Returns
         -------
-        pyarrow.Int16Array | None
+        pyarrow.Int16Array | pyarrow.FloatArray | None
             PCM when speaking, otherwise None.
         """
     def is_silent(self, key: VoiceKey) -> builtins.bool:
@@ -1,12 +1,17 @@
 use crate::error::IntoPyResult;
-use crate::model::PyFuture;
+use crate::model::{Generic, PyAsyncIterator, PyFuture};
+use crate::player::effects::{DuckSettings, Ducking};
 use crate::player::handle::PyTrackHandle;
 use crate::player::queue::PyQueue;
 use crate::player::track::PyTrack;
 use crate::receive::HandlerWrapper;
+use crate::receive::events::{VoiceEvent, VoiceEventHandler};
+use crate::receive::queue::{NotifyQueue, Overflow};
 use crate::receive::sink::SinkBase;
 use crate::receive::{VoiceIdentityMap, VoiceIdentityTracker};
 use crate::update::VoiceUpdater;
+use async_stream::stream;
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::PyAnyMethods;
 use pyo3::types::PyTuple;
 use pyo3::{
@@ -60,6 +65,7 @@ pub struct SongbirdImpl {
     application_id: UserId,
     call: Arc<Mutex<CallWrapper>>,
     identity_map: Arc<VoiceIdentityMap>,
+    ducking: Arc<Ducking>,
     current_loop: Option<Py<PyAny>>,
 }
 
@@ -115,6 +121,7 @@ impl SongbirdImpl {
             application_id: application_id.into(),
             call: Arc::new(Mutex::new(CallWrapper::new())),
             identity_map: Arc::new(VoiceIdentityMap::default()),
+            ducking: Arc::new(Ducking::default()),
             current_loop: Some(current_loop),
         })
     }
@@ -151,6 +158,7 @@ impl SongbirdImpl {
             .decode_mode(DecodeMode::Decode(DecodeConfig::default()));
         let self_call = slf.call.clone();
         let identity_map = slf.identity_map.clone();
+        let ducking = slf.ducking.clone();
         let guild_id = slf.guild_id;
         let channel_id = slf.channel_id;
         let application_id = slf.application_id;
@@ -169,6 +177,7 @@ impl SongbirdImpl {
 
         future_into_py(py, async move {
             identity_map.clear();
+            ducking.reset();
             let mut call = Call::from_config(guild_id, shard, application_id, config);
             let identity_tracker: Arc<dyn EventHandler + Send + Sync> =
                 Arc::new(VoiceIdentityTracker::new(identity_map.clone()));
@@ -180,6 +189,7 @@ impl SongbirdImpl {
                 Event::Core(CoreEvent::ClientDisconnect),
                 HandlerWrapper(identity_tracker),
             );
+            call.add_global_event(Event::Core(CoreEvent::VoiceTick), ducking.speech_tracker());
             {
                 let mut guard = self_call.lock().await;
                 guard.set(call);
@@ -230,6 +240,7 @@ impl SongbirdImpl {
         let call = guard.get_mut()?;
         call.leave().await.into_pyerr()?;
         self.identity_map.clear();
+        self.ducking.reset();
         Ok(())
     }
 
@@ -396,6 +407,67 @@ impl SongbirdImpl {
         Ok(call.is_deaf())
     }
 
+    /// Configure ducking of background tracks.
+    ///
+    /// Parameters
+    /// ----------
+    /// depth_db : float | None
+    ///     How far background tracks are lowered, in dB. None disables
+    ///     ducking.
+    /// attack_ms : float, optional
+    ///     Time taken to lower background tracks.
+    /// release_ms : float, optional
+    ///     Time taken to restore background tracks.
+    /// on_speech : bool, optional
+    ///     Also duck while received voice shows users speaking.
+    ///
+    /// Returns
+    /// -------
+    /// None
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If a value is negative or not finite.
+    ///
+    /// Notes
+    /// -----
+    /// Tracks take part through `Track.role`. Background tracks are lowered
+    /// while any primary track is playing, and with `on_speech` while users
+    /// are heard. Settings apply to tracks that are already playing.
+    ///
+    /// Examples
+    /// --------
+    /// ```python
+    /// vc.set_ducking(12.0, on_speech=True)
+    /// await vc.play(player.Track(music).role("background"))
+    /// await vc.play(player.Track(announcement).role("primary"))
+    /// ```
+    #[pyo3(signature = (depth_db = Some(12.0), *, attack_ms = 150.0, release_ms = 600.0, on_speech = false))]
+    fn set_ducking(
+        &self,
+        depth_db: Option<f32>,
+        attack_ms: f32,
+        release_ms: f32,
+        on_speech: bool,
+    ) -> PyResult<()> {
+        let settings = depth_db
+            .map(|depth_db| DuckSettings::new(depth_db, attack_ms, release_ms, on_speech))
+            .transpose()?;
+        self.ducking.configure(settings);
+        Ok(())
+    }
+
+    /// Check if background tracks are currently ducked.
+    ///
+    /// Returns
+    /// -------
+    /// bool
+    ///     Whether ducking is enabled and a primary track or speech is active.
+    fn is_ducking(&self) -> bool {
+        self.ducking.is_ducking()
+    }
+
     /// |coro|
     ///
     /// Move this account to another voice channel.
@@ -482,6 +554,67 @@ impl SongbirdImpl {
         Ok(())
     }
 
+    #[pyo3(signature = (*, max_events = None))]
+    /// Return an async iterator over speaking and membership events.
+    ///
+    /// Speaking starts and stops are derived per SSRC from consecutive voice
+    /// ticks, so no sink or PCM decoding is needed on the Python side. Each
+    /// call subscribes independently; iteration ends when the bot leaves the
+    /// channel.
+    ///
+    /// Parameters
+    /// ----------
+    /// max_events : int | None, optional
+    ///     Maximum number of unread events to keep. The oldest events are
+    ///     dropped once full. If None, unbounded.
+    ///
+    /// Returns
+    /// -------
+    /// PyAsyncIterator[VoiceEvent]
+    ///
+    /// Examples
+    /// --------
+    /// ```python
+    /// async for event in vc.voice_events():
+    ///     if isinstance(event, receive.VoiceEvent.SpeakingStarted):
+    ///         print(event.user_id, "is talking")
+    /// ```
+    fn voice_events<'py>(
+        &self,
+        _py: Python<'py>,
+        max_events: Option<usize>,
+    ) -> PyResult<Generic<'py, PyAsyncIterator, VoiceEvent>> {
+        if max_events == Some(0) {
+            return Err(PyValueError::new_err(
+                "max_events must be greater than zero",
+            ));
+        }
+        let queue = NotifyQueue::new(max_events, Overflow::DropOldest);
+        let handler: Arc<dyn EventHandler + Send + Sync> = Arc::new(VoiceEventHandler::new(
+            self.identity_map.clone(),
+            queue.clone(),
+        ));
+        {
+            let mut guard = self.call.blocking_lock();
+            let call = guard.get_mut()?;
+            for event in [
+                CoreEvent::VoiceTick,
+                CoreEvent::SpeakingStateUpdate,
+                CoreEvent::ClientDisconnect,
+                CoreEvent::DriverDisconnect,
+            ] {
+                call.add_global_event(Event::Core(event), HandlerWrapper(handler.clone()));
+            }
+        }
+
+        let s = stream! {
+            while let Some(event) = queue.next().await {
+                yield Python::attach(|py| event.into_py_any(py));
+            }
+        };
+        Ok(Generic::new(PyAsyncIterator::new_in_raw(s)))
+    }
+
     /// |coro|
     ///
     /// Play a track.
@@ -508,11 +641,13 @@ impl SongbirdImpl {
             })?
             .clone_ref(py);
         let track = track.unbind().clone_ref(py);
+        let ducking = self.ducking.clone();
         future_into_py(py, async move {
             let mut guard = call.lock().await;
             let call = guard.get_mut()?;
 
-            let track = Python::attach(|py| track.bind(py).borrow().to_track(py, current_loop))?;
+            let track =
+                Python::attach(|py| track.bind(py).borrow().to_track(py, current_loop, &ducking))?;
             let handle = call.play(track);
             Ok(PyTrackHandle::new(handle))
         })
@@ -567,11 +702,13 @@ impl SongbirdImpl {
                 pyo3::exceptions::PyRuntimeError::new_err("SongbirdImpl has been cleared")
             })?
             .clone_ref(py);
+        let ducking = self.ducking.clone();
         future_into_py(py, async move {
             let mut guard = call.lock().await;
             let call = guard.get_mut()?;
 
-            let track = Python::attach(|py| track.bind(py).borrow().to_track(py, current_loop))?;
+            let track =
+                Python::attach(|py| track.bind(py).borrow().to_track(py, current_loop, &ducking))?;
             let handle = call.enqueue(track).await;
             Ok(PyTrackHandle::new(handle))
         })
@@ -592,3 +729,20 @@ impl SongbirdImpl {
         self.current_loop = None;
     }
 }
+
+impl SongbirdImpl {
+    /// Register `handler` for `event` on the connected call.
+    pub(crate) fn add_global_event(
+        &self,
+        event: Event,
+        handler: impl EventHandler + 'static,
+    ) -> PyResult<()> {
+        let mut guard = self.call.blocking_lock();
+        guard.get_mut()?.add_global_event(event, handler);
+        Ok(())
+    }
+
+    pub(crate) fn identity_map(&self) -> Arc<VoiceIdentityMap> {
+        self.identity_map.clone()
+    }
+}
@@ -5,6 +5,7 @@ mod error;
 mod model;
 mod player;
 mod receive;
+mod resample;
 mod update;
 
 use crate::client::SongbirdImpl;
@@ -65,15 +66,48 @@ VERSION : str
         #[pymodule_export]
         use crate::player::handle::PyTrackHandle;
         #[pymodule_export]
+        use crate::player::handle::PyTrackState;
+        #[pymodule_export]
         use crate::player::queue::PyQueue;
         #[pymodule_export]
         use crate::player::track::PyTrack;
 
+        #[pymodule_export]
+        use crate::player::effects::PyCompressor;
+        #[pymodule_export]
+        use crate::player::effects::PyEffect;
+        #[pymodule_export]
+        use crate::player::effects::PyEqualizer;
+        #[pymodule_export]
+        use crate::player::effects::PyFadeIn;
+        #[pymodule_export]
+        use crate::player::effects::PyFadeOut;
+        #[pymodule_export]
+        use crate::player::effects::PyHighPass;
+        #[pymodule_export]
+        use crate::player::effects::PyLimiter;
+        #[pymodule_export]
+        use crate::player::effects::PyLowPass;
+
         #[pymodule_export]
         use crate::player::input::PyInputBase;
         #[pymodule_export]
         use crate::player::input::audio::PyAudioInput;
         #[pymodule_export]
+        use crate::player::input::clip::PyClipInput;
+        #[pymodule_export]
+        use crate::player::input::concat::PyConcatInput;
+        #[pymodule_export]
+        use crate::player::input::concat::PySegmentChange;
+        #[pymodule_export]
+        use crate::player::input::dca::PyDcaInput;
+        #[pymodule_export]
+        use crate::player::input::encode::encode_opus;
+        #[pymodule_export]
+        use crate::player::input::lazy::PyLazyInput;
+        #[pymodule_export]
+        use crate::player::input::mix::PyMixInput;
+        #[pymodule_export]
         use crate::player::input::opus::PyOpusPacketInput;
         #[pymodule_export]
         use crate::player::input::opus::PyOpusPacketStreamInput;
@@ -82,16 +116,38 @@ VERSION : str
         #[pymodule_export]
         use crate::player::input::pcm::PyRawPcmInput;
         #[pymodule_export]
+        use crate::player::input::relay::PyRelayInput;
+        #[pymodule_export]
         use crate::player::input::stream::PyStreamInput;
+
+        #[pyo3::pymodule]
+        mod dca {
+            #[pymodule_export]
+            use crate::player::input::dca::encode;
+        }
     }
 
     #[pymodule]
     mod receive {
+        #[pymodule_export]
+        use crate::receive::events::VoiceEvent;
         #[pymodule_export]
         use crate::receive::sink::BufferSink;
         #[pymodule_export]
+        use crate::receive::sink::CallbackSink;
+        #[pymodule_export]
+        use crate::receive::sink::FileSink;
+        #[pymodule_export]
+        use crate::receive::sink::IpcSink;
+        #[pymodule_export]
+        use crate::receive::sink::MixSink;
+        #[pymodule_export]
+        use crate::receive::sink::OpusSink;
+        #[pymodule_export]
         use crate::receive::sink::PyStream;
         #[pymodule_export]
+        use crate::receive::sink::RtcpSink;
+        #[pymodule_export]
         use crate::receive::sink::SinkBase;
         #[pymodule_export]
         use crate::receive::sink::StreamSink;
@@ -99,6 +155,8 @@ VERSION : str
         use crate::receive::tick::VoiceKey;
         #[pymodule_export]
         use crate::receive::tick::VoiceTick;
+        #[pymodule_export]
+        use crate::receive::user_stream::AudioChunk;
     }
 
     #[pymodule]
@@ -1,4 +1,4 @@
-use arrow::array::Int16Array;
+use arrow::array::{BinaryArray, Int16Array};
 use pyo3::{Bound, IntoPyObject, PyAny, Python};
 use pyo3_stub_gen::{PyStubType, TypeInfo};
 use std::collections::HashSet;
@@ -91,3 +91,8 @@ macro_rules! define_element {
 }
 
 define_element!(Int16Array);
+define_element!(BinaryArray);
+
+/// Received PCM, typed by the sink's `dtype`.
+pub struct PcmArray;
+define_element!(PcmArray, "Int16Array | pyarrow.FloatArray");
@@ -0,0 +1,1127 @@
+mod dsp;
+mod ducking;
+mod loudness;
+mod speed;
+
+use crate::player::input::audio::PyAudioInput;
+use crate::player::input::decode::{
+    DecodedInput, PCM_CHANNELS, PCM_SAMPLE_RATE, PcmSource, PcmStream, compose_inputs,
+};
+use crate::player::input::pcm::PyRawPcmInput;
+use async_trait::async_trait;
+use dsp::{
+    BUTTERWORTH_Q, Biquad, Coefficients, Compressor, CompressorSettings, Limiter, apply_ramp,
+};
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::types::PyAnyMethods;
+use pyo3::{Bound, Py, PyAny, PyResult, Python, pyclass, pymethods};
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use songbird::input::core::io::MediaSource;
+use songbird::input::{AudioStream, AudioStreamError, Compose, RawAdapter};
+use std::io;
+use std::sync::{Arc, Mutex};
+
+pub(crate) use ducking::{DuckRole, DuckSettings, Ducking};
+pub(crate) use loudness::Normalize;
+use loudness::Normalizer;
+use speed::SpeedSource;
+pub(crate) use speed::{SpeedControl, SpeedSettings};
+
+type Shared<T> = Arc<Mutex<T>>;
+
+#[gen_stub_pyclass]
+#[pyclass(
+    name = "Effect",
+    subclass,
+    module = "discord.ext.songbird.native.player",
+    skip_from_py_object
+)]
+/// Base class for audio effects attached with `Track.effects`.
+///
+/// Notes
+/// -----
+/// Effect parameters are shared with the running track, so assigning to an
+/// effect's properties takes effect on the next 20 ms block. The effects of a
+/// playing track are available from `TrackHandle.effects`.
+pub struct PyEffect {
+    kind: EffectKind,
+}
+
+#[derive(Clone)]
+enum EffectKind {
+    FadeIn(Shared<f32>),
+    FadeOut(Shared<f32>),
+    Equalizer(Shared<Vec<Band>>),
+    Compressor(Shared<CompressorSettings>),
+    Limiter(Shared<LimiterSettings>),
+    HighPass(Shared<f32>),
+    LowPass(Shared<f32>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Band {
+    frequency_hz: f32,
+    gain_db: f32,
+    q: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct LimiterSettings {
+    ceiling_db: f32,
+    release_ms: f32,
+}
+
+#[gen_stub_pyclass]
+#[pyclass(
+    name = "FadeIn",
+    extends = PyEffect,
+    module = "discord.ext.songbird.native.player",
+    skip_from_py_object
+)]
+/// Fade the track in from silence.
+pub struct PyFadeIn {
+    duration_ms: Shared<f32>,
+}
+
+#[gen_stub_pyclass]
+#[pyclass(
+    name = "FadeOut",
+    extends = PyEffect,
+    module = "discord.ext.songbird.native.player",
+    skip_from_py_object
+)]
+/// Fade the end of the track out to silence.
+///
+/// Notes
+/// -----
+/// The fade needs the track length, so it has no effect on inputs whose
+/// length is unknown, such as live streams.
+pub struct PyFadeOut {
+    duration_ms: Shared<f32>,
+}
+
+#[gen_stub_pyclass]
+#[pyclass(
+    name = "Equalizer",
+    extends = PyEffect,
+    module = "discord.ext.songbird.native.player",
+    skip_from_py_object
+)]
+/// Parametric equalizer built from peaking filters.
+///
+/// Examples
+/// --------
+/// ```python
+/// bass_boost = player.Equalizer([(60.0, 6.0), (120.0, 3.0)])
+/// ```
+pub struct PyEqualizer {
+    bands: Shared<Vec<Band>>,
+}
+
+#[gen_stub_pyclass]
+#[pyclass(
+    name = "Compressor",
+    extends = PyEffect,
+    module = "discord.ext.songbird.native.player",
+    skip_from_py_object
+)]
+/// Dynamic range compressor.
+pub struct PyCompressor {
+    settings: Shared<CompressorSettings>,
+}
+
+#[gen_stub_pyclass]
+#[pyclass(
+    name = "Limiter",
+    extends = PyEffect,
+    module = "discord.ext.songbird.native.player",
+    skip_from_py_object
+)]
+/// Peak limiter that keeps the signal below a ceiling.
+pub struct PyLimiter {
+    settings: Shared<LimiterSettings>,
+}
+
+#[gen_stub_pyclass]
+#[pyclass(
+    name = "HighPass",
+    extends = PyEffect,
+    module = "discord.ext.songbird.native.player",
+    skip_from_py_object
+)]
+/// Second-order Butterworth high-pass filter.
+pub struct PyHighPass {
+    cutoff_hz: Shared<f32>,
+}
+
+#[gen_stub_pyclass]
+#[pyclass(
+    name = "LowPass",
+    extends = PyEffect,
+    module = "discord.ext.songbird.native.player",
+    skip_from_py_object
+)]
+/// Second-order Butterworth low-pass filter.
+pub struct PyLowPass {
+    cutoff_hz: Shared<f32>,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl PyFadeIn {
+    #[gen_stub(override_return_type(type_repr = "typing.Self", imports = ("typing")))]
+    #[new]
+    /// Create a fade in.
+    ///
+    /// Parameters
+    /// ----------
+    /// duration_ms : float
+    ///     Length of the fade in milliseconds.
+    ///
+    /// Returns
+    /// -------
+    /// FadeIn
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If `duration_ms` is negative.
+    fn new(duration_ms: f32) -> PyResult<(Self, PyEffect)> {
+        let duration_ms = Arc::new(Mutex::new(non_negative("duration_ms", duration_ms)?));
+        Ok((
+            Self {
+                duration_ms: duration_ms.clone(),
+            },
+            PyEffect::new(EffectKind::FadeIn(duration_ms)),
+        ))
+    }
+
+    /// Length of the fade in milliseconds.
+    ///
+    /// Returns
+    /// -------
+    /// float
+    #[getter]
+    fn duration_ms(&self) -> f32 {
+        *self.duration_ms.lock().unwrap()
+    }
+
+    #[setter]
+    fn set_duration_ms(&self, value: f32) -> PyResult<()> {
+        *self.duration_ms.lock().unwrap() = non_negative("duration_ms", value)?;
+        Ok(())
+    }
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl PyFadeOut {
+    #[gen_stub(override_return_type(type_repr = "typing.Self", imports = ("typing")))]
+    #[new]
+    /// Create a fade out.
+    ///
+    /// Parameters
+    /// ----------
+    /// duration_ms : float
+    ///     Length of the fade in milliseconds, ending at the end of the track.
+    ///
+    /// Returns
+    /// -------
+    /// FadeOut
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If `duration_ms` is negative.
+    fn new(duration_ms: f32) -> PyResult<(Self, PyEffect)> {
+        let duration_ms = Arc::new(Mutex::new(non_negative("duration_ms", duration_ms)?));
+        Ok((
+            Self {
+                duration_ms: duration_ms.clone(),
+            },
+            PyEffect::new(EffectKind::FadeOut(duration_ms)),
+        ))
+    }
+
+    /// Length of the fade in milliseconds.
+    ///
+    /// Returns
+    /// -------
+    /// float
+    #[getter]
+    fn duration_ms(&self) -> f32 {
+        *self.duration_ms.lock().unwrap()
+    }
+
+    #[setter]
+    fn set_duration_ms(&self, value: f32) -> PyResult<()> {
+        *self.duration_ms.lock().unwrap() = non_negative("duration_ms", value)?;
+        Ok(())
+    }
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl PyEqualizer {
+    #[gen_stub(override_return_type(type_repr = "typing.Self", imports = ("typing")))]
+    #[new]
+    /// Create an equalizer.
+    ///
+    /// Parameters
+    /// ----------
+    /// bands : Sequence[tuple[float, float] | tuple[float, float, float]]
+    ///     `(frequency_hz, gain_db)` or `(frequency_hz, gain_db, q)` per band.
+    ///     `q` defaults to 1.0.
+    ///
+    /// Returns
+    /// -------
+    /// Equalizer
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If a frequency or `q` is not positive.
+    fn new(
+        #[gen_stub(override_type(
+            type_repr = "collections.abc.Sequence[tuple[float, float] | tuple[float, float, float]]",
+            imports = ("collections.abc")
+        ))]
+        bands: Vec<Bound<PyAny>>,
+    ) -> PyResult<(Self, PyEffect)> {
+        let bands = Arc::new(Mutex::new(Band::extract_all(&bands)?));
+        Ok((
+            Self {
+                bands: bands.clone(),
+            },
+            PyEffect::new(EffectKind::Equalizer(bands)),
+        ))
+    }
+
+    /// Current bands as `(frequency_hz, gain_db, q)`.
+    ///
+    /// Returns
+    /// -------
+    /// list[tuple[float, float, float]]
+    #[getter]
+    fn bands(&self) -> Vec<(f32, f32, f32)> {
+        self.bands
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|band| (band.frequency_hz, band.gain_db, band.q))
+            .collect()
+    }
+
+    #[setter]
+    fn set_bands(
+        &self,
+        #[gen_stub(override_type(
+            type_repr = "collections.abc.Sequence[tuple[float, float] | tuple[float, float, float]]",
+            imports = ("collections.abc")
+        ))]
+        bands: Vec<Bound<PyAny>>,
+    ) -> PyResult<()> {
+        *self.bands.lock().unwrap() = Band::extract_all(&bands)?;
+        Ok(())
+    }
+
+    /// Set the gain of one band.
+    ///
+    /// Parameters
+    /// ----------
+    /// index : int
+    ///     Band index.
+    /// gain_db : float
+    ///     New gain in decibels.
+    ///
+    /// Returns
+    /// -------
+    /// None
+    ///
+    /// Raises
+    /// ------
+    /// IndexError
+    ///     If `index` is out of range.
+    fn set_gain(&self, index: usize, gain_db: f32) -> PyResult<()> {
+        let mut bands = self.bands.lock().unwrap();
+        let band = bands
+            .get_mut(index)
+            .ok_or_else(|| PyIndexError::new_err("band index out of range"))?;
+        band.gain_db = gain_db;
+        Ok(())
+    }
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl PyCompressor {
+    #[gen_stub(override_return_type(type_repr = "typing.Self", imports = ("typing")))]
+    #[new]
+    #[pyo3(signature = (*, threshold_db = -18.0, ratio = 4.0, attack_ms = 10.0, release_ms = 100.0, makeup_db = 0.0))]
+    /// Create a compressor.
+    ///
+    /// Parameters
+    /// ----------
+    /// threshold_db : float, optional
+    ///     Level above which gain reduction starts, in dBFS.
+    /// ratio : float, optional
+    ///     Input to output ratio above the threshold.
+    /// attack_ms : float, optional
+    ///     Time constant for reacting to rising levels.
+    /// release_ms : float, optional
+    ///     Time constant for recovering after levels fall.
+    /// makeup_db : float, optional
+    ///     Gain applied after compression.
+    ///
+    /// Returns
+    /// -------
+    /// Compressor
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If `ratio` is below 1 or a time constant is negative.
+    fn new(
+        threshold_db: f32,
+        ratio: f32,
+        attack_ms: f32,
+        release_ms: f32,
+        makeup_db: f32,
+    ) -> PyResult<(Self, PyEffect)> {
+        let settings = CompressorSettings {
+            threshold_db,
+            ratio: at_least_one("ratio", ratio)?,
+            attack_secs: non_negative("attack_ms", attack_ms)? / 1000.0,
+            release_secs: non_negative("release_ms", release_ms)? / 1000.0,
+            makeup_db,
+        };
+        let settings = Arc::new(Mutex::new(settings));
+        Ok((
+            Self {
+                settings: settings.clone(),
+            },
+            PyEffect::new(EffectKind::Compressor(settings)),
+        ))
+    }
+
+    /// Threshold in dBFS.
+    ///
+    /// Returns
+    /// -------
+    /// float
+    #[getter]
+    fn threshold_db(&self) -> f32 {
+        self.settings.lock().unwrap().threshold_db
+    }
+
+    #[setter]
+    fn set_threshold_db(&self, value: f32) {
+        self.settings.lock().unwrap().threshold_db = value;
+    }
+
+    /// Compression ratio.
+    ///
+    /// Returns
+    /// -------
+    /// float
+    #[getter]
+    fn ratio(&self) -> f32 {
+        self.settings.lock().unwrap().ratio
+    }
+
+    #[setter]
+    fn set_ratio(&self, value: f32) -> PyResult<()> {
+        self.settings.lock().unwrap().ratio = at_least_one("ratio", value)?;
+        Ok(())
+    }
+
+    /// Attack time in milliseconds.
+    ///
+    /// Returns
+    /// -------
+    /// float
+    #[getter]
+    fn attack_ms(&self) -> f32 {
+        self.settings.lock().unwrap().attack_secs * 1000.0
+    }
+
+    #[setter]
+    fn set_attack_ms(&self, value: f32) -> PyResult<()> {
+        self.settings.lock().unwrap().attack_secs = non_negative("attack_ms", value)? / 1000.0;
+        Ok(())
+    }
+
+    /// Release time in milliseconds.
+    ///
+    /// Returns
+    /// -------
+    /// float
+    #[getter]
+    fn release_ms(&self) -> f32 {
+        self.settings.lock().unwrap().release_secs * 1000.0
+    }
+
+    #[setter]
+    fn set_release_ms(&self, value: f32) -> PyResult<()> {
+        self.settings.lock().unwrap().release_secs = non_negative("release_ms", value)? / 1000.0;
+        Ok(())
+    }
+
+    /// Makeup gain in decibels.
+    ///
+    /// Returns
+    /// -------
+    /// float
+    #[getter]
+    fn makeup_db(&self) -> f32 {
+        self.settings.lock().unwrap().makeup_db
+    }
+
+    #[setter]
+    fn set_makeup_db(&self, value: f32) {
+        self.settings.lock().unwrap().makeup_db = value;
+    }
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl PyLimiter {
+    #[gen_stub(override_return_type(type_repr = "typing.Self", imports = ("typing")))]
+    #[new]
+    #[pyo3(signature = (*, ceiling_db = -1.0, release_ms = 50.0))]
+    /// Create a limiter.
+    ///
+    /// Parameters
+    /// ----------
+    /// ceiling_db : float, optional
+    ///     Maximum output peak in dBFS.
+    /// release_ms : float, optional
+    ///     Time constant for recovering after a peak.
+    ///
+    /// Returns
+    /// -------
+    /// Limiter
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If `release_ms` is negative.
+    fn new(ceiling_db: f32, release_ms: f32) -> PyResult<(Self, PyEffect)> {
+        let settings = Arc::new(Mutex::new(LimiterSettings {
+            ceiling_db,
+            release_ms: non_negative("release_ms", release_ms)?,
+        }));
+        Ok((
+            Self {
+                settings: settings.clone(),
+            },
+            PyEffect::new(EffectKind::Limiter(settings)),
+        ))
+    }
+
+    /// Ceiling in dBFS.
+    ///
+    /// Returns
+    /// -------
+    /// float
+    #[getter]
+    fn ceiling_db(&self) -> f32 {
+        self.settings.lock().unwrap().ceiling_db
+    }
+
+    #[setter]
+    fn set_ceiling_db(&self, value: f32) {
+        self.settings.lock().unwrap().ceiling_db = value;
+    }
+
+    /// Release time in milliseconds.
+    ///
+    /// Returns
+    /// -------
+    /// float
+    #[getter]
+    fn release_ms(&self) -> f32 {
+        self.settings.lock().unwrap().release_ms
+    }
+
+    #[setter]
+    fn set_release_ms(&self, value: f32) -> PyResult<()> {
+        self.settings.lock().unwrap().release_ms = non_negative("release_ms", value)?;
+        Ok(())
+    }
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl PyHighPass {
+    #[gen_stub(override_return_type(type_repr = "typing.Self", imports = ("typing")))]
+    #[new]
+    /// Create a high-pass filter.
+    ///
+    /// Parameters
+    /// ----------
+    /// cutoff_hz : float
+    ///     Cutoff frequency in hertz.
+    ///
+    /// Returns
+    /// -------
+    /// HighPass
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If `cutoff_hz` is not positive.
+    fn new(cutoff_hz: f32) -> PyResult<(Self, PyEffect)> {
+        let cutoff_hz = Arc::new(Mutex::new(positive("cutoff_hz", cutoff_hz)?));
+        Ok((
+            Self {
+                cutoff_hz: cutoff_hz.clone(),
+            },
+            PyEffect::new(EffectKind::HighPass(cutoff_hz)),
+        ))
+    }
+
+    /// Cutoff frequency in hertz.
+    ///
+    /// Returns
+    /// -------
+    /// float
+    #[getter]
+    fn cutoff_hz(&self) -> f32 {
+        *self.cutoff_hz.lock().unwrap()
+    }
+
+    #[setter]
+    fn set_cutoff_hz(&self, value: f32) -> PyResult<()> {
+        *self.cutoff_hz.lock().unwrap() = positive("cutoff_hz", value)?;
+        Ok(())
+    }
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl PyLowPass {
+    #[gen_stub(override_return_type(type_repr = "typing.Self", imports = ("typing")))]
+    #[new]
+    /// Create a low-pass filter.
+    ///
+    /// Parameters
+    /// ----------
+    /// cutoff_hz : float
+    ///     Cutoff frequency in hertz.
+    ///
+    /// Returns
+    /// -------
+    /// LowPass
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If `cutoff_hz` is not positive.
+    fn new(cutoff_hz: f32) -> PyResult<(Self, PyEffect)> {
+        let cutoff_hz = Arc::new(Mutex::new(positive("cutoff_hz", cutoff_hz)?));
+        Ok((
+            Self {
+                cutoff_hz: cutoff_hz.clone(),
+            },
+            PyEffect::new(EffectKind::LowPass(cutoff_hz)),
+        ))
+    }
+
+    /// Cutoff frequency in hertz.
+    ///
+    /// Returns
+    /// -------
+    /// float
+    #[getter]
+    fn cutoff_hz(&self) -> f32 {
+        *self.cutoff_hz.lock().unwrap()
+    }
+
+    #[setter]
+    fn set_cutoff_hz(&self, value: f32) -> PyResult<()> {
+        *self.cutoff_hz.lock().unwrap() = positive("cutoff_hz", value)?;
+        Ok(())
+    }
+}
+
+impl PyEffect {
+    fn new(kind: EffectKind) -> Self {
+        Self { kind }
+    }
+}
+
+impl Band {
+    fn extract_all(bands: &[Bound<PyAny>]) -> PyResult<Vec<Self>> {
+        bands.iter().map(Self::extract).collect()
+    }
+
+    fn extract(band: &Bound<PyAny>) -> PyResult<Self> {
+        let (frequency_hz, gain_db, q) = match band.extract::<(f32, f32, f32)>() {
+            Ok(band) => band,
+            Err(_) => {
+                let (frequency_hz, gain_db) = band.extract::<(f32, f32)>()?;
+                (frequency_hz, gain_db, 1.0)
+            }
+        };
+        Ok(Self {
+            frequency_hz: positive("frequency", frequency_hz)?,
+            gain_db,
+            q: positive("q", q)?,
+        })
+    }
+
+    fn coefficients(&self) -> Coefficients {
+        Coefficients::peaking(self.frequency_hz, self.gain_db, self.q)
+    }
+}
+
+fn positive(name: &str, value: f32) -> PyResult<f32> {
+    if value > 0.0 {
+        Ok(value)
+    } else {
+        Err(PyValueError::new_err(format!("{name} must be positive")))
+    }
+}
+
+fn non_negative(name: &str, value: f32) -> PyResult<f32> {
+    if value >= 0.0 {
+        Ok(value)
+    } else {
+        Err(PyValueError::new_err(format!(
+            "{name} must be zero or positive"
+        )))
+    }
+}
+
+fn at_least_one(name: &str, value: f32) -> PyResult<f32> {
+    if value >= 1.0 {
+        Ok(value)
+    } else {
+        Err(PyValueError::new_err(format!("{name} must be at least 1")))
+    }
+}
+
+/// Effects resolved from a track, ready to be attached to its input.
+pub(crate) struct EffectChain {
+    effects: Vec<EffectKind>,
+    normalize: Option<Normalize>,
+    speed: Option<Arc<SpeedControl>>,
+    ducking: Option<Arc<Ducking>>,
+}
+
+impl EffectChain {
+    /// `ducking` is set for background tracks, which are lowered by the
+    /// call's ducking controller.
+    pub(crate) fn new(
+        py: Python,
+        effects: &[Py<PyEffect>],
+        normalize: Option<Normalize>,
+        speed: Option<Arc<SpeedControl>>,
+        ducking: Option<Arc<Ducking>>,
+    ) -> Self {
+        Self {
+            effects: effects
+                .iter()
+                .map(|effect| effect.borrow(py).kind.clone())
+                .collect(),
+            normalize,
+            speed,
+            ducking,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+            && self.normalize.is_none()
+            && self.speed.is_none()
+            && self.ducking.is_none()
+    }
+
+    /// Wrap `input` so that its decoded PCM runs through the chain.
+    pub(crate) fn compose(
+        self,
+        input: Py<PyAny>,
+        current_loop: Py<PyAny>,
+    ) -> Box<dyn Compose + Send + Sync> {
+        Box::new(EffectsCompose {
+            input,
+            current_loop,
+            chain: self.effects,
+            normalize: self.normalize,
+            speed: self.speed,
+            ducking: self.ducking,
+        })
+    }
+}
+
+struct EffectsCompose {
+    input: Py<PyAny>,
+    current_loop: Py<PyAny>,
+    chain: Vec<EffectKind>,
+    normalize: Option<Normalize>,
+    speed: Option<Arc<SpeedControl>>,
+    ducking: Option<Arc<Ducking>>,
+}
+
+#[async_trait]
+impl Compose for EffectsCompose {
+    fn create(&mut self) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+        Err(AudioStreamError::Unsupported)
+    }
+
+    async fn create_async(
+        &mut self,
+    ) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+        let (mut inputs, in_memory) = Python::attach(|py| {
+            let input = self.input.bind(py);
+            let in_memory =
+                input.cast::<PyAudioInput>().is_ok() || input.cast::<PyRawPcmInput>().is_ok();
+            compose_inputs(py, &[self.input.clone_ref(py)], &self.current_loop)
+                .map(|inputs| (inputs, in_memory))
+        })
+        .map_err(|err| AudioStreamError::Fail(Box::new(err)))?;
+        let mut input = DecodedInput::open(inputs.remove(0)).await?;
+        let mut chain: Vec<Box<dyn Processor>> = Vec::with_capacity(self.chain.len() + 2);
+        if let Some(normalize) = self.normalize {
+            let normalizer;
+            (input, normalizer) = Normalizer::prepare(normalize, input, in_memory).await?;
+            chain.push(Box::new(normalizer));
+        }
+        chain.extend(self.chain.iter().map(EffectKind::processor));
+        if let Some(ducking) = &self.ducking {
+            chain.push(Box::new(ducking.stage()));
+        }
+        let mut source: Box<dyn PcmSource> = Box::new(EffectSource::new(Box::new(input), chain));
+        if let Some(speed) = &self.speed {
+            source = Box::new(SpeedSource::new(source, speed.clone()));
+        }
+        Ok(AudioStream {
+            input: Box::new(RawAdapter::new(
+                PcmStream::new(source),
+                PCM_SAMPLE_RATE,
+                PCM_CHANNELS as u32,
+            )),
+        })
+    }
+
+    fn should_create_async(&self) -> bool {
+        true
+    }
+}
+
+/// Stateful DSP stage created from an `EffectKind` for one playback.
+trait Processor: Send + Sync {
+    /// Process `block` in place. `position` is the frame index of the first
+    /// frame and `len` the total track length, if known.
+    fn process(&mut self, block: &mut [f32], position: u64, len: Option<u64>);
+
+    /// Clear filter state after a seek.
+    fn reset(&mut self) {}
+}
+
+struct EffectSource {
+    input: Box<dyn PcmSource>,
+    chain: Vec<Box<dyn Processor>>,
+    position: u64,
+    len: Option<u64>,
+}
+
+impl EffectSource {
+    fn new(input: Box<dyn PcmSource>, chain: Vec<Box<dyn Processor>>) -> Self {
+        Self {
+            len: input.len_frames(),
+            input,
+            chain,
+            position: 0,
+        }
+    }
+}
+
+impl PcmSource for EffectSource {
+    fn read_frames(&mut self, out: &mut [f32]) -> usize {
+        let read = self.input.read_frames(out);
+        let block = &mut out[..read * PCM_CHANNELS];
+        for processor in self.chain.iter_mut() {
+            processor.process(block, self.position, self.len);
+        }
+        self.position += read as u64;
+        read
+    }
+
+    fn seek_frame(&mut self, frame: u64) -> io::Result<()> {
+        self.input.seek_frame(frame)?;
+        self.position = frame;
+        for processor in self.chain.iter_mut() {
+            processor.reset();
+        }
+        Ok(())
+    }
+
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    fn len_frames(&self) -> Option<u64> {
+        self.len
+    }
+}
+
+impl EffectKind {
+    fn processor(&self) -> Box<dyn Processor> {
+        match self {
+            Self::FadeIn(duration_ms) => Box::new(Fade {
+                duration_ms: duration_ms.clone(),
+                rising: true,
+            }),
+            Self::FadeOut(duration_ms) => Box::new(Fade {
+                duration_ms: duration_ms.clone(),
+                rising: false,
+            }),
+            Self::Equalizer(bands) => Box::new(Equalizer {
+                bands: bands.clone(),
+                current: Vec::new(),
+                filters: Vec::new(),
+            }),
+            Self::Compressor(settings) => Box::new(CompressorStage {
+                settings: settings.clone(),
+                state: Compressor::default(),
+            }),
+            Self::Limiter(settings) => Box::new(LimiterStage {
+                settings: settings.clone(),
+                state: Limiter::default(),
+            }),
+            Self::HighPass(cutoff_hz) => Box::new(Filter::new(cutoff_hz.clone(), |hz| {
+                Coefficients::high_pass(hz, BUTTERWORTH_Q)
+            })),
+            Self::LowPass(cutoff_hz) => Box::new(Filter::new(cutoff_hz.clone(), |hz| {
+                Coefficients::low_pass(hz, BUTTERWORTH_Q)
+            })),
+        }
+    }
+}
+
+struct Fade {
+    duration_ms: Shared<f32>,
+    rising: bool,
+}
+
+impl Processor for Fade {
+    fn process(&mut self, block: &mut [f32], position: u64, len: Option<u64>) {
+        let length = ms_frames(*self.duration_ms.lock().unwrap());
+        if self.rising {
+            apply_ramp(block, position as i64, length, true);
+        } else if let Some(len) = len {
+            let start = len.saturating_sub(length);
+            apply_ramp(block, position as i64 - start as i64, length, false);
+        }
+    }
+}
+
+struct Equalizer {
+    bands: Shared<Vec<Band>>,
+    current: Vec<Band>,
+    filters: Vec<Biquad>,
+}
+
+impl Processor for Equalizer {
+    fn process(&mut self, block: &mut [f32], _position: u64, _len: Option<u64>) {
+        let bands = self.bands.lock().unwrap().clone();
+        if bands != self.current {
+            // Keep filter state when only gains move, so adjustments do not click.
+            self.filters
+                .resize_with(bands.len(), || Biquad::new(Coefficients::IDENTITY));
+            for (filter, band) in self.filters.iter_mut().zip(&bands) {
+                filter.set(band.coefficients());
+            }
+            self.current = bands;
+        }
+        for filter in self.filters.iter_mut() {
+            filter.process(block);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.filters.iter_mut().for_each(Biquad::reset);
+    }
+}
+
+struct Filter {
+    cutoff_hz: Shared<f32>,
+    current: f32,
+    design: fn(f32) -> Coefficients,
+    biquad: Biquad,
+}
+
+impl Filter {
+    fn new(cutoff_hz: Shared<f32>, design: fn(f32) -> Coefficients) -> Self {
+        let current = *cutoff_hz.lock().unwrap();
+        Self {
+            cutoff_hz,
+            current,
+            design,
+            biquad: Biquad::new(design(current)),
+        }
+    }
+}
+
+impl Processor for Filter {
+    fn process(&mut self, block: &mut [f32], _position: u64, _len: Option<u64>) {
+        let cutoff_hz = *self.cutoff_hz.lock().unwrap();
+        if cutoff_hz != self.current {
+            self.biquad.set((self.design)(cutoff_hz));
+            self.current = cutoff_hz;
+        }
+        self.biquad.process(block);
+    }
+
+    fn reset(&mut self) {
+        self.biquad.reset();
+    }
+}
+
+struct CompressorStage {
+    settings: Shared<CompressorSettings>,
+    state: Compressor,
+}
+
+impl Processor for CompressorStage {
+    fn process(&mut self, block: &mut [f32], _position: u64, _len: Option<u64>) {
+        let settings = *self.settings.lock().unwrap();
+        self.state.process(block, &settings);
+    }
+
+    fn reset(&mut self) {
+        self.state.reset();
+    }
+}
+
+struct LimiterStage {
+    settings: Shared<LimiterSettings>,
+    state: Limiter,
+}
+
+impl Processor for LimiterStage {
+    fn process(&mut self, block: &mut [f32], _position: u64, _len: Option<u64>) {
+        let settings = *self.settings.lock().unwrap();
+        self.state
+            .process(block, settings.ceiling_db, settings.release_ms / 1000.0);
+    }
+
+    fn reset(&mut self) {
+        self.state.reset();
+    }
+}
+
+fn ms_frames(ms: f32) -> u64 {
+    (ms as f64 * PCM_SAMPLE_RATE as f64 / 1000.0).round() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Constant {
+        remaining: u64,
+        len: u64,
+    }
+
+    impl PcmSource for Constant {
+        fn read_frames(&mut self, out: &mut [f32]) -> usize {
+            let frames = ((out.len() / PCM_CHANNELS) as u64).min(self.remaining) as usize;
+            out[..frames * PCM_CHANNELS].fill(1.0);
+            self.remaining -= frames as u64;
+            frames
+        }
+
+        fn seek_frame(&mut self, frame: u64) -> io::Result<()> {
+            self.remaining = self.len.saturating_sub(frame);
+            Ok(())
+        }
+
+        fn is_seekable(&self) -> bool {
+            true
+        }
+
+        fn len_frames(&self) -> Option<u64> {
+            Some(self.len)
+        }
+    }
+
+    fn source(len: u64, effects: &[EffectKind]) -> EffectSource {
+        EffectSource::new(
+            Box::new(Constant {
+                remaining: len,
+                len,
+            }),
+            effects.iter().map(EffectKind::processor).collect(),
+        )
+    }
+
+    fn shared<T>(value: T) -> Shared<T> {
+        Arc::new(Mutex::new(value))
+    }
+
+    #[test]
+    fn fades_follow_track_position() {
+        // 0.1 ms is 4.8 frames at 48 kHz, rounded to 5.
+        let mut source = source(
+            10,
+            &[
+                EffectKind::FadeIn(shared(0.1)),
+                EffectKind::FadeOut(shared(0.1)),
+            ],
+        );
+        let mut out = vec![0.0; 10 * PCM_CHANNELS];
+        assert_eq!(source.read_frames(&mut out), 10);
+        let expected = [0.0, 0.2, 0.4, 0.6, 0.8, 1.0, 0.8, 0.6, 0.4, 0.2];
+        for (sample, expected) in out.iter().step_by(PCM_CHANNELS).zip(expected) {
+            assert!((sample - expected).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn parameter_changes_apply_to_the_next_block() {
+        let fade = shared(0.0);
+        let mut source = source(100, &[EffectKind::FadeIn(fade.clone())]);
+        let mut out = vec![0.0; 2 * PCM_CHANNELS];
+        source.read_frames(&mut out);
+        assert_eq!(out[0], 1.0);
+
+        *fade.lock().unwrap() = 1.0;
+        source.seek_frame(0).unwrap();
+        source.read_frames(&mut out);
+        assert_eq!(out[0], 0.0);
+    }
+
+    #[test]
+    fn equalizer_tracks_band_changes() {
+        let bands = shared(vec![Band {
+            frequency_hz: 1_000.0,
+            gain_db: 0.0,
+            q: 1.0,
+        }]);
+        let mut equalizer = EffectKind::Equalizer(bands.clone()).processor();
+        let mut block = vec![1.0; 4 * PCM_CHANNELS];
+        equalizer.process(&mut block, 0, None);
+        assert!(block.iter().all(|&s| (s - 1.0).abs() < 1e-6));
+
+        bands.lock().unwrap()[0].gain_db = 12.0;
+        let mut block = vec![0.0; 4 * PCM_CHANNELS];
+        block[0] = 1.0;
+        equalizer.process(&mut block, 4, None);
+        assert!(block[0] > 1.0);
+    }
+
+    #[test]
+    fn limiter_chain_keeps_output_below_ceiling() {
+        let settings = shared(LimiterSettings {
+            ceiling_db: -6.0,
+            release_ms: 50.0,
+        });
+        let mut source = source(8, &[EffectKind::Limiter(settings)]);
+        let mut out = vec![0.0; 8 * PCM_CHANNELS];
+        source.read_frames(&mut out);
+        assert!(out.iter().all(|&s| s <= 0.502));
+    }
+}
@@ -0,0 +1,296 @@
+use crate::player::input::decode::{PCM_CHANNELS, PCM_SAMPLE_RATE};
+use std::f32::consts::PI;
+
+const SAMPLE_RATE: f32 = PCM_SAMPLE_RATE as f32;
+
+/// Q of a second-order Butterworth section.
+pub(super) const BUTTERWORTH_Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+/// Normalised biquad coefficients (`a0 == 1`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct Coefficients {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl Coefficients {
+    /// Pass-through filter.
+    pub(super) const IDENTITY: Self = Self::new(1.0, 0.0, 0.0, 0.0, 0.0);
+
+    pub(super) const fn new(b0: f32, b1: f32, b2: f32, a1: f32, a2: f32) -> Self {
+        Self { b0, b1, b2, a1, a2 }
+    }
+
+    pub(super) fn low_pass(cutoff_hz: f32, q: f32) -> Self {
+        let (cos, alpha) = Self::angle(cutoff_hz, q);
+        Self::normalise(
+            (1.0 - cos) / 2.0,
+            1.0 - cos,
+            (1.0 - cos) / 2.0,
+            1.0 + alpha,
+            -2.0 * cos,
+            1.0 - alpha,
+        )
+    }
+
+    pub(super) fn high_pass(cutoff_hz: f32, q: f32) -> Self {
+        let (cos, alpha) = Self::angle(cutoff_hz, q);
+        Self::normalise(
+            (1.0 + cos) / 2.0,
+            -(1.0 + cos),
+            (1.0 + cos) / 2.0,
+            1.0 + alpha,
+            -2.0 * cos,
+            1.0 - alpha,
+        )
+    }
+
+    pub(super) fn peaking(center_hz: f32, gain_db: f32, q: f32) -> Self {
+        let (cos, alpha) = Self::angle(center_hz, q);
+        let amplitude = 10_f32.powf(gain_db / 40.0);
+        Self::normalise(
+            1.0 + alpha * amplitude,
+            -2.0 * cos,
+            1.0 - alpha * amplitude,
+            1.0 + alpha / amplitude,
+            -2.0 * cos,
+            1.0 - alpha / amplitude,
+        )
+    }
+
+    fn angle(frequency: f32, q: f32) -> (f32, f32) {
+        // Keep the design stable for frequencies at or above Nyquist.
+        let frequency = frequency.clamp(1.0, SAMPLE_RATE * 0.49);
+        let omega = 2.0 * PI * frequency / SAMPLE_RATE;
+        (omega.cos(), omega.sin() / (2.0 * q))
+    }
+
+    fn normalise(b0: f32, b1: f32, b2: f32, a0: f32, a1: f32, a2: f32) -> Self {
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+}
+
+/// Stereo biquad in transposed direct form II.
+#[derive(Debug, Clone)]
+pub(super) struct Biquad {
+    coefficients: Coefficients,
+    state: [[f32; 2]; PCM_CHANNELS],
+}
+
+impl Biquad {
+    pub(super) fn new(coefficients: Coefficients) -> Self {
+        Self {
+            coefficients,
+            state: [[0.0; 2]; PCM_CHANNELS],
+        }
+    }
+
+    pub(super) fn set(&mut self, coefficients: Coefficients) {
+        self.coefficients = coefficients;
+    }
+
+    pub(super) fn reset(&mut self) {
+        self.state = [[0.0; 2]; PCM_CHANNELS];
+    }
+
+    pub(super) fn process(&mut self, block: &mut [f32]) {
+        let c = self.coefficients;
+        for frame in block.chunks_exact_mut(PCM_CHANNELS) {
+            for (sample, state) in frame.iter_mut().zip(self.state.iter_mut()) {
+                let input = *sample;
+                let output = c.b0 * input + state[0];
+                state[0] = c.b1 * input - c.a1 * output + state[1];
+                state[1] = c.b2 * input - c.a2 * output;
+                *sample = output;
+            }
+        }
+    }
+}
+
+/// Feed-forward compressor with a peak envelope follower.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Compressor {
+    envelope_db: f32,
+}
+
+/// Parameters shared by `Compressor::process`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct CompressorSettings {
+    pub threshold_db: f32,
+    pub ratio: f32,
+    pub attack_secs: f32,
+    pub release_secs: f32,
+    pub makeup_db: f32,
+}
+
+impl Compressor {
+    pub(super) fn reset(&mut self) {
+        self.envelope_db = 0.0;
+    }
+
+    pub(super) fn process(&mut self, block: &mut [f32], settings: &CompressorSettings) {
+        let attack = smoothing(settings.attack_secs);
+        let release = smoothing(settings.release_secs);
+        let slope = 1.0 - 1.0 / settings.ratio.max(1.0);
+        for frame in block.chunks_exact_mut(PCM_CHANNELS) {
+            let peak = frame.iter().fold(0.0_f32, |peak, s| peak.max(s.abs()));
+            let over_db = (to_db(peak) - settings.threshold_db).max(0.0);
+            let coefficient = if over_db > self.envelope_db {
+                attack
+            } else {
+                release
+            };
+            self.envelope_db = over_db + coefficient * (self.envelope_db - over_db);
+            let gain = from_db(settings.makeup_db - self.envelope_db * slope);
+            for sample in frame.iter_mut() {
+                *sample *= gain;
+            }
+        }
+    }
+}
+
+/// Peak limiter with instant attack and exponential release.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Limiter {
+    envelope: f32,
+}
+
+impl Limiter {
+    pub(super) fn reset(&mut self) {
+        self.envelope = 0.0;
+    }
+
+    pub(super) fn process(&mut self, block: &mut [f32], ceiling_db: f32, release_secs: f32) {
+        let ceiling = from_db(ceiling_db);
+        let release = smoothing(release_secs);
+        for frame in block.chunks_exact_mut(PCM_CHANNELS) {
+            let peak = frame.iter().fold(0.0_f32, |peak, s| peak.max(s.abs()));
+            self.envelope = peak.max(self.envelope * release);
+            if self.envelope > ceiling {
+                let gain = ceiling / self.envelope;
+                for sample in frame.iter_mut() {
+                    *sample *= gain;
+                }
+            }
+        }
+    }
+}
+
+/// Apply a linear gain ramp of `length` frames starting at `offset` frames
+/// into the ramp. `rising` selects a fade in rather than a fade out.
+pub(super) fn apply_ramp(block: &mut [f32], offset: i64, length: u64, rising: bool) {
+    if length == 0 {
+        return;
+    }
+    for (index, frame) in block.chunks_exact_mut(PCM_CHANNELS).enumerate() {
+        let progress = ((offset + index as i64) as f32 / length as f32).clamp(0.0, 1.0);
+        let gain = if rising { progress } else { 1.0 - progress };
+        for sample in frame.iter_mut() {
+            *sample *= gain;
+        }
+    }
+}
+
+/// One-pole smoothing coefficient for a time constant in seconds.
+fn smoothing(seconds: f32) -> f32 {
+    if seconds <= 0.0 {
+        return 0.0;
+    }
+    (-1.0 / (seconds * SAMPLE_RATE)).exp()
+}
+
+pub(super) fn to_db(amplitude: f32) -> f32 {
+    20.0 * amplitude.max(1e-6).log10()
+}
+
+pub(super) fn from_db(db: f32) -> f32 {
+    10_f32.powf(db / 20.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(frequency: f32, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|i| {
+                let sample = (2.0 * PI * frequency * i as f32 / SAMPLE_RATE).sin();
+                [sample, sample]
+            })
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn low_pass_attenuates_high_frequencies() {
+        let mut filter = Biquad::new(Coefficients::low_pass(500.0, BUTTERWORTH_Q));
+        let mut high = sine(8_000.0, 4_800);
+        filter.process(&mut high);
+        filter.reset();
+        let mut low = sine(100.0, 4_800);
+        filter.process(&mut low);
+        assert!(rms(&high[4_800..]) < 0.05);
+        assert!(rms(&low[4_800..]) > 0.6);
+    }
+
+    #[test]
+    fn high_pass_attenuates_low_frequencies() {
+        let mut filter = Biquad::new(Coefficients::high_pass(2_000.0, BUTTERWORTH_Q));
+        let mut low = sine(50.0, 4_800);
+        filter.process(&mut low);
+        assert!(rms(&low[4_800..]) < 0.01);
+    }
+
+    #[test]
+    fn peaking_boosts_its_center_frequency() {
+        let mut filter = Biquad::new(Coefficients::peaking(1_000.0, 6.0, 1.0));
+        let mut samples = sine(1_000.0, 4_800);
+        filter.process(&mut samples);
+        let gain = rms(&samples[4_800..]) / std::f32::consts::FRAC_1_SQRT_2;
+        assert!((to_db(gain) - 6.0).abs() < 0.2);
+    }
+
+    #[test]
+    fn compressor_reduces_loud_signals() {
+        let settings = CompressorSettings {
+            threshold_db: -20.0,
+            ratio: 4.0,
+            attack_secs: 0.0,
+            release_secs: 0.1,
+            makeup_db: 0.0,
+        };
+        let mut block = vec![1.0; 100 * PCM_CHANNELS];
+        Compressor::default().process(&mut block, &settings);
+        // 20 dB over the threshold at 4:1 leaves 5 dB, i.e. -15 dBFS.
+        assert!((to_db(block[0]) + 15.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn limiter_holds_peaks_at_the_ceiling() {
+        let mut block: Vec<f32> = (0..200).map(|i| (i % 7) as f32 * 0.5).collect();
+        Limiter::default().process(&mut block, -1.0, 0.05);
+        assert!(block.iter().all(|s| s.abs() <= from_db(-1.0) + 1e-6));
+    }
+
+    #[test]
+    fn ramps_interpolate_linearly() {
+        let mut block = vec![1.0; 4 * PCM_CHANNELS];
+        apply_ramp(&mut block, 0, 4, true);
+        assert_eq!(block[..8], [0.0, 0.0, 0.25, 0.25, 0.5, 0.5, 0.75, 0.75]);
+        let mut block = vec![1.0; 2 * PCM_CHANNELS];
+        apply_ramp(&mut block, 3, 4, false);
+        assert_eq!(block, vec![0.25, 0.25, 0.0, 0.0]);
+    }
+}
@@ -0,0 +1,283 @@
+use super::Processor;
+use super::dsp::from_db;
+use crate::player::input::decode::{PCM_CHANNELS, PCM_SAMPLE_RATE};
+use async_trait::async_trait;
+use dashmap::DashSet;
+use pyo3::PyResult;
+use pyo3::exceptions::PyValueError;
+use songbird::events::EventData;
+use songbird::tracks::{PlayMode, ReadyState, Track};
+use songbird::{Event, EventContext, EventHandler, TrackEvent};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Received voice keeps ducking engaged this long after the last packet, so
+/// short pauses between words do not pump the music.
+const SPEECH_HOLD: Duration = Duration::from_millis(300);
+
+/// Role of a track under call-level ducking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DuckRole {
+    /// Ducks background tracks while it plays.
+    Primary,
+    /// Lowered while a primary track plays or users speak.
+    Background,
+}
+
+impl DuckRole {
+    pub(crate) fn parse(role: &str) -> PyResult<Self> {
+        match role {
+            "primary" => Ok(Self::Primary),
+            "background" => Ok(Self::Background),
+            _ => Err(PyValueError::new_err(
+                "role must be \"primary\", \"background\", or None",
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct DuckSettings {
+    depth_db: f32,
+    attack_secs: f32,
+    release_secs: f32,
+    on_speech: bool,
+}
+
+impl DuckSettings {
+    pub(crate) fn new(
+        depth_db: f32,
+        attack_ms: f32,
+        release_ms: f32,
+        on_speech: bool,
+    ) -> PyResult<Self> {
+        if !depth_db.is_finite() || depth_db < 0.0 {
+            return Err(PyValueError::new_err(
+                "depth_db must be a finite value of at least 0",
+            ));
+        }
+        if !(attack_ms.is_finite()
+            && attack_ms >= 0.0
+            && release_ms.is_finite()
+            && release_ms >= 0.0)
+        {
+            return Err(PyValueError::new_err(
+                "attack_ms and release_ms must be finite values of at least 0",
+            ));
+        }
+        Ok(Self {
+            depth_db,
+            attack_secs: attack_ms / 1000.0,
+            release_secs: release_ms / 1000.0,
+            on_speech,
+        })
+    }
+}
+
+/// Call-level ducking state shared by every background track of a call.
+#[derive(Default)]
+pub(crate) struct Ducking {
+    settings: Mutex<Option<DuckSettings>>,
+    /// Primary tracks that are currently playing, by track UUID.
+    primaries: DashSet<u128>,
+    last_speech: Mutex<Option<Instant>>,
+}
+
+impl Ducking {
+    pub(crate) fn configure(&self, settings: Option<DuckSettings>) {
+        *self.settings.lock().unwrap() = settings;
+    }
+
+    /// Forget primary tracks and speech from a previous connection.
+    pub(crate) fn reset(&self) {
+        self.primaries.clear();
+        *self.last_speech.lock().unwrap() = None;
+    }
+
+    /// Whether background tracks are currently being lowered.
+    pub(crate) fn is_ducking(&self) -> bool {
+        self.settings
+            .lock()
+            .unwrap()
+            .is_some_and(|settings| self.triggered(&settings))
+    }
+
+    fn triggered(&self, settings: &DuckSettings) -> bool {
+        !self.primaries.is_empty()
+            || settings.on_speech
+                && self
+                    .last_speech
+                    .lock()
+                    .unwrap()
+                    .is_some_and(|last| last.elapsed() < SPEECH_HOLD)
+    }
+
+    /// Register `track` so that it ducks background tracks while it plays.
+    pub(crate) fn attach_primary(self: &Arc<Self>, track: &mut Track) {
+        for event in [
+            TrackEvent::Playable,
+            TrackEvent::Play,
+            TrackEvent::Pause,
+            TrackEvent::End,
+            TrackEvent::Error,
+        ] {
+            track.events.add_event(
+                EventData::new(Event::Track(event), PrimaryTracker(self.clone())),
+                Duration::ZERO,
+            );
+        }
+    }
+
+    /// Handler for `CoreEvent::VoiceTick` that records received speech.
+    pub(crate) fn speech_tracker(self: &Arc<Self>) -> SpeechTracker {
+        SpeechTracker(self.clone())
+    }
+
+    pub(super) fn stage(self: &Arc<Self>) -> DuckStage {
+        DuckStage {
+            ducking: self.clone(),
+            gain_db: 0.0,
+        }
+    }
+
+    /// Gain background tracks should move towards, and the largest change
+    /// per frame, both in dB.
+    fn target(&self) -> (f32, f32) {
+        let Some(settings) = *self.settings.lock().unwrap() else {
+            return (0.0, f32::INFINITY);
+        };
+        let (target_db, ramp_secs) = if self.triggered(&settings) {
+            (-settings.depth_db, settings.attack_secs)
+        } else {
+            (0.0, settings.release_secs)
+        };
+        let step = if ramp_secs > 0.0 {
+            settings.depth_db / (ramp_secs * PCM_SAMPLE_RATE as f32)
+        } else {
+            f32::INFINITY
+        };
+        (target_db, step)
+    }
+}
+
+struct PrimaryTracker(Arc<Ducking>);
+
+#[async_trait]
+impl EventHandler for PrimaryTracker {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if let EventContext::Track(tracks) = ctx {
+            for (state, handle) in tracks.iter() {
+                let id = handle.uuid().as_u128();
+                if matches!(state.playing, PlayMode::Play) && state.ready == ReadyState::Playable {
+                    self.0.primaries.insert(id);
+                } else {
+                    self.0.primaries.remove(&id);
+                }
+            }
+        }
+        None
+    }
+}
+
+pub(crate) struct SpeechTracker(Arc<Ducking>);
+
+#[async_trait]
+impl EventHandler for SpeechTracker {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if let EventContext::VoiceTick(tick) = ctx
+            && !tick.speaking.is_empty()
+        {
+            *self.0.last_speech.lock().unwrap() = Some(Instant::now());
+        }
+        None
+    }
+}
+
+/// Gain stage of a background track.
+pub(super) struct DuckStage {
+    ducking: Arc<Ducking>,
+    gain_db: f32,
+}
+
+impl Processor for DuckStage {
+    fn process(&mut self, block: &mut [f32], _position: u64, _len: Option<u64>) {
+        let (target_db, step) = self.ducking.target();
+        for frame in block.chunks_exact_mut(PCM_CHANNELS) {
+            let difference = target_db - self.gain_db;
+            self.gain_db += difference.clamp(-step, step);
+            if self.gain_db != 0.0 {
+                let gain = from_db(self.gain_db);
+                for sample in frame.iter_mut() {
+                    *sample *= gain;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ducking(on_speech: bool) -> Arc<Ducking> {
+        let ducking = Arc::new(Ducking::default());
+        ducking.configure(Some(
+            DuckSettings::new(12.0, 10.0, 20.0, on_speech).unwrap(),
+        ));
+        ducking
+    }
+
+    fn run(stage: &mut DuckStage, frames: usize) -> Vec<f32> {
+        let mut block = vec![1.0; frames * PCM_CHANNELS];
+        stage.process(&mut block, 0, None);
+        block
+    }
+
+    #[test]
+    fn background_ramps_down_while_a_primary_plays() {
+        let ducking = ducking(false);
+        let mut stage = ducking.stage();
+        assert_eq!(run(&mut stage, 960), vec![1.0; 960 * PCM_CHANNELS]);
+
+        ducking.primaries.insert(1);
+        assert!(ducking.is_ducking());
+        let block = run(&mut stage, 960);
+        // The 10 ms attack reaches full depth halfway through the block.
+        assert!(block[2] < 1.0 && block[2] > 0.9);
+        assert!((block[960 * PCM_CHANNELS - 1] - from_db(-12.0)).abs() < 1e-6);
+
+        ducking.primaries.remove(&1);
+        run(&mut stage, 480);
+        let block = run(&mut stage, 960);
+        assert!((block[960 * PCM_CHANNELS - 1] - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn received_speech_ducks_only_when_enabled() {
+        for on_speech in [false, true] {
+            let ducking = ducking(on_speech);
+            *ducking.last_speech.lock().unwrap() = Some(Instant::now());
+            assert_eq!(ducking.is_ducking(), on_speech);
+        }
+        let ducking = ducking(true);
+        *ducking.last_speech.lock().unwrap() = Some(Instant::now() - SPEECH_HOLD * 2);
+        assert!(!ducking.is_ducking());
+    }
+
+    #[test]
+    fn disabling_restores_unity_gain() {
+        let ducking = ducking(false);
+        let mut stage = ducking.stage();
+        ducking.primaries.insert(1);
+        run(&mut stage, 960);
+        ducking.configure(None);
+        assert_eq!(run(&mut stage, 10), vec![1.0; 10 * PCM_CHANNELS]);
+    }
+
+    #[test]
+    fn invalid_settings_are_rejected() {
+        assert!(DuckRole::parse("music").is_err());
+        assert!(DuckSettings::new(-3.0, 10.0, 10.0, false).is_err());
+        assert!(DuckSettings::new(12.0, f32::NAN, 10.0, false).is_err());
+    }
+}
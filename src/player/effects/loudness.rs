@@ -0,0 +1,381 @@
+use super::Processor;
+use super::dsp::{Biquad, Coefficients, from_db};
+use crate::player::input::decode::{DecodedInput, PCM_CHANNELS, PCM_SAMPLE_RATE, PcmSource};
+use pyo3::PyResult;
+use pyo3::exceptions::PyValueError;
+use songbird::input::AudioStreamError;
+use std::collections::VecDeque;
+
+/// BS.1770 K-weighting pre-filter (high shelf) at 48 kHz.
+const K_SHELF: Coefficients = Coefficients::new(
+    1.535_124_9,
+    -2.691_696_2,
+    1.198_392_8,
+    -1.690_659_3,
+    0.732_480_8,
+);
+
+/// BS.1770 K-weighting RLB high-pass at 48 kHz.
+const K_HIGH_PASS: Coefficients = Coefficients::new(1.0, -2.0, 1.0, -1.990_047_5, 0.990_072_25);
+
+/// Gating blocks are 400 ms long and overlap by 75%, so they are built from
+/// four 100 ms steps.
+const STEP_FRAMES: usize = PCM_SAMPLE_RATE as usize / 10;
+const STEPS_PER_BLOCK: usize = 4;
+
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+const RELATIVE_GATE_LU: f64 = -10.0;
+
+/// Gating blocks are counted in 0.1 LU bins from the absolute gate up to
+/// +5 LUFS, as libebur128 does, so memory and gating work stay constant
+/// however long a stream plays.
+const BINS_PER_LU: f64 = 10.0;
+const HISTOGRAM_BINS: usize = 750;
+
+/// Loudness that ReplayGain gains are relative to (89 dB SPL).
+const REPLAYGAIN_REFERENCE_LUFS: f32 = -18.0;
+
+/// Range of gain applied by normalization, in dB.
+const MIN_GAIN_DB: f32 = -24.0;
+const MAX_GAIN_DB: f32 = 12.0;
+
+/// Time constant for adaptive gain changes on streams.
+const ADAPTIVE_SECS: f32 = 3.0;
+
+/// Loudness normalization requested for a track.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Normalize {
+    mode: NormalizeMode,
+    target_lufs: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NormalizeMode {
+    R128,
+    ReplayGain,
+}
+
+impl Normalize {
+    pub(crate) fn parse(mode: &str, target_lufs: f32) -> PyResult<Self> {
+        let mode = match mode {
+            "r128" => NormalizeMode::R128,
+            "replaygain" => NormalizeMode::ReplayGain,
+            _ => {
+                return Err(PyValueError::new_err(
+                    "normalize must be \"r128\", \"replaygain\", or None",
+                ));
+            }
+        };
+        if !target_lufs.is_finite() || target_lufs > 0.0 {
+            return Err(PyValueError::new_err(
+                "target_lufs must be a finite value of at most 0",
+            ));
+        }
+        Ok(Self { mode, target_lufs })
+    }
+}
+
+/// Gain stage that brings a track to the target loudness.
+///
+/// The gain comes from ReplayGain tags when requested and present, otherwise
+/// from a pre-scan of in-memory inputs, otherwise it adapts while playing.
+/// Files and network streams are never pre-scanned, since that would read
+/// them in full before playback starts.
+pub(super) struct Normalizer {
+    target_lufs: f32,
+    gain_db: f32,
+    meter: Option<LoudnessMeter>,
+}
+
+impl Normalizer {
+    /// `in_memory` marks inputs whose whole payload is already loaded.
+    pub(super) async fn prepare(
+        normalize: Normalize,
+        mut input: DecodedInput,
+        in_memory: bool,
+    ) -> Result<(DecodedInput, Self), AudioStreamError> {
+        let target_lufs = normalize.target_lufs;
+        if normalize.mode == NormalizeMode::ReplayGain
+            && let Some(gain_db) = input.replay_gain_db()
+        {
+            let gain_db = gain_db + target_lufs - REPLAYGAIN_REFERENCE_LUFS;
+            return Ok((input, Self::fixed(target_lufs, gain_db)));
+        }
+        if !in_memory || !input.is_seekable() {
+            return Ok((
+                input,
+                Self {
+                    target_lufs,
+                    gain_db: 0.0,
+                    meter: Some(LoudnessMeter::new()),
+                },
+            ));
+        }
+        tokio::task::spawn_blocking(move || {
+            let loudness = scan(&mut input)?;
+            let gain_db = loudness.map_or(0.0, |lufs| target_lufs - lufs);
+            Ok((input, Self::fixed(target_lufs, gain_db)))
+        })
+        .await
+        .map_err(|err| AudioStreamError::Fail(Box::new(err)))?
+        .map_err(|err: std::io::Error| AudioStreamError::Fail(Box::new(err)))
+    }
+
+    fn fixed(target_lufs: f32, gain_db: f32) -> Self {
+        Self {
+            target_lufs,
+            gain_db: gain_db.clamp(MIN_GAIN_DB, MAX_GAIN_DB),
+            meter: None,
+        }
+    }
+}
+
+impl Processor for Normalizer {
+    fn process(&mut self, block: &mut [f32], _position: u64, _len: Option<u64>) {
+        if let Some(meter) = self.meter.as_mut() {
+            meter.push(block);
+            if let Some(lufs) = meter.integrated_lufs() {
+                let wanted = (self.target_lufs - lufs).clamp(MIN_GAIN_DB, MAX_GAIN_DB);
+                let frames = (block.len() / PCM_CHANNELS) as f32;
+                let keep = (-frames / (ADAPTIVE_SECS * PCM_SAMPLE_RATE as f32)).exp();
+                self.gain_db = wanted + keep * (self.gain_db - wanted);
+            }
+        }
+        let gain = from_db(self.gain_db);
+        for sample in block.iter_mut() {
+            *sample *= gain;
+        }
+    }
+}
+
+/// Measure the whole input, then rewind it.
+fn scan(input: &mut DecodedInput) -> std::io::Result<Option<f32>> {
+    let mut meter = LoudnessMeter::new();
+    let mut buffer = vec![0.0; STEP_FRAMES * PCM_CHANNELS];
+    loop {
+        let read = input.read_frames(&mut buffer);
+        if read == 0 {
+            break;
+        }
+        meter.push(&buffer[..read * PCM_CHANNELS]);
+    }
+    input.seek_frame(0)?;
+    Ok(meter.integrated_lufs())
+}
+
+/// Integrated loudness meter following ITU-R BS.1770 / EBU R128.
+pub(super) struct LoudnessMeter {
+    shelf: Biquad,
+    high_pass: Biquad,
+    scratch: Vec<f32>,
+    step_energy: f64,
+    step_frames: usize,
+    steps: VecDeque<f64>,
+    /// Count and summed mean-square energy of the gating blocks above the
+    /// absolute gate, by loudness bin.
+    bins: Vec<(u64, f64)>,
+    /// Integrated loudness as of the last completed block.
+    integrated: Option<f32>,
+}
+
+impl LoudnessMeter {
+    pub(super) fn new() -> Self {
+        Self {
+            shelf: Biquad::new(K_SHELF),
+            high_pass: Biquad::new(K_HIGH_PASS),
+            scratch: Vec::new(),
+            step_energy: 0.0,
+            step_frames: 0,
+            steps: VecDeque::with_capacity(STEPS_PER_BLOCK),
+            bins: vec![(0, 0.0); HISTOGRAM_BINS],
+            integrated: None,
+        }
+    }
+
+    /// Measure interleaved stereo samples.
+    pub(super) fn push(&mut self, samples: &[f32]) {
+        self.scratch.clear();
+        self.scratch.extend_from_slice(samples);
+        self.shelf.process(&mut self.scratch);
+        self.high_pass.process(&mut self.scratch);
+        let scratch = std::mem::take(&mut self.scratch);
+        for frame in scratch.chunks_exact(PCM_CHANNELS) {
+            self.step_energy += frame.iter().map(|&s| (s as f64) * (s as f64)).sum::<f64>();
+            self.step_frames += 1;
+            if self.step_frames == STEP_FRAMES {
+                self.complete_step();
+            }
+        }
+        self.scratch = scratch;
+    }
+
+    fn complete_step(&mut self) {
+        if self.steps.len() == STEPS_PER_BLOCK {
+            self.steps.pop_front();
+        }
+        self.steps.push_back(self.step_energy / STEP_FRAMES as f64);
+        self.step_energy = 0.0;
+        self.step_frames = 0;
+        if self.steps.len() == STEPS_PER_BLOCK {
+            let block = self.steps.iter().sum::<f64>() / STEPS_PER_BLOCK as f64;
+            if block > energy(ABSOLUTE_GATE_LUFS) {
+                let bin = &mut self.bins[bin_index(lufs(block))];
+                bin.0 += 1;
+                bin.1 += block;
+                self.integrated = self.measure();
+            }
+        }
+    }
+
+    /// Gated integrated loudness in LUFS, or None if nothing is above the
+    /// absolute gate yet.
+    pub(super) fn integrated_lufs(&self) -> Option<f32> {
+        self.integrated
+    }
+
+    /// Apply the relative gate to the blocks counted so far. Blocks in the
+    /// bin holding the gate are kept, so the gate is exact to one bin.
+    fn measure(&self) -> Option<f32> {
+        let absolute = gated_mean(&self.bins)?;
+        let relative = lufs(absolute) + RELATIVE_GATE_LU;
+        let first = if relative > ABSOLUTE_GATE_LUFS {
+            bin_index(relative)
+        } else {
+            0
+        };
+        let mean = gated_mean(&self.bins[first..])?;
+        Some(lufs(mean) as f32)
+    }
+}
+
+fn bin_index(lufs: f64) -> usize {
+    (((lufs - ABSOLUTE_GATE_LUFS) * BINS_PER_LU) as usize).min(HISTOGRAM_BINS - 1)
+}
+
+fn gated_mean(bins: &[(u64, f64)]) -> Option<f64> {
+    let (count, sum) = bins
+        .iter()
+        .fold((0, 0.0), |(count, sum), bin| (count + bin.0, sum + bin.1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+fn lufs(energy: f64) -> f64 {
+    -0.691 + 10.0 * energy.log10()
+}
+
+fn energy(lufs: f64) -> f64 {
+    10_f64.powf((lufs + 0.691) / 10.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn sine(frequency: f32, amplitude: f32, seconds: usize) -> Vec<f32> {
+        (0..PCM_SAMPLE_RATE as usize * seconds)
+            .flat_map(|i| {
+                let t = i as f32 / PCM_SAMPLE_RATE as f32;
+                let sample = amplitude * (2.0 * PI * frequency * t).sin();
+                [sample, sample]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn full_scale_1khz_sine_measures_zero_lufs() {
+        // EBU Tech 3341: a stereo 1 kHz sine at X dBFS reads X LUFS.
+        let mut meter = LoudnessMeter::new();
+        for chunk in sine(1_000.0, 1.0, 3).chunks(960 * PCM_CHANNELS) {
+            meter.push(chunk);
+        }
+        let loudness = meter.integrated_lufs().unwrap();
+        assert!(loudness.abs() < 0.1, "{loudness}");
+    }
+
+    #[test]
+    fn quieter_signals_measure_proportionally_lower() {
+        let mut meter = LoudnessMeter::new();
+        meter.push(&sine(1_000.0, 0.1, 2));
+        let loudness = meter.integrated_lufs().unwrap();
+        assert!((loudness + 20.0).abs() < 0.1, "{loudness}");
+    }
+
+    #[test]
+    fn relative_gate_ignores_quiet_passages() {
+        let mut meter = LoudnessMeter::new();
+        meter.push(&sine(1_000.0, 0.1, 10));
+        meter.push(&sine(1_000.0, 0.01, 10));
+        let loudness = meter.integrated_lufs().unwrap();
+        assert!((loudness + 20.0).abs() < 0.1, "{loudness}");
+        assert_eq!(meter.bins.len(), HISTOGRAM_BINS);
+    }
+
+    #[test]
+    fn adaptive_gain_converges_on_the_target() {
+        let mut normalizer = Normalizer {
+            target_lufs: -13.0,
+            gain_db: 0.0,
+            meter: Some(LoudnessMeter::new()),
+        };
+        let mut last = Vec::new();
+        for mut chunk in sine(1_000.0, 0.1, 30)
+            .chunks(960 * PCM_CHANNELS)
+            .map(<[f32]>::to_vec)
+        {
+            normalizer.process(&mut chunk, 0, None);
+            last = chunk;
+        }
+        // -20 LUFS input needs +7 dB, taking a 0.1 sine to about 0.224.
+        let peak = last.iter().fold(0.0_f32, |peak, s| peak.max(s.abs()));
+        assert!((peak - 0.224).abs() < 0.005, "{peak}");
+    }
+
+    #[test]
+    fn invalid_modes_are_rejected() {
+        assert!(Normalize::parse("r128", -14.0).is_ok());
+        assert!(Normalize::parse("rms", -14.0).is_err());
+        assert!(Normalize::parse("r128", 3.0).is_err());
+    }
+
+    #[test]
+    fn silence_has_no_integrated_loudness() {
+        let mut meter = LoudnessMeter::new();
+        meter.push(&vec![0.0; PCM_SAMPLE_RATE as usize * PCM_CHANNELS]);
+        assert_eq!(meter.integrated_lufs(), None);
+    }
+
+    #[test]
+    fn only_in_memory_inputs_are_pre_scanned() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let normalize = Normalize::parse("r128", -10.0).unwrap();
+        let prepare = |in_memory| {
+            let bytes: Vec<u8> = sine(1_000.0, 0.1, 1)
+                .iter()
+                .flat_map(|s| s.to_le_bytes())
+                .collect();
+            let raw = songbird::input::RawAdapter::new(
+                std::io::Cursor::new(bytes),
+                PCM_SAMPLE_RATE,
+                PCM_CHANNELS as u32,
+            );
+            runtime.block_on(async {
+                let input = DecodedInput::open(raw.into()).await.unwrap();
+                Normalizer::prepare(normalize, input, in_memory)
+                    .await
+                    .unwrap()
+            })
+        };
+
+        let (_, scanned) = prepare(true);
+        assert!(scanned.meter.is_none());
+        assert!((scanned.gain_db - 10.0).abs() < 0.1, "{}", scanned.gain_db);
+
+        // A seekable file or HTTP source adapts instead of being read up front.
+        let (_, adaptive) = prepare(false);
+        assert!(adaptive.meter.is_some());
+        assert_eq!(adaptive.gain_db, 0.0);
+    }
+}
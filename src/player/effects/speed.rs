@@ -0,0 +1,482 @@
+use crate::player::input::decode::{PCM_CHANNELS, PCM_SAMPLE_RATE, PcmSource};
+use pyo3::PyResult;
+use pyo3::exceptions::PyValueError;
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub(crate) const MIN_SPEED: f32 = 0.25;
+pub(crate) const MAX_SPEED: f32 = 4.0;
+
+/// WSOLA analysis window (~21 ms); output advances by half a window.
+const WINDOW_FRAMES: usize = 1024;
+const HOP_FRAMES: usize = WINDOW_FRAMES / 2;
+/// How far the analysis window may move to line up with the previous one.
+const TOLERANCE_FRAMES: usize = 256;
+/// Only every few frames are compared when searching for the best overlap.
+const CORRELATION_STRIDE: usize = 4;
+
+const READ_FRAMES: usize = 1024;
+
+/// Playback speed requested for a track.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct SpeedSettings {
+    pub speed: f32,
+    pub preserve_pitch: bool,
+}
+
+impl SpeedSettings {
+    pub(crate) fn new(speed: f32, preserve_pitch: bool) -> PyResult<Self> {
+        if !(MIN_SPEED..=MAX_SPEED).contains(&speed) {
+            return Err(PyValueError::new_err(format!(
+                "speed must be between {MIN_SPEED} and {MAX_SPEED}"
+            )));
+        }
+        Ok(Self {
+            speed,
+            preserve_pitch,
+        })
+    }
+}
+
+/// Speed shared between a `TrackHandle` and the decode path of its track.
+pub(crate) struct SpeedControl {
+    settings: Mutex<SpeedSettings>,
+    /// Position in the source, in frames, of the audio last handed to the mixer.
+    position: AtomicU64,
+}
+
+impl SpeedControl {
+    pub(crate) fn new(settings: SpeedSettings) -> Arc<Self> {
+        Arc::new(Self {
+            settings: Mutex::new(settings),
+            position: AtomicU64::new(0),
+        })
+    }
+
+    pub(crate) fn settings(&self) -> SpeedSettings {
+        *self.settings.lock().unwrap()
+    }
+
+    pub(crate) fn set(&self, settings: SpeedSettings) {
+        *self.settings.lock().unwrap() = settings;
+    }
+
+    /// Playback position in the source, independent of the speed factor.
+    pub(crate) fn position(&self) -> Duration {
+        let frames = self.position.load(Ordering::Relaxed);
+        Duration::from_secs_f64(frames as f64 / PCM_SAMPLE_RATE as f64)
+    }
+}
+
+/// Changes the playback rate of its input.
+///
+/// With `preserve_pitch` the audio is time-stretched with WSOLA; otherwise it
+/// is resampled, which shifts the pitch along with the tempo.
+pub(super) struct SpeedSource {
+    input: Box<dyn PcmSource>,
+    control: Arc<SpeedControl>,
+    preserve_pitch: bool,
+    /// Interleaved source frames not yet fully consumed.
+    buffer: Vec<f32>,
+    input_done: bool,
+    /// Read position within `buffer`, in frames.
+    cursor: f64,
+    /// Where the previous WSOLA segment would naturally continue, within
+    /// `buffer`.
+    continuation: Option<usize>,
+    overlap: Vec<f32>,
+    ready: VecDeque<f32>,
+    tail_flushed: bool,
+    window: Vec<f32>,
+    /// Source position, in frames, of the next output frame.
+    source_position: f64,
+}
+
+impl SpeedSource {
+    pub(super) fn new(input: Box<dyn PcmSource>, control: Arc<SpeedControl>) -> Self {
+        let window = (0..WINDOW_FRAMES)
+            .map(|n| 0.5 - 0.5 * (2.0 * PI * n as f32 / WINDOW_FRAMES as f32).cos())
+            .collect();
+        control.position.store(0, Ordering::Relaxed);
+        Self {
+            preserve_pitch: control.settings().preserve_pitch,
+            input,
+            control,
+            buffer: Vec::new(),
+            input_done: false,
+            cursor: 0.0,
+            continuation: None,
+            overlap: vec![0.0; WINDOW_FRAMES * PCM_CHANNELS],
+            ready: VecDeque::new(),
+            tail_flushed: false,
+            window,
+            source_position: 0.0,
+        }
+    }
+
+    /// Drop buffered audio, keeping the source where it is.
+    fn reset(&mut self) {
+        self.buffer.clear();
+        self.cursor = 0.0;
+        self.continuation = None;
+        self.overlap.fill(0.0);
+        self.ready.clear();
+        self.tail_flushed = false;
+    }
+
+    /// Change mode, keeping buffered source audio and output already
+    /// stretched so no frames are skipped.
+    fn switch_mode(&mut self, preserve_pitch: bool) {
+        if let Some(continuation) = self.continuation.take() {
+            // Resume from the end of the audio already stretched.
+            self.cursor = continuation as f64;
+        }
+        self.overlap.fill(0.0);
+        self.tail_flushed = false;
+        self.preserve_pitch = preserve_pitch;
+    }
+
+    fn buffered_frames(&self) -> usize {
+        self.buffer.len() / PCM_CHANNELS
+    }
+
+    /// Read from the input until `frames` frames are buffered or it ends.
+    fn fill(&mut self, frames: usize) {
+        while self.buffered_frames() < frames && !self.input_done {
+            let start = self.buffer.len();
+            self.buffer.resize(start + READ_FRAMES * PCM_CHANNELS, 0.0);
+            let read = self.input.read_frames(&mut self.buffer[start..]);
+            self.buffer.truncate(start + read * PCM_CHANNELS);
+            self.input_done = read == 0;
+        }
+    }
+
+    fn discard(&mut self, frames: usize) {
+        let frames = frames.min(self.buffered_frames());
+        self.buffer.drain(..frames * PCM_CHANNELS);
+        self.cursor -= frames as f64;
+        if let Some(continuation) = self.continuation.as_mut() {
+            *continuation -= frames;
+        }
+    }
+
+    fn sample(&self, frame: usize, channel: usize) -> f32 {
+        self.buffer
+            .get(frame * PCM_CHANNELS + channel)
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Linear-interpolation resampling, reading `speed` source frames per
+    /// output frame.
+    fn resample(&mut self, out: &mut [f32], speed: f32) -> usize {
+        let mut written = 0;
+        for frame in out.chunks_exact_mut(PCM_CHANNELS) {
+            let index = self.cursor as usize;
+            self.fill(index + 2);
+            if index >= self.buffered_frames() {
+                break;
+            }
+            let fraction = (self.cursor - index as f64) as f32;
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                let a = self.sample(index, channel);
+                let b = if index + 1 < self.buffered_frames() {
+                    self.sample(index + 1, channel)
+                } else {
+                    a
+                };
+                *sample = a + (b - a) * fraction;
+            }
+            self.cursor += speed as f64;
+            written += 1;
+        }
+        self.discard(self.cursor as usize);
+        written
+    }
+
+    /// Waveform-similarity overlap-add time stretching.
+    fn stretch(&mut self, out: &mut [f32], speed: f32) -> usize {
+        while self.ready.len() < out.len() && self.stretch_segment(speed) {}
+        self.emit_ready(out)
+    }
+
+    /// Move stretched output into `out`.
+    fn emit_ready(&mut self, out: &mut [f32]) -> usize {
+        let written = (self.ready.len() / PCM_CHANNELS).min(out.len() / PCM_CHANNELS);
+        for (sample, ready) in out
+            .iter_mut()
+            .zip(self.ready.drain(..written * PCM_CHANNELS))
+        {
+            *sample = ready;
+        }
+        written
+    }
+
+    /// Overlap one more analysis window, completing `HOP_FRAMES` of output.
+    fn stretch_segment(&mut self, speed: f32) -> bool {
+        let nominal = self.cursor.round() as usize;
+        let natural = self.continuation;
+        let search = match natural {
+            Some(_) if speed != 1.0 => TOLERANCE_FRAMES,
+            _ => 0,
+        };
+        self.fill((nominal + search).max(natural.unwrap_or(0)) + WINDOW_FRAMES);
+        if nominal >= self.buffered_frames() {
+            if natural.is_some() && !self.tail_flushed {
+                // Emit the decaying half of the last window.
+                self.tail_flushed = true;
+                self.ready
+                    .extend(&self.overlap[..HOP_FRAMES * PCM_CHANNELS]);
+                return true;
+            }
+            return false;
+        }
+        let start = match natural {
+            Some(natural) => self.best_overlap(nominal, search, natural),
+            None => nominal,
+        };
+        for n in 0..WINDOW_FRAMES {
+            // The first window starts at full gain so playback does not fade in.
+            let weight = if natural.is_none() && n < HOP_FRAMES {
+                1.0
+            } else {
+                self.window[n]
+            };
+            for channel in 0..PCM_CHANNELS {
+                self.overlap[n * PCM_CHANNELS + channel] +=
+                    weight * self.sample(start + n, channel);
+            }
+        }
+        self.ready
+            .extend(self.overlap.drain(..HOP_FRAMES * PCM_CHANNELS));
+        self.overlap.resize(WINDOW_FRAMES * PCM_CHANNELS, 0.0);
+        self.continuation = Some(start + HOP_FRAMES);
+        self.cursor += HOP_FRAMES as f64 * speed as f64;
+        let consumed = (self.cursor as usize)
+            .saturating_sub(TOLERANCE_FRAMES)
+            .min(start + HOP_FRAMES);
+        self.discard(consumed);
+        true
+    }
+
+    /// Start of the window near `nominal` that best continues the waveform
+    /// at `natural`.
+    fn best_overlap(&self, nominal: usize, search: usize, natural: usize) -> usize {
+        let mono = |frame: usize| {
+            (0..PCM_CHANNELS)
+                .map(|c| self.sample(frame, c))
+                .sum::<f32>()
+        };
+        let mut best = (f32::NEG_INFINITY, nominal);
+        for start in nominal.saturating_sub(search)..=nominal + search {
+            let (mut correlation, mut energy) = (0.0, 0.0);
+            for n in (0..WINDOW_FRAMES).step_by(CORRELATION_STRIDE) {
+                let candidate = mono(start + n);
+                correlation += candidate * mono(natural + n);
+                energy += candidate * candidate;
+            }
+            let score = correlation / energy.sqrt().max(1e-6);
+            if score > best.0 {
+                best = (score, start);
+            }
+        }
+        best.1
+    }
+}
+
+impl PcmSource for SpeedSource {
+    fn read_frames(&mut self, out: &mut [f32]) -> usize {
+        let settings = self.control.settings();
+        if settings.preserve_pitch != self.preserve_pitch {
+            self.switch_mode(settings.preserve_pitch);
+        }
+        let written = if self.preserve_pitch {
+            self.stretch(out, settings.speed)
+        } else {
+            // Output stretched before a switch to resampling plays first.
+            let ready = self.emit_ready(out);
+            ready + self.resample(&mut out[ready * PCM_CHANNELS..], settings.speed)
+        };
+        self.source_position += written as f64 * settings.speed as f64;
+        self.control
+            .position
+            .store(self.source_position as u64, Ordering::Relaxed);
+        written
+    }
+
+    fn seek_frame(&mut self, frame: u64) -> io::Result<()> {
+        self.input.seek_frame(frame)?;
+        self.reset();
+        self.input_done = false;
+        self.source_position = frame as f64;
+        self.control.position.store(frame, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    fn len_frames(&self) -> Option<u64> {
+        self.input.len_frames()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Ramp {
+        frames: usize,
+        position: usize,
+    }
+
+    impl PcmSource for Ramp {
+        fn read_frames(&mut self, out: &mut [f32]) -> usize {
+            let count = (out.len() / PCM_CHANNELS).min(self.frames - self.position);
+            for (i, frame) in out.chunks_exact_mut(PCM_CHANNELS).take(count).enumerate() {
+                frame.fill(((self.position + i) % 1000) as f32 / 1000.0);
+            }
+            self.position += count;
+            count
+        }
+
+        fn seek_frame(&mut self, frame: u64) -> io::Result<()> {
+            self.position = frame as usize;
+            Ok(())
+        }
+
+        fn is_seekable(&self) -> bool {
+            true
+        }
+    }
+
+    fn source(frames: usize, speed: f32, preserve_pitch: bool) -> (SpeedSource, Arc<SpeedControl>) {
+        let control = SpeedControl::new(SpeedSettings::new(speed, preserve_pitch).unwrap());
+        let input = Box::new(Ramp {
+            frames,
+            position: 0,
+        });
+        (SpeedSource::new(input, control.clone()), control)
+    }
+
+    fn drain(source: &mut SpeedSource) -> Vec<f32> {
+        let mut output = Vec::new();
+        let mut block = vec![0.0; 960 * PCM_CHANNELS];
+        loop {
+            let read = source.read_frames(&mut block);
+            if read == 0 {
+                return output;
+            }
+            output.extend_from_slice(&block[..read * PCM_CHANNELS]);
+        }
+    }
+
+    #[test]
+    fn resampling_skips_frames_when_faster() {
+        let (mut source, control) = source(9_600, 2.0, false);
+        let output = drain(&mut source);
+        assert_eq!(output.len() / PCM_CHANNELS, 4_800);
+        assert_eq!(output[2..6], [0.002, 0.002, 0.004, 0.004]);
+        assert_eq!(control.position(), Duration::from_millis(200));
+    }
+
+    #[test]
+    fn stretching_at_normal_speed_is_transparent() {
+        let (mut source, _) = source(9_600, 1.0, true);
+        let output = drain(&mut source);
+        assert!(output.len() / PCM_CHANNELS >= 9_600);
+        for (i, frame) in output.chunks_exact(PCM_CHANNELS).take(9_600).enumerate() {
+            let expected = (i % 1000) as f32 / 1000.0;
+            assert!((frame[0] - expected).abs() < 1e-4, "frame {i}");
+        }
+    }
+
+    #[test]
+    fn stretching_changes_duration_not_sample_rate() {
+        let (mut source, control) = source(48_000, 1.5, true);
+        let frames = drain(&mut source).len() / PCM_CHANNELS;
+        assert!(
+            (frames as i64 - 32_000).abs() < WINDOW_FRAMES as i64,
+            "{frames}"
+        );
+        let position = control.position().as_secs_f32();
+        assert!((position - 1.0).abs() < 0.05, "{position}");
+    }
+
+    #[test]
+    fn seeking_reports_the_source_position() {
+        let (mut source, control) = source(48_000, 1.25, true);
+        source.seek_frame(24_000).unwrap();
+        assert_eq!(control.position(), Duration::from_millis(500));
+        let mut block = vec![0.0; 960 * PCM_CHANNELS];
+        assert_eq!(source.read_frames(&mut block), 960);
+        assert_eq!(control.position(), Duration::from_millis(525));
+    }
+
+    #[test]
+    fn stretching_keeps_the_pitch() {
+        struct Sine(usize);
+
+        impl PcmSource for Sine {
+            fn read_frames(&mut self, out: &mut [f32]) -> usize {
+                let count = (out.len() / PCM_CHANNELS).min(48_000 - self.0);
+                for (i, frame) in out.chunks_exact_mut(PCM_CHANNELS).take(count).enumerate() {
+                    let t = (self.0 + i) as f32 / PCM_SAMPLE_RATE as f32;
+                    frame.fill((2.0 * PI * 440.0 * t).sin());
+                }
+                self.0 += count;
+                count
+            }
+
+            fn seek_frame(&mut self, frame: u64) -> io::Result<()> {
+                self.0 = frame as usize;
+                Ok(())
+            }
+
+            fn is_seekable(&self) -> bool {
+                true
+            }
+        }
+
+        let control = SpeedControl::new(SpeedSettings::new(1.5, true).unwrap());
+        let mut source = SpeedSource::new(Box::new(Sine(0)), control);
+        let output = drain(&mut source);
+        let left: Vec<f32> = output.iter().step_by(PCM_CHANNELS).copied().collect();
+        let crossings = left
+            .windows(2)
+            .filter(|w| (w[0] < 0.0) != (w[1] < 0.0))
+            .count();
+        let frequency = crossings as f32 / 2.0 / (left.len() as f32 / PCM_SAMPLE_RATE as f32);
+        assert!((frequency - 440.0).abs() < 15.0, "{frequency}");
+    }
+
+    #[test]
+    fn switching_modes_keeps_buffered_audio() {
+        let (mut source, control) = source(9_600, 1.0, false);
+        let mut output = Vec::new();
+        let mut block = vec![0.0; 960 * PCM_CHANNELS];
+        for preserve_pitch in [true, false, true] {
+            let read = source.read_frames(&mut block);
+            output.extend_from_slice(&block[..read * PCM_CHANNELS]);
+            control.set(SpeedSettings::new(1.0, preserve_pitch).unwrap());
+        }
+        output.extend(drain(&mut source));
+
+        assert!(output.len() / PCM_CHANNELS >= 9_600);
+        for (i, frame) in output.chunks_exact(PCM_CHANNELS).take(9_600).enumerate() {
+            let expected = (i % 1000) as f32 / 1000.0;
+            assert!((frame[0] - expected).abs() < 1e-4, "frame {i}");
+        }
+    }
+
+    #[test]
+    fn out_of_range_speeds_are_rejected() {
+        assert!(SpeedSettings::new(0.1, true).is_err());
+        assert!(SpeedSettings::new(4.5, false).is_err());
+    }
+}
@@ -1,10 +1,12 @@
 use crate::error::PyControlError;
 use crate::model::PyFuture;
+use crate::player::effects::{PyEffect, SpeedSettings};
+use crate::player::track::TrackData;
 use nonmax::NonMaxU32;
-use pyo3::{PyResult, Python, pyclass, pymethods};
+use pyo3::{Py, PyResult, Python, pyclass, pymethods};
 use pyo3_async_runtimes::tokio::future_into_py;
 use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
-use songbird::tracks::TrackHandle;
+use songbird::tracks::{LoopState, PlayMode, TrackHandle};
 use std::time::Duration;
 
 #[gen_stub_pyclass]
@@ -23,6 +25,27 @@ pub struct PyTrackHandle {
     inner: TrackHandle,
 }
 
+#[gen_stub_pyclass]
+#[pyclass(
+    name = "TrackState",
+    module = "discord.ext.songbird.native.player",
+    frozen,
+    skip_from_py_object
+)]
+/// Snapshot of a track's playback state.
+///
+/// Notes
+/// -----
+/// Returned by `TrackHandle.get_info`.
+pub struct PyTrackState {
+    playing: &'static str,
+    volume: f32,
+    position: Duration,
+    play_time: Duration,
+    loops: Option<u32>,
+    speed: f32,
+}
+
 #[gen_stub_pymethods]
 #[pymethods]
 impl PyTrackHandle {
@@ -87,6 +110,124 @@ impl PyTrackHandle {
         Ok(())
     }
 
+    /// Effects attached to this track.
+    ///
+    /// Returns
+    /// -------
+    /// list[Effect]
+    ///     The same objects passed to `Track.effects`; changing their
+    ///     properties adjusts the playing track.
+    #[getter]
+    fn effects(&self, py: Python) -> Vec<Py<PyEffect>> {
+        self.inner
+            .data::<TrackData>()
+            .effects
+            .iter()
+            .map(|effect| effect.clone_ref(py))
+            .collect()
+    }
+
+    /// Change the playback speed.
+    ///
+    /// Parameters
+    /// ----------
+    /// speed : float
+    ///     Playback rate between 0.25 and 4.0; 1.0 is normal speed.
+    /// preserve_pitch : bool | None, optional
+    ///     Time-stretch (True) or resample (False). None keeps the current
+    ///     mode.
+    ///
+    /// Returns
+    /// -------
+    /// None
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If `speed` is out of range.
+    /// PyControlError
+    ///     If the track was not created with `Track.speed`.
+    ///
+    /// Examples
+    /// --------
+    /// ```python
+    /// handle = await vc.play(player.Track(source).speed())
+    /// handle.set_speed(1.25)
+    /// ```
+    #[pyo3(signature = (speed, *, preserve_pitch = None))]
+    fn set_speed(&self, speed: f32, preserve_pitch: Option<bool>) -> PyResult<()> {
+        let data = self.inner.data::<TrackData>();
+        let control = data.speed.as_ref().ok_or_else(|| {
+            PyControlError::new_err(
+                "speed control is not enabled; create the track with Track.speed()",
+            )
+        })?;
+        let preserve_pitch = preserve_pitch.unwrap_or(control.settings().preserve_pitch);
+        control.set(SpeedSettings::new(speed, preserve_pitch)?);
+        Ok(())
+    }
+
+    /// Current playback speed.
+    ///
+    /// Returns
+    /// -------
+    /// float
+    ///     1.0 for tracks created without `Track.speed`.
+    #[getter]
+    fn speed(&self) -> f32 {
+        self.inner
+            .data::<TrackData>()
+            .speed
+            .as_ref()
+            .map_or(1.0, |control| control.settings().speed)
+    }
+
+    /// Fetch the current playback state.
+    ///
+    /// Returns
+    /// -------
+    /// Future[TrackState]
+    ///
+    /// Raises
+    /// ------
+    /// PyControlError
+    ///     If the track has ended.
+    ///
+    /// Notes
+    /// -----
+    /// `position` is measured in the source, so it advances faster or slower
+    /// than `play_time` when the speed is changed.
+    fn get_info<'py>(&self, py: Python<'py>) -> PyResult<PyFuture<'py, PyTrackState>> {
+        let inner = self.inner.clone();
+        future_into_py(py, async move {
+            let state = inner
+                .get_info()
+                .await
+                .map_err(|err| PyControlError::new_err(err.to_string()))?;
+            let speed = inner.data::<TrackData>().speed.clone();
+            Ok(PyTrackState {
+                playing: match state.playing {
+                    PlayMode::Play => "play",
+                    PlayMode::Pause => "pause",
+                    PlayMode::Stop => "stop",
+                    PlayMode::End => "end",
+                    _ => "errored",
+                },
+                volume: state.volume,
+                position: speed
+                    .as_ref()
+                    .map_or(state.position, |control| control.position()),
+                play_time: state.play_time,
+                loops: match state.loops {
+                    LoopState::Infinite => None,
+                    LoopState::Finite(remaining) => Some(remaining.get()),
+                },
+                speed: speed.map_or(1.0, |control| control.settings().speed),
+            })
+        })
+        .map(|x| x.into())
+    }
+
     fn loop_for(&self, times: usize) -> PyResult<()> {
         let times = u32::try_from(times)
             .ok()
@@ -106,3 +247,83 @@ impl PyTrackHandle {
         Self { inner }
     }
 }
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl PyTrackState {
+    /// Play mode of the track.
+    ///
+    /// Returns
+    /// -------
+    /// {"play", "pause", "stop", "end", "errored"}
+    #[getter]
+    #[gen_stub(override_return_type(
+        type_repr = "typing.Literal[\"play\", \"pause\", \"stop\", \"end\", \"errored\"]",
+        imports = ("typing")
+    ))]
+    fn playing(&self) -> &'static str {
+        self.playing
+    }
+
+    /// Volume multiplier of the track.
+    ///
+    /// Returns
+    /// -------
+    /// float
+    #[getter]
+    fn volume(&self) -> f32 {
+        self.volume
+    }
+
+    /// Playback position in the source, accounting for the speed factor.
+    ///
+    /// Returns
+    /// -------
+    /// datetime.timedelta
+    #[getter]
+    fn position(&self) -> Duration {
+        self.position
+    }
+
+    /// Total time the track has been playing, unaffected by seeks or speed.
+    ///
+    /// Returns
+    /// -------
+    /// datetime.timedelta
+    #[getter]
+    fn play_time(&self) -> Duration {
+        self.play_time
+    }
+
+    /// Remaining loops, or None when looping forever.
+    ///
+    /// Returns
+    /// -------
+    /// int | None
+    #[getter]
+    fn loops(&self) -> Option<u32> {
+        self.loops
+    }
+
+    /// Playback speed.
+    ///
+    /// Returns
+    /// -------
+    /// float
+    #[getter]
+    fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Return a debug representation.
+    ///
+    /// Returns
+    /// -------
+    /// str
+    fn __repr__(&self) -> String {
+        format!(
+            "TrackState(playing={:?}, position={:?}, speed={})",
+            self.playing, self.position, self.speed
+        )
+    }
+}
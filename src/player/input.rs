@@ -1,10 +1,22 @@
 pub(crate) mod audio;
+pub mod clip;
+pub mod concat;
 mod data;
+pub mod dca;
+pub(crate) mod decode;
+pub mod encode;
+pub mod lazy;
+pub mod mix;
 pub mod opus;
 pub mod pcm;
+pub mod relay;
+mod source;
 pub mod stream;
 
-use pyo3::{Bound, PyAny, PyResult, pyclass, pymethods};
+use crate::player::input::source::{PcmFormat, PySourceCompose};
+use pyo3::exceptions::{PyNotImplementedError, PyValueError};
+use pyo3::types::{PyAnyMethods, PyDict, PyTuple};
+use pyo3::{Bound, PyAny, PyResult, intern, pyclass, pymethods};
 use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
 use songbird::input::{Compose, Input, LiveInput};
 
@@ -20,6 +32,28 @@ use songbird::input::{Compose, Input, LiveInput};
 /// Notes
 /// -----
 /// Concrete inputs are provided under `discord.ext.songbird.player.input`.
+///
+/// Python subclasses can provide their own source by implementing
+/// `read(n) -> bytes`. Implement `seek(pos) -> int` to make the source
+/// seekable and `byte_len() -> int | None` to report its total size. The
+/// bytes are probed as an encoded container unless `pcm_format` declares
+/// interleaved float32 PCM.
+///
+/// Examples
+/// --------
+/// ```python
+/// class ToneInput(InputBase):
+///     pcm_format = (48000, 2)
+///
+///     def __init__(self, data: bytes):
+///         self.buffer = io.BytesIO(data)
+///
+///     def read(self, n: int) -> bytes:
+///         return self.buffer.read(n)
+///
+///     def seek(self, pos: int) -> int:
+///         return self.buffer.seek(pos)
+/// ```
 pub struct PyInputBase;
 
 #[pyclass(
@@ -47,9 +81,55 @@ pub enum ComposeValue {
 #[gen_stub_pymethods]
 #[pymethods]
 impl PyInputBase {
+    #[gen_stub(override_return_type(type_repr = "typing.Self", imports = ("typing")))]
+    #[new]
+    #[pyo3(signature = (*_args, **_kwargs))]
+    /// Create an input base for a Python subclass.
+    ///
+    /// Notes
+    /// -----
+    /// Arguments are accepted so subclasses can define their own `__init__`.
+    fn new_py(_args: &Bound<PyTuple>, _kwargs: Option<&Bound<PyDict>>) -> Self {
+        Self::new()
+    }
+
+    /// Interleaved float32 PCM layout of a Python source.
+    ///
+    /// Notes
+    /// -----
+    /// Override with `(sample_rate, channels)` when `read` returns raw PCM.
+    /// Leave as `None` for encoded containers, which are detected from the
+    /// stream contents.
+    #[classattr]
+    fn pcm_format() -> Option<(u32, u32)> {
+        None
+    }
+
     #[gen_stub(skip)]
-    fn _compose(&self, _current_loop: Bound<PyAny>) -> PyResult<PyCompose> {
-        Err(pyo3::exceptions::PyNotImplementedError::new_err(()))
+    fn _compose(slf: &Bound<Self>, _current_loop: Bound<PyAny>) -> PyResult<PyCompose> {
+        let py = slf.py();
+        if !slf.hasattr(intern!(py, "read"))? {
+            return Err(PyNotImplementedError::new_err(
+                "InputBase subclasses must implement read(n)",
+            ));
+        }
+        let pcm = slf
+            .getattr(intern!(py, "pcm_format"))?
+            .extract::<Option<(u32, u32)>>()?
+            .map(|(sample_rate, channels)| {
+                if sample_rate == 0 || channels == 0 {
+                    return Err(PyValueError::new_err(
+                        "pcm_format must contain a positive sample rate and channel count",
+                    ));
+                }
+                Ok(PcmFormat {
+                    sample_rate,
+                    channels,
+                })
+            })
+            .transpose()?;
+        let compose = PySourceCompose::new(slf.clone().into_any().unbind(), pcm);
+        Ok(PyCompose::new_lazy(Box::new(compose)))
     }
 }
 
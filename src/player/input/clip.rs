@@ -0,0 +1,355 @@
+use crate::player::input::decode::{
+    DecodedInput, PCM_CHANNELS, PCM_SAMPLE_RATE, PcmSource, PcmStream, compose_inputs,
+    duration_frames,
+};
+use crate::player::input::{PyCompose, PyInputBase};
+use async_trait::async_trait;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::{Bound, Py, PyAny, PyResult, PyTraverseError, PyVisit, Python, pyclass, pymethods};
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use songbird::input::core::io::MediaSource;
+use songbird::input::{AudioStream, AudioStreamError, Compose, RawAdapter};
+use std::io;
+use std::time::Duration;
+
+#[gen_stub_pyclass]
+#[pyclass(
+    name = "ClipInput",
+    extends = PyInputBase,
+    module = "discord.ext.songbird.native.player",
+    skip_from_py_object
+)]
+/// Input that plays a time range of another input.
+///
+/// Notes
+/// -----
+/// The inner input is decoded to 48 kHz stereo. Playback starts exactly at
+/// `start` and the track ends exactly at `end`, so no audio outside the range
+/// is heard.
+///
+/// Examples
+/// --------
+/// ```python
+/// import datetime
+///
+/// clip = player.ClipInput(
+///     player.AudioInput(recording),
+///     start=datetime.timedelta(seconds=12),
+///     end=datetime.timedelta(seconds=14, milliseconds=500),
+/// )
+/// await vc.play(player.Track(clip))
+/// ```
+pub struct PyClipInput {
+    input: Option<Py<PyAny>>,
+    start: Duration,
+    end: Option<Duration>,
+}
+
+struct ClipCompose {
+    input: Py<PyAny>,
+    current_loop: Py<PyAny>,
+    start: u64,
+    end: Option<u64>,
+}
+
+struct ClipSource<S> {
+    input: S,
+    start: u64,
+    end: Option<u64>,
+    /// Frame position relative to `start`.
+    position: u64,
+    primed: bool,
+    scratch: Vec<f32>,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl PyClipInput {
+    #[gen_stub(override_return_type(type_repr = "typing.Self", imports = ("typing")))]
+    #[new]
+    #[pyo3(signature = (input, *, start = None, end = None))]
+    /// Create a clipped input.
+    ///
+    /// Parameters
+    /// ----------
+    /// input : InputBase
+    ///     The input to clip.
+    /// start : datetime.timedelta | None, optional
+    ///     Position to start playback from. Defaults to the beginning.
+    /// end : datetime.timedelta | None, optional
+    ///     Position to end playback at. Defaults to the end of `input`.
+    ///
+    /// Returns
+    /// -------
+    /// ClipInput
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If `end` is not after `start`.
+    fn new(
+        input: Bound<PyInputBase>,
+        start: Option<Duration>,
+        end: Option<Duration>,
+    ) -> PyResult<(Self, PyInputBase)> {
+        let start = start.unwrap_or_default();
+        if end.is_some_and(|end| end <= start) {
+            return Err(PyValueError::new_err("end must be after start"));
+        }
+        Ok((
+            Self {
+                input: Some(input.into_any().unbind()),
+                start,
+                end,
+            },
+            PyInputBase::new(),
+        ))
+    }
+
+    /// Start of the clip within the inner input.
+    ///
+    /// Returns
+    /// -------
+    /// datetime.timedelta
+    #[getter]
+    fn start(&self) -> Duration {
+        self.start
+    }
+
+    /// End of the clip within the inner input.
+    ///
+    /// Returns
+    /// -------
+    /// datetime.timedelta | None
+    #[getter]
+    fn end(&self) -> Option<Duration> {
+        self.end
+    }
+
+    #[gen_stub(skip)]
+    fn _compose(&self, py: Python, current_loop: Bound<PyAny>) -> PyResult<PyCompose> {
+        let input = self
+            .input
+            .as_ref()
+            .ok_or_else(|| PyRuntimeError::new_err("ClipInput has been cleared"))?
+            .clone_ref(py);
+        Ok(PyCompose::new_lazy(Box::new(ClipCompose {
+            input,
+            current_loop: current_loop.unbind(),
+            start: duration_frames(self.start),
+            end: self.end.map(duration_frames),
+        })))
+    }
+
+    #[gen_stub(skip)]
+    fn __traverse__(&self, visit: PyVisit<'_>) -> Result<(), PyTraverseError> {
+        if let Some(input) = &self.input {
+            visit.call(input)?;
+        }
+        Ok(())
+    }
+
+    #[gen_stub(skip)]
+    fn __clear__(&mut self) {
+        // Clear reference, this decrements ref counter.
+        self.input = None;
+    }
+}
+
+#[async_trait]
+impl Compose for ClipCompose {
+    fn create(&mut self) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+        Err(AudioStreamError::Unsupported)
+    }
+
+    async fn create_async(
+        &mut self,
+    ) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+        let mut inputs = Python::attach(|py| {
+            compose_inputs(py, &[self.input.clone_ref(py)], &self.current_loop)
+        })
+        .map_err(|err| AudioStreamError::Fail(Box::new(err)))?;
+        let input = DecodedInput::open(inputs.remove(0)).await?;
+        let source = ClipSource::new(input, self.start, self.end);
+        Ok(AudioStream {
+            input: Box::new(RawAdapter::new(
+                PcmStream::new(source),
+                PCM_SAMPLE_RATE,
+                PCM_CHANNELS as u32,
+            )),
+        })
+    }
+
+    fn should_create_async(&self) -> bool {
+        true
+    }
+}
+
+impl<S: PcmSource> ClipSource<S> {
+    fn new(input: S, start: u64, end: Option<u64>) -> Self {
+        Self {
+            input,
+            start,
+            end,
+            position: 0,
+            primed: false,
+            scratch: Vec::new(),
+        }
+    }
+
+    /// Move the inner input to `start` before the first read.
+    ///
+    /// This runs on the decode thread rather than while composing, since
+    /// unseekable inputs have to be decoded and discarded up to `start`.
+    fn prime(&mut self) -> io::Result<()> {
+        self.primed = true;
+        if self.start == 0 {
+            return Ok(());
+        }
+        if self.input.is_seekable() {
+            return self.input.seek_frame(self.start);
+        }
+        let mut remaining = self.start;
+        self.scratch
+            .resize(PCM_SAMPLE_RATE as usize / 10 * PCM_CHANNELS, 0.0);
+        while remaining > 0 {
+            let wanted = remaining.min((self.scratch.len() / PCM_CHANNELS) as u64) as usize;
+            let read = self
+                .input
+                .read_frames(&mut self.scratch[..wanted * PCM_CHANNELS]);
+            if read == 0 {
+                break;
+            }
+            remaining -= read as u64;
+        }
+        Ok(())
+    }
+
+    fn clip_len(&self) -> Option<u64> {
+        self.end.map(|end| end - self.start)
+    }
+}
+
+impl<S: PcmSource> PcmSource for ClipSource<S> {
+    fn read_frames(&mut self, out: &mut [f32]) -> usize {
+        if !self.primed && self.prime().is_err() {
+            return 0;
+        }
+        let mut frames = out.len() / PCM_CHANNELS;
+        if let Some(len) = self.clip_len() {
+            frames = frames.min(len.saturating_sub(self.position) as usize);
+        }
+        if frames == 0 {
+            return 0;
+        }
+        let read = self.input.read_frames(&mut out[..frames * PCM_CHANNELS]);
+        self.position += read as u64;
+        read
+    }
+
+    fn seek_frame(&mut self, frame: u64) -> io::Result<()> {
+        let frame = match self.clip_len() {
+            Some(len) => frame.min(len),
+            None => frame,
+        };
+        self.input.seek_frame(self.start + frame)?;
+        self.primed = true;
+        self.position = frame;
+        Ok(())
+    }
+
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    fn len_frames(&self) -> Option<u64> {
+        let available = self
+            .input
+            .len_frames()
+            .map(|len| len.saturating_sub(self.start));
+        match (self.clip_len(), available) {
+            (Some(clip), Some(available)) => Some(clip.min(available)),
+            (clip, available) => clip.or(available),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Source yielding its frame index as the left channel.
+    struct Ramp {
+        next: u64,
+        end: u64,
+        seekable: bool,
+    }
+
+    impl PcmSource for Ramp {
+        fn read_frames(&mut self, out: &mut [f32]) -> usize {
+            let frames = ((out.len() / PCM_CHANNELS) as u64).min(self.end - self.next) as usize;
+            for frame in out.chunks_exact_mut(PCM_CHANNELS).take(frames) {
+                frame[0] = self.next as f32;
+                frame[1] = 0.0;
+                self.next += 1;
+            }
+            frames
+        }
+
+        fn seek_frame(&mut self, frame: u64) -> io::Result<()> {
+            self.next = frame.min(self.end);
+            Ok(())
+        }
+
+        fn is_seekable(&self) -> bool {
+            self.seekable
+        }
+
+        fn len_frames(&self) -> Option<u64> {
+            Some(self.end)
+        }
+    }
+
+    fn ramp(end: u64, seekable: bool) -> Ramp {
+        Ramp {
+            next: 0,
+            end,
+            seekable,
+        }
+    }
+
+    fn read_left(clip: &mut ClipSource<Ramp>) -> Vec<f32> {
+        let mut out = vec![0.0; 4 * PCM_CHANNELS];
+        let mut left = Vec::new();
+        loop {
+            let read = clip.read_frames(&mut out);
+            if read == 0 {
+                return left;
+            }
+            left.extend(out[..read * PCM_CHANNELS].iter().step_by(PCM_CHANNELS));
+        }
+    }
+
+    #[test]
+    fn clips_play_exactly_the_requested_range() {
+        let mut clip = ClipSource::new(ramp(20, true), 5, Some(11));
+        assert_eq!(clip.len_frames(), Some(6));
+        assert_eq!(read_left(&mut clip), vec![5.0, 6.0, 7.0, 8.0, 9.0, 10.0]);
+    }
+
+    #[test]
+    fn unseekable_inputs_are_skipped_by_decoding() {
+        let mut clip = ClipSource::new(ramp(10, false), 7, None);
+        assert_eq!(clip.len_frames(), Some(3));
+        assert_eq!(read_left(&mut clip), vec![7.0, 8.0, 9.0]);
+    }
+
+    #[test]
+    fn seeks_are_relative_to_the_clip_start() {
+        let mut clip = ClipSource::new(ramp(20, true), 5, Some(11));
+        clip.seek_frame(4).unwrap();
+        assert_eq!(read_left(&mut clip), vec![9.0, 10.0]);
+        clip.seek_frame(100).unwrap();
+        assert_eq!(read_left(&mut clip), Vec::<f32>::new());
+    }
+}
@@ -0,0 +1,858 @@
+use crate::model::{Generic, PyAsyncIterator};
+use crate::player::input::decode::{
+    DecodedInput, PCM_CHANNELS, PCM_SAMPLE_RATE, PcmSource, PcmStream, compose_inputs,
+};
+use crate::player::input::{PyCompose, PyInputBase};
+use async_trait::async_trait;
+use futures::{FutureExt, StreamExt};
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::{
+    Bound, IntoPyObjectExt, Py, PyAny, PyResult, PyTraverseError, PyVisit, Python, pyclass,
+    pymethods,
+};
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use songbird::input::core::io::MediaSource;
+use songbird::input::{AudioStream, AudioStreamError, AuxMetadata, Compose, Input, RawAdapter};
+use std::future::Future;
+use std::io;
+use std::io::ErrorKind;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::Poll;
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::BroadcastStream;
+
+const SEGMENT_CHANNEL_CAPACITY: usize = 16;
+
+#[gen_stub_pyclass]
+#[pyclass(
+    name = "ConcatInput",
+    extends = PyInputBase,
+    module = "discord.ext.songbird.native.player",
+    skip_from_py_object
+)]
+/// Input that plays several inputs back to back as one track.
+///
+/// Notes
+/// -----
+/// Segments are decoded to 48 kHz stereo and joined without gaps. Only the
+/// first segment is opened when the track starts; each later segment is
+/// opened in the background once the one before it starts playing. If a
+/// segment is still opening when playback reaches it, silence plays until it
+/// is ready. The combined track is seekable, including across segment
+/// boundaries, when every segment is seekable and has a known length.
+///
+/// Examples
+/// --------
+/// ```python
+/// playlist = player.ConcatInput([intro, song, outro])
+/// await vc.enqueue(player.Track(playlist))
+///
+/// async for change in playlist.segment_changes():
+///     print("now playing segment", change.index)
+/// ```
+pub struct PyConcatInput {
+    segments: Vec<Py<PyAny>>,
+    state: Arc<ConcatState>,
+}
+
+#[gen_stub_pyclass]
+#[pyclass(
+    name = "SegmentChange",
+    module = "discord.ext.songbird.native.player",
+    frozen,
+    skip_from_py_object
+)]
+/// Event emitted when a `ConcatInput` starts playing another segment.
+#[derive(Clone)]
+pub struct PySegmentChange {
+    index: usize,
+    start: Duration,
+}
+
+struct ConcatState {
+    current: Mutex<Option<usize>>,
+    duration: Mutex<Option<Duration>>,
+    tx: broadcast::Sender<PySegmentChange>,
+}
+
+struct ConcatCompose {
+    segments: Vec<Py<PyAny>>,
+    current_loop: Py<PyAny>,
+    state: Arc<ConcatState>,
+}
+
+struct ConcatSource {
+    segments: Vec<Segment>,
+    /// Runtime that later segments are opened on.
+    runtime: Handle,
+    current: usize,
+    announced: Option<usize>,
+    position: u64,
+    segment_start: u64,
+    /// Seek target waiting for the segments before it to open.
+    pending_seek: Option<u64>,
+    state: Arc<ConcatState>,
+}
+
+type OpenResult = Result<Box<dyn PcmSource>, AudioStreamError>;
+type OpenFuture = Pin<Box<dyn Future<Output = OpenResult> + Send>>;
+
+struct Segment {
+    input: SegmentInput,
+    started: bool,
+}
+
+enum SegmentInput {
+    /// Not opened yet.
+    Pending(Mutex<OpenFuture>),
+    /// Being opened in the background.
+    Opening(JoinHandle<OpenResult>),
+    Open(Box<dyn PcmSource>),
+}
+
+/// Stands in for a segment that failed to open.
+struct Ended;
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl PyConcatInput {
+    #[gen_stub(override_return_type(type_repr = "typing.Self", imports = ("typing")))]
+    #[new]
+    /// Create a concatenated input.
+    ///
+    /// Parameters
+    /// ----------
+    /// inputs : Sequence[InputBase]
+    ///     Segments in playback order.
+    ///
+    /// Returns
+    /// -------
+    /// ConcatInput
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If `inputs` is empty.
+    /// TypeError
+    ///     If an entry is not an `InputBase`.
+    fn new(
+        #[gen_stub(override_type(
+            type_repr = "collections.abc.Sequence[InputBase]",
+            imports = ("collections.abc")
+        ))]
+        inputs: Vec<Bound<PyAny>>,
+    ) -> PyResult<(Self, PyInputBase)> {
+        if inputs.is_empty() {
+            return Err(PyValueError::new_err(
+                "ConcatInput requires at least one input",
+            ));
+        }
+        if inputs
+            .iter()
+            .any(|input| input.cast::<PyInputBase>().is_err())
+        {
+            return Err(PyTypeError::new_err(
+                "ConcatInput segments must be InputBase",
+            ));
+        }
+        let (tx, _) = broadcast::channel(SEGMENT_CHANNEL_CAPACITY);
+        Ok((
+            Self {
+                segments: inputs.into_iter().map(Bound::unbind).collect(),
+                state: Arc::new(ConcatState {
+                    current: Mutex::new(None),
+                    duration: Mutex::new(None),
+                    tx,
+                }),
+            },
+            PyInputBase::new(),
+        ))
+    }
+
+    /// Total duration of all segments.
+    ///
+    /// Returns
+    /// -------
+    /// datetime.timedelta | None
+    ///     None until every segment has been opened or has reported its
+    ///     duration, or if any segment length is unknown.
+    #[getter]
+    fn duration(&self) -> Option<Duration> {
+        *self.state.duration.lock().unwrap()
+    }
+
+    /// Index of the segment currently playing.
+    ///
+    /// Returns
+    /// -------
+    /// int | None
+    ///     None before playback starts.
+    #[getter]
+    fn current_segment(&self) -> Option<usize> {
+        *self.state.current.lock().unwrap()
+    }
+
+    /// Return an async iterator over segment changes.
+    ///
+    /// Returns
+    /// -------
+    /// PyAsyncIterator[SegmentChange]
+    ///
+    /// Notes
+    /// -----
+    /// Only changes that happen after this call are yielded. Seeking into
+    /// another segment also produces a change.
+    fn segment_changes(&self) -> Generic<'_, PyAsyncIterator, PySegmentChange> {
+        let stream = BroadcastStream::new(self.state.tx.subscribe())
+            .filter_map(|r| async move { r.ok() })
+            .map(|change| Python::attach(|py| change.into_py_any(py)));
+        Generic::new(PyAsyncIterator::new_in_raw(stream))
+    }
+
+    #[gen_stub(skip)]
+    fn _compose(&self, py: Python, current_loop: Bound<PyAny>) -> PyResult<PyCompose> {
+        Ok(PyCompose::new_lazy(Box::new(ConcatCompose {
+            segments: self
+                .segments
+                .iter()
+                .map(|segment| segment.clone_ref(py))
+                .collect(),
+            current_loop: current_loop.unbind(),
+            state: self.state.clone(),
+        })))
+    }
+
+    #[gen_stub(skip)]
+    fn __traverse__(&self, visit: PyVisit<'_>) -> Result<(), PyTraverseError> {
+        for segment in &self.segments {
+            visit.call(segment)?;
+        }
+        Ok(())
+    }
+
+    #[gen_stub(skip)]
+    fn __clear__(&mut self) {
+        // Clear reference, this decrements ref counter.
+        self.segments.clear();
+    }
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl PySegmentChange {
+    /// Index of the segment that started playing.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    #[getter]
+    fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Position of the segment start within the combined track.
+    ///
+    /// Returns
+    /// -------
+    /// datetime.timedelta
+    #[getter]
+    fn start(&self) -> Duration {
+        self.start
+    }
+
+    /// Return a debug representation.
+    ///
+    /// Returns
+    /// -------
+    /// str
+    fn __repr__(&self) -> String {
+        format!(
+            "SegmentChange(index={}, start={:?})",
+            self.index, self.start
+        )
+    }
+}
+
+#[async_trait]
+impl Compose for ConcatCompose {
+    fn create(&mut self) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+        Err(AudioStreamError::Unsupported)
+    }
+
+    async fn create_async(
+        &mut self,
+    ) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+        let mut inputs = self.compose()?.into_iter();
+        let first = inputs
+            .next()
+            .ok_or_else(|| AudioStreamError::Fail("ConcatInput has no segments".into()))?;
+        // Open the first segment here so a broken input fails the track.
+        let mut segments = vec![Segment::open(open_segment(first).await?)];
+        segments.extend(inputs.map(|input| Segment::pending(Box::pin(open_segment(input)))));
+        let source = ConcatSource::new(segments, Handle::current(), self.state.clone());
+        Ok(AudioStream {
+            input: Box::new(RawAdapter::new(
+                PcmStream::new(source),
+                PCM_SAMPLE_RATE,
+                PCM_CHANNELS as u32,
+            )),
+        })
+    }
+
+    fn should_create_async(&self) -> bool {
+        true
+    }
+
+    /// Report the combined duration from each segment's own metadata,
+    /// without opening any of them.
+    async fn aux_metadata(&mut self) -> Result<AuxMetadata, AudioStreamError> {
+        let known = *self.state.duration.lock().unwrap();
+        let duration = match known {
+            Some(duration) => Some(duration),
+            None => {
+                let total = self.metadata_duration().await;
+                if total.is_some() {
+                    *self.state.duration.lock().unwrap() = total;
+                }
+                total
+            }
+        };
+        Ok(AuxMetadata {
+            duration,
+            ..Default::default()
+        })
+    }
+}
+
+impl ConcatCompose {
+    fn compose(&self) -> Result<Vec<Input>, AudioStreamError> {
+        Python::attach(|py| compose_inputs(py, &self.segments, &self.current_loop))
+            .map_err(|err| AudioStreamError::Fail(Box::new(err)))
+    }
+
+    /// Sum of the segments' metadata durations, or None if any segment does
+    /// not report one.
+    async fn metadata_duration(&self) -> Option<Duration> {
+        let mut total = Duration::ZERO;
+        for input in self.compose().ok()? {
+            let mut compose = match input {
+                Input::Lazy(compose) | Input::Live(_, Some(compose)) => compose,
+                Input::Live(_, None) => return None,
+            };
+            total += compose.aux_metadata().await.ok()?.duration?;
+        }
+        Some(total)
+    }
+}
+
+async fn open_segment(input: Input) -> OpenResult {
+    Ok(Box::new(DecodedInput::open(input).await?))
+}
+
+impl Segment {
+    fn open(source: Box<dyn PcmSource>) -> Self {
+        Self {
+            input: SegmentInput::Open(source),
+            started: false,
+        }
+    }
+
+    fn pending(open: OpenFuture) -> Self {
+        Self {
+            input: SegmentInput::Pending(Mutex::new(open)),
+            started: false,
+        }
+    }
+
+    /// Start opening the segment in the background.
+    fn prefetch(&mut self, runtime: &Handle) {
+        if !matches!(self.input, SegmentInput::Pending(_)) {
+            return;
+        }
+        let SegmentInput::Pending(open) =
+            std::mem::replace(&mut self.input, SegmentInput::Open(Box::new(Ended)))
+        else {
+            unreachable!("segment was just checked to be pending");
+        };
+        self.input = SegmentInput::Opening(runtime.spawn(open.into_inner().unwrap()));
+    }
+
+    /// Check whether the segment is open, starting to open it if needed.
+    ///
+    /// Never waits, since this runs on the mixer thread: returns `Pending`
+    /// while the segment is opening, and `Ready(true)` the first time it is
+    /// found open.
+    fn poll_open(&mut self, runtime: &Handle) -> Poll<bool> {
+        self.prefetch(runtime);
+        let SegmentInput::Opening(task) = &mut self.input else {
+            return Poll::Ready(false);
+        };
+        if !task.is_finished() {
+            return Poll::Pending;
+        }
+        let result = task
+            .now_or_never()
+            .expect("a finished task has its output ready")
+            .unwrap_or_else(|err| Err(AudioStreamError::Fail(Box::new(err))));
+        self.input = SegmentInput::Open(match result {
+            Ok(source) => source,
+            Err(err) => {
+                log::warn!("Skipping ConcatInput segment that failed to open: {}", err);
+                Box::new(Ended)
+            }
+        });
+        Poll::Ready(true)
+    }
+
+    fn source(&mut self) -> &mut dyn PcmSource {
+        match &mut self.input {
+            SegmentInput::Open(source) => source.as_mut(),
+            _ => panic!("segment has not been opened"),
+        }
+    }
+
+    fn len_frames(&self) -> Option<u64> {
+        match &self.input {
+            SegmentInput::Open(source) => source.len_frames(),
+            _ => None,
+        }
+    }
+
+    fn is_seekable(&self) -> bool {
+        match &self.input {
+            SegmentInput::Open(source) => source.is_seekable() && source.len_frames().is_some(),
+            // Checked again once the segment is opened.
+            _ => true,
+        }
+    }
+}
+
+impl PcmSource for Ended {
+    fn read_frames(&mut self, _out: &mut [f32]) -> usize {
+        0
+    }
+
+    fn seek_frame(&mut self, _frame: u64) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn len_frames(&self) -> Option<u64> {
+        Some(0)
+    }
+}
+
+impl ConcatSource {
+    fn new(segments: Vec<Segment>, runtime: Handle, state: Arc<ConcatState>) -> Self {
+        let source = Self {
+            segments,
+            runtime,
+            current: 0,
+            announced: None,
+            position: 0,
+            segment_start: 0,
+            pending_seek: None,
+            state,
+        };
+        source.update_duration();
+        source
+    }
+
+    /// Publish the combined duration once every segment length is known.
+    fn update_duration(&self) {
+        if let Some(total) = self.len_frames() {
+            *self.state.duration.lock().unwrap() = Some(frames_duration(total));
+        }
+    }
+
+    /// Whether segment `index` is open, starting to open it if needed.
+    fn poll_open(&mut self, index: usize) -> bool {
+        match self.segments[index].poll_open(&self.runtime) {
+            Poll::Ready(opened) => {
+                if opened {
+                    self.update_duration();
+                }
+                true
+            }
+            Poll::Pending => false,
+        }
+    }
+
+    /// Apply `pending_seek` once every segment up to the target is open.
+    /// Returns false while a segment is still opening.
+    fn resolve_seek(&mut self) -> io::Result<bool> {
+        let Some(frame) = self.pending_seek else {
+            return Ok(true);
+        };
+        let mut index = 0;
+        let mut segment_start = 0;
+        let offset = loop {
+            if !self.poll_open(index) {
+                return Ok(false);
+            }
+            let len = self.segments[index].source().len_frames().ok_or_else(|| {
+                io::Error::new(ErrorKind::Unsupported, "segment lengths are unknown")
+            })?;
+            if frame < segment_start + len || index + 1 == self.segments.len() {
+                break (frame - segment_start).min(len);
+            }
+            segment_start += len;
+            index += 1;
+        };
+
+        self.pending_seek = None;
+        for (i, segment) in self.segments.iter_mut().enumerate() {
+            if i == index {
+                segment.source().seek_frame(offset)?;
+                segment.started = true;
+            } else if i > index && segment.started {
+                segment.source().seek_frame(0)?;
+                segment.started = false;
+            }
+        }
+        self.current = index;
+        self.position = segment_start + offset;
+        self.segment_start = segment_start;
+        self.announce();
+        Ok(true)
+    }
+
+    fn announce(&mut self) {
+        if self.announced == Some(self.current) {
+            return;
+        }
+        self.announced = Some(self.current);
+        if let Some(next) = self.segments.get_mut(self.current + 1) {
+            next.prefetch(&self.runtime);
+        }
+        *self.state.current.lock().unwrap() = Some(self.current);
+        drop(self.state.tx.send(PySegmentChange {
+            index: self.current,
+            start: frames_duration(self.segment_start),
+        }));
+    }
+}
+
+impl PcmSource for ConcatSource {
+    fn read_frames(&mut self, out: &mut [f32]) -> usize {
+        let frames = out.len() / PCM_CHANNELS;
+        let ready = self.resolve_seek().unwrap_or_else(|err| {
+            log::warn!("Abandoning ConcatInput seek: {}", err);
+            self.pending_seek = None;
+            true
+        });
+        let mut written = 0;
+        while ready && written < frames && self.current < self.segments.len() {
+            self.announce();
+            self.segments[self.current].started = true;
+            if !self.poll_open(self.current) {
+                break;
+            }
+            let read = self.segments[self.current]
+                .source()
+                .read_frames(&mut out[written * PCM_CHANNELS..frames * PCM_CHANNELS]);
+            written += read;
+            self.position += read as u64;
+            if read == 0 {
+                self.current += 1;
+                self.segment_start = self.position;
+            }
+        }
+        if self.current < self.segments.len() && written < frames {
+            // A segment is still opening: play silence rather than wait.
+            out[written * PCM_CHANNELS..frames * PCM_CHANNELS].fill(0.0);
+            written = frames;
+        }
+        written
+    }
+
+    /// Segments before the target that are not open yet are opened in the
+    /// background, and the seek completes on a later read.
+    fn seek_frame(&mut self, frame: u64) -> io::Result<()> {
+        self.pending_seek = Some(frame);
+        self.resolve_seek().map(drop).inspect_err(|_| {
+            self.pending_seek = None;
+        })
+    }
+
+    fn is_seekable(&self) -> bool {
+        self.segments.iter().all(Segment::is_seekable)
+    }
+
+    fn len_frames(&self) -> Option<u64> {
+        self.segments.iter().map(Segment::len_frames).sum()
+    }
+}
+
+fn frames_duration(frames: u64) -> Duration {
+    Duration::from_secs_f64(frames as f64 / PCM_SAMPLE_RATE as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::OnceLock;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tokio::sync::oneshot;
+
+    /// Segment yielding `start..end` as the left channel.
+    struct Ramp {
+        start: u64,
+        end: u64,
+        next: u64,
+    }
+
+    impl PcmSource for Ramp {
+        fn read_frames(&mut self, out: &mut [f32]) -> usize {
+            let frames = ((out.len() / PCM_CHANNELS) as u64).min(self.end - self.next) as usize;
+            for frame in out.chunks_exact_mut(PCM_CHANNELS).take(frames) {
+                frame[0] = self.next as f32;
+                frame[1] = 0.0;
+                self.next += 1;
+            }
+            frames
+        }
+
+        fn seek_frame(&mut self, frame: u64) -> io::Result<()> {
+            self.next = (self.start + frame).min(self.end);
+            Ok(())
+        }
+
+        fn is_seekable(&self) -> bool {
+            true
+        }
+
+        fn len_frames(&self) -> Option<u64> {
+            Some(self.end - self.start)
+        }
+    }
+
+    fn ramp(start: u64, end: u64) -> Box<dyn PcmSource> {
+        Box::new(Ramp {
+            start,
+            end,
+            next: start,
+        })
+    }
+
+    fn runtime() -> Handle {
+        static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+        RUNTIME
+            .get_or_init(|| {
+                tokio::runtime::Builder::new_multi_thread()
+                    .worker_threads(1)
+                    .build()
+                    .unwrap()
+            })
+            .handle()
+            .clone()
+    }
+
+    fn concat(sources: Vec<Box<dyn PcmSource>>, state: Arc<ConcatState>) -> ConcatSource {
+        ConcatSource::new(
+            sources.into_iter().map(Segment::open).collect(),
+            runtime(),
+            state,
+        )
+    }
+
+    /// Segment that records when it is opened.
+    fn pending(source: Box<dyn PcmSource>, opened: Arc<AtomicBool>) -> Segment {
+        Segment::pending(Box::pin(async move {
+            opened.store(true, Ordering::SeqCst);
+            Ok(source)
+        }))
+    }
+
+    /// Segment that opens once `gate` fires.
+    fn gated(source: Box<dyn PcmSource>, gate: oneshot::Receiver<()>) -> Segment {
+        Segment::pending(Box::pin(async move {
+            drop(gate.await);
+            Ok(source)
+        }))
+    }
+
+    /// Wait for segments being opened in the background.
+    fn settle(concat: &ConcatSource) {
+        while concat.segments.iter().any(
+            |segment| matches!(&segment.input, SegmentInput::Opening(task) if !task.is_finished()),
+        ) {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// Read until the end, waiting out segments that are still opening, and
+    /// return the left channel without the silence played meanwhile.
+    fn read_to_end(concat: &mut ConcatSource) -> Vec<f32> {
+        let mut out = vec![0.0; 4 * PCM_CHANNELS];
+        let mut played = Vec::new();
+        loop {
+            settle(concat);
+            let read = concat.read_frames(&mut out);
+            if read == 0 {
+                break;
+            }
+            played.extend(left(&out, read).into_iter().filter(|&sample| sample != 0.0));
+        }
+        played
+    }
+
+    fn state() -> (Arc<ConcatState>, broadcast::Receiver<PySegmentChange>) {
+        let (tx, rx) = broadcast::channel(SEGMENT_CHANNEL_CAPACITY);
+        let state = ConcatState {
+            current: Mutex::new(None),
+            duration: Mutex::new(None),
+            tx,
+        };
+        (Arc::new(state), rx)
+    }
+
+    fn left(out: &[f32], frames: usize) -> Vec<f32> {
+        out[..frames * PCM_CHANNELS]
+            .iter()
+            .step_by(PCM_CHANNELS)
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn segments_play_back_to_back() {
+        let (state, mut rx) = state();
+        let mut concat = concat(vec![ramp(0, 3), ramp(10, 12)], state.clone());
+        assert_eq!(*state.duration.lock().unwrap(), Some(frames_duration(5)));
+
+        let mut out = vec![0.0; 8 * PCM_CHANNELS];
+        let read = concat.read_frames(&mut out);
+        assert_eq!(read, 5);
+        assert_eq!(left(&out, read), vec![0.0, 1.0, 2.0, 10.0, 11.0]);
+        assert_eq!(concat.read_frames(&mut out), 0);
+
+        let first = rx.try_recv().unwrap();
+        assert_eq!((first.index, first.start), (0, Duration::ZERO));
+        let second = rx.try_recv().unwrap();
+        assert_eq!((second.index, second.start), (1, frames_duration(3)));
+        assert_eq!(*state.current.lock().unwrap(), Some(1));
+    }
+
+    #[test]
+    fn seeking_crosses_segment_boundaries() {
+        let (state, mut rx) = state();
+        let mut concat = concat(vec![ramp(0, 3), ramp(10, 14)], state);
+        assert!(concat.is_seekable());
+
+        concat.seek_frame(4).unwrap();
+        let mut out = vec![0.0; 2 * PCM_CHANNELS];
+        assert_eq!(concat.read_frames(&mut out), 2);
+        assert_eq!(left(&out, 2), vec![11.0, 12.0]);
+        assert_eq!(rx.try_recv().unwrap().index, 1);
+
+        concat.seek_frame(1).unwrap();
+        let mut out = vec![0.0; 8 * PCM_CHANNELS];
+        let read = concat.read_frames(&mut out);
+        assert_eq!(left(&out, read), vec![1.0, 2.0, 10.0, 11.0, 12.0, 13.0]);
+        assert_eq!(rx.try_recv().unwrap().index, 0);
+        assert_eq!(rx.try_recv().unwrap().index, 1);
+    }
+
+    #[test]
+    fn unknown_lengths_disable_seeking() {
+        struct Unknown;
+
+        impl PcmSource for Unknown {
+            fn read_frames(&mut self, _out: &mut [f32]) -> usize {
+                0
+            }
+
+            fn seek_frame(&mut self, _frame: u64) -> io::Result<()> {
+                Ok(())
+            }
+
+            fn is_seekable(&self) -> bool {
+                true
+            }
+        }
+
+        let (state, _rx) = state();
+        let mut concat = concat(vec![ramp(0, 3), Box::new(Unknown)], state.clone());
+        assert!(!concat.is_seekable());
+        assert!(concat.seek_frame(4).is_err());
+        assert_eq!(*state.duration.lock().unwrap(), None);
+    }
+
+    #[test]
+    fn later_segments_play_silence_until_opened() {
+        let (state, _rx) = state();
+        let (open_second, gate) = oneshot::channel();
+        let third = Arc::new(AtomicBool::new(false));
+        let mut concat = ConcatSource::new(
+            vec![
+                Segment::open(ramp(1, 4)),
+                gated(ramp(10, 12), gate),
+                pending(ramp(20, 22), third.clone()),
+            ],
+            runtime(),
+            state.clone(),
+        );
+        assert_eq!(*state.duration.lock().unwrap(), None);
+        settle(&concat);
+        assert!(!third.load(Ordering::SeqCst));
+
+        let mut out = vec![0.0; 4 * PCM_CHANNELS];
+        assert_eq!(concat.read_frames(&mut out), 4);
+        assert_eq!(left(&out, 4), vec![1.0, 2.0, 3.0, 0.0]);
+        assert_eq!(concat.read_frames(&mut out), 4);
+        assert_eq!(left(&out, 4), vec![0.0; 4]);
+        assert_eq!(concat.position, 3);
+
+        open_second.send(()).unwrap();
+        assert_eq!(read_to_end(&mut concat), vec![10.0, 11.0, 20.0, 21.0]);
+        assert!(third.load(Ordering::SeqCst));
+        assert_eq!(*state.duration.lock().unwrap(), Some(frames_duration(7)));
+    }
+
+    #[test]
+    fn seeking_waits_for_unopened_segments() {
+        let (state, _rx) = state();
+        let (open_second, gate) = oneshot::channel();
+        let mut concat = ConcatSource::new(
+            vec![
+                Segment::open(ramp(1, 4)),
+                gated(ramp(10, 12), gate),
+                Segment::open(ramp(20, 22)),
+            ],
+            runtime(),
+            state.clone(),
+        );
+
+        concat.seek_frame(4).unwrap();
+        let mut out = vec![0.0; 2 * PCM_CHANNELS];
+        assert_eq!(concat.read_frames(&mut out), 2);
+        assert_eq!(left(&out, 2), vec![0.0, 0.0]);
+
+        open_second.send(()).unwrap();
+        assert_eq!(read_to_end(&mut concat), vec![11.0, 20.0, 21.0]);
+        assert_eq!(*state.duration.lock().unwrap(), Some(frames_duration(7)));
+    }
+
+    #[test]
+    fn segments_that_fail_to_open_are_skipped() {
+        let (state, _rx) = state();
+        let mut concat = ConcatSource::new(
+            vec![
+                Segment::open(ramp(1, 3)),
+                Segment::pending(Box::pin(async { Err(AudioStreamError::Unsupported) })),
+                Segment::open(ramp(10, 12)),
+            ],
+            runtime(),
+            state,
+        );
+        assert_eq!(read_to_end(&mut concat), vec![1.0, 2.0, 10.0, 11.0]);
+    }
+}
@@ -0,0 +1,522 @@
+use crate::player::input::opus::{
+    OPUS_FRAME_SAMPLES, OPUS_SAMPLE_RATE, OpusFrameArray, OpusPacketFormatReader,
+    PyOpusPacketInput, parsed_input,
+};
+use crate::player::input::{PyCompose, PyInputBase};
+use arrow::array::BinaryBuilder;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::types::{PyAnyMethods, PyBytes, PyBytesMethods};
+use pyo3::{Bound, PyAny, PyResult, Python, pyclass, pyfunction, pymethods};
+use pyo3_arrow::PyArray;
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pyfunction, gen_stub_pymethods};
+use serde_json::{Value, json};
+use songbird::input::core::meta::{MetadataBuilder, MetadataRevision, StandardTagKey, Tag};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+const DCA1_MAGIC: &[u8; 4] = b"DCA1";
+
+pyo3_stub_gen::module_doc!(
+    "discord.ext.songbird.native.player.dca",
+    r#"
+DCA (Discord Compressed Audio) helpers.
+
+Use `encode` to write pre-encoded Opus frames as a DCA1 file and
+`player.DcaInput` to play DCA files back.
+"#,
+);
+
+#[gen_stub_pyclass]
+#[pyclass(
+    name = "DcaInput",
+    extends = PyInputBase,
+    module = "discord.ext.songbird.native.player",
+    skip_from_py_object
+)]
+/// DCA (Discord Compressed Audio) input.
+///
+/// Notes
+/// -----
+/// DCA1 files carry a JSON metadata header followed by length-prefixed Opus
+/// frames. Headerless DCA0 payloads are also accepted. Frames are played
+/// through the same reader as `OpusPacketInput`, so passthrough applies.
+pub struct PyDcaInput {
+    frames: OpusFrameArray,
+    header: DcaHeader,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct DcaHeader {
+    title: Option<String>,
+    artist: Option<String>,
+    album: Option<String>,
+    genre: Option<String>,
+    comments: Option<String>,
+    url: Option<String>,
+}
+
+struct DcaFile {
+    frames: OpusFrameArray,
+    header: DcaHeader,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl PyDcaInput {
+    #[gen_stub(override_return_type(type_repr = "typing.Self", imports = ("typing")))]
+    #[new]
+    /// Create a DCA input.
+    ///
+    /// Parameters
+    /// ----------
+    /// source : bytes | str | os.PathLike[str]
+    ///     DCA payload, or a path to a DCA file.
+    ///
+    /// Returns
+    /// -------
+    /// DcaInput
+    fn new(
+        #[gen_stub(override_type(type_repr = "bytes | str | os.PathLike[str]", imports = ("os")))]
+        source: Bound<PyAny>,
+    ) -> PyResult<(Self, PyInputBase)> {
+        let file = if let Ok(bytes) = source.cast::<PyBytes>() {
+            parse_dca(bytes.as_bytes())?
+        } else {
+            let path = source
+                .extract::<PathBuf>()
+                .map_err(|_| PyTypeError::new_err("Expected bytes or a path to a DCA file"))?;
+            parse_dca(&std::fs::read(path)?)?
+        };
+        Ok((
+            Self {
+                frames: file.frames,
+                header: file.header,
+            },
+            PyInputBase::new(),
+        ))
+    }
+
+    /// Title from the DCA1 metadata header, if present.
+    ///
+    /// Returns
+    /// -------
+    /// str | None
+    #[getter]
+    fn title(&self) -> Option<String> {
+        self.header.title.clone()
+    }
+
+    /// Artist from the DCA1 metadata header, if present.
+    ///
+    /// Returns
+    /// -------
+    /// str | None
+    #[getter]
+    fn artist(&self) -> Option<String> {
+        self.header.artist.clone()
+    }
+
+    /// Album from the DCA1 metadata header, if present.
+    ///
+    /// Returns
+    /// -------
+    /// str | None
+    #[getter]
+    fn album(&self) -> Option<String> {
+        self.header.album.clone()
+    }
+
+    /// Genre from the DCA1 metadata header, if present.
+    ///
+    /// Returns
+    /// -------
+    /// str | None
+    #[getter]
+    fn genre(&self) -> Option<String> {
+        self.header.genre.clone()
+    }
+
+    /// Comments from the DCA1 metadata header, if present.
+    ///
+    /// Returns
+    /// -------
+    /// str | None
+    #[getter]
+    fn comments(&self) -> Option<String> {
+        self.header.comments.clone()
+    }
+
+    /// Origin URL from the DCA1 metadata header, if present.
+    ///
+    /// Returns
+    /// -------
+    /// str | None
+    #[getter]
+    fn url(&self) -> Option<String> {
+        self.header.url.clone()
+    }
+
+    /// Total playback duration of the contained frames.
+    ///
+    /// Returns
+    /// -------
+    /// datetime.timedelta
+    #[getter]
+    fn duration(&self) -> Duration {
+        frames_duration(self.frames.len())
+    }
+
+    #[gen_stub(skip)]
+    fn _compose(&self, _current_loop: Bound<PyAny>) -> PyResult<PyCompose> {
+        let reader = OpusPacketFormatReader::batch(self.frames.clone())
+            .with_metadata(self.header.to_revision());
+        let input = parsed_input(reader, true)?;
+        Ok(PyCompose::new_live(input, None))
+    }
+}
+
+#[gen_stub_pyfunction(module = "discord.ext.songbird.native.player.dca")]
+#[pyfunction]
+#[pyo3(signature = (frames, *, title = None, artist = None, album = None, genre = None, comments = None, url = None))]
+#[allow(clippy::too_many_arguments)]
+/// Encode 20 ms Opus frames as a DCA1 file.
+///
+/// Parameters
+/// ----------
+/// frames : OpusPacketInput | pyarrow.BinaryArray | pyarrow.LargeBinaryArray
+///     Opus frames to write, one 20 ms frame per row.
+/// title : str | None, optional
+///     Title stored in the metadata header.
+/// artist : str | None, optional
+///     Artist stored in the metadata header.
+/// album : str | None, optional
+///     Album stored in the metadata header.
+/// genre : str | None, optional
+///     Genre stored in the metadata header.
+/// comments : str | None, optional
+///     Comments stored in the metadata header.
+/// url : str | None, optional
+///     Origin URL stored in the metadata header.
+///
+/// Returns
+/// -------
+/// bytes
+///     The DCA1 payload, readable by `DcaInput`.
+///
+/// Examples
+/// --------
+/// ```python
+/// data = player.dca.encode(player.OpusPacketInput(frames), title="Airhorn")
+/// pathlib.Path("airhorn.dca").write_bytes(data)
+/// ```
+pub fn encode<'py>(
+    py: Python<'py>,
+    #[gen_stub(override_type(
+        type_repr = "player.OpusPacketInput | pyarrow.BinaryArray | pyarrow.LargeBinaryArray",
+        imports = ("pyarrow", "discord.ext.songbird.native.player")
+    ))]
+    frames: Bound<'py, PyAny>,
+    title: Option<String>,
+    artist: Option<String>,
+    album: Option<String>,
+    genre: Option<String>,
+    comments: Option<String>,
+    url: Option<String>,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let frames = if let Ok(input) = frames.cast::<PyOpusPacketInput>() {
+        input.borrow().frames().clone()
+    } else {
+        let array = frames.extract::<PyArray>()?;
+        let frames = OpusFrameArray::try_from_array(array.array().clone())?;
+        frames.validate()?;
+        frames
+    };
+    let header = DcaHeader {
+        title,
+        artist,
+        album,
+        genre,
+        comments,
+        url,
+    };
+    let encoded = write_dca(&frames, &header)?;
+    Ok(PyBytes::new(py, &encoded))
+}
+
+impl DcaHeader {
+    fn from_json(value: &Value) -> PyResult<Self> {
+        if let Some(opus) = value.get("opus") {
+            let sample_rate = opus.get("sample_rate").and_then(Value::as_u64);
+            if sample_rate.is_some_and(|rate| rate != u64::from(OPUS_SAMPLE_RATE)) {
+                return Err(PyValueError::new_err(format!(
+                    "DCA sample rate must be {OPUS_SAMPLE_RATE} Hz"
+                )));
+            }
+            let frame_size = opus.get("frame_size").and_then(Value::as_u64);
+            if frame_size.is_some_and(|size| size != OPUS_FRAME_SAMPLES) {
+                return Err(PyValueError::new_err(format!(
+                    "DCA frame size must be {OPUS_FRAME_SAMPLES} samples"
+                )));
+            }
+        }
+        let info = value.get("info");
+        let text = |section: Option<&Value>, key: &str| {
+            section
+                .and_then(|section| section.get(key))
+                .and_then(Value::as_str)
+                .map(str::to_owned)
+        };
+        Ok(Self {
+            title: text(info, "title"),
+            artist: text(info, "artist"),
+            album: text(info, "album"),
+            genre: text(info, "genre"),
+            comments: text(info, "comments"),
+            url: text(value.get("origin"), "url"),
+        })
+    }
+
+    fn to_json(&self, channels: u8) -> Value {
+        json!({
+            "dca": {
+                "version": 1,
+                "tool": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "url": env!("CARGO_PKG_REPOSITORY"),
+                    "author": null,
+                },
+            },
+            "opus": {
+                "mode": "audio",
+                "sample_rate": OPUS_SAMPLE_RATE,
+                "frame_size": OPUS_FRAME_SAMPLES,
+                "abr": null,
+                "vbr": true,
+                "channels": channels,
+            },
+            "info": {
+                "title": self.title,
+                "artist": self.artist,
+                "album": self.album,
+                "genre": self.genre,
+                "cover": null,
+                "comments": self.comments,
+            },
+            "origin": {
+                "source": null,
+                "abr": null,
+                "channels": null,
+                "encoding": null,
+                "url": self.url,
+            },
+            "extra": {},
+        })
+    }
+
+    fn to_revision(&self) -> MetadataRevision {
+        let mut revision = MetadataBuilder::new();
+        let tags = [
+            (StandardTagKey::TrackTitle, "title", &self.title),
+            (StandardTagKey::Artist, "artist", &self.artist),
+            (StandardTagKey::Album, "album", &self.album),
+            (StandardTagKey::Genre, "genre", &self.genre),
+            (StandardTagKey::Comment, "comments", &self.comments),
+            (StandardTagKey::Url, "url", &self.url),
+        ];
+        for (key, name, value) in tags {
+            if let Some(value) = value {
+                revision.add_tag(Tag::new(Some(key), name, value.as_str().into()));
+            }
+        }
+        revision.metadata()
+    }
+}
+
+fn parse_dca(data: &[u8]) -> PyResult<DcaFile> {
+    let (header, mut rest) = match data {
+        [b'D', b'C', b'A', b'1', rest @ ..] => {
+            let (size, rest) = split_le_u32(rest)?;
+            if size < 2 {
+                return Err(PyValueError::new_err("Missing DCA1 metadata block"));
+            }
+            let size = size as usize;
+            if rest.len() < size {
+                return Err(PyValueError::new_err("Truncated DCA1 metadata block"));
+            }
+            let (raw_json, rest) = rest.split_at(size);
+            let value = serde_json::from_slice::<Value>(raw_json)
+                .map_err(|err| PyValueError::new_err(format!("Malformed DCA1 metadata: {err}")))?;
+            (DcaHeader::from_json(&value)?, rest)
+        }
+        [b'D', b'C', b'A', ..] => {
+            return Err(PyValueError::new_err("Unsupported DCA version"));
+        }
+        _ => (DcaHeader::default(), data),
+    };
+
+    let mut builder = BinaryBuilder::new();
+    while !rest.is_empty() {
+        let Some((len, tail)) = rest.split_first_chunk::<2>() else {
+            return Err(PyValueError::new_err("Truncated DCA frame header"));
+        };
+        let len = i16::from_le_bytes(*len);
+        if len <= 0 {
+            return Err(PyValueError::new_err(
+                "DCA frame headers must have a positive length",
+            ));
+        }
+        let len = len as usize;
+        if tail.len() < len {
+            return Err(PyValueError::new_err("Truncated DCA frame"));
+        }
+        let (frame, tail) = tail.split_at(len);
+        builder.append_value(frame);
+        rest = tail;
+    }
+
+    let frames = OpusFrameArray::try_from_array(Arc::new(builder.finish()))?;
+    frames.validate()?;
+    Ok(DcaFile { frames, header })
+}
+
+fn write_dca(frames: &OpusFrameArray, header: &DcaHeader) -> PyResult<Vec<u8>> {
+    let channels = if frames.len() == 0 {
+        2
+    } else {
+        opus2::packet::get_nb_channels(frames.value(0))
+            .map(|channels| channels as u8)
+            .map_err(|_| PyValueError::new_err("Opus frame channel count could not be read"))?
+    };
+    let header = serde_json::to_vec(&header.to_json(channels))
+        .map_err(|err| PyValueError::new_err(err.to_string()))?;
+    let header_len = u32::try_from(header.len())
+        .map_err(|_| PyValueError::new_err("DCA1 metadata block is too large"))?;
+
+    let frame_bytes = (0..frames.len())
+        .map(|index| frames.value(index).len() + 2)
+        .sum::<usize>();
+    let mut out = Vec::with_capacity(DCA1_MAGIC.len() + 4 + header.len() + frame_bytes);
+    out.extend_from_slice(DCA1_MAGIC);
+    out.extend_from_slice(&header_len.to_le_bytes());
+    out.extend_from_slice(&header);
+    for index in 0..frames.len() {
+        let frame = frames.value(index);
+        let len = i16::try_from(frame.len())
+            .map_err(|_| PyValueError::new_err("Opus frame is too large for DCA"))?;
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(frame);
+    }
+    Ok(out)
+}
+
+fn split_le_u32(data: &[u8]) -> PyResult<(u32, &[u8])> {
+    data.split_first_chunk::<4>()
+        .map(|(value, rest)| (u32::from_le_bytes(*value), rest))
+        .ok_or_else(|| PyValueError::new_err("Truncated DCA1 header"))
+}
+
+fn frames_duration(frames: usize) -> Duration {
+    Duration::from_millis(frames as u64 * 20)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opus2::{Application, Channels, Encoder};
+    use songbird::input::core::formats::FormatReader;
+
+    fn opus_frame() -> Vec<u8> {
+        let mut encoder =
+            Encoder::new(OPUS_SAMPLE_RATE, Channels::Stereo, Application::Audio).unwrap();
+        let samples = vec![0.0_f32; OPUS_FRAME_SAMPLES as usize * 2];
+        encoder.encode_vec_float(&samples, 4_000).unwrap()
+    }
+
+    fn frame_array(frames: &[&[u8]]) -> OpusFrameArray {
+        let array = arrow::array::BinaryArray::from_vec(frames.to_vec());
+        OpusFrameArray::try_from_array(Arc::new(array)).unwrap()
+    }
+
+    #[test]
+    fn encoded_dca1_round_trips_frames_and_metadata() {
+        let first = opus_frame();
+        let second = opus_frame();
+        let header = DcaHeader {
+            title: Some("Airhorn".into()),
+            url: Some("https://example.com/airhorn".into()),
+            ..Default::default()
+        };
+        let encoded = write_dca(&frame_array(&[&first, &second]), &header).unwrap();
+        assert_eq!(&encoded[..4], DCA1_MAGIC);
+
+        let file = parse_dca(&encoded).unwrap();
+        assert_eq!(file.header, header);
+        assert_eq!(file.frames.len(), 2);
+        assert_eq!(file.frames.value(0), first.as_slice());
+        assert_eq!(file.frames.value(1), second.as_slice());
+        assert_eq!(
+            frames_duration(file.frames.len()),
+            Duration::from_millis(40)
+        );
+    }
+
+    #[test]
+    fn headerless_dca0_payloads_are_accepted() {
+        let frame = opus_frame();
+        let mut payload = (frame.len() as i16).to_le_bytes().to_vec();
+        payload.extend_from_slice(&frame);
+
+        let file = parse_dca(&payload).unwrap();
+        assert_eq!(file.header, DcaHeader::default());
+        assert_eq!(file.frames.len(), 1);
+        assert_eq!(file.frames.value(0), frame.as_slice());
+    }
+
+    #[test]
+    fn malformed_dca_payloads_are_rejected() {
+        assert!(parse_dca(b"DCA2").is_err());
+        assert!(parse_dca(b"DCA1\x10\x00\x00\x00{}").is_err());
+        assert!(parse_dca(b"DCA1\x02\x00\x00\x00{]").is_err());
+
+        let frame = opus_frame();
+        let mut truncated = (frame.len() as i16 + 1).to_le_bytes().to_vec();
+        truncated.extend_from_slice(&frame);
+        assert!(parse_dca(&truncated).is_err());
+
+        let header = br#"{"opus":{"sample_rate":48000,"frame_size":1920}}"#;
+        let mut wrong_frame_size = DCA1_MAGIC.to_vec();
+        wrong_frame_size.extend_from_slice(&(header.len() as u32).to_le_bytes());
+        wrong_frame_size.extend_from_slice(header);
+        assert!(parse_dca(&wrong_frame_size).is_err());
+    }
+
+    #[test]
+    fn reader_exposes_header_tags_as_track_metadata() {
+        let frame = opus_frame();
+        let header = DcaHeader {
+            title: Some("Airhorn".into()),
+            artist: Some("Soundboard".into()),
+            ..Default::default()
+        };
+        let mut reader = OpusPacketFormatReader::batch(frame_array(&[&frame]))
+            .with_metadata(header.to_revision());
+        let metadata = reader.metadata();
+        let revision = metadata
+            .current()
+            .expect("metadata revision should be present");
+        let tags = revision
+            .tags()
+            .iter()
+            .map(|tag| (tag.std_key, tag.value.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tags,
+            vec![
+                (Some(StandardTagKey::TrackTitle), "Airhorn".to_owned()),
+                (Some(StandardTagKey::Artist), "Soundboard".to_owned()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,529 @@
+use crate::player::input::PyCompose;
+use crate::resample::StreamResampler;
+use pyo3::types::PyAnyMethods;
+use pyo3::{Py, PyAny, PyResult, Python, intern};
+use songbird::input::codecs::{get_codec_registry, get_probe};
+use songbird::input::core::audio::SampleBuffer;
+use songbird::input::core::errors::Error as SymphError;
+use songbird::input::core::formats::{SeekMode, SeekTo};
+use songbird::input::core::io::MediaSource;
+use songbird::input::core::meta::{MetadataRevision, StandardTagKey};
+use songbird::input::core::units::Time;
+use songbird::input::{AudioStreamError, Input, LiveInput, Parsed};
+use std::collections::VecDeque;
+use std::io;
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use std::time::Duration;
+
+/// Sample rate of decoded PCM handed to the mixer.
+pub(crate) const PCM_SAMPLE_RATE: u32 = 48_000;
+/// Channel count of decoded PCM handed to the mixer.
+pub(crate) const PCM_CHANNELS: usize = 2;
+
+const FRAME_BYTES: usize = PCM_CHANNELS * size_of::<f32>();
+// `RawAdapter` forwards absolute positions, including its 16 byte header.
+const RAW_HEADER_BYTES: u64 = 16;
+
+/// Source of interleaved stereo PCM at 48 kHz.
+pub(crate) trait PcmSource: Send + Sync {
+    /// Fill `out` with interleaved samples and return the number of frames
+    /// written. Returning 0 signals the end of the source.
+    fn read_frames(&mut self, out: &mut [f32]) -> usize;
+
+    /// Move to an absolute frame position.
+    fn seek_frame(&mut self, frame: u64) -> io::Result<()>;
+
+    fn is_seekable(&self) -> bool;
+
+    /// Total length in frames, if known.
+    fn len_frames(&self) -> Option<u64> {
+        None
+    }
+}
+
+impl PcmSource for Box<dyn PcmSource> {
+    fn read_frames(&mut self, out: &mut [f32]) -> usize {
+        (**self).read_frames(out)
+    }
+
+    fn seek_frame(&mut self, frame: u64) -> io::Result<()> {
+        (**self).seek_frame(frame)
+    }
+
+    fn is_seekable(&self) -> bool {
+        (**self).is_seekable()
+    }
+
+    fn len_frames(&self) -> Option<u64> {
+        (**self).len_frames()
+    }
+}
+
+/// Byte stream over a `PcmSource`, suitable for `RawAdapter`.
+pub(crate) struct PcmStream<S> {
+    source: S,
+    carry: Vec<u8>,
+    scratch: Vec<f32>,
+}
+
+/// A child input decoded to stereo 48 kHz PCM.
+pub(crate) struct DecodedInput {
+    parsed: Parsed,
+    samples: Option<SampleBuffer<f32>>,
+    resampler: Option<StreamResampler>,
+    pending: VecDeque<f32>,
+    skip_frames: u64,
+    finished: bool,
+}
+
+impl<S: PcmSource> PcmStream<S> {
+    pub(crate) fn new(source: S) -> Self {
+        Self {
+            source,
+            carry: Vec::new(),
+            scratch: Vec::new(),
+        }
+    }
+}
+
+impl<S: PcmSource> Read for PcmStream<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.carry.is_empty() {
+            let frames = buf.len().div_ceil(FRAME_BYTES);
+            self.scratch.resize(frames * PCM_CHANNELS, 0.0);
+            let written = self.source.read_frames(&mut self.scratch);
+            self.carry = self.scratch[..written * PCM_CHANNELS]
+                .iter()
+                .flat_map(|sample| sample.to_le_bytes())
+                .collect();
+        }
+        let len = buf.len().min(self.carry.len());
+        buf[..len].copy_from_slice(&self.carry[..len]);
+        self.carry.drain(..len);
+        Ok(len)
+    }
+}
+
+impl<S: PcmSource> Seek for PcmStream<S> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let SeekFrom::Start(position) = pos else {
+            return Err(ErrorKind::Unsupported.into());
+        };
+        let frame = position.saturating_sub(RAW_HEADER_BYTES) / FRAME_BYTES as u64;
+        self.source.seek_frame(frame)?;
+        self.carry.clear();
+        Ok(frame * FRAME_BYTES as u64)
+    }
+}
+
+impl<S: PcmSource> MediaSource for PcmStream<S> {
+    fn is_seekable(&self) -> bool {
+        self.source.is_seekable()
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        None
+    }
+}
+
+impl DecodedInput {
+    /// Create and parse `input`, awaiting async sources first.
+    pub(crate) async fn open(input: Input) -> Result<Self, AudioStreamError> {
+        let live = match input {
+            Input::Lazy(mut compose) => LiveInput::Raw(if compose.should_create_async() {
+                compose.create_async().await?
+            } else {
+                compose.create()?
+            }),
+            Input::Live(live, _) => live,
+        };
+        let promoted =
+            tokio::task::spawn_blocking(move || live.promote(get_codec_registry(), get_probe()))
+                .await
+                .map_err(|err| AudioStreamError::Fail(Box::new(err)))?
+                .map_err(|err| AudioStreamError::Fail(Box::new(err)))?;
+        match promoted {
+            LiveInput::Parsed(parsed) => Ok(Self::new(parsed)),
+            _ => unreachable!("LiveInput::promote always returns Parsed on success"),
+        }
+    }
+
+    /// ReplayGain track gain in dB, from container or probed tags.
+    pub(crate) fn replay_gain_db(&mut self) -> Option<f32> {
+        let format = self.parsed.format.metadata();
+        if let Some(gain) = format.current().and_then(track_gain) {
+            return Some(gain);
+        }
+        let probed = self.parsed.meta.get()?;
+        probed.current().and_then(track_gain)
+    }
+
+    fn new(parsed: Parsed) -> Self {
+        Self {
+            parsed,
+            samples: None,
+            resampler: None,
+            pending: VecDeque::new(),
+            skip_frames: 0,
+            finished: false,
+        }
+    }
+
+    /// Decode packets until at least `frames` output frames are pending.
+    fn fill(&mut self, frames: usize) {
+        while self.pending.len() < frames * PCM_CHANNELS && !self.finished {
+            let packet = match self.parsed.format.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphError::ResetRequired) => {
+                    self.parsed.decoder.reset();
+                    continue;
+                }
+                Err(_) => {
+                    self.finish();
+                    break;
+                }
+            };
+            if packet.track_id() != self.parsed.track_id {
+                continue;
+            }
+            let decoded = match self.parsed.decoder.decode(&packet) {
+                Ok(decoded) => decoded,
+                Err(SymphError::DecodeError(_)) => continue,
+                Err(_) => {
+                    self.finish();
+                    break;
+                }
+            };
+            let spec = *decoded.spec();
+            let channels = spec.channels.count();
+            if self
+                .samples
+                .as_ref()
+                .is_none_or(|buffer| buffer.capacity() < decoded.frames() * channels)
+            {
+                self.samples = Some(SampleBuffer::new(decoded.capacity() as u64, spec));
+            }
+            let buffer = self
+                .samples
+                .as_mut()
+                .expect("sample buffer was just allocated");
+            buffer.copy_interleaved_ref(decoded);
+            let mut stereo = to_stereo(buffer.samples(), channels);
+            if spec.rate != PCM_SAMPLE_RATE {
+                if self.resampler.is_none() {
+                    match StreamResampler::new(PCM_CHANNELS, spec.rate, PCM_SAMPLE_RATE) {
+                        Ok(resampler) => self.resampler = Some(resampler),
+                        Err(_) => {
+                            self.finished = true;
+                            break;
+                        }
+                    }
+                }
+                let resampler = self.resampler.as_mut().expect("resampler was just created");
+                stereo = resampler.push(&stereo);
+            }
+            self.push(&stereo);
+        }
+    }
+
+    fn push(&mut self, samples: &[f32]) {
+        let skip = (self.skip_frames as usize * PCM_CHANNELS).min(samples.len());
+        self.skip_frames -= (skip / PCM_CHANNELS) as u64;
+        self.pending.extend(&samples[skip..]);
+    }
+
+    fn finish(&mut self) {
+        if let Some(mut resampler) = self.resampler.take() {
+            let tail = resampler.flush();
+            self.push(&tail);
+        }
+        self.finished = true;
+    }
+}
+
+impl PcmSource for DecodedInput {
+    fn read_frames(&mut self, out: &mut [f32]) -> usize {
+        let frames = out.len() / PCM_CHANNELS;
+        self.fill(frames);
+        let available = (self.pending.len() / PCM_CHANNELS).min(frames);
+        for (slot, sample) in out
+            .iter_mut()
+            .zip(self.pending.drain(..available * PCM_CHANNELS))
+        {
+            *slot = sample;
+        }
+        available
+    }
+
+    fn seek_frame(&mut self, frame: u64) -> io::Result<()> {
+        let seconds = frame as f64 / PCM_SAMPLE_RATE as f64;
+        let seeked = self
+            .parsed
+            .format
+            .seek(
+                SeekMode::Accurate,
+                SeekTo::Time {
+                    time: Time::from(seconds),
+                    track_id: Some(self.parsed.track_id),
+                },
+            )
+            .map_err(|err| io::Error::new(ErrorKind::InvalidInput, err))?;
+        self.parsed.decoder.reset();
+        self.pending.clear();
+        self.resampler = None;
+        self.finished = false;
+
+        let time_base = self
+            .parsed
+            .format
+            .tracks()
+            .iter()
+            .find(|track| track.id == self.parsed.track_id)
+            .and_then(|track| track.codec_params.time_base);
+        self.skip_frames = match time_base {
+            Some(time_base) if seeked.required_ts > seeked.actual_ts => {
+                let time = time_base.calc_time(seeked.required_ts - seeked.actual_ts);
+                ((time.seconds as f64 + time.frac) * PCM_SAMPLE_RATE as f64) as u64
+            }
+            _ => 0,
+        };
+        Ok(())
+    }
+
+    fn is_seekable(&self) -> bool {
+        self.parsed.supports_backseek
+    }
+
+    fn len_frames(&self) -> Option<u64> {
+        let params = &self
+            .parsed
+            .format
+            .tracks()
+            .iter()
+            .find(|track| track.id == self.parsed.track_id)?
+            .codec_params;
+        let rate = params.sample_rate?;
+        params
+            .n_frames
+            .map(|frames| frames * PCM_SAMPLE_RATE as u64 / rate as u64)
+    }
+}
+
+/// Resolve inputs by calling `_compose` on each Python input object.
+pub(crate) fn compose_inputs(
+    py: Python,
+    inputs: &[Py<PyAny>],
+    current_loop: &Py<PyAny>,
+) -> PyResult<Vec<Input>> {
+    inputs
+        .iter()
+        .map(|input| {
+            let compose = input
+                .bind(py)
+                .call_method1(intern!(py, "_compose"), (current_loop.clone_ref(py),))?;
+            compose
+                .cast::<PyCompose>()?
+                .borrow_mut()
+                .get_input()
+                .ok_or_else(|| {
+                    pyo3::exceptions::PyRuntimeError::new_err("Input has already been consumed")
+                })
+        })
+        .collect()
+}
+
+/// Convert a duration to a frame count at the mixer rate.
+pub(crate) fn duration_frames(duration: Duration) -> u64 {
+    (duration.as_secs_f64() * PCM_SAMPLE_RATE as f64).round() as u64
+}
+
+/// Parse a ReplayGain value such as `-6.50 dB`.
+fn track_gain(revision: &MetadataRevision) -> Option<f32> {
+    revision
+        .tags()
+        .iter()
+        .find(|tag| tag.std_key == Some(StandardTagKey::ReplayGainTrackGain))
+        .and_then(|tag| parse_gain(&tag.value.to_string()))
+}
+
+fn parse_gain(value: &str) -> Option<f32> {
+    let value = value.trim();
+    let value = value
+        .strip_suffix("dB")
+        .or_else(|| value.strip_suffix("db"))
+        .unwrap_or(value);
+    value.trim().parse().ok()
+}
+
+fn to_stereo(samples: &[f32], channels: usize) -> Vec<f32> {
+    match channels {
+        0 => Vec::new(),
+        1 => samples
+            .iter()
+            .flat_map(|&sample| [sample, sample])
+            .collect(),
+        2 => samples.to_vec(),
+        _ => samples
+            .chunks_exact(channels)
+            .flat_map(|frame| [frame[0], frame[1]])
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Counter {
+        next: u64,
+        end: u64,
+    }
+
+    impl PcmSource for Counter {
+        fn read_frames(&mut self, out: &mut [f32]) -> usize {
+            let frames = ((out.len() / PCM_CHANNELS) as u64).min(self.end - self.next) as usize;
+            for frame in 0..frames {
+                out[frame * 2] = (self.next + frame as u64) as f32;
+                out[frame * 2 + 1] = -((self.next + frame as u64) as f32);
+            }
+            self.next += frames as u64;
+            frames
+        }
+
+        fn seek_frame(&mut self, frame: u64) -> io::Result<()> {
+            self.next = frame;
+            Ok(())
+        }
+
+        fn is_seekable(&self) -> bool {
+            true
+        }
+    }
+
+    fn read_samples(stream: &mut PcmStream<Counter>, bytes: usize) -> Vec<f32> {
+        let mut buf = vec![0_u8; bytes];
+        let mut filled = 0;
+        while filled < bytes {
+            let n = stream.read(&mut buf[filled..]).unwrap();
+            if n == 0 {
+                break;
+            }
+            filled += n;
+        }
+        buf[..filled]
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn pcm_stream_serializes_frames_across_partial_reads() {
+        let mut stream = PcmStream::new(Counter { next: 0, end: 4 });
+        assert_eq!(read_samples(&mut stream, 12), vec![0.0, -0.0, 1.0]);
+        assert_eq!(
+            read_samples(&mut stream, 64),
+            vec![-1.0, 2.0, -2.0, 3.0, -3.0]
+        );
+    }
+
+    #[test]
+    fn pcm_stream_seeks_past_raw_header() {
+        let mut stream = PcmStream::new(Counter { next: 0, end: 10 });
+        let position = stream
+            .seek(SeekFrom::Start(RAW_HEADER_BYTES + 3 * FRAME_BYTES as u64))
+            .unwrap();
+        assert_eq!(position, 3 * FRAME_BYTES as u64);
+        assert_eq!(read_samples(&mut stream, 8), vec![3.0, -3.0]);
+    }
+
+    fn raw_input(samples: &[f32], sample_rate: u32, channels: u32) -> Input {
+        let bytes: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        songbird::input::RawAdapter::new(std::io::Cursor::new(bytes), sample_rate, channels).into()
+    }
+
+    fn wav_input(samples: &[f32], sample_rate: u32, channels: u16) -> Input {
+        let data_len = (samples.len() * 4) as u32;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16_u32.to_le_bytes());
+        bytes.extend_from_slice(&3_u16.to_le_bytes());
+        bytes.extend_from_slice(&channels.to_le_bytes());
+        bytes.extend_from_slice(&sample_rate.to_le_bytes());
+        bytes.extend_from_slice(&(sample_rate * channels as u32 * 4).to_le_bytes());
+        bytes.extend_from_slice(&(channels * 4).to_le_bytes());
+        bytes.extend_from_slice(&32_u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_len.to_le_bytes());
+        bytes.extend(samples.iter().flat_map(|s| s.to_le_bytes()));
+        Input::Live(
+            LiveInput::Raw(songbird::input::AudioStream {
+                input: Box::new(std::io::Cursor::new(bytes)),
+            }),
+            None,
+        )
+    }
+
+    fn open(input: Input) -> DecodedInput {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(DecodedInput::open(input))
+            .unwrap()
+    }
+
+    fn read_all(input: &mut DecodedInput) -> Vec<f32> {
+        let mut out = Vec::new();
+        let mut chunk = vec![0.0; 1_000 * PCM_CHANNELS];
+        loop {
+            let frames = input.read_frames(&mut chunk);
+            if frames == 0 {
+                return out;
+            }
+            out.extend_from_slice(&chunk[..frames * PCM_CHANNELS]);
+        }
+    }
+
+    #[test]
+    fn decoded_inputs_are_resampled_to_stereo_48k() {
+        let mut input = open(raw_input(&vec![0.5; 24_000], 24_000, 1));
+        let samples = read_all(&mut input);
+        assert_eq!(samples.len(), 48_000 * PCM_CHANNELS);
+        let middle = &samples[samples.len() / 4..samples.len() * 3 / 4];
+        assert!(middle.iter().all(|s| (s - 0.5).abs() < 0.01));
+    }
+
+    #[test]
+    fn decoded_lengths_are_reported_at_48k() {
+        let input = open(wav_input(&vec![0.0; 22_050], 44_100, 1));
+        assert_eq!(input.len_frames(), Some(24_000));
+    }
+
+    #[test]
+    fn replay_gain_values_are_parsed() {
+        assert_eq!(parse_gain("-6.50 dB"), Some(-6.5));
+        assert_eq!(parse_gain("+2.1 db"), Some(2.1));
+        assert_eq!(parse_gain("3"), Some(3.0));
+        assert_eq!(parse_gain("loud"), None);
+    }
+
+    #[test]
+    fn decoded_inputs_seek_to_exact_frames() {
+        let ramp: Vec<f32> = (0..4_800).flat_map(|i| [i as f32, 0.0]).collect();
+        let mut input = open(wav_input(&ramp, PCM_SAMPLE_RATE, 2));
+        input.seek_frame(1_234).unwrap();
+        let mut out = vec![0.0; 2 * PCM_CHANNELS];
+        assert_eq!(input.read_frames(&mut out), 2);
+        assert_eq!(out, vec![1_234.0, 0.0, 1_235.0, 0.0]);
+    }
+
+    #[test]
+    fn channels_are_mapped_to_stereo() {
+        assert_eq!(to_stereo(&[0.5, 0.25], 1), vec![0.5, 0.5, 0.25, 0.25]);
+        assert_eq!(
+            to_stereo(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 3),
+            vec![1.0, 2.0, 4.0, 5.0]
+        );
+    }
+}
@@ -0,0 +1,217 @@
+use crate::model::ArrowArray;
+use crate::player::input::opus::{OPUS_FRAME_SAMPLES, OPUS_SAMPLE_RATE};
+use crate::resample::resample_interleaved;
+use arrow::array::{AsArray, BinaryArray, BinaryBuilder};
+use arrow::datatypes::{DataType, Float32Type, Int16Type};
+use opus2::{Application, Bitrate, Channels, Encoder};
+use pyo3::exceptions::{PyRuntimeError, PyTypeError, PyValueError};
+use pyo3::{PyResult, Python, pyfunction};
+use pyo3_arrow::PyArray;
+use pyo3_stub_gen::derive::gen_stub_pyfunction;
+use std::sync::Arc;
+
+const MAX_PACKET_BYTES: usize = 4_000;
+const FEC_PACKET_LOSS_PERC: i32 = 10;
+
+#[derive(Debug, Clone, Copy)]
+struct EncodeOptions {
+    channels: Channels,
+    bitrate: i32,
+    application: Application,
+    fec: bool,
+}
+
+#[gen_stub_pyfunction(module = "discord.ext.songbird.native.player")]
+#[pyfunction]
+#[pyo3(signature = (pcm, sample_rate, channels, *, bitrate = 128_000, application = "audio", fec = false))]
+/// Encode interleaved PCM into 20 ms Opus frames.
+///
+/// Parameters
+/// ----------
+/// pcm : pyarrow.FloatArray | pyarrow.Int16Array
+///     Interleaved PCM samples. Float samples are expected in `[-1.0, 1.0]`.
+/// sample_rate : int
+///     Sample rate of `pcm` in Hz. Audio is resampled to 48 kHz when needed.
+/// channels : int
+///     Channel count of `pcm`, either 1 or 2.
+/// bitrate : int, optional
+///     Target bitrate in bits per second.
+/// application : {"audio", "voip"}, optional
+///     Encoder tuning. Use `"voip"` for speech.
+/// fec : bool, optional
+///     Enable in-band forward error correction, tuned for 10% packet loss.
+///
+/// Returns
+/// -------
+/// pyarrow.BinaryArray
+///     One Opus frame per row, ready for `OpusPacketInput` or `dca.encode`.
+///
+/// Notes
+/// -----
+/// The final frame is padded with silence up to 20 ms. Encoding releases the
+/// GIL.
+///
+/// Examples
+/// --------
+/// ```python
+/// frames = player.encode_opus(pcm, 44100, 2)
+/// track = Track(OpusPacketInput(frames))
+/// ```
+pub fn encode_opus<'py>(
+    py: Python<'py>,
+    #[gen_stub(override_type(
+        type_repr = "pyarrow.FloatArray | pyarrow.Int16Array",
+        imports = ("pyarrow")
+    ))]
+    pcm: PyArray,
+    sample_rate: u32,
+    channels: u32,
+    bitrate: i32,
+    application: &str,
+    fec: bool,
+) -> PyResult<ArrowArray<'py, BinaryArray>> {
+    let options = EncodeOptions {
+        channels: match channels {
+            1 => Channels::Mono,
+            2 => Channels::Stereo,
+            _ => return Err(PyValueError::new_err("channels must be 1 or 2")),
+        },
+        bitrate,
+        application: parse_application(application)?,
+        fec,
+    };
+    let samples = pcm_samples(&pcm)?;
+    let frames = py.detach(|| {
+        let samples =
+            resample_interleaved(&samples, channels as usize, sample_rate, OPUS_SAMPLE_RATE)?;
+        encode_frames(&samples, options)
+    })?;
+    Ok(PyArray::from_array_ref(Arc::new(frames))
+        .into_arro3(py)?
+        .into())
+}
+
+fn parse_application(value: &str) -> PyResult<Application> {
+    match value {
+        "audio" => Ok(Application::Audio),
+        "voip" => Ok(Application::Voip),
+        _ => Err(PyValueError::new_err(
+            "application must be \"audio\" or \"voip\"",
+        )),
+    }
+}
+
+fn pcm_samples(pcm: &PyArray) -> PyResult<Vec<f32>> {
+    let array = pcm.array();
+    if array.null_count() > 0 {
+        return Err(PyValueError::new_err("PCM samples must not contain nulls"));
+    }
+    match array.data_type() {
+        DataType::Float32 => Ok(array.as_primitive::<Float32Type>().values().to_vec()),
+        DataType::Int16 => Ok(array
+            .as_primitive::<Int16Type>()
+            .values()
+            .iter()
+            .map(|&s| s as f32 / 32_768.0)
+            .collect()),
+        other => Err(PyTypeError::new_err(format!(
+            "Expected a Float32 or Int16 array, got {other}"
+        ))),
+    }
+}
+
+fn encode_frames(samples: &[f32], options: EncodeOptions) -> PyResult<BinaryArray> {
+    let mut encoder = Encoder::new(OPUS_SAMPLE_RATE, options.channels, options.application)
+        .map_err(to_py_runtime_error)?;
+    encoder
+        .set_bitrate(Bitrate::Bits(options.bitrate))
+        .map_err(|e| PyValueError::new_err(format!("Invalid bitrate: {e}")))?;
+    if options.fec {
+        encoder.set_inband_fec(true).map_err(to_py_runtime_error)?;
+        encoder
+            .set_packet_loss_perc(FEC_PACKET_LOSS_PERC)
+            .map_err(to_py_runtime_error)?;
+    }
+
+    let frame_len = OPUS_FRAME_SAMPLES as usize * options.channels as usize;
+    let mut builder = BinaryBuilder::with_capacity(samples.len().div_ceil(frame_len), 0);
+    let mut output = vec![0_u8; MAX_PACKET_BYTES];
+    let mut padded = vec![0.0_f32; frame_len];
+    for chunk in samples.chunks(frame_len) {
+        let frame = if chunk.len() == frame_len {
+            chunk
+        } else {
+            padded[..chunk.len()].copy_from_slice(chunk);
+            padded[chunk.len()..].fill(0.0);
+            &padded
+        };
+        let len = encoder
+            .encode_float(frame, &mut output)
+            .map_err(to_py_runtime_error)?;
+        builder.append_value(&output[..len]);
+    }
+    Ok(builder.finish())
+}
+
+fn to_py_runtime_error(error: opus2::Error) -> pyo3::PyErr {
+    PyRuntimeError::new_err(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::input::opus::validate_opus_frame;
+    use arrow::array::Array;
+
+    fn options(channels: Channels) -> EncodeOptions {
+        EncodeOptions {
+            channels,
+            bitrate: 64_000,
+            application: Application::Audio,
+            fec: false,
+        }
+    }
+
+    #[test]
+    fn trailing_samples_are_padded_into_a_full_frame() {
+        let samples = vec![0.25_f32; (OPUS_FRAME_SAMPLES as usize * 2 + 100) * 2];
+        let frames = encode_frames(&samples, options(Channels::Stereo)).unwrap();
+        assert_eq!(frames.len(), 3);
+        for frame in frames.iter() {
+            validate_opus_frame(frame.unwrap()).unwrap();
+        }
+    }
+
+    #[test]
+    fn mono_voip_with_fec_produces_mono_frames() {
+        let samples = vec![0.0_f32; OPUS_FRAME_SAMPLES as usize];
+        let frames = encode_frames(
+            &samples,
+            EncodeOptions {
+                application: Application::Voip,
+                fec: true,
+                ..options(Channels::Mono)
+            },
+        )
+        .unwrap();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(
+            opus2::packet::get_nb_channels(frames.value(0)).unwrap(),
+            Channels::Mono
+        );
+    }
+
+    #[test]
+    fn resampled_input_yields_expected_frame_count() {
+        let samples = vec![0.0_f32; 44_100 * 2];
+        let samples = resample_interleaved(&samples, 2, 44_100, OPUS_SAMPLE_RATE).unwrap();
+        let frames = encode_frames(&samples, options(Channels::Stereo)).unwrap();
+        assert_eq!(frames.len(), 50);
+    }
+
+    #[test]
+    fn unknown_applications_are_rejected() {
+        assert!(parse_application("audio").is_ok());
+        assert!(parse_application("lowdelay").is_err());
+    }
+}
@@ -0,0 +1,345 @@
+use crate::player::input::{PyCompose, PyInputBase};
+use async_trait::async_trait;
+use pyo3::exceptions::PyTypeError;
+use pyo3::types::{PyAnyMethods, PyBytes, PyBytesMethods, PyTypeMethods};
+use pyo3::{
+    Bound, Py, PyAny, PyErr, PyRef, PyResult, PyTraverseError, PyVisit, Python, pyclass, pymethods,
+};
+use pyo3_async_runtimes::{TaskLocals, into_future_with_locals};
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use songbird::input::core::io::MediaSource;
+use songbird::input::{AudioStream, AudioStreamError, AuxMetadata, Compose, Input, LiveInput};
+use std::io::Cursor;
+use std::time::Duration;
+
+#[gen_stub_pyclass]
+#[pyclass(
+    name = "LazyInput",
+    extends = PyInputBase,
+    module = "discord.ext.songbird.native.player",
+    skip_from_py_object
+)]
+/// Input created by an async factory when the track starts.
+///
+/// Notes
+/// -----
+/// The factory is awaited on the client's event loop only when playback of
+/// the track begins, so queued tracks do not need to be downloaded up front.
+///
+/// The factory must return encoded bytes or an input Songbird can read as a
+/// byte stream, such as `AudioInput`, `StreamInput` or `RawPCMInput`. Inputs
+/// parsed natively up front (`OpusPacketInput`, `OpusPacketStreamInput` and
+/// `DcaInput`) are rejected with a `TypeError` when the track starts.
+pub struct PyLazyInput {
+    factory: Option<Py<PyAny>>,
+    metadata: AuxMetadata,
+}
+
+struct LazyCompose {
+    factory: Py<PyAny>,
+    current_loop: Py<PyAny>,
+    metadata: AuxMetadata,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl PyLazyInput {
+    #[gen_stub(override_return_type(type_repr = "typing.Self", imports = ("typing")))]
+    #[new]
+    #[pyo3(signature = (factory, *, title = None, artist = None, album = None, duration = None, source_url = None, thumbnail = None))]
+    #[allow(clippy::too_many_arguments)]
+    /// Create a lazy input.
+    ///
+    /// Parameters
+    /// ----------
+    /// factory : Callable[[], Awaitable[InputBase | bytes]]
+    ///     Async callable returning another input or encoded audio bytes.
+    ///     Natively parsed inputs such as `OpusPacketInput` and `DcaInput`
+    ///     are not supported.
+    /// title : str | None, optional
+    ///     Title known before the factory runs.
+    /// artist : str | None, optional
+    ///     Artist known before the factory runs.
+    /// album : str | None, optional
+    ///     Album known before the factory runs.
+    /// duration : datetime.timedelta | None, optional
+    ///     Duration known before the factory runs.
+    /// source_url : str | None, optional
+    ///     Origin URL of the audio.
+    /// thumbnail : str | None, optional
+    ///     Thumbnail URL of the audio.
+    ///
+    /// Returns
+    /// -------
+    /// LazyInput
+    ///
+    /// Raises
+    /// ------
+    /// TypeError
+    ///     If `factory` is not callable.
+    fn new(
+        #[gen_stub(override_type(
+            type_repr = "collections.abc.Callable[[], collections.abc.Awaitable[InputBase | bytes]]",
+            imports = ("collections.abc")
+        ))]
+        factory: Bound<PyAny>,
+        title: Option<String>,
+        artist: Option<String>,
+        album: Option<String>,
+        duration: Option<Duration>,
+        source_url: Option<String>,
+        thumbnail: Option<String>,
+    ) -> PyResult<(Self, PyInputBase)> {
+        if !factory.is_callable() {
+            return Err(PyTypeError::new_err("factory must be callable"));
+        }
+        Ok((
+            Self {
+                factory: Some(factory.unbind()),
+                metadata: AuxMetadata {
+                    title,
+                    artist,
+                    album,
+                    duration,
+                    source_url,
+                    thumbnail,
+                    ..Default::default()
+                },
+            },
+            PyInputBase::new(),
+        ))
+    }
+
+    /// Title provided up front.
+    ///
+    /// Returns
+    /// -------
+    /// str | None
+    #[getter]
+    fn title(&self) -> Option<String> {
+        self.metadata.title.clone()
+    }
+
+    /// Artist provided up front.
+    ///
+    /// Returns
+    /// -------
+    /// str | None
+    #[getter]
+    fn artist(&self) -> Option<String> {
+        self.metadata.artist.clone()
+    }
+
+    /// Album provided up front.
+    ///
+    /// Returns
+    /// -------
+    /// str | None
+    #[getter]
+    fn album(&self) -> Option<String> {
+        self.metadata.album.clone()
+    }
+
+    /// Duration provided up front.
+    ///
+    /// Returns
+    /// -------
+    /// datetime.timedelta | None
+    #[getter]
+    fn duration(&self) -> Option<Duration> {
+        self.metadata.duration
+    }
+
+    /// Origin URL provided up front.
+    ///
+    /// Returns
+    /// -------
+    /// str | None
+    #[getter]
+    fn source_url(&self) -> Option<String> {
+        self.metadata.source_url.clone()
+    }
+
+    /// Thumbnail URL provided up front.
+    ///
+    /// Returns
+    /// -------
+    /// str | None
+    #[getter]
+    fn thumbnail(&self) -> Option<String> {
+        self.metadata.thumbnail.clone()
+    }
+
+    #[gen_stub(skip)]
+    fn _compose<'py>(
+        slf: PyRef<Self>,
+        py: Python<'py>,
+        current_loop: Bound<'py, PyAny>,
+    ) -> PyResult<PyCompose> {
+        let factory = slf
+            .factory
+            .as_ref()
+            .ok_or_else(|| pyo3::exceptions::PyRuntimeError::new_err("LazyInput has been cleared"))?
+            .clone_ref(py);
+        Ok(PyCompose::new_lazy(Box::new(LazyCompose {
+            factory,
+            current_loop: current_loop.unbind(),
+            metadata: slf.metadata.clone(),
+        })))
+    }
+
+    #[gen_stub(skip)]
+    fn __traverse__(&self, visit: PyVisit<'_>) -> Result<(), PyTraverseError> {
+        if let Some(factory) = &self.factory {
+            visit.call(factory)?;
+        }
+        Ok(())
+    }
+
+    #[gen_stub(skip)]
+    fn __clear__(&mut self) {
+        // Clear reference, this decrements ref counter.
+        self.factory = None;
+    }
+}
+
+#[async_trait]
+impl Compose for LazyCompose {
+    fn create(&mut self) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+        Err(AudioStreamError::Unsupported)
+    }
+
+    async fn create_async(
+        &mut self,
+    ) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+        let pending = Python::attach(|py| {
+            let awaitable = self.factory.call0(py)?;
+            let locals = TaskLocals::new(self.current_loop.bind(py).clone());
+            into_future_with_locals(&locals, awaitable.bind(py).clone())
+        })
+        .map_err(to_stream_error)?;
+        let created = pending.await.map_err(to_stream_error)?;
+
+        let input = Python::attach(|py| {
+            let created = created.bind(py);
+            if let Ok(bytes) = created.cast::<PyBytes>() {
+                return Ok(LazyOutput::Bytes(bytes.as_bytes().to_vec()));
+            }
+            if created.cast::<PyInputBase>().is_err() {
+                return Err(PyTypeError::new_err(
+                    "LazyInput factory must return an InputBase or bytes",
+                ));
+            }
+            let compose = created.call_method1("_compose", (self.current_loop.clone_ref(py),))?;
+            let input = compose.cast::<PyCompose>()?.borrow_mut().get_input();
+            match input {
+                Some(Input::Live(LiveInput::Parsed(_), _)) => Err(PyTypeError::new_err(format!(
+                    "LazyInput factory returned {}, which is parsed natively up front; \
+                     return encoded bytes or an input such as AudioInput, StreamInput or RawPCMInput",
+                    created.get_type().name()?
+                ))),
+                Some(input) => Ok(LazyOutput::Input(input)),
+                None => Err(pyo3::exceptions::PyRuntimeError::new_err(
+                    "Input has already been consumed",
+                )),
+            }
+        })
+        .map_err(to_stream_error)?;
+
+        match input {
+            LazyOutput::Bytes(bytes) => Ok(AudioStream {
+                input: Box::new(Cursor::new(bytes)),
+            }),
+            LazyOutput::Input(input) => into_audio_stream(input).await,
+        }
+    }
+
+    fn should_create_async(&self) -> bool {
+        true
+    }
+
+    async fn aux_metadata(&mut self) -> Result<AuxMetadata, AudioStreamError> {
+        Ok(self.metadata.clone())
+    }
+}
+
+enum LazyOutput {
+    Bytes(Vec<u8>),
+    Input(Input),
+}
+
+async fn into_audio_stream(
+    input: Input,
+) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+    match input {
+        Input::Lazy(mut compose) => {
+            if compose.should_create_async() {
+                compose.create_async().await
+            } else {
+                compose.create()
+            }
+        }
+        Input::Live(LiveInput::Raw(stream), _) => Ok(stream),
+        Input::Live(LiveInput::Wrapped(stream), _) => Ok(AudioStream {
+            input: Box::new(stream.input),
+        }),
+        Input::Live(LiveInput::Parsed(_), _) => Err(AudioStreamError::Fail(
+            "LazyInput factories cannot return pre-parsed inputs such as OpusPacketInput or DcaInput"
+                .into(),
+        )),
+    }
+}
+
+fn to_stream_error(err: PyErr) -> AudioStreamError {
+    AudioStreamError::Fail(Box::new(err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use songbird::input::RawAdapter;
+    use std::io::Read;
+
+    struct BytesCompose(Vec<u8>);
+
+    #[async_trait]
+    impl Compose for BytesCompose {
+        fn create(&mut self) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+            Ok(AudioStream {
+                input: Box::new(Cursor::new(self.0.clone())),
+            })
+        }
+
+        async fn create_async(
+            &mut self,
+        ) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+            Err(AudioStreamError::Unsupported)
+        }
+
+        fn should_create_async(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn lazy_inputs_are_created_through_their_compose() {
+        let input = Input::Lazy(Box::new(BytesCompose(vec![1, 2, 3])));
+        let mut stream = block_on(into_audio_stream(input)).unwrap();
+        let mut bytes = Vec::new();
+        stream.input.read_to_end(&mut bytes).unwrap();
+        assert_eq!(bytes, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn raw_live_inputs_are_passed_through() {
+        let raw = RawAdapter::new(Cursor::new(vec![0_u8; 8]), 48_000, 2);
+        let input = Input::Live(
+            LiveInput::Raw(AudioStream {
+                input: Box::new(raw),
+            }),
+            None,
+        );
+        assert!(block_on(into_audio_stream(input)).is_ok());
+    }
+}
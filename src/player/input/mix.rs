@@ -0,0 +1,389 @@
+use crate::player::input::decode::{
+    DecodedInput, PCM_CHANNELS, PCM_SAMPLE_RATE, PcmSource, PcmStream, compose_inputs,
+    duration_frames,
+};
+use crate::player::input::{PyCompose, PyInputBase};
+use async_trait::async_trait;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::types::{PyAnyMethods, PyTuple, PyTupleMethods};
+use pyo3::{Bound, Py, PyAny, PyResult, PyTraverseError, PyVisit, Python, pyclass, pymethods};
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use songbird::input::core::io::MediaSource;
+use songbird::input::{AudioStream, AudioStreamError, Compose, RawAdapter};
+use std::io;
+use std::time::Duration;
+
+const SOFT_CLIP_KNEE: f32 = 0.9;
+
+#[gen_stub_pyclass]
+#[pyclass(
+    name = "MixInput",
+    extends = PyInputBase,
+    module = "discord.ext.songbird.native.player",
+    skip_from_py_object
+)]
+/// Input that overlays several inputs into one track.
+///
+/// Notes
+/// -----
+/// Children are decoded to 48 kHz stereo, scaled by their gain, and summed
+/// with a soft clipper to avoid harsh distortion. The mix ends when the last
+/// child ends.
+pub struct PyMixInput {
+    children: Vec<MixChild>,
+}
+
+struct MixChild {
+    input: Py<PyAny>,
+    gain: f32,
+    offset: Duration,
+}
+
+struct MixCompose {
+    children: Vec<(Py<PyAny>, f32, u64)>,
+    current_loop: Py<PyAny>,
+}
+
+struct MixSource {
+    children: Vec<MixTrack>,
+    position: u64,
+    scratch: Vec<f32>,
+}
+
+struct MixTrack {
+    input: Box<dyn PcmSource>,
+    gain: f32,
+    offset: u64,
+    finished: bool,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl PyMixInput {
+    #[gen_stub(override_return_type(type_repr = "typing.Self", imports = ("typing")))]
+    #[new]
+    /// Create a mixed input.
+    ///
+    /// Parameters
+    /// ----------
+    /// inputs : list[InputBase | tuple[InputBase, float] | tuple[InputBase, float, datetime.timedelta]]
+    ///     Child inputs, optionally with a gain multiplier and a start offset.
+    ///
+    /// Returns
+    /// -------
+    /// MixInput
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If `inputs` is empty or a gain is negative.
+    fn new(
+        #[gen_stub(override_type(
+            type_repr = "collections.abc.Sequence[InputBase | tuple[InputBase, float] | tuple[InputBase, float, datetime.timedelta]]",
+            imports = ("collections.abc", "datetime")
+        ))]
+        inputs: Vec<Bound<PyAny>>,
+    ) -> PyResult<(Self, PyInputBase)> {
+        if inputs.is_empty() {
+            return Err(PyValueError::new_err(
+                "MixInput requires at least one input",
+            ));
+        }
+        let children = inputs
+            .iter()
+            .map(MixChild::extract)
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok((Self { children }, PyInputBase::new()))
+    }
+
+    #[gen_stub(skip)]
+    fn _compose(&self, py: Python, current_loop: Bound<PyAny>) -> PyResult<PyCompose> {
+        let children = self
+            .children
+            .iter()
+            .map(|child| {
+                (
+                    child.input.clone_ref(py),
+                    child.gain,
+                    duration_frames(child.offset),
+                )
+            })
+            .collect();
+        Ok(PyCompose::new_lazy(Box::new(MixCompose {
+            children,
+            current_loop: current_loop.unbind(),
+        })))
+    }
+
+    #[gen_stub(skip)]
+    fn __traverse__(&self, visit: PyVisit<'_>) -> Result<(), PyTraverseError> {
+        for child in &self.children {
+            visit.call(&child.input)?;
+        }
+        Ok(())
+    }
+
+    #[gen_stub(skip)]
+    fn __clear__(&mut self) {
+        // Clear reference, this decrements ref counter.
+        self.children.clear();
+    }
+}
+
+impl MixChild {
+    fn extract(item: &Bound<PyAny>) -> PyResult<Self> {
+        let (input, gain, offset): (_, f32, Duration) = if let Ok(tuple) = item.cast::<PyTuple>() {
+            match tuple.len() {
+                2 => (
+                    tuple.get_item(0)?,
+                    tuple.get_item(1)?.extract()?,
+                    Duration::ZERO,
+                ),
+                3 => (
+                    tuple.get_item(0)?,
+                    tuple.get_item(1)?.extract()?,
+                    tuple.get_item(2)?.extract()?,
+                ),
+                _ => {
+                    return Err(PyTypeError::new_err(
+                        "MixInput entries must be (input, gain) or (input, gain, offset)",
+                    ));
+                }
+            }
+        } else {
+            (item.clone(), 1.0, Duration::ZERO)
+        };
+        if input.cast::<PyInputBase>().is_err() {
+            return Err(PyTypeError::new_err("MixInput children must be InputBase"));
+        }
+        if gain.is_nan() || gain < 0.0 {
+            return Err(PyValueError::new_err("gain must be zero or positive"));
+        }
+        Ok(Self {
+            input: input.unbind(),
+            gain,
+            offset,
+        })
+    }
+}
+
+#[async_trait]
+impl Compose for MixCompose {
+    fn create(&mut self) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+        Err(AudioStreamError::Unsupported)
+    }
+
+    async fn create_async(
+        &mut self,
+    ) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+        let inputs = Python::attach(|py| {
+            let inputs = self
+                .children
+                .iter()
+                .map(|(input, _, _)| input.clone_ref(py))
+                .collect::<Vec<_>>();
+            compose_inputs(py, &inputs, &self.current_loop)
+        })
+        .map_err(|err| AudioStreamError::Fail(Box::new(err)))?;
+
+        let mut children = Vec::with_capacity(inputs.len());
+        for (input, (_, gain, offset)) in inputs.into_iter().zip(&self.children) {
+            children.push(MixTrack {
+                input: Box::new(DecodedInput::open(input).await?),
+                gain: *gain,
+                offset: *offset,
+                finished: false,
+            });
+        }
+        let source = PcmStream::new(MixSource {
+            children,
+            position: 0,
+            scratch: Vec::new(),
+        });
+        Ok(AudioStream {
+            input: Box::new(RawAdapter::new(
+                source,
+                PCM_SAMPLE_RATE,
+                PCM_CHANNELS as u32,
+            )),
+        })
+    }
+
+    fn should_create_async(&self) -> bool {
+        true
+    }
+}
+
+impl PcmSource for MixSource {
+    fn read_frames(&mut self, out: &mut [f32]) -> usize {
+        let frames = out.len() / PCM_CHANNELS;
+        out.fill(0.0);
+        let mut written = 0;
+        for child in self.children.iter_mut() {
+            if child.finished {
+                continue;
+            }
+            // Silence before the child's offset still counts as output.
+            let lead = child
+                .offset
+                .saturating_sub(self.position)
+                .min(frames as u64) as usize;
+            written = written.max(lead);
+            let wanted = frames - lead;
+            if wanted == 0 {
+                continue;
+            }
+            self.scratch.resize(wanted * PCM_CHANNELS, 0.0);
+            let read = child.input.read_frames(&mut self.scratch);
+            if read < wanted {
+                child.finished = true;
+            }
+            let target = &mut out[lead * PCM_CHANNELS..(lead + read) * PCM_CHANNELS];
+            for (slot, sample) in target.iter_mut().zip(&self.scratch) {
+                *slot += sample * child.gain;
+            }
+            written = written.max(lead + read);
+        }
+        for sample in out[..written * PCM_CHANNELS].iter_mut() {
+            *sample = soft_clip(*sample);
+        }
+        self.position += written as u64;
+        written
+    }
+
+    fn seek_frame(&mut self, frame: u64) -> io::Result<()> {
+        for child in self.children.iter_mut() {
+            child.input.seek_frame(frame.saturating_sub(child.offset))?;
+            child.finished = false;
+        }
+        self.position = frame;
+        Ok(())
+    }
+
+    fn is_seekable(&self) -> bool {
+        self.children.iter().all(|child| child.input.is_seekable())
+    }
+}
+
+/// Pass samples below the knee unchanged and compress peaks towards 1.0.
+pub(crate) fn soft_clip(sample: f32) -> f32 {
+    let magnitude = sample.abs();
+    if magnitude <= SOFT_CLIP_KNEE {
+        return sample;
+    }
+    let headroom = 1.0 - SOFT_CLIP_KNEE;
+    let compressed = SOFT_CLIP_KNEE + headroom * ((magnitude - SOFT_CLIP_KNEE) / headroom).tanh();
+    compressed.copysign(sample)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Constant {
+        value: f32,
+        remaining: usize,
+    }
+
+    impl PcmSource for Constant {
+        fn read_frames(&mut self, out: &mut [f32]) -> usize {
+            let frames = (out.len() / PCM_CHANNELS).min(self.remaining);
+            out[..frames * PCM_CHANNELS].fill(self.value);
+            self.remaining -= frames;
+            frames
+        }
+
+        fn seek_frame(&mut self, _frame: u64) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn is_seekable(&self) -> bool {
+            false
+        }
+    }
+
+    fn track(value: f32, frames: usize, gain: f32, offset: u64) -> MixTrack {
+        MixTrack {
+            input: Box::new(Constant {
+                value,
+                remaining: frames,
+            }),
+            gain,
+            offset,
+            finished: false,
+        }
+    }
+
+    #[test]
+    fn children_are_summed_with_gain_and_offset() {
+        let mut mix = MixSource {
+            children: vec![track(0.25, 4, 1.0, 0), track(0.5, 4, 0.5, 2)],
+            position: 0,
+            scratch: Vec::new(),
+        };
+        let mut out = vec![0.0; 8 * PCM_CHANNELS];
+        assert_eq!(mix.read_frames(&mut out), 6);
+        let left: Vec<f32> = out.iter().step_by(2).copied().collect();
+        assert_eq!(left, vec![0.25, 0.25, 0.5, 0.5, 0.25, 0.25, 0.0, 0.0]);
+        assert_eq!(mix.read_frames(&mut out), 0);
+    }
+
+    #[test]
+    fn mix_stream_plays_back_through_the_raw_reader() {
+        let source = PcmStream::new(MixSource {
+            children: vec![track(0.25, 960, 1.0, 0), track(0.25, 960, 1.0, 480)],
+            position: 0,
+            scratch: Vec::new(),
+        });
+        let input = RawAdapter::new(source, PCM_SAMPLE_RATE, PCM_CHANNELS as u32).into();
+        let mut decoded = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(DecodedInput::open(input))
+            .unwrap();
+        let mut out = vec![0.0; 2_000 * PCM_CHANNELS];
+        let mut frames = 0;
+        loop {
+            let read = decoded.read_frames(&mut out[frames * PCM_CHANNELS..]);
+            if read == 0 {
+                break;
+            }
+            frames += read;
+        }
+        assert_eq!(frames, 1_440);
+        assert_eq!(out[0], 0.25);
+        assert_eq!(out[600 * PCM_CHANNELS], 0.5);
+        assert_eq!(out[1_200 * PCM_CHANNELS], 0.25);
+    }
+
+    #[test]
+    fn mixed_peaks_are_soft_clipped() {
+        let mut mix = MixSource {
+            children: vec![track(0.8, 2, 1.0, 0), track(0.8, 2, 1.0, 0)],
+            position: 0,
+            scratch: Vec::new(),
+        };
+        let mut out = vec![0.0; 2 * PCM_CHANNELS];
+        assert_eq!(mix.read_frames(&mut out), 2);
+        assert!(
+            out.iter()
+                .all(|&sample| sample > SOFT_CLIP_KNEE && sample <= 1.0)
+        );
+    }
+
+    #[test]
+    fn soft_clip_is_transparent_below_the_knee() {
+        assert_eq!(soft_clip(0.5), 0.5);
+        assert_eq!(soft_clip(-0.9), -0.9);
+    }
+
+    #[test]
+    fn soft_clip_keeps_peaks_within_full_scale() {
+        for sample in [1.0_f32, 1.5, 4.0, 100.0] {
+            let clipped = soft_clip(sample);
+            assert!(clipped > SOFT_CLIP_KNEE && clipped <= 1.0);
+            assert_eq!(soft_clip(-sample), -clipped);
+        }
+        assert!(soft_clip(1.5) > soft_clip(1.0));
+    }
+}
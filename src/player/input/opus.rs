@@ -16,7 +16,7 @@ use songbird::input::core::formats::{
     Cue, FormatOptions, FormatReader, Packet, SeekMode, SeekTo, SeekedTo, Track,
 };
 use songbird::input::core::io::{MediaSourceStream, MediaSourceStreamOptions};
-use songbird::input::core::meta::{Metadata as SymphMetadata, MetadataLog};
+use songbird::input::core::meta::{Metadata as SymphMetadata, MetadataLog, MetadataRevision};
 use songbird::input::core::probe::{
     Descriptor, Hint, Instantiate, Probe, ProbedMetadata, QueryDescriptor,
 };
@@ -27,8 +27,8 @@ use std::io::Cursor;
 use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
 
-const OPUS_SAMPLE_RATE: u32 = 48_000;
-const OPUS_FRAME_SAMPLES: u64 = 960;
+pub(super) const OPUS_SAMPLE_RATE: u32 = 48_000;
+pub(super) const OPUS_FRAME_SAMPLES: u64 = 960;
 const OPUS_TRACK_ID: u32 = 0;
 
 type OpusPacket = Box<[u8]>;
@@ -74,12 +74,12 @@ pub struct PyOpusPacketStreamInput {
 }
 
 #[derive(Clone)]
-enum OpusFrameArray {
+pub(super) enum OpusFrameArray {
     Binary(BinaryArray),
     LargeBinary(LargeBinaryArray),
 }
 
-struct OpusPacketFormatReader {
+pub(super) struct OpusPacketFormatReader {
     source: OpusPacketSource,
     track: Vec<Track>,
     metas: MetadataLog,
@@ -282,6 +282,12 @@ pub fn supported_codecs() -> Vec<&'static str> {
     values
 }
 
+impl PyOpusPacketInput {
+    pub(super) fn frames(&self) -> &OpusFrameArray {
+        &self.frames
+    }
+}
+
 impl PyOpusPacketStreamInput {
     fn sender(&self) -> PyResult<OpusPacketSender> {
         self.sender
@@ -312,7 +318,7 @@ impl PyOpusPacketStreamInput {
 }
 
 impl OpusFrameArray {
-    fn try_from_array(array: ArrayRef) -> PyResult<Self> {
+    pub(super) fn try_from_array(array: ArrayRef) -> PyResult<Self> {
         match array.data_type() {
             DataType::Binary => Ok(Self::Binary(
                 array
@@ -334,7 +340,7 @@ impl OpusFrameArray {
         }
     }
 
-    fn validate(&self) -> PyResult<()> {
+    pub(super) fn validate(&self) -> PyResult<()> {
         if self.null_count() != 0 {
             return Err(PyValueError::new_err(
                 "Opus packet arrays must not contain nulls",
@@ -346,7 +352,7 @@ impl OpusFrameArray {
         Ok(())
     }
 
-    fn len(&self) -> usize {
+    pub(super) fn len(&self) -> usize {
         match self {
             Self::Binary(array) => array.len(),
             Self::LargeBinary(array) => array.len(),
@@ -360,7 +366,7 @@ impl OpusFrameArray {
         }
     }
 
-    fn value(&self, index: usize) -> &[u8] {
+    pub(super) fn value(&self, index: usize) -> &[u8] {
         match self {
             Self::Binary(array) => array.value(index),
             Self::LargeBinary(array) => array.value(index),
@@ -377,7 +383,7 @@ impl OpusFrameArray {
 }
 
 impl OpusPacketFormatReader {
-    fn batch(frames: OpusFrameArray) -> Self {
+    pub(super) fn batch(frames: OpusFrameArray) -> Self {
         Self::new(OpusPacketSource::Batch { frames, index: 0 })
     }
 
@@ -395,6 +401,11 @@ impl OpusPacketFormatReader {
             metas: MetadataLog::default(),
         }
     }
+
+    pub(super) fn with_metadata(mut self, revision: MetadataRevision) -> Self {
+        self.metas.push(revision);
+        self
+    }
 }
 
 impl FormatReader for OpusPacketFormatReader {
@@ -533,7 +544,7 @@ impl FormatReader for EmptyMetadataReader {
     }
 }
 
-fn validate_opus_frame(frame: &[u8]) -> PyResult<()> {
+pub(super) fn validate_opus_frame(frame: &[u8]) -> PyResult<()> {
     if frame.is_empty() {
         return Err(PyValueError::new_err("Opus frames must not be empty"));
     }
@@ -551,7 +562,10 @@ fn validate_opus_frame(frame: &[u8]) -> PyResult<()> {
     Ok(())
 }
 
-fn parsed_input(reader: OpusPacketFormatReader, supports_backseek: bool) -> PyResult<LiveInput> {
+pub(super) fn parsed_input(
+    reader: OpusPacketFormatReader,
+    supports_backseek: bool,
+) -> PyResult<LiveInput> {
     let codec_params = reader
         .default_track()
         .expect("OpusPacketFormatReader always has one track")
@@ -0,0 +1,435 @@
+use crate::client::SongbirdImpl;
+use crate::player::input::decode::{PCM_CHANNELS, PCM_SAMPLE_RATE, PcmSource, PcmStream};
+use crate::player::input::{PyCompose, PyInputBase};
+use crate::receive::VoiceIdentityMap;
+use crate::receive::format::{PcmDtype, PcmFormat, TICK_DURATION, TickBuilder};
+use crate::receive::sink::{Mixer, StreamSink};
+use crate::receive::tick::{VoiceKey, VoiceTickBatch, key_id};
+use arrow::array::AsArray;
+use arrow::datatypes::Float32Type;
+use async_trait::async_trait;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::{Bound, PyAny, PyResult, pyclass, pymethods};
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use songbird::input::{AudioStream, LiveInput, RawAdapter};
+use songbird::{CoreEvent, Event, EventContext, EventHandler};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io;
+use std::sync::{Arc, Mutex, Weak};
+use tokio::sync::broadcast::error::RecvError;
+
+/// Interleaved samples in one 20 ms tick of relayed audio.
+const TICK_SAMPLES: usize = PCM_SAMPLE_RATE as usize / 50 * PCM_CHANNELS;
+
+#[gen_stub_pyclass]
+#[pyclass(
+    name = "RelayInput",
+    extends = PyInputBase,
+    module = "discord.ext.songbird.native.player",
+    skip_from_py_object
+)]
+/// Live input that plays audio received on a voice connection.
+///
+/// Ticks from the source are mixed natively and played with bounded latency,
+/// so one channel can be relayed into another, or a user echoed back for a
+/// microphone test, without Python in the audio path.
+///
+/// Notes
+/// -----
+/// The input never ends on its own; stop its track to end the relay. When
+/// the source falls behind, silence is played; when playback falls behind,
+/// the oldest audio beyond `max_latency_ms` is dropped.
+///
+/// Examples
+/// --------
+/// ```python
+/// source = await lobby.connect(cls=songbird.SongbirdClient)
+/// target = await stage.connect(cls=songbird.SongbirdClient)
+/// await target.play(songbird.Track(player.RelayInput(source)))
+/// ```
+pub struct PyRelayInput {
+    hub: Arc<RelayHub>,
+}
+
+/// Selects and mixes the sources of each tick, then feeds every output.
+struct RelayHub {
+    keys: Option<HashSet<VoiceKey>>,
+    mixdown: bool,
+    max_samples: usize,
+    /// Sums the selected speakers of each tick into 48 kHz stereo floats.
+    mixer: Mutex<Mixer>,
+    outputs: Mutex<Vec<Weak<RelayBuffer>>>,
+}
+
+/// Relayed samples waiting to be played by one output.
+struct RelayBuffer {
+    samples: Mutex<VecDeque<f32>>,
+    max_samples: usize,
+}
+
+/// Playback side of a `RelayBuffer`, keeping the hub alive while it plays.
+struct RelaySource {
+    buffer: Arc<RelayBuffer>,
+    _hub: Arc<RelayHub>,
+}
+
+/// Feeds ticks from a call into a hub, until the hub is dropped.
+struct RelayHandler {
+    hub: Weak<RelayHub>,
+    builder: TickBuilder,
+    identities: Arc<VoiceIdentityMap>,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl PyRelayInput {
+    #[gen_stub(override_return_type(type_repr = "typing.Self", imports = ("typing")))]
+    #[new]
+    #[pyo3(signature = (source, *, keys = None, mixdown = true, max_latency_ms = 100))]
+    /// Create a relay input.
+    ///
+    /// Parameters
+    /// ----------
+    /// source : SongbirdClient | receive.StreamSink
+    ///     A connected voice client to receive from, or a `StreamSink` that
+    ///     is already listening. A `StreamSink` must use the default 48 kHz
+    ///     stereo format, and the relay holds one of its stream permits.
+    /// keys : list[receive.VoiceKey] | None, optional
+    ///     Sources to relay. If None, every speaker is relayed.
+    /// mixdown : bool, optional
+    ///     If True, the selected speakers are summed. If False, only the one
+    ///     most likely to be speaking in each tick is played, which needs
+    ///     voice activity scores: a `StreamSink` source must be created with
+    ///     `vad=True`.
+    /// max_latency_ms : int, optional
+    ///     Most audio, in milliseconds, buffered before the oldest is
+    ///     dropped. Must be at least 20.
+    ///
+    /// Returns
+    /// -------
+    /// RelayInput
+    ///
+    /// Raises
+    /// ------
+    /// TypeError
+    ///     If `source` is neither a voice client nor a `StreamSink`.
+    /// ValueError
+    ///     If the client is not connected, the sink's format is not 48 kHz
+    ///     stereo, `mixdown` is False for a sink without `vad=True`, or
+    ///     `max_latency_ms` is below 20.
+    fn new(
+        #[gen_stub(override_type(
+            type_repr = "native.SongbirdImpl | receive.StreamSink",
+            imports = ("discord.ext.songbird.native", "discord.ext.songbird.native.receive")
+        ))]
+        source: &Bound<PyAny>,
+        keys: Option<Vec<VoiceKey>>,
+        mixdown: bool,
+        max_latency_ms: u64,
+    ) -> PyResult<(Self, PyInputBase)> {
+        if max_latency_ms < 20 {
+            return Err(PyValueError::new_err("max_latency_ms must be at least 20"));
+        }
+        let max_frames = max_latency_ms * u64::from(PCM_SAMPLE_RATE) / 1000;
+        let hub = Arc::new(RelayHub::new(
+            keys.map(|keys| keys.into_iter().collect()),
+            mixdown,
+            max_frames as usize * PCM_CHANNELS,
+        ));
+
+        if let Ok(client) = source.cast::<SongbirdImpl>() {
+            let client = client.borrow();
+            client.add_global_event(
+                Event::Core(CoreEvent::VoiceTick),
+                RelayHandler {
+                    hub: Arc::downgrade(&hub),
+                    builder: relay_builder(mixdown),
+                    identities: client.identity_map(),
+                },
+            )?;
+        } else if let Ok(sink) = source.cast::<StreamSink>() {
+            let sink = sink.borrow();
+            if !mixdown && !sink.scores_vad() {
+                return Err(PyValueError::new_err(
+                    "mixdown=False needs a StreamSink created with vad=True",
+                ));
+            }
+            let (format, mut ticks, permit) = sink.subscribe()?;
+            if format.sample_rate != PCM_SAMPLE_RATE || format.channels != PCM_CHANNELS {
+                return Err(PyValueError::new_err(
+                    "RelayInput needs a StreamSink with 48 kHz stereo PCM",
+                ));
+            }
+            let weak = Arc::downgrade(&hub);
+            pyo3_async_runtimes::tokio::get_runtime().spawn(async move {
+                let _permit = permit;
+                loop {
+                    match ticks.recv().await {
+                        Ok(tick) => match weak.upgrade() {
+                            Some(hub) => hub.push(&tick),
+                            None => break,
+                        },
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => break,
+                    }
+                }
+            });
+        } else {
+            return Err(PyTypeError::new_err(
+                "source must be a SongbirdClient or a StreamSink",
+            ));
+        }
+        Ok((Self { hub }, PyInputBase::new()))
+    }
+
+    #[gen_stub(skip)]
+    fn _compose(&self, _current_loop: Bound<PyAny>) -> PyResult<PyCompose> {
+        let source = RelaySource {
+            buffer: self.hub.add_output(),
+            _hub: self.hub.clone(),
+        };
+        Ok(PyCompose::new_live(
+            LiveInput::Raw(AudioStream {
+                input: Box::new(RawAdapter::new(
+                    PcmStream::new(source),
+                    PCM_SAMPLE_RATE,
+                    PCM_CHANNELS as u32,
+                )),
+            }),
+            None,
+        ))
+    }
+}
+
+/// Builds ticks received from a client. Picking one speaker per tick relies
+/// on voice activity scores, which are only computed when asked for.
+fn relay_builder(mixdown: bool) -> TickBuilder {
+    TickBuilder::new(PcmFormat::default()).with_vad(!mixdown)
+}
+
+impl RelayHub {
+    fn new(keys: Option<HashSet<VoiceKey>>, mixdown: bool, max_samples: usize) -> Self {
+        let format = PcmFormat {
+            sample_rate: PCM_SAMPLE_RATE,
+            channels: PCM_CHANNELS,
+            dtype: PcmDtype::Float32,
+        };
+        let chunk_ms = TICK_DURATION.as_millis() as u64;
+        let mixer = Mixer::new(format, HashMap::new(), HashSet::new(), chunk_ms)
+            .expect("one tick is a valid chunk length");
+        Self {
+            keys,
+            mixdown,
+            max_samples,
+            mixer: Mutex::new(mixer),
+            outputs: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn add_output(&self) -> Arc<RelayBuffer> {
+        let buffer = Arc::new(RelayBuffer {
+            samples: Mutex::new(VecDeque::with_capacity(self.max_samples)),
+            max_samples: self.max_samples,
+        });
+        self.outputs.lock().unwrap().push(Arc::downgrade(&buffer));
+        buffer
+    }
+
+    fn is_idle(&self) -> bool {
+        self.outputs.lock().unwrap().is_empty()
+    }
+
+    fn push(&self, batch: &VoiceTickBatch) {
+        let mut outputs = self.outputs.lock().unwrap();
+        outputs.retain(|output| output.strong_count() > 0);
+        if outputs.is_empty() {
+            return;
+        }
+        let samples = self.mix(batch);
+        for output in outputs.iter().filter_map(Weak::upgrade) {
+            output.push(&samples);
+        }
+    }
+
+    /// One tick of relayed audio; silence when no selected source speaks.
+    fn mix(&self, batch: &VoiceTickBatch) -> Vec<f32> {
+        let mut speakers: Vec<_> = batch
+            .speaking_keys()
+            .into_iter()
+            .filter(|key| self.keys.as_ref().is_none_or(|keys| keys.contains(key)))
+            .collect();
+        if !self.mixdown {
+            let loudest = speakers.into_iter().max_by(|a, b| {
+                let vad = |key| batch.vad(key).unwrap_or_default();
+                vad(a).total_cmp(&vad(b)).then(key_id(b).cmp(&key_id(a)))
+            });
+            speakers = loudest.into_iter().collect();
+        }
+
+        let chunk = self
+            .mixer
+            .lock()
+            .unwrap()
+            .push_keys(batch, &speakers)
+            .expect("the mixer emits every tick");
+        chunk.as_primitive::<Float32Type>().values().to_vec()
+    }
+}
+
+impl RelayBuffer {
+    fn push(&self, samples: &[f32]) {
+        let mut queued = self.samples.lock().unwrap();
+        queued.extend(samples);
+        let excess = queued.len().saturating_sub(self.max_samples);
+        queued.drain(..excess);
+    }
+}
+
+impl PcmSource for RelaySource {
+    fn read_frames(&mut self, out: &mut [f32]) -> usize {
+        let mut queued = self.buffer.samples.lock().unwrap();
+        let frames = (out.len() / PCM_CHANNELS).min(queued.len() / PCM_CHANNELS);
+        if frames == 0 {
+            // Never end: cover an underrun with at most one tick of silence.
+            let frames = (out.len() / PCM_CHANNELS).min(TICK_SAMPLES / PCM_CHANNELS);
+            out[..frames * PCM_CHANNELS].fill(0.0);
+            return frames;
+        }
+        for (slot, sample) in out.iter_mut().zip(queued.drain(..frames * PCM_CHANNELS)) {
+            *slot = sample;
+        }
+        frames
+    }
+
+    fn seek_frame(&mut self, _frame: u64) -> io::Result<()> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    fn is_seekable(&self) -> bool {
+        false
+    }
+}
+
+#[async_trait]
+impl EventHandler for RelayHandler {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        let Some(hub) = self.hub.upgrade() else {
+            return Some(Event::Cancel);
+        };
+        if let EventContext::VoiceTick(tick) = ctx {
+            if hub.is_idle() {
+                self.builder.skip();
+            } else {
+                hub.push(&self.builder.build(tick, &*self.identities));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::receive::vad::{test_noise, test_voiced};
+
+    fn hub(keys: Option<&[VoiceKey]>, mixdown: bool, max_samples: usize) -> RelayHub {
+        RelayHub::new(
+            keys.map(|keys| keys.iter().cloned().collect()),
+            mixdown,
+            max_samples,
+        )
+    }
+
+    #[test]
+    fn selected_speakers_are_mixed() {
+        let batch = VoiceTickBatch::from_test_constants(
+            0,
+            TICK_SAMPLES,
+            &[
+                (VoiceKey::User(1), 8_192),
+                (VoiceKey::User(2), 4_096),
+                (VoiceKey::User(3), 16_384),
+            ],
+        );
+        let all = hub(None, true, TICK_SAMPLES).mix(&batch);
+        assert!(all.iter().all(|sample| *sample == 0.875));
+
+        let keys = [VoiceKey::User(1), VoiceKey::User(2)];
+        let some = hub(Some(&keys), true, TICK_SAMPLES).mix(&batch);
+        assert_eq!(some[0], 0.375);
+
+        let nobody = hub(Some(&[VoiceKey::User(9)]), true, TICK_SAMPLES).mix(&batch);
+        assert!(nobody.iter().all(|sample| *sample == 0.0));
+    }
+
+    #[test]
+    fn without_mixdown_only_the_likeliest_speaker_plays() {
+        let pcm = vec![8_192; TICK_SAMPLES];
+        let quiet = VoiceTickBatch::from_test_row(0, VoiceKey::User(1), Some(&pcm), None, 0.9);
+        let mix = hub(None, false, TICK_SAMPLES).mix(&quiet);
+        assert_eq!(mix[0], 0.25);
+
+        let batch = VoiceTickBatch::from_test_constants(
+            0,
+            TICK_SAMPLES,
+            &[(VoiceKey::User(1), 8_192), (VoiceKey::User(2), 4_096)],
+        );
+        // Equal scores fall back to the lowest key.
+        assert_eq!(hub(None, false, TICK_SAMPLES).mix(&batch)[0], 0.25);
+    }
+
+    #[test]
+    fn without_mixdown_received_ticks_are_scored_for_speech() {
+        let identities = VoiceIdentityMap::default();
+        identities.insert(10, 1);
+        identities.insert(20, 2);
+        let builder = relay_builder(false);
+        let hub = hub(None, false, TICK_SAMPLES);
+        let mut seed = 7;
+        let mut mix = Vec::new();
+        for tick in 0..5 {
+            let noise = test_noise(0.1, &mut seed);
+            let voiced = test_voiced(0.1, tick * TICK_SAMPLES / PCM_CHANNELS);
+            let batch = builder.build_test_rows(&[(10, noise), (20, voiced.clone())], &identities);
+            mix = hub.mix(&batch);
+            assert_eq!(mix.len(), voiced.len());
+            mix.iter_mut()
+                .zip(&voiced)
+                .for_each(|(out, sample)| *out -= f32::from(*sample) / 32_768.0);
+        }
+        // The voiced user 2 plays, not user 1 with the lower key.
+        assert!(mix.iter().all(|sample| *sample == 0.0));
+    }
+
+    #[test]
+    fn outputs_are_bounded_and_cover_underruns_with_silence() {
+        let hub = Arc::new(hub(None, true, TICK_SAMPLES * 2));
+        let mut source = RelaySource {
+            buffer: hub.add_output(),
+            _hub: hub.clone(),
+        };
+        for value in [1, 2, 3] {
+            hub.push(&VoiceTickBatch::from_test_constants(
+                0,
+                TICK_SAMPLES,
+                &[(VoiceKey::User(1), value * 8_192)],
+            ));
+        }
+
+        let mut out = vec![1.0; TICK_SAMPLES * 4];
+        assert_eq!(source.read_frames(&mut out), TICK_SAMPLES);
+        assert_eq!(out[0], 0.5, "the oldest tick was dropped");
+        assert_eq!(out[TICK_SAMPLES], 0.75);
+
+        assert_eq!(source.read_frames(&mut out), TICK_SAMPLES / PCM_CHANNELS);
+        assert!(out[..TICK_SAMPLES].iter().all(|sample| *sample == 0.0));
+
+        drop(source);
+        hub.push(&VoiceTickBatch::from_test_constants(
+            0,
+            TICK_SAMPLES,
+            &[(VoiceKey::User(1), 1)],
+        ));
+        assert!(hub.is_idle());
+    }
+}
@@ -0,0 +1,182 @@
+use async_trait::async_trait;
+use pyo3::types::PyAnyMethods;
+use pyo3::{Py, PyAny, PyResult, Python, intern};
+use songbird::input::core::io::MediaSource;
+use songbird::input::{AudioStream, AudioStreamError, Compose, RawAdapter};
+use std::io;
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+
+/// Byte source backed by a Python object implementing the `InputBase` protocol.
+///
+/// `read(n)` is required. `seek(pos)` makes the source seekable, and
+/// `byte_len()` reports the total length when it is known.
+pub(super) struct PySource {
+    source: Py<PyAny>,
+    seekable: bool,
+    position: u64,
+}
+
+/// Interleaved float32 PCM layout declared by a Python input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct PcmFormat {
+    pub sample_rate: u32,
+    pub channels: u32,
+}
+
+pub(super) struct PySourceCompose {
+    source: Py<PyAny>,
+    pcm: Option<PcmFormat>,
+}
+
+impl PySource {
+    fn new(py: Python, source: Py<PyAny>) -> Self {
+        let seekable = source
+            .bind(py)
+            .hasattr(intern!(py, "seek"))
+            .unwrap_or(false);
+        Self {
+            source,
+            seekable,
+            position: 0,
+        }
+    }
+}
+
+impl PySourceCompose {
+    pub(super) fn new(source: Py<PyAny>, pcm: Option<PcmFormat>) -> Self {
+        Self { source, pcm }
+    }
+}
+
+impl Read for PySource {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let chunk = Python::attach(|py| {
+            self.source
+                .call_method1(py, intern!(py, "read"), (buf.len(),))?
+                .extract::<Vec<u8>>(py)
+        })
+        .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+        if chunk.len() > buf.len() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "read(n) returned more than n bytes",
+            ));
+        }
+        buf[..chunk.len()].copy_from_slice(&chunk);
+        self.position += chunk.len() as u64;
+        Ok(chunk.len())
+    }
+}
+
+impl Seek for PySource {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        if !self.seekable {
+            return Err(io::Error::new(
+                ErrorKind::Unsupported,
+                "input does not implement seek(pos)",
+            ));
+        }
+        let byte_len = match pos {
+            SeekFrom::End(_) => MediaSource::byte_len(self),
+            _ => None,
+        };
+        let target = seek_target(self.position, byte_len, pos)?;
+        let position = Python::attach(|py| -> PyResult<u64> {
+            let result = self
+                .source
+                .call_method1(py, intern!(py, "seek"), (target,))?;
+            Ok(result.extract::<Option<u64>>(py)?.unwrap_or(target))
+        })
+        .map_err(|err| io::Error::new(ErrorKind::InvalidInput, err))?;
+        self.position = position;
+        Ok(position)
+    }
+}
+
+impl MediaSource for PySource {
+    fn is_seekable(&self) -> bool {
+        self.seekable
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        Python::attach(|py| {
+            let source = self.source.bind(py);
+            if !source.hasattr(intern!(py, "byte_len")).ok()? {
+                return None;
+            }
+            source
+                .call_method0(intern!(py, "byte_len"))
+                .and_then(|len| len.extract::<Option<u64>>())
+                .ok()
+                .flatten()
+        })
+    }
+}
+
+#[async_trait]
+impl Compose for PySourceCompose {
+    fn create(&mut self) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+        let source = Python::attach(|py| PySource::new(py, self.source.clone_ref(py)));
+        let input: Box<dyn MediaSource> = match self.pcm {
+            Some(pcm) => Box::new(RawAdapter::new(source, pcm.sample_rate, pcm.channels)),
+            None => Box::new(source),
+        };
+        Ok(AudioStream { input })
+    }
+
+    async fn create_async(
+        &mut self,
+    ) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+        Err(AudioStreamError::Unsupported)
+    }
+
+    fn should_create_async(&self) -> bool {
+        false
+    }
+}
+
+fn seek_target(position: u64, byte_len: Option<u64>, pos: SeekFrom) -> io::Result<u64> {
+    let target = match pos {
+        SeekFrom::Start(offset) => Some(offset),
+        SeekFrom::Current(delta) => position.checked_add_signed(delta),
+        SeekFrom::End(delta) => {
+            let len = byte_len.ok_or_else(|| {
+                io::Error::new(
+                    ErrorKind::Unsupported,
+                    "seeking from the end requires byte_len()",
+                )
+            })?;
+            len.checked_add_signed(delta)
+        }
+    };
+    target.ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "seek before start of input"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seek_targets_resolve_relative_offsets() {
+        assert_eq!(seek_target(10, None, SeekFrom::Start(4)).unwrap(), 4);
+        assert_eq!(seek_target(10, None, SeekFrom::Current(-3)).unwrap(), 7);
+        assert_eq!(seek_target(10, Some(100), SeekFrom::End(-20)).unwrap(), 80);
+    }
+
+    #[test]
+    fn invalid_seek_targets_are_rejected() {
+        assert_eq!(
+            seek_target(2, None, SeekFrom::Current(-3))
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidInput
+        );
+        assert_eq!(
+            seek_target(2, None, SeekFrom::End(0)).unwrap_err().kind(),
+            ErrorKind::Unsupported
+        );
+    }
+}
@@ -1,3 +1,4 @@
+pub mod effects;
 pub mod handle;
 pub mod input;
 pub(crate) mod queue;
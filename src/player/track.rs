@@ -1,10 +1,15 @@
+use crate::player::effects::{
+    DuckRole, Ducking, EffectChain, Normalize, PyEffect, SpeedControl, SpeedSettings,
+};
 use crate::player::input::{PyCompose, PyInputBase};
 use nonmax::NonMaxU32;
 use pyo3::{
     Bound, Py, PyAny, PyRefMut, PyResult, PyTraverseError, PyVisit, Python, pyclass, pymethods,
 };
 use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use songbird::input::Input;
 use songbird::tracks::{LoopState, PlayMode, Track};
+use std::sync::Arc;
 
 #[gen_stub_pyclass]
 #[pyclass(
@@ -29,6 +34,16 @@ pub struct PyTrack {
     mode: PlayMode,
     volume: f32,
     loops: LoopState,
+    effects: Vec<Py<PyEffect>>,
+    normalize: Option<Normalize>,
+    speed: Option<SpeedSettings>,
+    role: Option<DuckRole>,
+}
+
+/// Data attached to every songbird track created from a `PyTrack`.
+pub struct TrackData {
+    pub effects: Vec<Py<PyEffect>>,
+    pub(crate) speed: Option<Arc<SpeedControl>>,
 }
 
 #[gen_stub_pymethods]
@@ -51,6 +66,10 @@ impl PyTrack {
             mode: PlayMode::Play,
             volume: 1.0,
             loops: LoopState::Finite(NonMaxU32::ZERO),
+            effects: Vec::new(),
+            normalize: None,
+            speed: None,
+            role: None,
         }
     }
 
@@ -103,11 +122,153 @@ impl PyTrack {
         slf
     }
 
+    /// Set the effects applied to this track.
+    ///
+    /// Parameters
+    /// ----------
+    /// effects : Sequence[Effect]
+    ///     Effects applied in order to the decoded 48 kHz stereo PCM.
+    ///
+    /// Returns
+    /// -------
+    /// Track
+    ///     This track.
+    ///
+    /// Notes
+    /// -----
+    /// A track with effects is always decoded, so Opus passthrough does not
+    /// apply. Pass an empty list to remove effects.
+    fn effects<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        #[gen_stub(override_type(
+            type_repr = "collections.abc.Sequence[Effect]",
+            imports = ("collections.abc")
+        ))]
+        effects: Vec<Bound<'py, PyEffect>>,
+    ) -> PyRefMut<'py, Self> {
+        slf.effects = effects.into_iter().map(Bound::unbind).collect();
+        slf
+    }
+
+    /// Normalize the track loudness.
+    ///
+    /// Parameters
+    /// ----------
+    /// mode : {"r128", "replaygain"} | None
+    ///     `"r128"` measures EBU R128 integrated loudness. `"replaygain"` uses
+    ///     ReplayGain track gain tags and falls back to measuring. None
+    ///     disables normalization.
+    /// target_lufs : float, optional
+    ///     Loudness to normalize to, in LUFS.
+    ///
+    /// Returns
+    /// -------
+    /// Track
+    ///     This track.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If `mode` is unknown or `target_lufs` is above 0.
+    ///
+    /// Notes
+    /// -----
+    /// `AudioInput` and `RawPCMInput` are measured in full before playback
+    /// starts. Other inputs, such as streams, adjust their gain gradually
+    /// while playing.
+    #[pyo3(signature = (mode, *, target_lufs = -18.0))]
+    fn normalize<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        #[gen_stub(override_type(
+            type_repr = "typing.Literal[\"r128\", \"replaygain\"] | None",
+            imports = ("typing")
+        ))]
+        mode: Option<&str>,
+        target_lufs: f32,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        slf.normalize = mode
+            .map(|mode| Normalize::parse(mode, target_lufs))
+            .transpose()?;
+        Ok(slf)
+    }
+
+    /// Enable speed control for this track.
+    ///
+    /// Parameters
+    /// ----------
+    /// speed : float
+    ///     Playback rate between 0.25 and 4.0; 1.0 is normal speed.
+    /// preserve_pitch : bool, optional
+    ///     Time-stretch the audio so the pitch stays the same. When False the
+    ///     audio is resampled, so pitch rises and falls with the speed.
+    ///
+    /// Returns
+    /// -------
+    /// Track
+    ///     This track.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If `speed` is out of range.
+    ///
+    /// Notes
+    /// -----
+    /// Only tracks created with this option accept `TrackHandle.set_speed`.
+    /// Like effects, speed control decodes the track, so Opus passthrough
+    /// does not apply.
+    #[pyo3(signature = (speed = 1.0, *, preserve_pitch = true))]
+    fn speed<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        speed: f32,
+        preserve_pitch: bool,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        slf.speed = Some(SpeedSettings::new(speed, preserve_pitch)?);
+        Ok(slf)
+    }
+
+    /// Tag this track for call-level ducking.
+    ///
+    /// Parameters
+    /// ----------
+    /// role : {"primary", "background"} | None
+    ///     Primary tracks, such as announcements, lower background tracks
+    ///     while they play. None leaves the track unaffected.
+    ///
+    /// Returns
+    /// -------
+    /// Track
+    ///     This track.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If `role` is unknown.
+    ///
+    /// Notes
+    /// -----
+    /// Ducking is configured per call with `SongbirdClient.set_ducking`.
+    /// Background tracks are decoded, so Opus passthrough does not apply.
+    fn role<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        #[gen_stub(override_type(
+            type_repr = "typing.Literal[\"primary\", \"background\"] | None",
+            imports = ("typing")
+        ))]
+        role: Option<&str>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        slf.role = role.map(DuckRole::parse).transpose()?;
+        Ok(slf)
+    }
+
     #[gen_stub(skip)]
     fn __traverse__(&self, visit: PyVisit<'_>) -> Result<(), PyTraverseError> {
         if let Some(input) = &self.input {
             visit.call(input)?;
         }
+        for effect in &self.effects {
+            visit.call(effect)?;
+        }
         Ok(())
     }
 
@@ -115,22 +276,43 @@ impl PyTrack {
     fn __clear__(&mut self) {
         // Clear reference, this decrements ref counter.
         self.input = None;
+        self.effects.clear();
     }
 }
 
 impl PyTrack {
-    pub fn to_track(&self, py: Python, current_loop: Py<PyAny>) -> PyResult<Track> {
+    pub(crate) fn to_track(
+        &self,
+        py: Python,
+        current_loop: Py<PyAny>,
+        ducking: &Arc<Ducking>,
+    ) -> PyResult<Track> {
         let input = self.input.as_ref().ok_or_else(|| {
             pyo3::exceptions::PyRuntimeError::new_err("Track input has been cleared")
         })?;
-        let mut compose = input
-            .call_method1(py, "_compose", (current_loop,))?
-            .cast_bound::<PyCompose>(py)?
-            .borrow_mut();
-        let mut track = Track::new(compose.get_input().unwrap())
+        let speed = self.speed.map(SpeedControl::new);
+        let background = (self.role == Some(DuckRole::Background)).then(|| ducking.clone());
+        let chain = EffectChain::new(py, &self.effects, self.normalize, speed.clone(), background);
+        let input = if chain.is_empty() {
+            let mut compose = input
+                .call_method1(py, "_compose", (current_loop,))?
+                .cast_bound::<PyCompose>(py)?
+                .borrow_mut();
+            compose.get_input().unwrap()
+        } else {
+            Input::Lazy(chain.compose(input.clone_ref(py).into_any(), current_loop))
+        };
+        let data = TrackData {
+            effects: self.effects.iter().map(|e| e.clone_ref(py)).collect(),
+            speed,
+        };
+        let mut track = Track::new_with_data(input, Arc::new(data))
             .loops(self.loops)
             .volume(self.volume);
         track.playing = self.mode.clone();
+        if self.role == Some(DuckRole::Primary) {
+            ducking.attach_primary(&mut track);
+        }
         Ok(track)
     }
 }
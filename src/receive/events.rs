@@ -0,0 +1,284 @@
+use crate::receive::identity::{VoiceIdentityMap, VoiceIdentityResolver};
+use crate::receive::queue::NotifyQueue;
+use async_trait::async_trait;
+use pyo3::{pyclass, pymethods};
+use pyo3_stub_gen::derive::{gen_stub_pyclass_complex_enum, gen_stub_pymethods};
+use songbird::events::context_data::DisconnectReason;
+use songbird::{Event, EventContext, EventHandler};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+#[gen_stub_pyclass_complex_enum]
+#[pyclass(
+    module = "discord.ext.songbird.native.receive",
+    frozen,
+    skip_from_py_object
+)]
+/// Speaking and membership event yielded by `SongbirdClient.voice_events()`.
+///
+/// `user_id` is None for speaking events from an SSRC whose user Discord has
+/// not announced yet; an `SsrcMapped` event follows once it does.
+///
+/// Examples
+/// --------
+/// ```python
+/// async for event in vc.voice_events():
+///     match event:
+///         case receive.VoiceEvent.SpeakingStarted(user_id=user_id):
+///             indicators.on(user_id)
+///         case receive.VoiceEvent.SpeakingStopped(user_id=user_id):
+///             indicators.off(user_id)
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VoiceEvent {
+    /// A source started sending audio.
+    SpeakingStarted { user_id: Option<u64>, ssrc: u32 },
+    /// A source stopped sending audio.
+    SpeakingStopped { user_id: Option<u64>, ssrc: u32 },
+    /// A member left the voice channel.
+    UserDisconnected { user_id: u64 },
+    /// Discord announced which user sends an SSRC.
+    SsrcMapped { ssrc: u32, user_id: u64 },
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl VoiceEvent {
+    /// Return a debug representation.
+    ///
+    /// Returns
+    /// -------
+    /// str
+    fn __repr__(&self) -> String {
+        let user = |user_id: &Option<u64>| match user_id {
+            Some(user_id) => user_id.to_string(),
+            None => "None".to_string(),
+        };
+        match self {
+            VoiceEvent::SpeakingStarted { user_id, ssrc } => format!(
+                "VoiceEvent.SpeakingStarted(user_id={}, ssrc={})",
+                user(user_id),
+                ssrc
+            ),
+            VoiceEvent::SpeakingStopped { user_id, ssrc } => format!(
+                "VoiceEvent.SpeakingStopped(user_id={}, ssrc={})",
+                user(user_id),
+                ssrc
+            ),
+            VoiceEvent::UserDisconnected { user_id } => {
+                format!("VoiceEvent.UserDisconnected(user_id={})", user_id)
+            }
+            VoiceEvent::SsrcMapped { ssrc, user_id } => {
+                format!("VoiceEvent.SsrcMapped(ssrc={}, user_id={})", ssrc, user_id)
+            }
+        }
+    }
+}
+
+/// Speaking and SSRC state used to turn songbird events into `VoiceEvent`s.
+#[derive(Default)]
+struct VoiceEventState {
+    /// SSRCs that sent audio in the last tick, with their user if known.
+    speaking: HashMap<u32, Option<u64>>,
+    /// SSRC mappings already reported.
+    mapped: HashMap<u32, u64>,
+}
+
+impl VoiceEventState {
+    fn user_id(&self, ssrc: u32, identities: &impl VoiceIdentityResolver) -> Option<u64> {
+        self.mapped
+            .get(&ssrc)
+            .copied()
+            .or_else(|| identities.user_id_for_ssrc(ssrc))
+    }
+
+    /// Compare a tick's speaking SSRCs with the previous tick's.
+    fn tick(
+        &mut self,
+        speaking: impl IntoIterator<Item = u32>,
+        identities: &impl VoiceIdentityResolver,
+    ) -> Vec<VoiceEvent> {
+        let now: HashSet<u32> = speaking.into_iter().collect();
+        let mut events = Vec::new();
+
+        let mut stopped: Vec<_> = self
+            .speaking
+            .iter()
+            .filter(|(ssrc, _)| !now.contains(ssrc))
+            .map(|(ssrc, user_id)| (*ssrc, *user_id))
+            .collect();
+        stopped.sort_unstable();
+        for (ssrc, user_id) in stopped {
+            self.speaking.remove(&ssrc);
+            events.push(VoiceEvent::SpeakingStopped {
+                user_id: user_id.or_else(|| self.user_id(ssrc, identities)),
+                ssrc,
+            });
+        }
+
+        let mut started: Vec<_> = now
+            .into_iter()
+            .filter(|ssrc| !self.speaking.contains_key(ssrc))
+            .collect();
+        started.sort_unstable();
+        for ssrc in started {
+            let user_id = self.user_id(ssrc, identities);
+            self.speaking.insert(ssrc, user_id);
+            events.push(VoiceEvent::SpeakingStarted { user_id, ssrc });
+        }
+        events
+    }
+
+    /// Record a speaking update, returning an event if it maps an SSRC to a
+    /// new user.
+    fn speaking_update(&mut self, ssrc: u32, user_id: Option<u64>) -> Option<VoiceEvent> {
+        let user_id = user_id?;
+        if self.mapped.insert(ssrc, user_id) == Some(user_id) {
+            return None;
+        }
+        if let Some(speaking) = self.speaking.get_mut(&ssrc) {
+            *speaking = Some(user_id);
+        }
+        Some(VoiceEvent::SsrcMapped { ssrc, user_id })
+    }
+
+    fn disconnect(&mut self, user_id: u64) -> VoiceEvent {
+        // A rejoining user may be assigned a new SSRC; report it again.
+        self.mapped.retain(|_, mapped| *mapped != user_id);
+        VoiceEvent::UserDisconnected { user_id }
+    }
+}
+
+/// Feeds one `voice_events()` iterator.
+///
+/// The queue is closed when the bot leaves the channel or the call is
+/// dropped, and the handler cancels itself once the iterator is gone.
+pub(crate) struct VoiceEventHandler {
+    identities: Arc<VoiceIdentityMap>,
+    queue: Arc<NotifyQueue<VoiceEvent>>,
+    state: Mutex<VoiceEventState>,
+}
+
+impl VoiceEventHandler {
+    pub(crate) fn new(
+        identities: Arc<VoiceIdentityMap>,
+        queue: Arc<NotifyQueue<VoiceEvent>>,
+    ) -> Self {
+        Self {
+            identities,
+            queue,
+            state: Mutex::new(VoiceEventState::default()),
+        }
+    }
+}
+
+impl Drop for VoiceEventHandler {
+    fn drop(&mut self) {
+        self.queue.close();
+    }
+}
+
+#[async_trait]
+impl EventHandler for VoiceEventHandler {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if self.queue.is_closed() || Arc::strong_count(&self.queue) == 1 {
+            return Some(Event::Cancel);
+        }
+        let events = {
+            let mut state = self.state.lock().unwrap();
+            match ctx {
+                EventContext::VoiceTick(tick) => {
+                    state.tick(tick.speaking.keys().copied(), &*self.identities)
+                }
+                EventContext::SpeakingStateUpdate(speaking) => state
+                    .speaking_update(speaking.ssrc, speaking.user_id.map(|user_id| user_id.0))
+                    .into_iter()
+                    .collect(),
+                EventContext::ClientDisconnect(disconnect) => {
+                    vec![state.disconnect(disconnect.user_id.0)]
+                }
+                EventContext::DriverDisconnect(disconnect)
+                    if matches!(disconnect.reason, None | Some(DisconnectReason::Requested)) =>
+                {
+                    self.queue.close();
+                    return Some(Event::Cancel);
+                }
+                _ => Vec::new(),
+            }
+        };
+        self.queue.extend(events);
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::receive::queue::Overflow;
+
+    #[test]
+    fn speaking_events_follow_consecutive_ticks() {
+        let identities = VoiceIdentityMap::default();
+        identities.insert(1, 10);
+        let mut state = VoiceEventState::default();
+
+        assert_eq!(
+            state.tick([2, 1], &identities),
+            vec![
+                VoiceEvent::SpeakingStarted {
+                    user_id: Some(10),
+                    ssrc: 1
+                },
+                VoiceEvent::SpeakingStarted {
+                    user_id: None,
+                    ssrc: 2
+                },
+            ]
+        );
+        assert_eq!(state.tick([1, 2], &identities), vec![]);
+        assert_eq!(
+            state.speaking_update(2, Some(20)),
+            Some(VoiceEvent::SsrcMapped {
+                ssrc: 2,
+                user_id: 20
+            })
+        );
+        assert_eq!(
+            state.tick([1], &identities),
+            vec![VoiceEvent::SpeakingStopped {
+                user_id: Some(20),
+                ssrc: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn ssrc_mappings_are_reported_once_per_session() {
+        let mut state = VoiceEventState::default();
+
+        assert!(state.speaking_update(5, Some(50)).is_some());
+        assert_eq!(state.speaking_update(5, Some(50)), None);
+        assert_eq!(state.speaking_update(6, None), None);
+        assert_eq!(
+            state.disconnect(50),
+            VoiceEvent::UserDisconnected { user_id: 50 }
+        );
+        assert!(state.speaking_update(5, Some(50)).is_some());
+    }
+
+    #[tokio::test]
+    async fn bounded_queue_keeps_newest_events_until_closed() {
+        let queue = NotifyQueue::new(Some(1), Overflow::DropOldest);
+        queue.extend([
+            VoiceEvent::UserDisconnected { user_id: 1 },
+            VoiceEvent::UserDisconnected { user_id: 2 },
+        ]);
+        queue.close();
+
+        assert_eq!(
+            queue.next().await,
+            Some(VoiceEvent::UserDisconnected { user_id: 2 })
+        );
+        assert_eq!(queue.next().await, None);
+    }
+}
@@ -0,0 +1,231 @@
+use crate::receive::tick::VoiceTickBatch;
+use arrow::array::RecordBatch;
+use arrow::compute::concat_batches;
+use arrow::datatypes::SchemaRef;
+use arrow::error::ArrowError;
+use arrow::ipc::writer::{FileWriter, StreamWriter};
+use pyo3::exceptions::{PyRuntimeError, PyTypeError, PyValueError};
+use pyo3::types::{PyAnyMethods, PyBytes};
+use pyo3::{Bound, Py, PyAny, PyErr, PyResult, Python};
+use pyo3_arrow::PyTable;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Ticks coalesced into each written record batch, one second of audio.
+pub(crate) const TICKS_PER_BATCH: usize = 50;
+
+type Output = BufWriter<Box<dyn Write + Send>>;
+
+/// Arrow IPC flavour to write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum IpcFormat {
+    /// Random-access file format (Feather v2), readable once finished.
+    File,
+    /// Streaming format, readable while it is still being written.
+    Stream,
+}
+
+impl IpcFormat {
+    pub(crate) fn parse(format: &str) -> PyResult<Self> {
+        match format {
+            "file" => Ok(Self::File),
+            "stream" => Ok(Self::Stream),
+            _ => Err(PyValueError::new_err(
+                "format must be \"file\" or \"stream\"",
+            )),
+        }
+    }
+}
+
+pub(crate) enum IpcWriter {
+    File(FileWriter<Output>),
+    Stream(StreamWriter<Output>),
+}
+
+impl IpcWriter {
+    /// Write the schema header to `output`.
+    pub(crate) fn try_new(
+        format: IpcFormat,
+        output: Box<dyn Write + Send>,
+        schema: &SchemaRef,
+    ) -> Result<Self, ArrowError> {
+        Ok(match format {
+            IpcFormat::File => Self::File(FileWriter::try_new_buffered(output, schema)?),
+            IpcFormat::Stream => Self::Stream(StreamWriter::try_new_buffered(output, schema)?),
+        })
+    }
+
+    /// Write `ticks` as record batches of up to [`TICKS_PER_BATCH`] ticks.
+    pub(crate) fn write_ticks(
+        &mut self,
+        schema: &SchemaRef,
+        ticks: &[Arc<VoiceTickBatch>],
+    ) -> Result<(), ArrowError> {
+        for chunk in ticks.chunks(TICKS_PER_BATCH) {
+            let batch = coalesce(schema, chunk)?;
+            match self {
+                Self::File(writer) => writer.write(&batch)?,
+                Self::Stream(writer) => writer.write(&batch)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the footer or end-of-stream marker and flush.
+    pub(crate) fn finish(self) -> Result<(), ArrowError> {
+        let mut output = match self {
+            Self::File(writer) => writer.into_inner()?,
+            Self::Stream(writer) => writer.into_inner()?,
+        };
+        output.flush()?;
+        Ok(())
+    }
+}
+
+/// Concatenate ticks into a single record batch.
+pub(crate) fn coalesce(
+    schema: &SchemaRef,
+    ticks: &[Arc<VoiceTickBatch>],
+) -> Result<RecordBatch, ArrowError> {
+    let batches: Vec<_> = ticks.iter().map(|tick| tick.record_batch()).collect();
+    concat_batches(schema, &batches)
+}
+
+/// Open a path, or wrap a writable binary file object, for export.
+pub(crate) fn open_output(target: &Bound<'_, PyAny>) -> PyResult<Box<dyn Write + Send>> {
+    if let Ok(path) = target.extract::<PathBuf>() {
+        return Ok(Box::new(File::create(path)?));
+    }
+    if target.hasattr("write")? {
+        return Ok(Box::new(PyWriter(target.clone().unbind())));
+    }
+    Err(PyTypeError::new_err(
+        "target must be a path or a writable binary file object",
+    ))
+}
+
+/// Forwards writes to a Python binary file object, attaching to the
+/// interpreter for each call.
+struct PyWriter(Py<PyAny>);
+
+impl Write for PyWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Python::attach(|py| {
+            let written = self
+                .0
+                .bind(py)
+                .call_method1("write", (PyBytes::new(py, buf),))?;
+            // Raw files may write less than asked; buffered ones return None.
+            Ok::<_, PyErr>(written.extract::<Option<usize>>()?.unwrap_or(buf.len()))
+        })
+        .map_err(io::Error::from)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Python::attach(|py| {
+            let file = self.0.bind(py);
+            if file.hasattr("flush")? {
+                file.call_method0("flush")?;
+            }
+            Ok::<_, PyErr>(())
+        })
+        .map_err(io::Error::from)
+    }
+}
+
+/// Write `ticks` as a Parquet file with pyarrow.
+pub(crate) fn write_parquet(
+    py: Python<'_>,
+    target: &Bound<'_, PyAny>,
+    schema: SchemaRef,
+    ticks: &[Arc<VoiceTickBatch>],
+) -> PyResult<()> {
+    let batches = ticks
+        .chunks(TICKS_PER_BATCH)
+        .map(|chunk| coalesce(&schema, chunk))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(arrow_error)?;
+    let table = PyTable::try_new(batches, schema)?.into_pyarrow(py)?;
+    py.import("pyarrow.parquet")?
+        .call_method1("write_table", (table, target))?;
+    Ok(())
+}
+
+pub(crate) fn arrow_error(err: ArrowError) -> PyErr {
+    match err {
+        ArrowError::IoError(_, err) => err.into(),
+        err => PyRuntimeError::new_err(err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::receive::format::PcmDtype;
+    use crate::receive::tick::{VoiceKey, voice_tick_schema};
+    use arrow::ipc::reader::{FileReader, StreamReader};
+    use std::io::Cursor;
+    use std::sync::Mutex;
+
+    /// In-memory output that stays readable after the writer is consumed.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn ticks(count: u64) -> Vec<Arc<VoiceTickBatch>> {
+        (0..count)
+            .map(|tick| {
+                VoiceTickBatch::from_test_rows(
+                    tick,
+                    vec![(VoiceKey::User(1), vec![tick as i16; 4])],
+                )
+            })
+            .collect()
+    }
+
+    fn export(format: IpcFormat, count: u64) -> Vec<u8> {
+        let schema = voice_tick_schema(PcmDtype::Int16);
+        let buffer = SharedBuffer::default();
+        let mut writer = IpcWriter::try_new(format, Box::new(buffer.clone()), &schema).unwrap();
+        writer.write_ticks(&schema, &ticks(count)).unwrap();
+        writer.finish().unwrap();
+        buffer.0.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn file_export_coalesces_ticks() {
+        let bytes = export(IpcFormat::File, 120);
+        let reader = FileReader::try_new(Cursor::new(bytes), None).unwrap();
+        let rows: Vec<_> = reader.map(|batch| batch.unwrap().num_rows()).collect();
+        assert_eq!(rows, [50, 50, 20]);
+    }
+
+    #[test]
+    fn stream_export_round_trips_schema() {
+        let bytes = export(IpcFormat::Stream, 3);
+        let reader = StreamReader::try_new(Cursor::new(bytes), None).unwrap();
+        assert_eq!(reader.schema(), voice_tick_schema(PcmDtype::Int16));
+        let batches: Vec<_> = reader.map(Result::unwrap).collect();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].num_rows(), 3);
+    }
+
+    #[test]
+    fn empty_exports_are_valid() {
+        let bytes = export(IpcFormat::File, 0);
+        let reader = FileReader::try_new(Cursor::new(bytes), None).unwrap();
+        assert_eq!(reader.num_batches(), 0);
+    }
+}
@@ -0,0 +1,74 @@
+use crate::receive::tick::VoiceKey;
+use std::collections::HashSet;
+
+/// Which sources a sink records.
+///
+/// Filtered keys are dropped before their rows are built, so no PCM is
+/// converted or scored for them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyFilter {
+    /// Only these keys are kept, if set. `Unknown` keys are kept as well,
+    /// since they may belong to one of these users.
+    keys: Option<HashSet<VoiceKey>>,
+    /// These keys are always dropped.
+    exclude: HashSet<VoiceKey>,
+    /// Drop ticks in which no kept key is speaking.
+    skip_silent: bool,
+}
+
+impl KeyFilter {
+    pub fn new(
+        keys: Option<Vec<VoiceKey>>,
+        exclude: Option<Vec<VoiceKey>>,
+        skip_silent: bool,
+    ) -> Self {
+        Self {
+            keys: keys.map(|keys| keys.into_iter().collect()),
+            exclude: exclude.unwrap_or_default().into_iter().collect(),
+            skip_silent,
+        }
+    }
+
+    pub fn allows(&self, key: &VoiceKey) -> bool {
+        !self.exclude.contains(key)
+            && (matches!(key, VoiceKey::Unknown(_))
+                || self.keys.as_ref().is_none_or(|keys| keys.contains(key)))
+    }
+
+    pub fn skip_silent(&self) -> bool {
+        self.skip_silent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exclusions_override_allowed_keys() {
+        let open = KeyFilter::default();
+        assert!(open.allows(&VoiceKey::Unknown(3)));
+
+        let filter = KeyFilter::new(
+            Some(vec![VoiceKey::User(1), VoiceKey::User(2)]),
+            Some(vec![VoiceKey::User(2)]),
+            false,
+        );
+        assert!(filter.allows(&VoiceKey::User(1)));
+        assert!(!filter.allows(&VoiceKey::User(2)));
+        assert!(!filter.allows(&VoiceKey::User(3)));
+        // An unmapped source may turn out to be user 1.
+        assert!(filter.allows(&VoiceKey::Unknown(3)));
+
+        let exclude_only = KeyFilter::new(None, Some(vec![VoiceKey::User(2)]), false);
+        assert!(exclude_only.allows(&VoiceKey::User(3)));
+        assert!(!exclude_only.allows(&VoiceKey::User(2)));
+
+        let exclude_unknown = KeyFilter::new(
+            Some(vec![VoiceKey::User(1)]),
+            Some(vec![VoiceKey::Unknown(3)]),
+            false,
+        );
+        assert!(!exclude_unknown.allows(&VoiceKey::Unknown(3)));
+    }
+}
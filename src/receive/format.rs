@@ -0,0 +1,425 @@
+use crate::receive::filter::KeyFilter;
+use crate::receive::identity::VoiceIdentityResolver;
+use crate::receive::tick::{PacketLossCounter, VoiceKey, VoiceTickBatch, key_for_ssrc};
+use crate::receive::vad::VoiceActivityDetector;
+use arrow::array::{ArrayRef, Float32Array, Int16Array};
+use arrow::compute::concat;
+use arrow::datatypes::DataType;
+use pyo3::PyResult;
+use pyo3::exceptions::PyValueError;
+use rubato::{FftFixedInOut, Resampler};
+use songbird::events::context_data::VoiceTick as SongbirdVoiceTick;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+/// Sample rate of PCM decoded by songbird.
+pub(crate) const DECODED_SAMPLE_RATE: u32 = 48_000;
+/// Channel count of PCM decoded by songbird.
+pub(crate) const DECODED_CHANNELS: usize = 2;
+/// Voice ticks arrive every 20 ms.
+pub(crate) const TICKS_PER_SECOND: u32 = 50;
+pub(crate) const TICK_DURATION: Duration = Duration::from_millis(20);
+const DECODED_TICK_FRAMES: usize = (DECODED_SAMPLE_RATE / TICKS_PER_SECOND) as usize;
+
+const MIN_SAMPLE_RATE: u32 = 8_000;
+const MAX_SAMPLE_RATE: u32 = 192_000;
+
+/// Sample type of the `pcm` column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PcmDtype {
+    Int16,
+    Float32,
+}
+
+/// Layout of PCM delivered by a receive sink.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PcmFormat {
+    pub sample_rate: u32,
+    pub channels: usize,
+    pub dtype: PcmDtype,
+}
+
+impl Default for PcmFormat {
+    fn default() -> Self {
+        Self {
+            sample_rate: DECODED_SAMPLE_RATE,
+            channels: DECODED_CHANNELS,
+            dtype: PcmDtype::Int16,
+        }
+    }
+}
+
+impl PcmFormat {
+    pub fn new(sample_rate: u32, channels: usize, dtype: &str) -> PyResult<Self> {
+        if !(MIN_SAMPLE_RATE..=MAX_SAMPLE_RATE).contains(&sample_rate)
+            || !sample_rate.is_multiple_of(TICKS_PER_SECOND)
+        {
+            return Err(PyValueError::new_err(format!(
+                "sample_rate must be a multiple of {TICKS_PER_SECOND} between {MIN_SAMPLE_RATE} and {MAX_SAMPLE_RATE}"
+            )));
+        }
+        if !(1..=DECODED_CHANNELS).contains(&channels) {
+            return Err(PyValueError::new_err("channels must be 1 or 2"));
+        }
+        let dtype = match dtype {
+            "int16" => PcmDtype::Int16,
+            "float32" => PcmDtype::Float32,
+            _ => {
+                return Err(PyValueError::new_err(
+                    "dtype must be \"int16\" or \"float32\"",
+                ));
+            }
+        };
+        Ok(Self {
+            sample_rate,
+            channels,
+            dtype,
+        })
+    }
+
+    /// Whether PCM can be passed through exactly as songbird decoded it.
+    pub fn is_native(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Interleaved samples in one 20 ms tick.
+    pub fn tick_samples(&self) -> usize {
+        (self.sample_rate / TICKS_PER_SECOND) as usize * self.channels
+    }
+
+    /// Arrow type of a single sample.
+    pub fn data_type(&self) -> DataType {
+        match self.dtype {
+            PcmDtype::Int16 => DataType::Int16,
+            PcmDtype::Float32 => DataType::Float32,
+        }
+    }
+}
+
+/// Converts decoded 48 kHz stereo `int16` PCM to a sink's `PcmFormat`.
+///
+/// Resampler state is kept per voice source so consecutive ticks join up,
+/// and dropped once a source stops sending audio. Sources are resampled in
+/// whole 20 ms chunks; samples left over from a shorter tick wait for the
+/// next one.
+pub struct PcmConverter {
+    format: PcmFormat,
+    resamplers: HashMap<VoiceKey, SourceResampler>,
+    input: Vec<Vec<f32>>,
+}
+
+struct SourceResampler {
+    resampler: FftFixedInOut<f32>,
+    /// Per-channel input not yet resampled, shorter than one chunk between
+    /// calls.
+    pending: Vec<Vec<f32>>,
+}
+
+impl PcmConverter {
+    pub fn new(format: PcmFormat) -> Self {
+        Self {
+            format,
+            resamplers: HashMap::new(),
+            input: vec![Vec::with_capacity(DECODED_TICK_FRAMES); format.channels],
+        }
+    }
+
+    pub fn format(&self) -> PcmFormat {
+        self.format
+    }
+
+    /// Convert one tick of decoded PCM from `key` to interleaved `f32`
+    /// samples in the target rate and channel count.
+    ///
+    /// When resampling, a tick that is not exactly 20 ms long yields the
+    /// whole chunks available so far, which may be none.
+    pub fn convert(&mut self, key: &VoiceKey, pcm: &[i16]) -> Vec<f32> {
+        let channels = self.format.channels;
+        for channel in self.input.iter_mut() {
+            channel.clear();
+        }
+        for frame in pcm.chunks_exact(DECODED_CHANNELS) {
+            let frame = frame.iter().map(|&sample| sample as f32 / 32_768.0);
+            if channels == 1 {
+                self.input[0].push(frame.sum::<f32>() / DECODED_CHANNELS as f32);
+            } else {
+                for (channel, sample) in self.input.iter_mut().zip(frame) {
+                    channel.push(sample);
+                }
+            }
+        }
+        if self.format.sample_rate == DECODED_SAMPLE_RATE {
+            return interleave(&self.input, self.input[0].len());
+        }
+        let source = self
+            .resamplers
+            .entry(key.clone())
+            .or_insert_with(|| SourceResampler {
+                resampler: FftFixedInOut::new(
+                    DECODED_SAMPLE_RATE as usize,
+                    self.format.sample_rate as usize,
+                    DECODED_TICK_FRAMES,
+                    channels,
+                )
+                .expect("sample rates are validated by PcmFormat"),
+                pending: vec![Vec::with_capacity(2 * DECODED_TICK_FRAMES); channels],
+            });
+        for (pending, input) in source.pending.iter_mut().zip(&self.input) {
+            pending.extend_from_slice(input);
+        }
+        let mut samples = Vec::new();
+        let mut output = source.resampler.output_buffer_allocate(true);
+        while source.pending[0].len() >= DECODED_TICK_FRAMES {
+            let chunk: Vec<&[f32]> = source
+                .pending
+                .iter()
+                .map(|pending| &pending[..DECODED_TICK_FRAMES])
+                .collect();
+            let (_, written) = source
+                .resampler
+                .process_into_buffer(&chunk, &mut output, None)
+                .expect("input is exactly one resampler chunk");
+            samples.extend(interleave(&output, written));
+            for pending in source.pending.iter_mut() {
+                pending.drain(..DECODED_TICK_FRAMES);
+            }
+        }
+        samples
+    }
+
+    /// Drop resampler state for sources that did not send audio this tick.
+    pub fn retain(&mut self, mut speaking: impl FnMut(&VoiceKey) -> bool) {
+        self.resamplers.retain(|key, _| speaking(key));
+    }
+}
+
+/// Turns songbird ticks into batches in a sink's `PcmFormat`, scoring voice
+/// activity on the way if enabled.
+pub struct TickBuilder {
+    converter: Option<Mutex<PcmConverter>>,
+    vad: Option<Mutex<VoiceActivityDetector>>,
+    loss: Mutex<PacketLossCounter>,
+    filter: Option<Arc<RwLock<KeyFilter>>>,
+    next_tick: AtomicU64,
+}
+
+impl TickBuilder {
+    pub fn new(format: PcmFormat) -> Self {
+        Self {
+            converter: (!format.is_native()).then(|| Mutex::new(PcmConverter::new(format))),
+            vad: None,
+            loss: Mutex::default(),
+            filter: None,
+            next_tick: AtomicU64::new(0),
+        }
+    }
+
+    /// Score voice activity for the `vad` column, which is zero otherwise.
+    pub fn with_vad(mut self, enabled: bool) -> Self {
+        self.vad = enabled.then(Mutex::default);
+        self
+    }
+
+    /// Only build rows for sources `filter` allows. The sink keeps the other
+    /// handle to change it at runtime.
+    pub fn with_filter(mut self, filter: Arc<RwLock<KeyFilter>>) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Whether the filter, if any, keeps `key`.
+    pub fn allows(&self, key: &VoiceKey) -> bool {
+        self.filter
+            .as_ref()
+            .is_none_or(|filter| filter.read().unwrap().allows(key))
+    }
+
+    /// Whether the filter, if any, drops ticks in which no kept source is
+    /// speaking.
+    pub fn skips_silent(&self) -> bool {
+        self.filter
+            .as_ref()
+            .is_some_and(|filter| filter.read().unwrap().skip_silent())
+    }
+
+    /// Count a tick the sink did not keep, so later tick indices still
+    /// reflect elapsed time.
+    pub fn skip(&self) {
+        self.next_tick.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn build(
+        &self,
+        tick: &SongbirdVoiceTick,
+        identities: &impl VoiceIdentityResolver,
+    ) -> Arc<VoiceTickBatch> {
+        let mut converter = self
+            .converter
+            .as_ref()
+            .map(|converter| converter.lock().unwrap());
+        let mut vad = self.vad.as_ref().map(|vad| vad.lock().unwrap());
+        let filter = self.filter.as_ref().map(|filter| filter.read().unwrap());
+        let index = self.next_tick.fetch_add(1, Ordering::Relaxed);
+        VoiceTickBatch::from_parts(
+            tick,
+            index,
+            identities,
+            converter.as_deref_mut(),
+            vad.as_deref_mut(),
+            Some(&mut self.loss.lock().unwrap()),
+            filter.as_deref(),
+        )
+    }
+
+    /// Like `build`, for a tick in which each SSRC sent `pcm`.
+    #[cfg(test)]
+    pub(crate) fn build_test_rows(
+        &self,
+        rows: &[(u32, Vec<i16>)],
+        identities: &impl VoiceIdentityResolver,
+    ) -> Arc<VoiceTickBatch> {
+        let mut converter = self
+            .converter
+            .as_ref()
+            .map(|converter| converter.lock().unwrap());
+        let mut vad = self.vad.as_ref().map(|vad| vad.lock().unwrap());
+        VoiceTickBatch::from_ssrc_rows(
+            rows.iter()
+                .map(|(ssrc, pcm)| (*ssrc, Some(pcm.as_slice()), None)),
+            [],
+            self.next_tick.fetch_add(1, Ordering::Relaxed),
+            identities,
+            converter.as_deref_mut(),
+            vad.as_deref_mut(),
+            None,
+        )
+    }
+
+    /// Like `build`, but returns `None` for a tick the filter drops because
+    /// no kept source is speaking.
+    pub fn build_filtered(
+        &self,
+        tick: &SongbirdVoiceTick,
+        identities: &impl VoiceIdentityResolver,
+    ) -> Option<Arc<VoiceTickBatch>> {
+        if let Some(filter) = &self.filter {
+            let filter = filter.read().unwrap();
+            if filter.skip_silent()
+                && !tick.speaking.iter().any(|(ssrc, data)| {
+                    data.decoded_voice.is_some() && filter.allows(&key_for_ssrc(*ssrc, identities))
+                })
+            {
+                self.skip();
+                return None;
+            }
+        }
+        Some(self.build(tick, identities))
+    }
+}
+
+fn interleave(channels: &[Vec<f32>], frames: usize) -> Vec<f32> {
+    let mut samples = Vec::with_capacity(frames * channels.len());
+    for frame in 0..frames {
+        samples.extend(channels.iter().map(|channel| channel[frame]));
+    }
+    samples
+}
+
+/// A tick's worth of zero samples.
+pub(crate) fn silence(data_type: &DataType, samples: usize) -> ArrayRef {
+    match data_type {
+        DataType::Float32 => Arc::new(Float32Array::from(vec![0.0; samples])),
+        _ => Arc::new(Int16Array::from(vec![0; samples])),
+    }
+}
+
+/// Join consecutive PCM pieces of one source, reusing a lone piece as is.
+pub(crate) fn concat_pcm(pieces: &[ArrayRef]) -> ArrayRef {
+    match pieces {
+        [piece] => piece.clone(),
+        pieces => concat(&pieces.iter().map(AsRef::as_ref).collect::<Vec<_>>())
+            .expect("PCM pieces of one source share a data type"),
+    }
+}
+
+/// Convert a normalised float sample back to `int16`.
+pub fn to_i16(sample: f32) -> i16 {
+    (sample * 32_768.0)
+        .round()
+        .clamp(i16::MIN as f32, i16::MAX as f32) as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(left: i16, right: i16) -> Vec<i16> {
+        [left, right].repeat(DECODED_TICK_FRAMES)
+    }
+
+    #[test]
+    fn downmixes_to_mono_float() {
+        let format = PcmFormat::new(48_000, 1, "float32").unwrap();
+        let mut converter = PcmConverter::new(format);
+        let samples = converter.convert(&VoiceKey::User(1), &tick(16_384, 0));
+        assert_eq!(samples.len(), DECODED_TICK_FRAMES);
+        assert!(samples.iter().all(|&s| s == 0.25));
+    }
+
+    #[test]
+    fn resamples_each_tick_to_a_fixed_length() {
+        let format = PcmFormat::new(16_000, 1, "float32").unwrap();
+        let mut converter = PcmConverter::new(format);
+        let key = VoiceKey::User(1);
+        let mut last = Vec::new();
+        for _ in 0..10 {
+            last = converter.convert(&key, &tick(8_192, 8_192));
+            assert_eq!(last.len(), 320);
+        }
+        // Once the filter has settled, a constant signal stays constant.
+        assert!(last.iter().all(|s| (s - 0.25).abs() < 0.01));
+    }
+
+    #[test]
+    fn short_ticks_are_resampled_in_whole_chunks() {
+        let format = PcmFormat::new(16_000, 1, "float32").unwrap();
+        let mut converter = PcmConverter::new(format);
+        let key = VoiceKey::User(1);
+        let half = [8_192, 8_192].repeat(DECODED_TICK_FRAMES / 2);
+
+        assert!(converter.convert(&key, &half).is_empty());
+        assert_eq!(converter.convert(&key, &half).len(), 320);
+        // A tick and a half yields one chunk and keeps the rest.
+        let long = [8_192, 8_192].repeat(DECODED_TICK_FRAMES * 3 / 2);
+        assert_eq!(converter.convert(&key, &long).len(), 320);
+        assert_eq!(converter.convert(&key, &half).len(), 320);
+    }
+
+    #[test]
+    fn silent_sources_lose_their_resampler() {
+        let format = PcmFormat::new(44_100, 2, "int16").unwrap();
+        let mut converter = PcmConverter::new(format);
+        let samples = converter.convert(&VoiceKey::Unknown(3), &tick(0, 0));
+        assert_eq!(samples.len(), 882 * 2);
+        converter.retain(|_| false);
+        assert!(converter.resamplers.is_empty());
+    }
+
+    #[test]
+    fn invalid_formats_are_rejected() {
+        assert!(PcmFormat::new(16_000, 1, "int16").is_ok());
+        assert!(PcmFormat::new(16_001, 1, "int16").is_err());
+        assert!(PcmFormat::new(16_000, 3, "int16").is_err());
+        assert!(PcmFormat::new(16_000, 1, "float64").is_err());
+        assert!(PcmFormat::default().is_native());
+    }
+
+    #[test]
+    fn float_samples_round_trip_to_int16() {
+        assert_eq!(to_i16(0.5), 16_384);
+        assert_eq!(to_i16(2.0), i16::MAX);
+        assert_eq!(to_i16(-2.0), i16::MIN);
+    }
+}
@@ -1,7 +1,17 @@
+pub(crate) mod events;
+pub(crate) mod export;
+pub(crate) mod filter;
+pub(crate) mod format;
 mod handler;
 mod identity;
+pub(crate) mod packet;
+pub(crate) mod queue;
+pub(crate) mod record;
 pub mod sink;
 pub(crate) mod tick;
+pub mod user_stream;
+pub(crate) mod utterance;
+pub(crate) mod vad;
 
 pub use handler::HandlerWrapper;
 pub(crate) use identity::{VoiceIdentityMap, VoiceIdentityTracker};
@@ -20,8 +30,24 @@ BufferSink
     Buffering sink that yields Arrow `RecordBatch` snapshots.
 StreamSink
     Streaming sink that yields Arrow `RecordBatch` snapshots.
+MixSink
+    Sink that mixes all speakers into a single PCM stream.
+FileSink
+    Sink that records WAV, FLAC, or Ogg Opus files.
+IpcSink
+    Sink that streams ticks to an Arrow IPC file.
+OpusSink
+    Sink that yields undecoded Opus packets.
+RtcpSink
+    Sink that yields received RTCP packets.
+CallbackSink
+    Sink that hands received voice to async Python callbacks.
 Stream
     Async stream handle returned by `StreamSink.stream()`.
+AudioChunk
+    Contiguous per-source PCM yielded by `user_stream()`.
+VoiceEvent
+    Speaking and membership event yielded by `voice_events()`.
 VoiceTick
     Compatibility wrapper for per-tick helpers.
 VoiceKey
@@ -46,7 +72,8 @@ async for batch in sink:
 Notes
 -----
 Receive iterators return `pyarrow.RecordBatch` values with `key_kind`,
-`key_id`, `speaking`, and `pcm` columns. Per-key convenience iterators still
-return `pyarrow.Int16Array | None`.
+`key_id`, `speaking`, `pcm`, `vad`, `tick`, `timestamp`, `rtp_sequence`,
+`rtp_timestamp`, `concealed`, and `lost_packets` columns. Per-key
+convenience iterators still return `pyarrow.Int16Array | None`.
 "#,
 );
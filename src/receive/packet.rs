@@ -0,0 +1,255 @@
+use crate::receive::identity::VoiceIdentityResolver;
+use crate::receive::tick::{TIMESTAMP_TZ, key_for_ssrc, key_id, key_kind, timestamp_micros};
+use arrow::array::{
+    ArrayRef, BinaryArray, RecordBatch, TimestampMicrosecondArray, UInt8Array, UInt16Array,
+    UInt32Array, UInt64Array,
+};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use songbird::events::context_data::{RtcpData, RtpData, VoiceTick as SongbirdVoiceTick};
+use songbird::packet::rtp::{RtpExtensionPacket, RtpPacket};
+use songbird::packet::{Packet, PacketSize};
+use std::sync::{Arc, OnceLock};
+use std::time::SystemTime;
+
+/// Fixed RTCP header: version/count, packet type, length, and sender SSRC.
+const RTCP_HEADER_LEN: usize = 8;
+
+/// The Opus frame carried by a received RTP packet.
+///
+/// These are the same bytes songbird hands to its decoder: the payload with
+/// encryption framing and any RTP header extension stripped.
+fn opus_frame(data: &RtpData) -> Option<&[u8]> {
+    // Within a tick, `payload_end_pad` is the end of the body, not a suffix.
+    opus_frame_within(&data.packet, data.payload_offset, data.payload_end_pad)
+}
+
+fn opus_frame_within(packet: &[u8], start: usize, end: usize) -> Option<&[u8]> {
+    let rtp = RtpPacket::new(packet)?;
+    let header_len = packet.len() - rtp.payload().len();
+    let body = packet.get(header_len + start..header_len + end)?;
+    if rtp.get_extension() == 0 {
+        return Some(body);
+    }
+    let extension = RtpExtensionPacket::new(body)?.packet_size();
+    body.get(extension..)
+}
+
+/// The sender SSRC, packet type, and decrypted body of an RTCP packet.
+fn rtcp_parts(packet: &[u8], start: usize, tail: usize) -> Option<(u32, u8, &[u8])> {
+    let end = packet.len().checked_sub(tail)?;
+    let payload = packet.get(RTCP_HEADER_LEN + start..end)?;
+    let ssrc = u32::from_be_bytes(packet[4..8].try_into().ok()?);
+    Some((ssrc, packet[1], payload))
+}
+
+pub(crate) fn opus_schema() -> SchemaRef {
+    static SCHEMA: OnceLock<SchemaRef> = OnceLock::new();
+    SCHEMA
+        .get_or_init(|| {
+            Arc::new(Schema::new(vec![
+                Field::new("key_kind", DataType::UInt8, false),
+                Field::new("key_id", DataType::UInt64, false),
+                Field::new("rtp_sequence", DataType::UInt16, false),
+                Field::new("rtp_timestamp", DataType::UInt32, false),
+                Field::new("opus", DataType::Binary, false),
+                Field::new("tick", DataType::UInt64, false),
+                timestamp_field(),
+            ]))
+        })
+        .clone()
+}
+
+pub(crate) fn rtcp_schema() -> SchemaRef {
+    static SCHEMA: OnceLock<SchemaRef> = OnceLock::new();
+    SCHEMA
+        .get_or_init(|| {
+            Arc::new(Schema::new(vec![
+                Field::new("key_kind", DataType::UInt8, false),
+                Field::new("key_id", DataType::UInt64, false),
+                Field::new("packet_type", DataType::UInt8, false),
+                Field::new("payload", DataType::Binary, false),
+                timestamp_field(),
+            ]))
+        })
+        .clone()
+}
+
+fn timestamp_field() -> Field {
+    Field::new(
+        "timestamp",
+        DataType::Timestamp(TimeUnit::Microsecond, Some(TIMESTAMP_TZ.into())),
+        false,
+    )
+}
+
+/// One received Opus packet.
+struct OpusRow<'a> {
+    ssrc: u32,
+    sequence: u16,
+    timestamp: u32,
+    opus: &'a [u8],
+}
+
+/// Build a batch of the Opus packets clocked out in a tick, one row per
+/// source that sent one. Lost packets have no row.
+pub(crate) fn opus_batch(
+    tick: &SongbirdVoiceTick,
+    index: u64,
+    identities: &impl VoiceIdentityResolver,
+) -> RecordBatch {
+    let rows = tick.speaking.iter().filter_map(|(ssrc, data)| {
+        let packet = data.packet.as_ref()?;
+        let rtp = packet.rtp();
+        Some(OpusRow {
+            ssrc: *ssrc,
+            sequence: rtp.get_sequence().into(),
+            timestamp: rtp.get_timestamp().into(),
+            opus: opus_frame(packet)?,
+        })
+    });
+    opus_rows_batch(rows.collect(), index, identities, SystemTime::now())
+}
+
+fn opus_rows_batch(
+    rows: Vec<OpusRow<'_>>,
+    index: u64,
+    identities: &impl VoiceIdentityResolver,
+    timestamp: SystemTime,
+) -> RecordBatch {
+    let mut rows: Vec<_> = rows
+        .into_iter()
+        .map(|row| (key_for_ssrc(row.ssrc, identities), row))
+        .collect();
+    rows.sort_by_key(|(key, _)| (key_kind(key), key_id(key)));
+
+    let len = rows.len();
+    RecordBatch::try_new(
+        opus_schema(),
+        vec![
+            Arc::new(UInt8Array::from_iter_values(
+                rows.iter().map(|(key, _)| key_kind(key)),
+            )) as ArrayRef,
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|(key, _)| key_id(key)),
+            )) as ArrayRef,
+            Arc::new(UInt16Array::from_iter_values(
+                rows.iter().map(|(_, row)| row.sequence),
+            )) as ArrayRef,
+            Arc::new(UInt32Array::from_iter_values(
+                rows.iter().map(|(_, row)| row.timestamp),
+            )) as ArrayRef,
+            Arc::new(BinaryArray::from_iter_values(
+                rows.iter().map(|(_, row)| row.opus),
+            )) as ArrayRef,
+            Arc::new(UInt64Array::from(vec![index; len])) as ArrayRef,
+            Arc::new(
+                TimestampMicrosecondArray::from(vec![timestamp_micros(timestamp); len])
+                    .with_timezone(TIMESTAMP_TZ),
+            ) as ArrayRef,
+        ],
+    )
+    .expect("Opus batch columns must match the fixed schema")
+}
+
+/// Build a one-row batch for a received RTCP packet, or `None` if it is too
+/// short to carry a sender SSRC.
+pub(crate) fn rtcp_batch(
+    data: &RtcpData,
+    identities: &impl VoiceIdentityResolver,
+) -> Option<RecordBatch> {
+    // Around the RTCP event, `payload_end_pad` is a suffix length.
+    let (ssrc, packet_type, payload) =
+        rtcp_parts(&data.packet, data.payload_offset, data.payload_end_pad)?;
+    let key = key_for_ssrc(ssrc, identities);
+    let batch = RecordBatch::try_new(
+        rtcp_schema(),
+        vec![
+            Arc::new(UInt8Array::from(vec![key_kind(&key)])) as ArrayRef,
+            Arc::new(UInt64Array::from(vec![key_id(&key)])) as ArrayRef,
+            Arc::new(UInt8Array::from(vec![packet_type])) as ArrayRef,
+            Arc::new(BinaryArray::from_iter_values([payload])) as ArrayRef,
+            Arc::new(
+                TimestampMicrosecondArray::from(vec![timestamp_micros(SystemTime::now())])
+                    .with_timezone(TIMESTAMP_TZ),
+            ) as ArrayRef,
+        ],
+    )
+    .expect("RTCP batch columns must match the fixed schema");
+    Some(batch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::receive::identity::VoiceIdentityMap;
+    use arrow::array::AsArray;
+    use arrow::datatypes::{UInt16Type, UInt64Type};
+
+    /// An RTP packet with a 20-byte AEAD tag and nonce after `body`.
+    fn rtp_packet(extension: bool, body: &[u8]) -> Vec<u8> {
+        let mut packet = vec![if extension { 0x90 } else { 0x80 }, 0x78];
+        packet.extend_from_slice(&7u16.to_be_bytes());
+        packet.extend_from_slice(&960u32.to_be_bytes());
+        packet.extend_from_slice(&42u32.to_be_bytes());
+        packet.extend_from_slice(body);
+        packet.extend_from_slice(&[0xEE; 20]);
+        packet
+    }
+
+    #[test]
+    fn opus_frames_skip_encryption_framing_and_extensions() {
+        let opus = [0xFC, 0x01, 0x02];
+        let packet = rtp_packet(false, &opus);
+        assert_eq!(opus_frame_within(&packet, 0, opus.len()), Some(&opus[..]));
+
+        let mut body = vec![0xBE, 0xDE, 0x00, 0x01, 0x10, 0xAA, 0x00, 0x00];
+        body.extend_from_slice(&opus);
+        let packet = rtp_packet(true, &body);
+        assert_eq!(opus_frame_within(&packet, 0, body.len()), Some(&opus[..]));
+
+        assert_eq!(opus_frame_within(&packet, 0, 1_000), None);
+    }
+
+    #[test]
+    fn rtcp_parts_read_sender_and_decrypted_body() {
+        let mut packet = vec![0x81, 201, 0x00, 0x07];
+        packet.extend_from_slice(&42u32.to_be_bytes());
+        packet.extend_from_slice(&[1, 2, 3, 4]);
+        packet.extend_from_slice(&[0xEE; 20]);
+        assert_eq!(
+            rtcp_parts(&packet, 0, 20),
+            Some((42, 201, &[1, 2, 3, 4][..]))
+        );
+        assert_eq!(rtcp_parts(&packet[..6], 0, 0), None);
+    }
+
+    #[test]
+    fn opus_batches_are_keyed_and_sorted() {
+        let identities = VoiceIdentityMap::default();
+        identities.insert(42, 7);
+        let row = |ssrc, sequence, opus| OpusRow {
+            ssrc,
+            sequence,
+            timestamp: 0,
+            opus,
+        };
+        let batch = opus_rows_batch(
+            vec![row(99, 1, &[9][..]), row(42, 2, &[4, 2][..])],
+            5,
+            &identities,
+            SystemTime::now(),
+        );
+
+        assert_eq!(batch.schema(), opus_schema());
+        assert_eq!(
+            batch["key_id"].as_primitive::<UInt64Type>().values(),
+            &[7, 99]
+        );
+        assert_eq!(
+            batch["rtp_sequence"].as_primitive::<UInt16Type>().values(),
+            &[2, 1]
+        );
+        assert_eq!(batch["opus"].as_binary::<i32>().value(0), [4, 2]);
+        assert_eq!(batch["tick"].as_primitive::<UInt64Type>().value(1), 5);
+    }
+}
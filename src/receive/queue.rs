@@ -0,0 +1,160 @@
+use pyo3::PyResult;
+use pyo3::exceptions::PyValueError;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// What to discard when a queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Overflow {
+    DropOldest,
+    DropNewest,
+}
+
+impl Overflow {
+    pub(crate) fn parse(value: &str) -> PyResult<Self> {
+        match value {
+            "drop_oldest" => Ok(Self::DropOldest),
+            "drop_newest" => Ok(Self::DropNewest),
+            _ => Err(PyValueError::new_err(
+                "overflow must be \"drop_oldest\" or \"drop_newest\"",
+            )),
+        }
+    }
+}
+
+/// Queue between songbird's event task and a single async reader.
+///
+/// Pushing never waits and never takes the GIL; a full queue discards items
+/// according to its overflow policy. Once closed, the reader drains what is
+/// left and then sees the end.
+pub(crate) struct NotifyQueue<T> {
+    items: Mutex<VecDeque<T>>,
+    capacity: Option<usize>,
+    overflow: Overflow,
+    dropped: AtomicU64,
+    is_closed: AtomicBool,
+    notify: Notify,
+}
+
+impl<T> NotifyQueue<T> {
+    /// A queue holding at most `capacity` items, or unbounded if None.
+    pub(crate) fn new(capacity: Option<usize>, overflow: Overflow) -> Arc<Self> {
+        Arc::new(Self {
+            items: Mutex::new(VecDeque::new()),
+            capacity,
+            overflow,
+            dropped: AtomicU64::new(0),
+            is_closed: AtomicBool::new(false),
+            notify: Notify::new(),
+        })
+    }
+
+    pub(crate) fn push(&self, item: T) {
+        self.extend([item]);
+    }
+
+    /// Queue several items, waking the reader once.
+    pub(crate) fn extend(&self, new_items: impl IntoIterator<Item = T>) {
+        let mut items = self.items.lock().unwrap();
+        let mut pushed = false;
+        for item in new_items {
+            if self
+                .capacity
+                .is_some_and(|capacity| items.len() >= capacity)
+            {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                match self.overflow {
+                    Overflow::DropOldest => drop(items.pop_front()),
+                    Overflow::DropNewest => continue,
+                }
+            }
+            items.push_back(item);
+            pushed = true;
+        }
+        drop(items);
+        if pushed {
+            self.notify.notify_one();
+        }
+    }
+
+    /// Number of items discarded because the queue was full.
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.is_closed.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn close(&self) {
+        self.is_closed.store(true, Ordering::Relaxed);
+        self.notify.notify_waiters();
+    }
+
+    /// The next item, if one is queued.
+    pub(crate) fn try_pop(&self) -> Option<T> {
+        self.items.lock().unwrap().pop_front()
+    }
+
+    /// Wait for the next item, or `None` once closed and drained.
+    pub(crate) async fn next(&self) -> Option<T> {
+        loop {
+            let notified = self.notify.notified();
+            if let Some(item) = self.try_pop() {
+                return Some(item);
+            }
+            if self.is_closed() {
+                return None;
+            }
+            notified.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain(queue: &NotifyQueue<u32>) -> Vec<u32> {
+        std::iter::from_fn(|| queue.try_pop()).collect()
+    }
+
+    #[test]
+    fn full_queue_applies_overflow_policy() {
+        let oldest = NotifyQueue::new(Some(2), Overflow::DropOldest);
+        let newest = NotifyQueue::new(Some(2), Overflow::DropNewest);
+        oldest.extend([1, 2, 3]);
+        newest.extend([1, 2, 3]);
+        assert_eq!(drain(&oldest), [2, 3]);
+        assert_eq!(drain(&newest), [1, 2]);
+        assert_eq!((oldest.dropped(), newest.dropped()), (1, 1));
+
+        let unbounded = NotifyQueue::new(None, Overflow::DropOldest);
+        unbounded.extend(0..100);
+        assert_eq!(drain(&unbounded).len(), 100);
+        assert!(Overflow::parse("block").is_err());
+    }
+
+    #[tokio::test]
+    async fn closed_queue_drains_before_ending() {
+        let queue = NotifyQueue::new(Some(4), Overflow::DropOldest);
+        queue.push(7);
+        queue.close();
+        assert_eq!(queue.next().await, Some(7));
+        assert_eq!(queue.next().await, None);
+    }
+
+    #[tokio::test]
+    async fn readers_wake_for_new_items() {
+        let queue = NotifyQueue::new(None, Overflow::DropOldest);
+        let reader = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.next().await }
+        });
+        tokio::task::yield_now().await;
+        queue.push(5);
+        assert_eq!(reader.await.unwrap(), Some(5));
+    }
+}
@@ -0,0 +1,204 @@
+mod flac;
+mod ogg;
+mod wav;
+
+use pyo3::PyResult;
+use pyo3::exceptions::PyValueError;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::Path;
+#[cfg(test)]
+use std::path::PathBuf;
+
+/// Encoder for one recording file, fed interleaved 16-bit PCM.
+pub(crate) trait AudioWriter: Send {
+    fn write(&mut self, samples: &[i16]) -> io::Result<()>;
+
+    /// Flush buffered audio and finalise headers.
+    fn finish(self: Box<Self>) -> io::Result<()>;
+}
+
+/// Container and codec of a recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RecordFormat {
+    Wav,
+    Flac,
+    OggOpus,
+}
+
+impl RecordFormat {
+    pub(crate) fn parse(format: &str) -> PyResult<Self> {
+        match format {
+            "wav" => Ok(Self::Wav),
+            "flac" => Ok(Self::Flac),
+            "ogg-opus" => Ok(Self::OggOpus),
+            _ => Err(PyValueError::new_err(
+                "format must be \"wav\", \"flac\", or \"ogg-opus\"",
+            )),
+        }
+    }
+
+    pub(crate) fn extension(&self) -> &'static str {
+        match self {
+            Self::Wav => "wav",
+            Self::Flac => "flac",
+            Self::OggOpus => "opus",
+        }
+    }
+
+    /// Reject sample rates the format cannot store.
+    pub(crate) fn check_sample_rate(&self, sample_rate: u32) -> PyResult<()> {
+        if *self == Self::OggOpus && !ogg::SAMPLE_RATES.contains(&sample_rate) {
+            return Err(PyValueError::new_err(format!(
+                "ogg-opus recordings need a sample_rate of {:?}",
+                ogg::SAMPLE_RATES
+            )));
+        }
+        Ok(())
+    }
+
+    /// Create `path`, and any missing parent directories, for writing.
+    pub(crate) fn create(
+        &self,
+        path: &Path,
+        sample_rate: u32,
+        channels: usize,
+    ) -> io::Result<Box<dyn AudioWriter>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = BufWriter::new(File::create(path)?);
+        Ok(match self {
+            Self::Wav => Box::new(wav::WavWriter::new(file, sample_rate, channels)?),
+            Self::Flac => Box::new(flac::FlacWriter::new(file, sample_rate, channels)?),
+            Self::OggOpus => Box::new(ogg::OggOpusWriter::new(file, sample_rate, channels)?),
+        })
+    }
+}
+
+/// Scratch directory for tests, removed on drop so that a failing assertion
+/// does not leave files behind.
+#[cfg(test)]
+pub(crate) struct TestDir(PathBuf);
+
+#[cfg(test)]
+impl TestDir {
+    pub(crate) fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("songbird-{name}-{}", std::process::id()));
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+
+#[cfg(test)]
+impl std::ops::Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use songbird::input::codecs::{get_codec_registry, get_probe};
+    use symphonia::core::audio::SampleBuffer;
+    use symphonia::core::formats::FormatOptions;
+    use symphonia::core::io::MediaSourceStream;
+    use symphonia::core::meta::MetadataOptions;
+    use symphonia::core::probe::Hint;
+
+    fn signal(frames: usize, channels: usize) -> Vec<i16> {
+        (0..frames)
+            .flat_map(|n| {
+                let sample = ((n as f32 * 0.05).sin() * 8_000.0) as i16;
+                (0..channels).map(move |channel| sample / (channel as i16 + 1))
+            })
+            .collect()
+    }
+
+    /// Record `samples` in `format` and decode the file back.
+    fn round_trip(format: RecordFormat, samples: &[i16], sample_rate: u32) -> (u32, Vec<i16>) {
+        let dir = TestDir::new(&format!("record-{format:?}"));
+        let path = dir.join(format!("take.{}", format.extension()));
+        let mut writer = format.create(&path, sample_rate, 2).unwrap();
+        for tick in samples.chunks(sample_rate as usize / 50 * 2) {
+            writer.write(tick).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let source =
+            MediaSourceStream::new(Box::new(File::open(&path).unwrap()), Default::default());
+        let mut probed = get_probe()
+            .format(
+                &Hint::new(),
+                source,
+                &FormatOptions::default(),
+                &MetadataOptions::default(),
+            )
+            .unwrap();
+        let track = probed.format.default_track().unwrap().clone();
+        let rate = track.codec_params.sample_rate.unwrap();
+        let mut decoded = Vec::new();
+        if format == RecordFormat::OggOpus {
+            // Opus is lossy; count packets instead of comparing samples.
+            while let Ok(packet) = probed.format.next_packet() {
+                decoded.push(packet.data.len() as i16);
+            }
+        } else {
+            let mut decoder = get_codec_registry()
+                .make(&track.codec_params, &Default::default())
+                .unwrap();
+            while let Ok(packet) = probed.format.next_packet() {
+                let audio = decoder.decode(&packet).unwrap();
+                let mut buffer = SampleBuffer::<i16>::new(audio.capacity() as u64, *audio.spec());
+                buffer.copy_interleaved_ref(audio);
+                decoded.extend_from_slice(buffer.samples());
+            }
+        }
+        (rate, decoded)
+    }
+
+    #[test]
+    fn wav_round_trips_losslessly() {
+        let samples = signal(4_410, 2);
+        assert_eq!(
+            round_trip(RecordFormat::Wav, &samples, 44_100),
+            (44_100, samples)
+        );
+    }
+
+    #[test]
+    fn flac_round_trips_losslessly() {
+        // Spans several blocks and ends with a partial one.
+        let samples = signal(10_080, 2);
+        assert_eq!(
+            round_trip(RecordFormat::Flac, &samples, 48_000),
+            (48_000, samples)
+        );
+    }
+
+    #[test]
+    fn ogg_opus_stores_one_packet_per_tick() {
+        let samples = signal(16_000, 2);
+        let (rate, packets) = round_trip(RecordFormat::OggOpus, &samples, 16_000);
+        assert_eq!(rate, 48_000);
+        assert_eq!(packets.len(), 50);
+        assert!(packets.iter().all(|len| *len > 0));
+    }
+
+    #[test]
+    fn formats_are_validated() {
+        assert!(RecordFormat::parse("mp3").is_err());
+        assert!(RecordFormat::OggOpus.check_sample_rate(44_100).is_err());
+        assert!(RecordFormat::Flac.check_sample_rate(44_100).is_ok());
+    }
+}
@@ -0,0 +1,212 @@
+use crate::receive::record::AudioWriter;
+use opus2::{Application, Channels, Encoder};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+/// Sample rates libopus accepts for encoding.
+pub(super) const SAMPLE_RATES: [u32; 5] = [8_000, 12_000, 16_000, 24_000, 48_000];
+/// Ogg Opus granule positions always count 48 kHz samples.
+const GRANULE_RATE: u64 = 48_000;
+const FRAME_MS: usize = 20;
+const MAX_PACKET_BYTES: usize = 4_000;
+/// Audio packets per page, one second at 20 ms frames.
+const PACKETS_PER_PAGE: usize = 50;
+/// Segment table entries an Ogg page can hold.
+const MAX_LACING_VALUES: usize = 255;
+const VENDOR: &str = concat!("discord-ext-songbird ", env!("CARGO_PKG_VERSION"));
+
+const FLAG_BOS: u8 = 0x02;
+const FLAG_EOS: u8 = 0x04;
+
+/// Ogg Opus writer producing one 20 ms packet per frame.
+pub(super) struct OggOpusWriter {
+    pages: PageWriter,
+    encoder: Encoder,
+    channels: usize,
+    frame_len: usize,
+    /// Interleaved samples not yet encoded.
+    pending: Vec<i16>,
+    /// Packets waiting for the next page, and the granule position at the
+    /// end of the last one.
+    packets: Vec<Vec<u8>>,
+    packets_granule: u64,
+    pre_skip: u64,
+    /// Input samples per channel encoded so far, excluding padding.
+    samples: u64,
+    sample_rate: u32,
+}
+
+impl OggOpusWriter {
+    pub(super) fn new(
+        file: BufWriter<File>,
+        sample_rate: u32,
+        channels: usize,
+    ) -> io::Result<Self> {
+        let opus_channels = match channels {
+            1 => Channels::Mono,
+            2 => Channels::Stereo,
+            _ => return Err(io::Error::other("ogg-opus supports 1 or 2 channels")),
+        };
+        let mut encoder = Encoder::new(sample_rate, opus_channels, Application::Audio)
+            .map_err(io::Error::other)?;
+        let lookahead = encoder.get_lookahead().map_err(io::Error::other)? as u64;
+        let pre_skip = lookahead * GRANULE_RATE / sample_rate as u64;
+
+        let mut head = Vec::with_capacity(19);
+        head.extend_from_slice(b"OpusHead");
+        head.push(1);
+        head.push(channels as u8);
+        head.extend_from_slice(&(pre_skip as u16).to_le_bytes());
+        head.extend_from_slice(&sample_rate.to_le_bytes());
+        // Output gain and channel mapping family 0.
+        head.extend_from_slice(&0i16.to_le_bytes());
+        head.push(0);
+
+        let mut tags = Vec::new();
+        tags.extend_from_slice(b"OpusTags");
+        tags.extend_from_slice(&(VENDOR.len() as u32).to_le_bytes());
+        tags.extend_from_slice(VENDOR.as_bytes());
+        tags.extend_from_slice(&0u32.to_le_bytes());
+
+        let mut pages = PageWriter::new(file);
+        pages.write_page(&[head], 0, FLAG_BOS)?;
+        pages.write_page(&[tags], 0, 0)?;
+        let frame_len = sample_rate as usize * FRAME_MS / 1_000 * channels;
+        Ok(Self {
+            pages,
+            encoder,
+            channels,
+            frame_len,
+            pending: Vec::with_capacity(frame_len),
+            packets: Vec::with_capacity(PACKETS_PER_PAGE),
+            packets_granule: 0,
+            pre_skip,
+            samples: 0,
+            sample_rate,
+        })
+    }
+
+    /// Encode one full frame holding `samples` real samples per channel.
+    fn encode(&mut self, frame: &[i16], samples: usize) -> io::Result<()> {
+        let mut output = [0; MAX_PACKET_BYTES];
+        let len = self
+            .encoder
+            .encode(frame, &mut output)
+            .map_err(io::Error::other)?;
+        let queued: usize = self.packets.iter().map(|packet| lacing_len(packet)).sum();
+        if self.packets.len() == PACKETS_PER_PAGE
+            || queued + lacing_len(&output[..len]) > MAX_LACING_VALUES
+        {
+            self.flush_page(0)?;
+        }
+        self.packets.push(output[..len].to_vec());
+        self.samples += samples as u64;
+        self.packets_granule = self.granule();
+        Ok(())
+    }
+
+    fn flush_page(&mut self, flags: u8) -> io::Result<()> {
+        let packets = std::mem::take(&mut self.packets);
+        self.pages.write_page(&packets, self.packets_granule, flags)
+    }
+
+    /// Granule position at the end of the encoded samples.
+    fn granule(&self) -> u64 {
+        self.pre_skip + self.samples * GRANULE_RATE / self.sample_rate as u64
+    }
+}
+
+impl AudioWriter for OggOpusWriter {
+    fn write(&mut self, samples: &[i16]) -> io::Result<()> {
+        self.pending.extend_from_slice(samples);
+        while self.pending.len() >= self.frame_len {
+            let frame: Vec<i16> = self.pending.drain(..self.frame_len).collect();
+            self.encode(&frame, self.frame_len / self.channels)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> io::Result<()> {
+        if !self.pending.is_empty() {
+            let remaining = self.pending.len() / self.channels;
+            let mut frame = std::mem::take(&mut self.pending);
+            frame.resize(self.frame_len, 0);
+            self.encode(&frame, remaining)?;
+        }
+        // The end granule lets players trim the padding of the last frame.
+        self.flush_page(FLAG_EOS)?;
+        self.pages.file.flush()
+    }
+}
+
+/// Writes Ogg pages for a single logical stream.
+struct PageWriter {
+    file: BufWriter<File>,
+    serial: u32,
+    sequence: u32,
+}
+
+impl PageWriter {
+    fn new(file: BufWriter<File>) -> Self {
+        let serial = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| {
+                elapsed.subsec_nanos() ^ elapsed.as_secs() as u32
+            });
+        Self {
+            file,
+            serial,
+            sequence: 0,
+        }
+    }
+
+    /// Write `packets` as one page. Callers keep pages within
+    /// [`MAX_LACING_VALUES`].
+    fn write_page(&mut self, packets: &[Vec<u8>], granule: u64, flags: u8) -> io::Result<()> {
+        let mut lacing = Vec::new();
+        for packet in packets {
+            lacing.extend(std::iter::repeat_n(255u8, packet.len() / 255));
+            lacing.push((packet.len() % 255) as u8);
+        }
+        debug_assert!(lacing.len() <= MAX_LACING_VALUES);
+
+        let mut page = Vec::with_capacity(27 + lacing.len());
+        page.extend_from_slice(b"OggS");
+        page.push(0);
+        page.push(flags);
+        page.extend_from_slice(&granule.to_le_bytes());
+        page.extend_from_slice(&self.serial.to_le_bytes());
+        page.extend_from_slice(&self.sequence.to_le_bytes());
+        page.extend_from_slice(&[0; 4]);
+        page.push(lacing.len() as u8);
+        page.extend_from_slice(&lacing);
+        for packet in packets {
+            page.extend_from_slice(packet);
+        }
+        let crc = ogg_crc(&page);
+        page[22..26].copy_from_slice(&crc.to_le_bytes());
+
+        self.file.write_all(&page)?;
+        self.sequence += 1;
+        Ok(())
+    }
+}
+
+/// Segment table entries needed for one packet.
+fn lacing_len(packet: &[u8]) -> usize {
+    packet.len() / 255 + 1
+}
+
+fn ogg_crc(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0, |mut crc, &byte| {
+        crc ^= (byte as u32) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04c1_1db7
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
@@ -0,0 +1,56 @@
+use crate::receive::record::AudioWriter;
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+
+const HEADER_LEN: u32 = 44;
+
+/// 16-bit PCM RIFF/WAVE writer. Chunk sizes are patched in on finish.
+pub(super) struct WavWriter {
+    file: BufWriter<File>,
+    data_len: u32,
+}
+
+impl WavWriter {
+    pub(super) fn new(
+        mut file: BufWriter<File>,
+        sample_rate: u32,
+        channels: usize,
+    ) -> io::Result<Self> {
+        let block_align = channels as u16 * 2;
+        file.write_all(b"RIFF")?;
+        file.write_all(&0u32.to_le_bytes())?;
+        file.write_all(b"WAVEfmt ")?;
+        file.write_all(&16u32.to_le_bytes())?;
+        file.write_all(&1u16.to_le_bytes())?;
+        file.write_all(&(channels as u16).to_le_bytes())?;
+        file.write_all(&sample_rate.to_le_bytes())?;
+        file.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+        file.write_all(&block_align.to_le_bytes())?;
+        file.write_all(&16u16.to_le_bytes())?;
+        file.write_all(b"data")?;
+        file.write_all(&0u32.to_le_bytes())?;
+        Ok(Self { file, data_len: 0 })
+    }
+}
+
+impl AudioWriter for WavWriter {
+    fn write(&mut self, samples: &[i16]) -> io::Result<()> {
+        for sample in samples {
+            self.file.write_all(&sample.to_le_bytes())?;
+        }
+        self.data_len = self
+            .data_len
+            .checked_add(samples.len() as u32 * 2)
+            .ok_or_else(|| io::Error::other("WAV files cannot exceed 4 GiB"))?;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> io::Result<()> {
+        self.file.seek(SeekFrom::Start(4))?;
+        self.file
+            .write_all(&(HEADER_LEN - 8 + self.data_len).to_le_bytes())?;
+        self.file.seek(SeekFrom::Start(HEADER_LEN as u64 - 4))?;
+        self.file.write_all(&self.data_len.to_le_bytes())?;
+        self.file.flush()
+    }
+}
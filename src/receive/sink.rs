@@ -1,4 +1,10 @@
 mod buffer;
+mod callback;
+mod file;
+mod ipc;
+mod mix;
+mod packet;
+mod recorder;
 mod stream;
 
 use super::identity::{VoiceIdentityBindError, VoiceIdentityBinding, VoiceIdentityMap};
@@ -10,6 +16,12 @@ use std::collections::HashSet;
 use std::sync::Arc;
 
 pub use buffer::BufferSink;
+pub use callback::CallbackSink;
+pub use file::FileSink;
+pub use ipc::IpcSink;
+pub use mix::MixSink;
+pub(crate) use mix::Mixer;
+pub use packet::{OpusSink, RtcpSink};
 pub use stream::{PyStream, StreamSink};
 
 #[gen_stub_pyclass]
@@ -23,7 +35,8 @@ pub use stream::{PyStream, StreamSink};
 /// Notes
 /// -----
 /// This is an internal type exposed to Python for sink registration.
-/// Custom sinks are not currently supported from Python.
+/// Subclassing it from Python is not supported; implement custom sinks with
+/// `CallbackSink` instead.
 pub struct SinkBase {
     subscriber: Arc<dyn EventHandler + Send + Sync>,
     identity: Arc<VoiceIdentityBinding>,
@@ -1,17 +1,26 @@
-use crate::model::{ArrowArray, ArrowRecordBatch, Generic, PyAsyncIterator};
+use crate::model::{ArrowArray, ArrowRecordBatch, Generic, PcmArray, PyAsyncIterator, PyFuture};
+use crate::receive::export::{
+    IpcFormat, IpcWriter, arrow_error, coalesce, open_output, write_parquet,
+};
+use crate::receive::filter::KeyFilter;
+use crate::receive::format::{PcmFormat, TICK_DURATION, TICKS_PER_SECOND, TickBuilder};
 use crate::receive::identity::VoiceIdentityBinding;
 use crate::receive::sink::SinkBase;
-use crate::receive::tick::{VoiceKey, VoiceTickBatch};
-use arrow::array::Int16Array;
+use crate::receive::tick::{VoiceKey, VoiceTickBatch, rekey_ticks, voice_tick_schema};
+use crate::receive::user_stream::{AudioChunk, UserChunker};
+use crate::receive::utterance::{Segmenter, UtteranceSettings, UtteranceTuple, require_vad};
 use async_stream::stream;
 use async_trait::async_trait;
 use pyo3::exceptions::PyValueError;
-use pyo3::{IntoPyObjectExt, PyRef, PyResult, Python, pyclass, pymethods};
+use pyo3::{Bound, IntoPyObjectExt, PyAny, PyRef, PyResult, Python, pyclass, pymethods};
+use pyo3_arrow::PyRecordBatch;
+use pyo3_async_runtimes::tokio::future_into_py;
 use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
 use songbird::{CoreEvent, Event, EventContext, EventHandler};
 use std::collections::VecDeque;
-use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use tokio::sync::Mutex;
 
 pub struct BufferSinkHandler {
@@ -20,6 +29,7 @@ pub struct BufferSinkHandler {
     ticks: Arc<Mutex<VecDeque<Arc<VoiceTickBatch>>>>,
     max_ticks: Option<usize>,
     drop_oldest: bool,
+    builder: TickBuilder,
 }
 
 #[gen_stub_pyclass]
@@ -48,6 +58,9 @@ pub struct BufferSinkHandler {
 pub struct BufferSink {
     is_stopped: Arc<AtomicBool>,
     ticks: Arc<Mutex<VecDeque<Arc<VoiceTickBatch>>>>,
+    format: PcmFormat,
+    filter: Arc<RwLock<KeyFilter>>,
+    vad: bool,
 }
 
 impl BufferSinkHandler {
@@ -57,6 +70,7 @@ impl BufferSinkHandler {
         identity: Arc<VoiceIdentityBinding>,
         max_ticks: Option<usize>,
         drop_oldest: bool,
+        builder: TickBuilder,
     ) -> Self {
         Self {
             is_stopped,
@@ -64,6 +78,7 @@ impl BufferSinkHandler {
             ticks,
             max_ticks,
             drop_oldest,
+            builder,
         }
     }
 }
@@ -71,22 +86,49 @@ impl BufferSinkHandler {
 #[async_trait]
 impl EventHandler for BufferSinkHandler {
     async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
-        if self.is_stopped.load(Ordering::Relaxed) {
-            return None;
-        }
-        if let EventContext::VoiceTick(tick) = ctx {
-            let mut guard = self.ticks.lock().await;
-            if let Some(max_ticks) = self.max_ticks {
-                if self.drop_oldest {
-                    while guard.len() >= max_ticks {
-                        guard.pop_front();
-                    }
-                } else if guard.len() >= max_ticks {
+        match ctx {
+            EventContext::VoiceTick(tick) => {
+                if self.is_stopped.load(Ordering::Relaxed) {
                     return None;
                 }
+                let mut guard = self.ticks.lock().await;
+                if let Some(max_ticks) = self.max_ticks {
+                    if self.drop_oldest {
+                        while guard.len() >= max_ticks {
+                            guard.pop_front();
+                        }
+                    } else if guard.len() >= max_ticks {
+                        self.builder.skip();
+                        return None;
+                    }
+                }
+                if let Some(tick) = self.builder.build_filtered(tick, &*self.identity) {
+                    guard.push_back(tick);
+                }
             }
-            let tick = VoiceTickBatch::from_parts(tick, &*self.identity);
-            guard.push_back(tick);
+            // Audio buffered before Discord mapped its SSRC is still unread;
+            // attribute it to the user, or drop it if the filter turns out
+            // not to keep that user. A tick left with no one speaking is
+            // dropped too when the filter skips silent ticks.
+            EventContext::SpeakingStateUpdate(speaking) => {
+                if let Some(user_id) = speaking.user_id {
+                    let mut guard = self.ticks.lock().await;
+                    if self.builder.allows(&VoiceKey::User(user_id.0)) {
+                        rekey_ticks(guard.iter_mut(), speaking.ssrc, user_id.0);
+                    } else {
+                        let unknown = VoiceKey::Unknown(speaking.ssrc);
+                        let skip_silent = self.builder.skips_silent();
+                        guard.retain_mut(|tick| match tick.without(&unknown) {
+                            Some(kept) => {
+                                *tick = kept;
+                                !(skip_silent && tick.speaking_keys().is_empty())
+                            }
+                            None => true,
+                        });
+                    }
+                }
+            }
+            _ => {}
         }
         None
     }
@@ -97,7 +139,18 @@ impl EventHandler for BufferSinkHandler {
 impl BufferSink {
     #[gen_stub(override_return_type(type_repr = "typing.Self", imports = ("typing")))]
     #[new]
-    #[pyo3(signature = (*, max_duration_secs = None, drop_oldest = true))]
+    #[pyo3(signature = (
+        *,
+        max_duration_secs = None,
+        drop_oldest = true,
+        sample_rate = 48_000,
+        channels = 2,
+        dtype = "int16",
+        keys = None,
+        exclude = None,
+        skip_silent = false,
+        vad = false
+    ))]
     /// Create a new BufferSink.
     ///
     /// Parameters
@@ -107,6 +160,23 @@ impl BufferSink {
     /// drop_oldest : bool, optional
     ///     If True, drop the oldest ticks when the buffer is full. If False,
     ///     drop new ticks instead.
+    /// sample_rate : int, optional
+    ///     Sample rate of the `pcm` column, a multiple of 50 between 8000 and
+    ///     192000. Rates other than 48000 are resampled per speaker.
+    /// channels : int, optional
+    ///     1 downmixes to mono, 2 keeps stereo.
+    /// dtype : {"int16", "float32"}, optional
+    ///     Sample type of the `pcm` column. `float32` samples are in [-1, 1].
+    /// keys : list[VoiceKey] | None, optional
+    ///     Sources to record. If None, every source is recorded. Unmapped
+    ///     `VoiceKey.Unknown` sources are kept until their user is known.
+    /// exclude : list[VoiceKey] | None, optional
+    ///     Sources never to record, even if listed in `keys`.
+    /// skip_silent : bool, optional
+    ///     If True, ticks in which no recorded source is speaking are dropped.
+    /// vad : bool, optional
+    ///     If True, score voice activity into the `vad` column, as required
+    ///     by `utterances()`. Otherwise the column is zero.
     ///
     /// Notes
     /// -----
@@ -117,7 +187,24 @@ impl BufferSink {
     /// Returns
     /// -------
     /// BufferSink
-    fn new(max_duration_secs: Option<usize>, drop_oldest: bool) -> PyResult<(Self, SinkBase)> {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        max_duration_secs: Option<usize>,
+        drop_oldest: bool,
+        sample_rate: u32,
+        channels: usize,
+        #[gen_stub(override_type(
+            type_repr = "typing.Literal[\"int16\", \"float32\"]",
+            imports = ("typing")
+        ))]
+        dtype: &str,
+        keys: Option<Vec<VoiceKey>>,
+        exclude: Option<Vec<VoiceKey>>,
+        skip_silent: bool,
+        vad: bool,
+    ) -> PyResult<(Self, SinkBase)> {
+        let format = PcmFormat::new(sample_rate, channels, dtype)?;
+        let filter = Arc::new(RwLock::new(KeyFilter::new(keys, exclude, skip_silent)));
         let is_stopped = Arc::new(AtomicBool::new(false));
         let max_ticks = match max_duration_secs {
             Some(0) => {
@@ -143,15 +230,27 @@ impl BufferSink {
             identity.clone(),
             max_ticks,
             drop_oldest,
+            TickBuilder::new(format)
+                .with_filter(filter.clone())
+                .with_vad(vad),
         );
         Ok((
-            Self { is_stopped, ticks },
+            Self {
+                is_stopped,
+                ticks,
+                format,
+                filter,
+                vad,
+            },
             SinkBase::new(
                 Arc::new(handler),
                 identity,
-                vec![Event::Core(CoreEvent::VoiceTick)]
-                    .into_iter()
-                    .collect(),
+                vec![
+                    Event::Core(CoreEvent::VoiceTick),
+                    Event::Core(CoreEvent::SpeakingStateUpdate),
+                ]
+                .into_iter()
+                .collect(),
             )?,
         ))
     }
@@ -169,6 +268,247 @@ impl BufferSink {
         self.is_stopped.store(true, Ordering::Relaxed);
     }
 
+    #[pyo3(signature = (*, keys = None, exclude = None, skip_silent = false))]
+    /// Replace the source filter for ticks received from now on.
+    ///
+    /// Parameters
+    /// ----------
+    /// keys : list[VoiceKey] | None, optional
+    ///     Sources to record. If None, every source is recorded. Unmapped
+    ///     `VoiceKey.Unknown` sources are kept until their user is known.
+    /// exclude : list[VoiceKey] | None, optional
+    ///     Sources never to record, even if listed in `keys`.
+    /// skip_silent : bool, optional
+    ///     If True, ticks in which no recorded source is speaking are dropped.
+    ///
+    /// Returns
+    /// -------
+    /// None
+    ///
+    /// Examples
+    /// --------
+    /// ```python
+    /// sink.set_filter(keys=[receive.VoiceKey.User(host_id)], skip_silent=True)
+    /// ```
+    fn set_filter(
+        &self,
+        keys: Option<Vec<VoiceKey>>,
+        exclude: Option<Vec<VoiceKey>>,
+        skip_silent: bool,
+    ) {
+        *self.filter.write().unwrap() = KeyFilter::new(keys, exclude, skip_silent);
+    }
+
+    /// Write the buffered ticks to an Arrow IPC file or stream.
+    ///
+    /// The buffer is left unchanged. Ticks are coalesced into record batches
+    /// of one second each.
+    ///
+    /// Parameters
+    /// ----------
+    /// target : str | os.PathLike[str] | typing.BinaryIO
+    ///     Path to create, or a writable binary file object.
+    /// format : {"file", "stream"}, optional
+    ///     IPC file format (Feather v2) or IPC streaming format.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    ///     Number of ticks written.
+    ///
+    /// Examples
+    /// --------
+    /// ```python
+    /// sink.write_ipc("session.arrow")
+    /// df = polars.read_ipc("session.arrow")
+    /// ```
+    #[pyo3(signature = (target, *, format = "file"))]
+    fn write_ipc(
+        &self,
+        py: Python<'_>,
+        #[gen_stub(override_type(
+            type_repr = "str | os.PathLike[str] | typing.BinaryIO",
+            imports = ("os", "typing")
+        ))]
+        target: &Bound<'_, PyAny>,
+        #[gen_stub(override_type(
+            type_repr = "typing.Literal[\"file\", \"stream\"]",
+            imports = ("typing")
+        ))]
+        format: &str,
+    ) -> PyResult<usize> {
+        let format = IpcFormat::parse(format)?;
+        let schema = voice_tick_schema(self.format.dtype);
+        let ticks = self.buffered();
+        let output = open_output(target)?;
+        py.detach(|| {
+            let mut writer = IpcWriter::try_new(format, output, &schema)?;
+            writer.write_ticks(&schema, &ticks)?;
+            writer.finish()
+        })
+        .map_err(arrow_error)?;
+        Ok(ticks.len())
+    }
+
+    /// Write the buffered ticks to a Parquet file.
+    ///
+    /// The buffer is left unchanged. Encoding uses `pyarrow.parquet`.
+    ///
+    /// Parameters
+    /// ----------
+    /// target : str | os.PathLike[str] | typing.BinaryIO
+    ///     Path to create, or a writable binary file object.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    ///     Number of ticks written.
+    ///
+    /// Examples
+    /// --------
+    /// ```python
+    /// sink.write_parquet("session.parquet")
+    /// duckdb.sql("SELECT key_id, count(*) FROM 'session.parquet' GROUP BY 1")
+    /// ```
+    fn write_parquet(
+        &self,
+        py: Python<'_>,
+        #[gen_stub(override_type(
+            type_repr = "str | os.PathLike[str] | typing.BinaryIO",
+            imports = ("os", "typing")
+        ))]
+        target: &Bound<'_, PyAny>,
+    ) -> PyResult<usize> {
+        let ticks = self.buffered();
+        write_parquet(py, target, voice_tick_schema(self.format.dtype), &ticks)?;
+        Ok(ticks.len())
+    }
+
+    /// |coro|
+    ///
+    /// Remove every buffered tick and return them as one record batch.
+    ///
+    /// Returns
+    /// -------
+    /// pyarrow.RecordBatch
+    ///     All buffered ticks in order, with the same columns as iteration.
+    ///     Empty if nothing was buffered.
+    ///
+    /// Examples
+    /// --------
+    /// ```python
+    /// batch = await sink.drain()
+    /// ```
+    fn drain<'py>(&self, py: Python<'py>) -> PyResult<PyFuture<'py, ArrowRecordBatch<'static>>> {
+        let ticks = self.ticks.clone();
+        let format = self.format;
+        future_into_py(py, async move {
+            let drained: Vec<_> = ticks.lock().await.drain(..).collect();
+            Python::attach(|py| concat_ticks(format, &drained, py)?.into_py_any(py))
+        })
+        .map(|x| x.into())
+    }
+
+    #[pyo3(signature = (*, last_secs = None))]
+    /// Return buffered ticks as one record batch, leaving the buffer intact.
+    ///
+    /// Parameters
+    /// ----------
+    /// last_secs : int | None, optional
+    ///     Only include ticks from the last `last_secs` seconds before the
+    ///     newest tick. If None, every buffered tick is included.
+    ///
+    /// Returns
+    /// -------
+    /// pyarrow.RecordBatch
+    ///
+    /// Examples
+    /// --------
+    /// ```python
+    /// clip = sink.snapshot(last_secs=30)
+    /// ```
+    fn snapshot<'py>(
+        &self,
+        py: Python<'py>,
+        last_secs: Option<u64>,
+    ) -> PyResult<ArrowRecordBatch<'py>> {
+        let ticks = match last_secs {
+            Some(0) => {
+                return Err(PyValueError::new_err("last_secs must be greater than zero"));
+            }
+            Some(secs) => {
+                let guard = self.ticks.blocking_lock();
+                match guard.back() {
+                    Some(newest) => last_ticks(guard.iter(), newest.tick(), secs),
+                    None => Vec::new(),
+                }
+            }
+            None => self.buffered(),
+        };
+        self.to_record_batch(py, &ticks)
+    }
+
+    /// Return buffered ticks within a tick range, leaving the buffer intact.
+    ///
+    /// Parameters
+    /// ----------
+    /// start_tick : int
+    ///     First tick index to include.
+    /// end_tick : int
+    ///     Tick index to stop before.
+    ///
+    /// Returns
+    /// -------
+    /// pyarrow.RecordBatch
+    ///
+    /// Examples
+    /// --------
+    /// ```python
+    /// batch = sink.window(500, 1000)  # ticks 500..999, ten seconds
+    /// ```
+    fn window<'py>(
+        &self,
+        py: Python<'py>,
+        start_tick: u64,
+        end_tick: u64,
+    ) -> PyResult<ArrowRecordBatch<'py>> {
+        if end_tick < start_tick {
+            return Err(PyValueError::new_err(
+                "end_tick must not be less than start_tick",
+            ));
+        }
+        let ticks = window_ticks(self.ticks.blocking_lock().iter(), start_tick, end_tick);
+        self.to_record_batch(py, &ticks)
+    }
+
+    /// Remove every buffered tick.
+    ///
+    /// Returns
+    /// -------
+    /// None
+    fn clear(&self) {
+        self.ticks.blocking_lock().clear();
+    }
+
+    /// Duration of audio currently buffered, at 20 ms per tick.
+    ///
+    /// Returns
+    /// -------
+    /// datetime.timedelta
+    #[getter]
+    fn duration(&self) -> Duration {
+        TICK_DURATION * self.ticks.blocking_lock().len() as u32
+    }
+
+    /// Return the number of buffered ticks.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    fn __len__(&self) -> usize {
+        self.ticks.blocking_lock().len()
+    }
+
     /// Return an async iterator over PCM for a specific key.
     ///
     /// Parameters
@@ -178,7 +518,7 @@ impl BufferSink {
     ///
     /// Returns
     /// -------
-    /// PyAsyncIterator[pyarrow.Int16Array | None]
+    /// PyAsyncIterator[pyarrow.Int16Array | pyarrow.FloatArray | None]
     ///
     /// Examples
     /// --------
@@ -191,7 +531,7 @@ impl BufferSink {
     fn __getitem__(
         &self,
         key: VoiceKey,
-    ) -> PyResult<Generic<'_, PyAsyncIterator, Option<ArrowArray<'_, Int16Array>>>> {
+    ) -> PyResult<Generic<'_, PyAsyncIterator, Option<ArrowArray<'_, PcmArray>>>> {
         let ticks = self.ticks.clone();
         let s = stream! {
             loop {
@@ -211,6 +551,138 @@ impl BufferSink {
         Ok(Generic::new(PyAsyncIterator::new_in_raw(s)))
     }
 
+    /// Return an async iterator over contiguous PCM for a specific key.
+    ///
+    /// Parameters
+    /// ----------
+    /// key : VoiceKey
+    ///     The user/ssrc key to follow.
+    /// fill_silence : bool, optional
+    ///     If True, ticks where the key sent no audio, and ticks the sink
+    ///     dropped, are zero-filled once the key has first spoken, giving a
+    ///     gapless timeline. If False, chunks only contain received audio and
+    ///     end early at each silence.
+    /// chunk_ms : int, optional
+    ///     Length of each chunk in milliseconds. Must be a positive multiple
+    ///     of 20.
+    ///
+    /// Returns
+    /// -------
+    /// PyAsyncIterator[AudioChunk]
+    ///
+    /// Examples
+    /// --------
+    /// ```python
+    /// key = receive.VoiceKey.User(user_id)
+    /// async for chunk in sink.user_stream(key, chunk_ms=200):
+    ///     transcriber.feed(chunk.pcm, chunk.timestamp)
+    /// ```
+    #[pyo3(signature = (key, *, fill_silence = true, chunk_ms = 20))]
+    fn user_stream(
+        &self,
+        key: VoiceKey,
+        fill_silence: bool,
+        chunk_ms: u64,
+    ) -> PyResult<Generic<'_, PyAsyncIterator, AudioChunk>> {
+        let mut chunker = UserChunker::new(key, fill_silence, chunk_ms)?;
+        let ticks = self.ticks.clone();
+        let s = stream! {
+            loop {
+                let Some(tick) = ({
+                    let mut guard = ticks.lock().await;
+                    guard.pop_front()
+                }) else {
+                    break;
+                };
+                for chunk in chunker.push(&tick) {
+                    yield Python::attach(|py| chunk.into_py_any(py));
+                }
+            }
+            if let Some(chunk) = chunker.finish() {
+                yield Python::attach(|py| chunk.into_py_any(py));
+            }
+        };
+        Ok(Generic::new(PyAsyncIterator::new_in_raw(s)))
+    }
+
+    /// Return an async iterator over speech segments from every source.
+    ///
+    /// Segments are cut from the `vad` column: one starts when a source's
+    /// speech probability reaches `threshold` and ends once it has stayed
+    /// below it for `post_padding_ms`. The sink must be created with
+    /// `vad=True`.
+    ///
+    /// Parameters
+    /// ----------
+    /// threshold : float, optional
+    ///     Speech probability, between 0 and 1, that counts as speech.
+    /// pre_padding_ms : int, optional
+    ///     Audio kept before the onset.
+    /// post_padding_ms : int, optional
+    ///     Quiet audio kept after the last speech; also how long a pause must
+    ///     last to end the segment.
+    /// min_duration_ms : int, optional
+    ///     Segments with less speech than this, excluding padding, are dropped.
+    ///
+    /// Returns
+    /// -------
+    /// PyAsyncIterator[tuple[VoiceKey, datetime.datetime, datetime.datetime, pyarrow.Int16Array | pyarrow.FloatArray]]
+    ///     `(key, start, end, pcm)` for each segment, in the sink's PCM format.
+    ///
+    /// Raises
+    /// ------
+    /// RuntimeError
+    ///     If the sink was created without `vad=True`.
+    ///
+    /// Examples
+    /// --------
+    /// ```python
+    /// sink = receive.BufferSink(vad=True)
+    /// ...
+    /// async for key, start, end, pcm in sink.utterances(min_duration_ms=500):
+    ///     await transcribe(key, pcm)
+    /// ```
+    #[pyo3(signature = (
+        *,
+        threshold = 0.5,
+        pre_padding_ms = 200,
+        post_padding_ms = 500,
+        min_duration_ms = 300
+    ))]
+    fn utterances(
+        &self,
+        threshold: f32,
+        pre_padding_ms: u64,
+        post_padding_ms: u64,
+        min_duration_ms: u64,
+    ) -> PyResult<Generic<'_, PyAsyncIterator, UtteranceTuple<'_>>> {
+        require_vad(self.vad)?;
+        let mut segmenter = Segmenter::new(UtteranceSettings::new(
+            threshold,
+            pre_padding_ms,
+            post_padding_ms,
+            min_duration_ms,
+        )?);
+        let ticks = self.ticks.clone();
+        let s = stream! {
+            loop {
+                let Some(tick) = ({
+                    let mut guard = ticks.lock().await;
+                    guard.pop_front()
+                }) else {
+                    break;
+                };
+                for utterance in segmenter.push(&tick) {
+                    yield Python::attach(|py| utterance.into_py_any(py));
+                }
+            }
+            for utterance in segmenter.finish() {
+                yield Python::attach(|py| utterance.into_py_any(py));
+            }
+        };
+        Ok(Generic::new(PyAsyncIterator::new_in_raw(s)))
+    }
+
     /// Return an async iterator over buffered Arrow record batches.
     ///
     /// Returns
@@ -244,3 +716,121 @@ impl BufferSink {
         Generic::new(PyAsyncIterator::new_in_raw(s))
     }
 }
+
+impl BufferSink {
+    fn buffered(&self) -> Vec<Arc<VoiceTickBatch>> {
+        self.ticks.blocking_lock().iter().cloned().collect()
+    }
+
+    fn to_record_batch<'py>(
+        &self,
+        py: Python<'py>,
+        ticks: &[Arc<VoiceTickBatch>],
+    ) -> PyResult<ArrowRecordBatch<'py>> {
+        concat_ticks(self.format, ticks, py)
+    }
+}
+
+fn concat_ticks<'py>(
+    format: PcmFormat,
+    ticks: &[Arc<VoiceTickBatch>],
+    py: Python<'py>,
+) -> PyResult<ArrowRecordBatch<'py>> {
+    let batch = coalesce(&voice_tick_schema(format.dtype), ticks).map_err(arrow_error)?;
+    PyRecordBatch::new(batch).into_arro3(py).map(Into::into)
+}
+
+/// Ticks received in the last `secs` seconds, counted back from the newest
+/// tick so dropped ticks still take up time.
+fn last_ticks<'a>(
+    ticks: impl Iterator<Item = &'a Arc<VoiceTickBatch>>,
+    newest: u64,
+    secs: u64,
+) -> Vec<Arc<VoiceTickBatch>> {
+    let start = (newest + 1).saturating_sub(secs.saturating_mul(TICKS_PER_SECOND.into()));
+    window_ticks(ticks, start, u64::MAX)
+}
+
+/// Ticks with `start <= tick < end`.
+fn window_ticks<'a>(
+    ticks: impl Iterator<Item = &'a Arc<VoiceTickBatch>>,
+    start: u64,
+    end: u64,
+) -> Vec<Arc<VoiceTickBatch>> {
+    ticks
+        .filter(|tick| (start..end).contains(&tick.tick()))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use songbird::model::SpeakingState;
+    use songbird::model::id::UserId;
+    use songbird::model::payload::Speaking;
+
+    fn ticks(indices: &[u64]) -> VecDeque<Arc<VoiceTickBatch>> {
+        indices
+            .iter()
+            .map(|index| VoiceTickBatch::from_test_rows(*index, vec![]))
+            .collect()
+    }
+
+    fn indices(ticks: &[Arc<VoiceTickBatch>]) -> Vec<u64> {
+        ticks.iter().map(|tick| tick.tick()).collect()
+    }
+
+    #[test]
+    fn last_ticks_count_back_by_tick_index() {
+        let buffered = ticks(&[0, 10, 60, 99, 100]);
+        assert_eq!(indices(&last_ticks(buffered.iter(), 100, 1)), [60, 99, 100]);
+        assert_eq!(
+            indices(&last_ticks(buffered.iter(), 100, 60)),
+            [0, 10, 60, 99, 100]
+        );
+    }
+
+    #[test]
+    fn windows_are_half_open() {
+        let buffered = ticks(&[3, 4, 5, 6]);
+        assert_eq!(indices(&window_ticks(buffered.iter(), 4, 6)), [4, 5]);
+        assert!(window_ticks(buffered.iter(), 7, 9).is_empty());
+    }
+
+    #[tokio::test]
+    async fn silent_ticks_left_by_an_excluded_user_are_dropped() {
+        let filter = KeyFilter::new(Some(vec![VoiceKey::User(1)]), None, true);
+        let ticks = Arc::new(Mutex::new(VecDeque::from([
+            VoiceTickBatch::from_test_rows(0, vec![(VoiceKey::Unknown(9), vec![1; 4])]),
+            VoiceTickBatch::from_test_rows(
+                1,
+                vec![
+                    (VoiceKey::User(1), vec![1; 4]),
+                    (VoiceKey::Unknown(9), vec![1; 4]),
+                ],
+            ),
+        ])));
+        let handler = BufferSinkHandler::new(
+            ticks.clone(),
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(VoiceIdentityBinding::default()),
+            None,
+            true,
+            TickBuilder::new(PcmFormat::default()).with_filter(Arc::new(RwLock::new(filter))),
+        );
+
+        handler
+            .act(&EventContext::SpeakingStateUpdate(Speaking {
+                delay: None,
+                speaking: SpeakingState::MICROPHONE,
+                ssrc: 9,
+                user_id: Some(UserId(2)),
+            }))
+            .await;
+        let ticks = ticks.lock().await;
+        assert_eq!(ticks.len(), 1);
+        assert_eq!(ticks[0].tick(), 1);
+        assert_eq!(ticks[0].all_keys(), [VoiceKey::User(1)].into());
+    }
+}
@@ -0,0 +1,360 @@
+use crate::receive::format::{PcmFormat, TickBuilder};
+use crate::receive::identity::VoiceIdentityBinding;
+use crate::receive::queue::{NotifyQueue, Overflow};
+use crate::receive::sink::SinkBase;
+use crate::receive::tick::{VoiceKey, VoiceTickBatch};
+use async_trait::async_trait;
+use pyo3::exceptions::PyValueError;
+use pyo3::{Py, PyAny, PyResult, Python, pyclass, pymethods};
+use pyo3_async_runtimes::{TaskLocals, into_future_with_locals};
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use songbird::{CoreEvent, Event, EventContext, EventHandler};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+/// An event waiting to be handed to a Python callback.
+enum SinkEvent {
+    Tick(Arc<VoiceTickBatch>),
+    Speaking(VoiceKey, bool),
+    Disconnect(u64),
+}
+
+pub struct CallbackSinkHandler {
+    identity: Arc<VoiceIdentityBinding>,
+    builder: TickBuilder,
+    queue: Arc<NotifyQueue<SinkEvent>>,
+    on_tick: bool,
+    on_speaking: bool,
+    speaking: Mutex<HashSet<VoiceKey>>,
+}
+
+impl CallbackSinkHandler {
+    fn push_tick(&self, batch: Arc<VoiceTickBatch>) {
+        if self.on_speaking {
+            let now = batch.speaking_keys();
+            let mut speaking = self.speaking.lock().unwrap();
+            for key in now.difference(&speaking) {
+                self.queue.push(SinkEvent::Speaking(key.clone(), true));
+            }
+            for key in speaking.difference(&now) {
+                self.queue.push(SinkEvent::Speaking(key.clone(), false));
+            }
+            *speaking = now;
+        }
+        if self.on_tick {
+            self.queue.push(SinkEvent::Tick(batch));
+        }
+    }
+}
+
+#[async_trait]
+impl EventHandler for CallbackSinkHandler {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if self.queue.is_closed() {
+            return None;
+        }
+        match ctx {
+            EventContext::VoiceTick(tick) if self.on_tick || self.on_speaking => {
+                self.push_tick(self.builder.build(tick, &*self.identity));
+            }
+            EventContext::ClientDisconnect(disconnect) => {
+                self.queue.push(SinkEvent::Disconnect(disconnect.user_id.0));
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+impl Drop for CallbackSinkHandler {
+    /// Once both the sink and its voice connection have released the
+    /// handler no more events can arrive; end the dispatcher so that it
+    /// releases the callbacks even if `stop()` was never called.
+    fn drop(&mut self) {
+        self.queue.close();
+    }
+}
+
+/// Python callbacks, owned by the dispatcher task.
+struct Callbacks {
+    on_tick: Option<Py<PyAny>>,
+    on_speaking: Option<Py<PyAny>>,
+    on_disconnect: Option<Py<PyAny>>,
+}
+
+impl Callbacks {
+    /// Call the callback for `event` and wait for the awaitable it returns.
+    async fn dispatch(&self, event: SinkEvent, locals: &TaskLocals) -> PyResult<()> {
+        let future = Python::attach(|py| {
+            let awaitable = match &event {
+                SinkEvent::Tick(batch) => match &self.on_tick {
+                    Some(callback) => callback.call1(py, (batch.to_record_batch(py)?,))?,
+                    None => return Ok(None),
+                },
+                SinkEvent::Speaking(key, speaking) => match &self.on_speaking {
+                    Some(callback) => callback.call1(py, (key.clone(), *speaking))?,
+                    None => return Ok(None),
+                },
+                SinkEvent::Disconnect(user_id) => match &self.on_disconnect {
+                    Some(callback) => callback.call1(py, (*user_id,))?,
+                    None => return Ok(None),
+                },
+            };
+            into_future_with_locals(locals, awaitable.into_bound(py)).map(Some)
+        })?;
+        if let Some(future) = future {
+            future.await?;
+        }
+        Ok(())
+    }
+}
+
+async fn run_callbacks(
+    queue: Arc<NotifyQueue<SinkEvent>>,
+    callbacks: Callbacks,
+    locals: TaskLocals,
+) {
+    while let Some(event) = queue.next().await {
+        if let Err(err) = callbacks.dispatch(event, &locals).await {
+            log::error!("CallbackSink callback raised: {err}");
+        }
+    }
+}
+
+#[gen_stub_pyclass]
+#[pyclass(
+    extends = SinkBase,
+    module = "discord.ext.songbird.native.receive",
+    skip_from_py_object
+)]
+/// Sink that hands received voice to async Python callbacks.
+///
+/// This is the supported way to implement a custom sink in Python. Events are
+/// queued natively without taking the GIL and delivered one at a time, in
+/// order, on the event loop that created the sink. Each callback is awaited
+/// before the next event is delivered, so a slow callback fills the queue
+/// rather than delaying voice receive; once full, events are discarded
+/// according to `overflow`.
+///
+/// Examples
+/// --------
+/// ```python
+/// from discord.ext import songbird
+/// from discord.ext.songbird import receive
+///
+/// async def on_tick(batch):
+///     await transcriber.feed(batch)
+///
+/// async def on_speaking(key, speaking):
+///     print(key, "started" if speaking else "stopped")
+///
+/// vc = await channel.connect(cls=songbird.SongbirdClient)
+/// sink = receive.CallbackSink(on_tick=on_tick, on_speaking=on_speaking)
+/// vc.listen(sink)
+/// ```
+pub struct CallbackSink {
+    queue: Arc<NotifyQueue<SinkEvent>>,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl CallbackSink {
+    #[gen_stub(override_return_type(type_repr = "typing.Self", imports = ("typing")))]
+    #[new]
+    #[pyo3(signature = (
+        *,
+        on_tick = None,
+        on_speaking = None,
+        on_disconnect = None,
+        max_queue = 500,
+        overflow = "drop_oldest",
+        sample_rate = 48_000,
+        channels = 2,
+        dtype = "int16",
+        vad = false
+    ))]
+    /// Create a new CallbackSink.
+    ///
+    /// Must be called while an event loop is running; callbacks run on that
+    /// loop.
+    ///
+    /// Parameters
+    /// ----------
+    /// on_tick : Callable[[pyarrow.RecordBatch], Awaitable[None]] | None, optional
+    ///     Called with the record batch of every 20 ms tick.
+    /// on_speaking : Callable[[VoiceKey, bool], Awaitable[None]] | None, optional
+    ///     Called when a source starts (True) or stops (False) speaking.
+    /// on_disconnect : Callable[[int], Awaitable[None]] | None, optional
+    ///     Called with the user ID of a member who left the channel.
+    /// max_queue : int, optional
+    ///     Maximum number of undelivered events. Must be greater than zero.
+    /// overflow : {"drop_oldest", "drop_newest"}, optional
+    ///     Which events to discard when the queue is full.
+    /// sample_rate : int, optional
+    ///     Sample rate of the `pcm` column, a multiple of 50 between 8000 and
+    ///     192000. Rates other than 48000 are resampled per speaker.
+    /// channels : int, optional
+    ///     1 downmixes to mono, 2 keeps stereo.
+    /// dtype : {"int16", "float32"}, optional
+    ///     Sample type of the `pcm` column. `float32` samples are in [-1, 1].
+    /// vad : bool, optional
+    ///     If True, score voice activity into the `vad` column. Otherwise the
+    ///     column is zero.
+    ///
+    /// Returns
+    /// -------
+    /// CallbackSink
+    ///
+    /// Raises
+    /// ------
+    /// RuntimeError
+    ///     If no event loop is running.
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        py: Python<'_>,
+        #[gen_stub(override_type(
+            type_repr = "typing.Callable[[pyarrow.RecordBatch], typing.Awaitable[None]] | None",
+            imports = ("typing", "pyarrow")
+        ))]
+        on_tick: Option<Py<PyAny>>,
+        #[gen_stub(override_type(
+            type_repr = "typing.Callable[[VoiceKey, bool], typing.Awaitable[None]] | None",
+            imports = ("typing")
+        ))]
+        on_speaking: Option<Py<PyAny>>,
+        #[gen_stub(override_type(
+            type_repr = "typing.Callable[[int], typing.Awaitable[None]] | None",
+            imports = ("typing")
+        ))]
+        on_disconnect: Option<Py<PyAny>>,
+        max_queue: usize,
+        #[gen_stub(override_type(
+            type_repr = "typing.Literal[\"drop_oldest\", \"drop_newest\"]",
+            imports = ("typing")
+        ))]
+        overflow: &str,
+        sample_rate: u32,
+        channels: usize,
+        #[gen_stub(override_type(
+            type_repr = "typing.Literal[\"int16\", \"float32\"]",
+            imports = ("typing")
+        ))]
+        dtype: &str,
+        vad: bool,
+    ) -> PyResult<(Self, SinkBase)> {
+        let format = PcmFormat::new(sample_rate, channels, dtype)?;
+        if max_queue == 0 {
+            return Err(PyValueError::new_err("max_queue must be greater than zero"));
+        }
+        let queue = NotifyQueue::new(Some(max_queue), Overflow::parse(overflow)?);
+        let locals = pyo3_async_runtimes::tokio::get_current_locals(py)?;
+
+        let identity = Arc::new(VoiceIdentityBinding::default());
+        let handler = CallbackSinkHandler {
+            identity: identity.clone(),
+            builder: TickBuilder::new(format).with_vad(vad),
+            queue: queue.clone(),
+            on_tick: on_tick.is_some(),
+            on_speaking: on_speaking.is_some(),
+            speaking: Mutex::new(HashSet::new()),
+        };
+        let mut events: HashSet<Event> = HashSet::from([Event::Core(CoreEvent::VoiceTick)]);
+        if on_disconnect.is_some() {
+            events.insert(Event::Core(CoreEvent::ClientDisconnect));
+        }
+
+        let callbacks = Callbacks {
+            on_tick,
+            on_speaking,
+            on_disconnect,
+        };
+        pyo3_async_runtimes::tokio::get_runtime().spawn(run_callbacks(
+            queue.clone(),
+            callbacks,
+            locals,
+        ));
+
+        Ok((
+            Self { queue },
+            SinkBase::new(Arc::new(handler), identity, events)?,
+        ))
+    }
+
+    /// Number of events discarded because the queue was full.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    #[getter]
+    fn dropped(&self) -> u64 {
+        self.queue.dropped()
+    }
+
+    /// Stop delivering events.
+    ///
+    /// Events already queued are still delivered, then the callbacks are
+    /// released.
+    ///
+    /// Notes
+    /// -----
+    /// This does not unregister the sink.
+    ///
+    /// Returns
+    /// -------
+    /// None
+    fn stop(&self) {
+        self.queue.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn dropping_the_handler_ends_the_queue() {
+        let queue = NotifyQueue::new(Some(4), Overflow::DropOldest);
+        let handler = CallbackSinkHandler {
+            identity: Arc::new(VoiceIdentityBinding::default()),
+            builder: TickBuilder::new(PcmFormat::default()),
+            queue: queue.clone(),
+            on_tick: false,
+            on_speaking: false,
+            speaking: Mutex::new(HashSet::new()),
+        };
+        queue.push(SinkEvent::Disconnect(7));
+        drop(handler);
+
+        assert!(matches!(queue.next().await, Some(SinkEvent::Disconnect(7))));
+        assert!(queue.next().await.is_none());
+    }
+
+    #[test]
+    fn speaking_changes_are_queued_before_the_tick() {
+        let queue = NotifyQueue::new(Some(8), Overflow::DropOldest);
+        let handler = CallbackSinkHandler {
+            identity: Arc::new(VoiceIdentityBinding::default()),
+            builder: TickBuilder::new(PcmFormat::default()),
+            queue: queue.clone(),
+            on_tick: true,
+            on_speaking: true,
+            speaking: Mutex::new(HashSet::new()),
+        };
+        handler.push_tick(VoiceTickBatch::from_test_rows(
+            0,
+            vec![(VoiceKey::User(7), vec![1; 1_920])],
+        ));
+        handler.push_tick(VoiceTickBatch::from_test_rows(1, vec![]));
+
+        let events: Vec<_> = std::iter::from_fn(|| queue.try_pop()).collect();
+        assert!(matches!(
+            events.as_slice(),
+            [
+                SinkEvent::Speaking(VoiceKey::User(7), true),
+                SinkEvent::Tick(_),
+                SinkEvent::Speaking(VoiceKey::User(7), false),
+                SinkEvent::Tick(_),
+            ]
+        ));
+    }
+}
@@ -0,0 +1,648 @@
+use crate::model::{Generic, PyAsyncIterator};
+use crate::receive::format::{PcmFormat, TICK_DURATION, TickBuilder};
+use crate::receive::identity::VoiceIdentityBinding;
+use crate::receive::record::{AudioWriter, RecordFormat};
+use crate::receive::sink::SinkBase;
+use crate::receive::sink::mix::Mixer;
+use crate::receive::sink::recorder::{RECORDER_QUEUE_TICKS, RecorderCommand, RecorderQueue};
+use crate::receive::tick::{VoiceKey, VoiceTickBatch};
+use arrow::array::AsArray;
+use arrow::datatypes::Int16Type;
+use async_stream::stream;
+use async_trait::async_trait;
+use pyo3::exceptions::PyValueError;
+use pyo3::{IntoPyObjectExt, PyErr, PyRef, PyResult, Python, pyclass, pymethods};
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use songbird::{CoreEvent, Event, EventContext, EventHandler};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+/// Finalised file paths, or the error that ended a file.
+type Report = io::Result<PathBuf>;
+
+/// What a recording file holds.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Target {
+    Source(VoiceKey),
+    Mix,
+}
+
+impl Target {
+    /// Value substituted for `{key}` in the path template.
+    fn name(&self) -> String {
+        match self {
+            Target::Source(VoiceKey::User(id)) => id.to_string(),
+            Target::Source(VoiceKey::Unknown(ssrc)) => format!("ssrc-{ssrc}"),
+            Target::Mix => "mix".to_string(),
+        }
+    }
+}
+
+/// Path template with `{key}`, `{segment}`, `{timestamp}`, and `{ext}`
+/// placeholders.
+#[derive(Debug, Clone)]
+struct PathTemplate(String);
+
+impl PathTemplate {
+    fn new(template: String, per_user: bool, rotates: bool) -> PyResult<Self> {
+        if per_user && !template.contains("{key}") {
+            return Err(PyValueError::new_err(
+                "path_template must contain {key} when per_user is True",
+            ));
+        }
+        if rotates && !template.contains("{segment}") && !template.contains("{timestamp}") {
+            return Err(PyValueError::new_err(
+                "path_template must contain {segment} or {timestamp} when rotate_secs is set",
+            ));
+        }
+        Ok(Self(template))
+    }
+
+    fn render(&self, target: &Target, segment: u64, start: SystemTime, ext: &str) -> PathBuf {
+        let timestamp = start
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        PathBuf::from(
+            self.0
+                .replace("{key}", &target.name())
+                .replace("{segment}", &segment.to_string())
+                .replace("{timestamp}", &timestamp.to_string())
+                .replace("{ext}", ext),
+        )
+    }
+}
+
+struct OpenFile {
+    path: PathBuf,
+    /// Where to move the file once finished, after its SSRC was attributed.
+    rename_to: Option<PathBuf>,
+    writer: Box<dyn AudioWriter>,
+}
+
+/// Writes ticks to files on a dedicated thread.
+struct Recorder {
+    template: PathTemplate,
+    format: RecordFormat,
+    pcm: PcmFormat,
+    per_user: bool,
+    mixer: Option<Mixer>,
+    rotate_ticks: Option<u64>,
+    segment: u64,
+    segment_start: Option<SystemTime>,
+    segment_ticks: u64,
+    files: HashMap<Target, OpenFile>,
+    /// Targets whose file failed this segment; retried after rotation.
+    failed: HashSet<Target>,
+    reports: UnboundedSender<Report>,
+}
+
+impl Recorder {
+    fn run(mut self, commands: mpsc::Receiver<RecorderCommand>) {
+        loop {
+            match commands.recv() {
+                Ok(RecorderCommand::Tick(batch)) => self.tick(&batch),
+                Ok(RecorderCommand::Identify(ssrc, user_id)) => self.identify(ssrc, user_id),
+                Ok(RecorderCommand::Stop) | Err(_) => break,
+            }
+        }
+        self.close_all();
+    }
+
+    fn tick(&mut self, batch: &VoiceTickBatch) {
+        if self.rotate_ticks == Some(self.segment_ticks) {
+            self.close_all();
+            self.segment += 1;
+            self.segment_ticks = 0;
+            self.segment_start = None;
+        }
+        self.segment_start.get_or_insert(batch.timestamp());
+        self.segment_ticks += 1;
+
+        if self.per_user {
+            // Files open on a source's first speech, then stay continuous.
+            for key in batch.speaking_keys() {
+                self.open(Target::Source(key));
+            }
+            let silence = vec![0; self.pcm.tick_samples()];
+            let targets: Vec<_> = self.files.keys().cloned().collect();
+            for target in targets {
+                let Target::Source(key) = &target else {
+                    continue;
+                };
+                match batch.get_array_ref(key) {
+                    Some(pcm) => self.write(target, pcm.as_primitive::<Int16Type>().values()),
+                    None => self.write(target, &silence),
+                }
+            }
+        }
+
+        let mixed = self.mixer.as_mut().and_then(|mixer| mixer.push(batch));
+        if let Some(mixed) = mixed {
+            self.open(Target::Mix);
+            self.write(Target::Mix, mixed.as_primitive::<Int16Type>().values());
+        }
+    }
+
+    /// Continue an unknown SSRC's file as its user's, renaming it on close.
+    fn identify(&mut self, ssrc: u32, user_id: u64) {
+        let user = Target::Source(VoiceKey::User(user_id));
+        if self.files.contains_key(&user) {
+            return;
+        }
+        let Some(mut file) = self.files.remove(&Target::Source(VoiceKey::Unknown(ssrc))) else {
+            return;
+        };
+        let start = self.segment_start.unwrap_or_else(SystemTime::now);
+        file.rename_to =
+            Some(
+                self.template
+                    .render(&user, self.segment, start, self.format.extension()),
+            );
+        self.files.insert(user, file);
+    }
+
+    fn open(&mut self, target: Target) {
+        if self.files.contains_key(&target) || self.failed.contains(&target) {
+            return;
+        }
+        let start = self.segment_start.unwrap_or_else(SystemTime::now);
+        let path = self
+            .template
+            .render(&target, self.segment, start, self.format.extension());
+        match self
+            .format
+            .create(&path, self.pcm.sample_rate, self.pcm.channels)
+        {
+            Ok(writer) => {
+                self.files.insert(
+                    target,
+                    OpenFile {
+                        path,
+                        rename_to: None,
+                        writer,
+                    },
+                );
+            }
+            Err(err) => self.fail(target, &path, err),
+        }
+    }
+
+    fn write(&mut self, target: Target, samples: &[i16]) {
+        let Some(file) = self.files.get_mut(&target) else {
+            return;
+        };
+        if let Err(err) = file.writer.write(samples) {
+            let file = self.files.remove(&target).expect("file was just written");
+            // Salvage what was written before the error.
+            let _ = file.writer.finish();
+            self.fail(target, &file.path, err);
+        }
+    }
+
+    fn fail(&mut self, target: Target, path: &std::path::Path, err: io::Error) {
+        let err = io::Error::new(err.kind(), format!("{}: {err}", path.display()));
+        let _ = self.reports.send(Err(err));
+        self.failed.insert(target);
+    }
+
+    fn close_all(&mut self) {
+        for (target, file) in std::mem::take(&mut self.files) {
+            match file.writer.finish() {
+                Ok(()) => {
+                    let path = match file.rename_to {
+                        Some(renamed) => match std::fs::rename(&file.path, &renamed) {
+                            Ok(()) => renamed,
+                            Err(err) => {
+                                let _ = self.reports.send(Err(io::Error::new(
+                                    err.kind(),
+                                    format!("{}: {err}", renamed.display()),
+                                )));
+                                file.path
+                            }
+                        },
+                        None => file.path,
+                    };
+                    let _ = self.reports.send(Ok(path));
+                }
+                Err(err) => self.fail(target, &file.path, err),
+            }
+        }
+        self.failed.clear();
+    }
+}
+
+pub struct FileSinkHandler {
+    is_stopped: Arc<AtomicBool>,
+    identity: Arc<VoiceIdentityBinding>,
+    builder: TickBuilder,
+    queue: Arc<RecorderQueue>,
+}
+
+#[async_trait]
+impl EventHandler for FileSinkHandler {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if self.is_stopped.load(Ordering::Relaxed) {
+            return None;
+        }
+        match ctx {
+            EventContext::VoiceTick(tick) => {
+                self.queue
+                    .push_tick(self.builder.build(tick, &*self.identity));
+            }
+            EventContext::SpeakingStateUpdate(speaking) => {
+                if let Some(user_id) = speaking.user_id {
+                    self.queue.identify(speaking.ssrc, user_id.0);
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+#[gen_stub_pyclass]
+#[pyclass(
+    extends = SinkBase,
+    module = "discord.ext.songbird.native.receive",
+    skip_from_py_object
+)]
+/// Sink that records received audio straight to files.
+///
+/// Encoding and file I/O run on a background thread, so recording never
+/// blocks the voice connection or needs the GIL. Per-user files start at a
+/// source's first speech and are filled with silence while it is quiet, so
+/// their timing stays aligned; mixed files start with the first tick.
+///
+/// Examples
+/// --------
+/// ```python
+/// from discord.ext import songbird
+/// from discord.ext.songbird import receive
+///
+/// vc = await channel.connect(cls=songbird.SongbirdClient)
+/// sink = receive.FileSink("recordings/{key}-{segment}.{ext}", format="flac", rotate_secs=600)
+/// vc.listen(sink)
+///
+/// async for path in sink:
+///     upload(path)
+/// ```
+pub struct FileSink {
+    is_stopped: Arc<AtomicBool>,
+    queue: Arc<RecorderQueue>,
+    reports: Arc<Mutex<UnboundedReceiver<Report>>>,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl FileSink {
+    #[gen_stub(override_return_type(type_repr = "typing.Self", imports = ("typing")))]
+    #[new]
+    #[pyo3(signature = (
+        path_template,
+        *,
+        format = "wav",
+        per_user = true,
+        mixdown = false,
+        rotate_secs = None,
+        sample_rate = 48_000,
+        channels = 2
+    ))]
+    /// Create a new FileSink.
+    ///
+    /// Parameters
+    /// ----------
+    /// path_template : str
+    ///     Path of each file. `{key}` becomes the user ID, `ssrc-<n>` for
+    ///     unmapped sources, or `mix`; `{segment}` the rotation index;
+    ///     `{timestamp}` the segment start in Unix seconds; `{ext}` the
+    ///     format's extension. Missing directories are created.
+    /// format : {"wav", "flac", "ogg-opus"}, optional
+    ///     File format. `ogg-opus` needs a sample rate of 8000, 12000,
+    ///     16000, 24000, or 48000.
+    /// per_user : bool, optional
+    ///     Write one file per source. Requires `{key}` in `path_template`.
+    /// mixdown : bool, optional
+    ///     Write one file with every source mixed together.
+    /// rotate_secs : int | None, optional
+    ///     Close every file and start new ones after this many seconds.
+    ///     Requires `{segment}` or `{timestamp}` in `path_template`.
+    /// sample_rate : int, optional
+    ///     Sample rate of the files, a multiple of 50 between 8000 and 192000.
+    /// channels : int, optional
+    ///     1 for mono files, 2 for stereo.
+    ///
+    /// Returns
+    /// -------
+    /// FileSink
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        path_template: String,
+        #[gen_stub(override_type(
+            type_repr = "typing.Literal[\"wav\", \"flac\", \"ogg-opus\"]",
+            imports = ("typing")
+        ))]
+        format: &str,
+        per_user: bool,
+        mixdown: bool,
+        rotate_secs: Option<u64>,
+        sample_rate: u32,
+        channels: usize,
+    ) -> PyResult<(Self, SinkBase)> {
+        let record_format = RecordFormat::parse(format)?;
+        let pcm = PcmFormat::new(sample_rate, channels, "int16")?;
+        record_format.check_sample_rate(sample_rate)?;
+        if !per_user && !mixdown {
+            return Err(PyValueError::new_err(
+                "at least one of per_user and mixdown must be True",
+            ));
+        }
+        let rotate_ticks = match rotate_secs {
+            Some(0) => {
+                return Err(PyValueError::new_err(
+                    "rotate_secs must be greater than zero",
+                ));
+            }
+            Some(secs) => Some(
+                secs.checked_mul(1000)
+                    .map(|ms| ms / TICK_DURATION.as_millis() as u64)
+                    .ok_or_else(|| PyValueError::new_err("rotate_secs is too large"))?,
+            ),
+            None => None,
+        };
+        let template = PathTemplate::new(path_template, per_user, rotate_ticks.is_some())?;
+        let mixer = mixdown
+            .then(|| Mixer::new(pcm, HashMap::new(), HashSet::new(), 20))
+            .transpose()?;
+
+        let (queue, command_rx) = RecorderQueue::new(RECORDER_QUEUE_TICKS);
+        let (report_tx, reports) = unbounded_channel();
+        let recorder = Recorder {
+            template,
+            format: record_format,
+            pcm,
+            per_user,
+            mixer,
+            rotate_ticks,
+            segment: 0,
+            segment_start: None,
+            segment_ticks: 0,
+            files: HashMap::new(),
+            failed: HashSet::new(),
+            reports: report_tx,
+        };
+        std::thread::Builder::new()
+            .name("songbird-file-sink".to_string())
+            .spawn(move || recorder.run(command_rx))
+            .map_err(PyErr::from)?;
+
+        let is_stopped = Arc::new(AtomicBool::new(false));
+        let identity = Arc::new(VoiceIdentityBinding::default());
+        let handler = FileSinkHandler {
+            is_stopped: is_stopped.clone(),
+            identity: identity.clone(),
+            builder: TickBuilder::new(pcm),
+            queue: queue.clone(),
+        };
+        Ok((
+            Self {
+                is_stopped,
+                queue,
+                reports: Arc::new(Mutex::new(reports)),
+            },
+            SinkBase::new(
+                Arc::new(handler),
+                identity,
+                vec![
+                    Event::Core(CoreEvent::VoiceTick),
+                    Event::Core(CoreEvent::SpeakingStateUpdate),
+                ]
+                .into_iter()
+                .collect(),
+            )?,
+        ))
+    }
+
+    /// Stop recording and finalise every open file.
+    ///
+    /// Finalised paths are still reported by the iterator, which ends once
+    /// the last file is closed.
+    ///
+    /// Notes
+    /// -----
+    /// This does not unregister the sink. Files left open when the process
+    /// exits without `stop()` may have incomplete headers.
+    ///
+    /// Returns
+    /// -------
+    /// None
+    fn stop(&self, py: Python<'_>) {
+        if self.is_stopped.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        py.detach(|| {
+            self.queue.stop();
+        });
+    }
+
+    #[getter]
+    /// Number of ticks discarded because the writer fell behind.
+    ///
+    /// Up to about 10 seconds of ticks are queued for the writer thread.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    fn dropped(&self) -> u64 {
+        self.queue.dropped()
+    }
+
+    /// Return an async iterator over the paths of finished files.
+    ///
+    /// A path is yielded each time a file is finalised, on rotation or
+    /// `stop()`. Iteration ends after `stop()` once every file is closed.
+    ///
+    /// Returns
+    /// -------
+    /// PyAsyncIterator[str]
+    ///
+    /// Raises
+    /// ------
+    /// OSError
+    ///     If a file could not be created or written. Recording of other
+    ///     files continues, and the failed file is retried after rotation.
+    ///
+    /// Examples
+    /// --------
+    /// ```python
+    /// async for path in sink:
+    ///     print("recorded", path)
+    /// ```
+    fn __aiter__<'py>(slf: PyRef<'py, Self>) -> Generic<'py, PyAsyncIterator, String> {
+        let reports = slf.reports.clone();
+        let s = stream! {
+            loop {
+                let report = reports.lock().await.recv().await;
+                match report {
+                    Some(Ok(path)) => {
+                        yield Python::attach(|py| path.to_string_lossy().into_py_any(py));
+                    }
+                    Some(Err(err)) => yield Err(PyErr::from(err)),
+                    None => break,
+                }
+            }
+        };
+        Generic::new(PyAsyncIterator::new_in_raw(s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::receive::record::TestDir;
+    use songbird::model::SpeakingState;
+    use songbird::model::id::UserId;
+    use songbird::model::payload::Speaking;
+
+    fn recorder(
+        dir: &std::path::Path,
+        per_user: bool,
+        mixdown: bool,
+    ) -> (Recorder, UnboundedReceiver<Report>) {
+        let pcm = PcmFormat::new(8_000, 1, "int16").unwrap();
+        let template = dir
+            .join("{key}-{segment}.{ext}")
+            .to_string_lossy()
+            .into_owned();
+        let (reports, rx) = unbounded_channel();
+        let recorder = Recorder {
+            template: PathTemplate::new(template, per_user, true).unwrap(),
+            format: RecordFormat::Wav,
+            pcm,
+            per_user,
+            mixer: mixdown.then(|| Mixer::new(pcm, HashMap::new(), HashSet::new(), 20).unwrap()),
+            rotate_ticks: Some(3),
+            segment: 0,
+            segment_start: None,
+            segment_ticks: 0,
+            files: HashMap::new(),
+            failed: HashSet::new(),
+            reports,
+        };
+        (recorder, rx)
+    }
+
+    fn paths(rx: &mut UnboundedReceiver<Report>) -> Vec<String> {
+        let mut paths = Vec::new();
+        while let Ok(report) = rx.try_recv() {
+            let path = report.unwrap();
+            paths.push(path.file_name().unwrap().to_string_lossy().into_owned());
+        }
+        paths.sort();
+        paths
+    }
+
+    fn data_len(dir: &std::path::Path, name: &str) -> u64 {
+        std::fs::metadata(dir.join(name)).unwrap().len() - 44
+    }
+
+    #[test]
+    fn writes_aligned_per_user_and_mixed_files_with_rotation() {
+        let dir = TestDir::new("file-sink");
+        let (mut recorder, mut rx) = recorder(&dir, true, true);
+        let (a, b) = (VoiceKey::User(1), VoiceKey::Unknown(9));
+        recorder.tick(&VoiceTickBatch::from_test_constants(
+            0,
+            160,
+            &[(a.clone(), 100)],
+        ));
+        recorder.tick(&VoiceTickBatch::from_test_constants(
+            1,
+            160,
+            &[(b.clone(), 200)],
+        ));
+        recorder.tick(&VoiceTickBatch::from_test_constants(2, 160, &[]));
+        assert!(paths(&mut rx).is_empty());
+        // The fourth tick starts a new segment.
+        recorder.tick(&VoiceTickBatch::from_test_constants(3, 160, &[(a, 300)]));
+        assert_eq!(paths(&mut rx), ["1-0.wav", "mix-0.wav", "ssrc-9-0.wav"]);
+        assert_eq!(data_len(&dir, "1-0.wav"), 3 * 320);
+        assert_eq!(data_len(&dir, "ssrc-9-0.wav"), 2 * 320);
+        assert_eq!(data_len(&dir, "mix-0.wav"), 3 * 320);
+
+        let (_tx, commands) = mpsc::channel();
+        drop(_tx);
+        recorder.run(commands);
+        assert_eq!(paths(&mut rx), ["1-1.wav", "mix-1.wav"]);
+    }
+
+    #[test]
+    fn identified_ssrc_files_continue_as_the_user_and_are_renamed() {
+        let dir = TestDir::new("file-rekey");
+        let (mut recorder, mut rx) = recorder(&dir, true, false);
+        recorder.tick(&VoiceTickBatch::from_test_constants(
+            0,
+            160,
+            &[(VoiceKey::Unknown(9), 100)],
+        ));
+        recorder.identify(9, 4);
+        recorder.tick(&VoiceTickBatch::from_test_constants(
+            1,
+            160,
+            &[(VoiceKey::User(4), 200)],
+        ));
+        recorder.close_all();
+
+        assert_eq!(paths(&mut rx), ["4-0.wav"]);
+        assert_eq!(data_len(&dir, "4-0.wav"), 2 * 320);
+        assert!(!dir.join("ssrc-9-0.wav").exists());
+    }
+
+    #[test]
+    fn templates_are_validated() {
+        assert!(PathTemplate::new("out.{ext}".into(), true, false).is_err());
+        assert!(PathTemplate::new("{key}.{ext}".into(), true, true).is_err());
+        let template = PathTemplate::new("{timestamp}/{key}.{ext}".into(), true, true).unwrap();
+        let path = template.render(
+            &Target::Source(VoiceKey::User(5)),
+            2,
+            UNIX_EPOCH + std::time::Duration::from_secs(60),
+            "flac",
+        );
+        assert_eq!(path, PathBuf::from("60/5.flac"));
+    }
+
+    #[tokio::test]
+    async fn mappings_do_not_block_on_a_full_queue() {
+        let (queue, commands) = RecorderQueue::new(1);
+        let handler = FileSinkHandler {
+            is_stopped: Arc::new(AtomicBool::new(false)),
+            identity: Arc::new(VoiceIdentityBinding::default()),
+            builder: TickBuilder::new(PcmFormat::default()),
+            queue: queue.clone(),
+        };
+        queue.push_tick(VoiceTickBatch::from_test_rows(0, vec![]));
+
+        // A blocking send would never return here.
+        handler
+            .act(&EventContext::SpeakingStateUpdate(Speaking {
+                delay: None,
+                speaking: SpeakingState::MICROPHONE,
+                ssrc: 9,
+                user_id: Some(UserId(4)),
+            }))
+            .await;
+        assert!(matches!(commands.try_recv(), Ok(RecorderCommand::Tick(_))));
+        queue.push_tick(VoiceTickBatch::from_test_rows(1, vec![]));
+        assert!(matches!(
+            commands.try_recv(),
+            Ok(RecorderCommand::Identify(9, 4))
+        ));
+    }
+}
@@ -0,0 +1,309 @@
+use crate::receive::export::{IpcFormat, IpcWriter, TICKS_PER_BATCH, arrow_error, open_output};
+use crate::receive::format::{PcmFormat, TickBuilder};
+use crate::receive::identity::VoiceIdentityBinding;
+use crate::receive::sink::SinkBase;
+use crate::receive::sink::recorder::{RECORDER_QUEUE_TICKS, RecorderCommand, RecorderQueue};
+use crate::receive::tick::{VoiceTickBatch, rekey_ticks, voice_tick_schema};
+use arrow::datatypes::SchemaRef;
+use arrow::error::ArrowError;
+use async_trait::async_trait;
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::{Bound, PyAny, PyErr, PyResult, Python, pyclass, pymethods};
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use songbird::{CoreEvent, Event, EventContext, EventHandler};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+/// Appends ticks to an Arrow IPC output on a dedicated thread.
+struct IpcRecorder {
+    writer: IpcWriter,
+    schema: SchemaRef,
+    pending: Vec<Arc<VoiceTickBatch>>,
+}
+
+impl IpcRecorder {
+    fn run(mut self, commands: mpsc::Receiver<RecorderCommand>) -> Result<(), ArrowError> {
+        loop {
+            match commands.recv() {
+                Ok(RecorderCommand::Tick(tick)) => {
+                    self.pending.push(tick);
+                    if self.pending.len() == TICKS_PER_BATCH {
+                        self.flush()?;
+                    }
+                }
+                // Only ticks not yet written can still be attributed.
+                Ok(RecorderCommand::Identify(ssrc, user_id)) => {
+                    rekey_ticks(self.pending.iter_mut(), ssrc, user_id);
+                }
+                Ok(RecorderCommand::Stop) | Err(_) => break,
+            }
+        }
+        self.flush()?;
+        self.writer.finish()
+    }
+
+    fn flush(&mut self) -> Result<(), ArrowError> {
+        self.writer.write_ticks(&self.schema, &self.pending)?;
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+pub struct IpcSinkHandler {
+    is_stopped: Arc<AtomicBool>,
+    identity: Arc<VoiceIdentityBinding>,
+    builder: TickBuilder,
+    queue: Arc<RecorderQueue>,
+}
+
+#[async_trait]
+impl EventHandler for IpcSinkHandler {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if self.is_stopped.load(Ordering::Relaxed) {
+            return None;
+        }
+        match ctx {
+            EventContext::VoiceTick(tick) => {
+                self.queue
+                    .push_tick(self.builder.build(tick, &*self.identity));
+            }
+            EventContext::SpeakingStateUpdate(speaking) => {
+                if let Some(user_id) = speaking.user_id {
+                    self.queue.identify(speaking.ssrc, user_id.0);
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+#[gen_stub_pyclass]
+#[pyclass(
+    extends = SinkBase,
+    module = "discord.ext.songbird.native.receive",
+    skip_from_py_object
+)]
+/// Sink that streams every tick to an Arrow IPC file or stream.
+///
+/// Rows use the same schema as `BufferSink` batches, including the `tick`
+/// and `timestamp` columns, so recordings can be analysed offline with
+/// pyarrow, Polars, or DuckDB. Ticks are written from a background thread in
+/// record batches of one second each.
+///
+/// Examples
+/// --------
+/// ```python
+/// from discord.ext import songbird
+/// from discord.ext.songbird import receive
+///
+/// vc = await channel.connect(cls=songbird.SongbirdClient)
+/// sink = receive.IpcSink("session.arrow")
+/// vc.listen(sink)
+/// ...
+/// sink.stop()
+///
+/// df = polars.read_ipc("session.arrow")
+/// ```
+pub struct IpcSink {
+    is_stopped: Arc<AtomicBool>,
+    queue: Arc<RecorderQueue>,
+    worker: Mutex<Option<JoinHandle<Result<(), ArrowError>>>>,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl IpcSink {
+    #[gen_stub(override_return_type(type_repr = "typing.Self", imports = ("typing")))]
+    #[new]
+    #[pyo3(signature = (
+        target,
+        *,
+        format = "file",
+        sample_rate = 48_000,
+        channels = 2,
+        dtype = "int16",
+        vad = false
+    ))]
+    /// Create a new IpcSink.
+    ///
+    /// Parameters
+    /// ----------
+    /// target : str | os.PathLike[str] | typing.BinaryIO
+    ///     Path to create, or a writable binary file object. File objects are
+    ///     written to from a background thread and are not closed.
+    /// format : {"file", "stream"}, optional
+    ///     `file` writes the random-access IPC file format (Feather v2),
+    ///     which is readable once `stop()` has written its footer. `stream`
+    ///     writes the IPC streaming format, readable while recording.
+    /// sample_rate : int, optional
+    ///     Sample rate of the `pcm` column, a multiple of 50 between 8000 and
+    ///     192000.
+    /// channels : int, optional
+    ///     1 downmixes to mono, 2 keeps stereo.
+    /// dtype : {"int16", "float32"}, optional
+    ///     Sample type of the `pcm` column.
+    /// vad : bool, optional
+    ///     If True, score voice activity into the `vad` column. Otherwise the
+    ///     column is zero.
+    ///
+    /// Returns
+    /// -------
+    /// IpcSink
+    ///
+    /// Raises
+    /// ------
+    /// OSError
+    ///     If `target` cannot be created or written.
+    fn new(
+        #[gen_stub(override_type(
+            type_repr = "str | os.PathLike[str] | typing.BinaryIO",
+            imports = ("os", "typing")
+        ))]
+        target: &Bound<'_, PyAny>,
+        #[gen_stub(override_type(
+            type_repr = "typing.Literal[\"file\", \"stream\"]",
+            imports = ("typing")
+        ))]
+        format: &str,
+        sample_rate: u32,
+        channels: usize,
+        #[gen_stub(override_type(
+            type_repr = "typing.Literal[\"int16\", \"float32\"]",
+            imports = ("typing")
+        ))]
+        dtype: &str,
+        vad: bool,
+    ) -> PyResult<(Self, SinkBase)> {
+        let ipc_format = IpcFormat::parse(format)?;
+        let pcm = PcmFormat::new(sample_rate, channels, dtype)?;
+        let schema = voice_tick_schema(pcm.dtype);
+        let writer =
+            IpcWriter::try_new(ipc_format, open_output(target)?, &schema).map_err(arrow_error)?;
+
+        let (queue, command_rx) = RecorderQueue::new(RECORDER_QUEUE_TICKS);
+        let recorder = IpcRecorder {
+            writer,
+            schema,
+            pending: Vec::with_capacity(TICKS_PER_BATCH),
+        };
+        let worker = std::thread::Builder::new()
+            .name("songbird-ipc-sink".to_string())
+            .spawn(move || recorder.run(command_rx))
+            .map_err(PyErr::from)?;
+
+        let is_stopped = Arc::new(AtomicBool::new(false));
+        let identity = Arc::new(VoiceIdentityBinding::default());
+        let handler = IpcSinkHandler {
+            is_stopped: is_stopped.clone(),
+            identity: identity.clone(),
+            builder: TickBuilder::new(pcm).with_vad(vad),
+            queue: queue.clone(),
+        };
+        Ok((
+            Self {
+                is_stopped,
+                queue,
+                worker: Mutex::new(Some(worker)),
+            },
+            SinkBase::new(
+                Arc::new(handler),
+                identity,
+                vec![
+                    Event::Core(CoreEvent::VoiceTick),
+                    Event::Core(CoreEvent::SpeakingStateUpdate),
+                ]
+                .into_iter()
+                .collect(),
+            )?,
+        ))
+    }
+
+    /// Stop recording, write any pending ticks, and finish the output.
+    ///
+    /// Blocks, without holding the GIL, until the output is complete.
+    ///
+    /// Notes
+    /// -----
+    /// This does not unregister the sink.
+    ///
+    /// Returns
+    /// -------
+    /// None
+    ///
+    /// Raises
+    /// ------
+    /// OSError
+    ///     If writing failed at any point during recording.
+    fn stop(&self, py: Python<'_>) -> PyResult<()> {
+        self.is_stopped.store(true, Ordering::Relaxed);
+        let Some(worker) = self.worker.lock().unwrap().take() else {
+            return Ok(());
+        };
+        py.detach(|| {
+            self.queue.stop();
+            worker.join()
+        })
+        .map_err(|_| PyRuntimeError::new_err("IPC writer thread panicked"))?
+        .map_err(arrow_error)
+    }
+
+    #[getter]
+    /// Number of ticks discarded because the writer fell behind.
+    ///
+    /// Up to about 10 seconds of ticks are queued for the writer thread.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    fn dropped(&self) -> u64 {
+        self.queue.dropped()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::receive::format::PcmDtype;
+    use crate::receive::record::TestDir;
+    use crate::receive::tick::VoiceKey;
+    use arrow::array::AsArray;
+    use arrow::datatypes::UInt64Type;
+    use arrow::ipc::reader::FileReader;
+    use std::fs::File;
+
+    #[test]
+    fn recorder_writes_every_tick_before_finishing() {
+        let dir = TestDir::new("ipc-sink");
+        let path = dir.join("session.arrow");
+        let schema = voice_tick_schema(PcmDtype::Int16);
+        let output = Box::new(File::create(&path).unwrap());
+        let writer = IpcWriter::try_new(IpcFormat::File, output, &schema).unwrap();
+        let (commands, command_rx) = mpsc::channel();
+        for tick in 0..60 {
+            let batch = VoiceTickBatch::from_test_rows(tick, vec![(VoiceKey::User(1), vec![0; 4])]);
+            commands.send(RecorderCommand::Tick(batch)).unwrap();
+        }
+        commands.send(RecorderCommand::Stop).unwrap();
+        IpcRecorder {
+            writer,
+            schema,
+            pending: Vec::new(),
+        }
+        .run(command_rx)
+        .unwrap();
+
+        let reader = FileReader::try_new(File::open(&path).unwrap(), None).unwrap();
+        let ticks: Vec<u64> = reader
+            .flat_map(|batch| {
+                batch.unwrap()["tick"]
+                    .as_primitive::<UInt64Type>()
+                    .values()
+                    .to_vec()
+            })
+            .collect();
+        assert_eq!(ticks, (0..60).collect::<Vec<_>>());
+    }
+}
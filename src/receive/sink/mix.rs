@@ -0,0 +1,405 @@
+use crate::model::{ArrowArray, Generic, PcmArray, PyAsyncIterator};
+use crate::player::input::mix::soft_clip;
+use crate::receive::format::{PcmDtype, PcmFormat, TICK_DURATION, TickBuilder, to_i16};
+use crate::receive::identity::VoiceIdentityBinding;
+use crate::receive::queue::{NotifyQueue, Overflow};
+use crate::receive::sink::SinkBase;
+use crate::receive::tick::{VoiceKey, VoiceTickBatch};
+use arrow::array::{ArrayRef, AsArray, Float32Array, Int16Array};
+use arrow::datatypes::{DataType, Float32Type, Int16Type};
+use async_stream::stream;
+use async_trait::async_trait;
+use pyo3::exceptions::PyValueError;
+use pyo3::{IntoPyObjectExt, PyRef, PyResult, Python, pyclass, pymethods};
+use pyo3_arrow::PyArray;
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use songbird::{CoreEvent, Event, EventContext, EventHandler};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// Sums every speaking row of a tick into a single PCM stream.
+pub(crate) struct Mixer {
+    format: PcmFormat,
+    gains: HashMap<VoiceKey, f32>,
+    exclude: HashSet<VoiceKey>,
+    ticks_per_chunk: usize,
+    pending: Vec<f32>,
+    pending_ticks: usize,
+}
+
+impl Mixer {
+    pub(crate) fn new(
+        format: PcmFormat,
+        gains: HashMap<VoiceKey, f32>,
+        exclude: HashSet<VoiceKey>,
+        chunk_ms: u64,
+    ) -> PyResult<Self> {
+        let tick_ms = TICK_DURATION.as_millis() as u64;
+        if chunk_ms == 0 || !chunk_ms.is_multiple_of(tick_ms) {
+            return Err(PyValueError::new_err(format!(
+                "chunk_ms must be a positive multiple of {tick_ms}"
+            )));
+        }
+        if gains.values().any(|gain| !gain.is_finite() || *gain < 0.0) {
+            return Err(PyValueError::new_err(
+                "gains must be finite values of at least 0",
+            ));
+        }
+        let ticks_per_chunk = (chunk_ms / tick_ms) as usize;
+        Ok(Self {
+            format,
+            gains,
+            exclude,
+            ticks_per_chunk,
+            pending: Vec::with_capacity(format.tick_samples() * ticks_per_chunk),
+            pending_ticks: 0,
+        })
+    }
+
+    /// Mix one tick, returning a chunk once enough ticks have been mixed.
+    pub(super) fn push(&mut self, batch: &VoiceTickBatch) -> Option<ArrayRef> {
+        let speakers: Vec<_> = batch
+            .speaking_keys()
+            .into_iter()
+            .filter(|key| !self.exclude.contains(key))
+            .collect();
+        self.push_keys(batch, &speakers)
+    }
+
+    /// Like `push`, mixing only the rows of `keys`.
+    pub(crate) fn push_keys(
+        &mut self,
+        batch: &VoiceTickBatch,
+        keys: &[VoiceKey],
+    ) -> Option<ArrayRef> {
+        let start = self.pending.len();
+        self.pending.resize(start + self.format.tick_samples(), 0.0);
+        let mix = &mut self.pending[start..];
+        for key in keys {
+            let gain = self.gains.get(key).copied().unwrap_or(1.0);
+            let Some(pcm) = batch.get_array_ref(key) else {
+                continue;
+            };
+            match pcm.data_type() {
+                DataType::Float32 => {
+                    let samples = pcm.as_primitive::<Float32Type>().values();
+                    for (out, sample) in mix.iter_mut().zip(samples.iter()) {
+                        *out += sample * gain;
+                    }
+                }
+                _ => {
+                    let samples = pcm.as_primitive::<Int16Type>().values();
+                    for (out, sample) in mix.iter_mut().zip(samples.iter()) {
+                        *out += *sample as f32 / 32_768.0 * gain;
+                    }
+                }
+            }
+        }
+        self.pending_ticks += 1;
+        (self.pending_ticks == self.ticks_per_chunk).then(|| self.flush())?
+    }
+
+    /// Emit whatever has been mixed so far.
+    pub(super) fn flush(&mut self) -> Option<ArrayRef> {
+        if self.pending_ticks == 0 {
+            return None;
+        }
+        self.pending_ticks = 0;
+        let samples = self.pending.drain(..).map(soft_clip);
+        Some(match self.format.dtype {
+            PcmDtype::Int16 => Arc::new(samples.map(to_i16).collect::<Int16Array>()),
+            PcmDtype::Float32 => Arc::new(samples.collect::<Float32Array>()),
+        })
+    }
+}
+
+pub struct MixSinkHandler {
+    identity: Arc<VoiceIdentityBinding>,
+    builder: TickBuilder,
+    mixer: Arc<Mutex<Mixer>>,
+    queue: Arc<NotifyQueue<ArrayRef>>,
+}
+
+#[async_trait]
+impl EventHandler for MixSinkHandler {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if self.queue.is_closed() {
+            return None;
+        }
+        if let EventContext::VoiceTick(tick) = ctx {
+            let tick = self.builder.build(tick, &*self.identity);
+            let chunk = self.mixer.lock().unwrap().push(&tick);
+            if let Some(chunk) = chunk {
+                self.queue.push(chunk);
+            }
+        }
+        None
+    }
+}
+
+#[gen_stub_pyclass]
+#[pyclass(
+    extends = SinkBase,
+    module = "discord.ext.songbird.native.receive",
+    skip_from_py_object
+)]
+/// Sink that mixes every speaker into a single PCM stream.
+///
+/// Each tick, the PCM of all speaking sources is summed natively with soft
+/// clipping, so a whole conversation can be recorded without per-row work in
+/// Python. Ticks with nobody speaking produce silence, so the output is
+/// continuous.
+///
+/// Examples
+/// --------
+/// ```python
+/// from discord.ext import songbird
+/// from discord.ext.songbird import receive
+///
+/// vc = await channel.connect(cls=songbird.SongbirdClient)
+/// sink = receive.MixSink(chunk_ms=1000, exclude=[receive.VoiceKey.User(bot_id)])
+/// vc.listen(sink)
+///
+/// async for pcm in sink:
+///     recording.write(pcm)
+/// ```
+pub struct MixSink {
+    mixer: Arc<Mutex<Mixer>>,
+    queue: Arc<NotifyQueue<ArrayRef>>,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl MixSink {
+    #[gen_stub(override_return_type(type_repr = "typing.Self", imports = ("typing")))]
+    #[new]
+    #[pyo3(signature = (
+        *,
+        chunk_ms = 20,
+        gains = None,
+        exclude = None,
+        max_duration_secs = None,
+        sample_rate = 48_000,
+        channels = 2,
+        dtype = "int16"
+    ))]
+    /// Create a new MixSink.
+    ///
+    /// Parameters
+    /// ----------
+    /// chunk_ms : int, optional
+    ///     Length of each mixed chunk in milliseconds. Must be a positive
+    ///     multiple of 20.
+    /// gains : dict[VoiceKey, float] | None, optional
+    ///     Linear gain per source. Sources not listed are mixed at 1.0.
+    /// exclude : list[VoiceKey] | None, optional
+    ///     Sources left out of the mix.
+    /// max_duration_secs : int | None, optional
+    ///     Maximum seconds of unread mix to keep. The oldest chunks are
+    ///     dropped once full. If None, unbounded.
+    /// sample_rate : int, optional
+    ///     Sample rate of the mix, a multiple of 50 between 8000 and 192000.
+    /// channels : int, optional
+    ///     1 for a mono mix, 2 for stereo.
+    /// dtype : {"int16", "float32"}, optional
+    ///     Sample type of the mix. `float32` samples are in [-1, 1].
+    ///
+    /// Returns
+    /// -------
+    /// MixSink
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        chunk_ms: u64,
+        gains: Option<HashMap<VoiceKey, f32>>,
+        exclude: Option<Vec<VoiceKey>>,
+        max_duration_secs: Option<usize>,
+        sample_rate: u32,
+        channels: usize,
+        #[gen_stub(override_type(
+            type_repr = "typing.Literal[\"int16\", \"float32\"]",
+            imports = ("typing")
+        ))]
+        dtype: &str,
+    ) -> PyResult<(Self, SinkBase)> {
+        let format = PcmFormat::new(sample_rate, channels, dtype)?;
+        let mixer = Mixer::new(
+            format,
+            gains.unwrap_or_default(),
+            exclude.unwrap_or_default().into_iter().collect(),
+            chunk_ms,
+        )?;
+        let max_chunks = match max_duration_secs {
+            Some(0) => {
+                return Err(PyValueError::new_err(
+                    "max_duration_secs must be greater than zero",
+                ));
+            }
+            Some(secs) => Some(
+                secs.checked_mul(1000)
+                    .map(|ms| (ms as u64).div_ceil(chunk_ms) as usize)
+                    .ok_or_else(|| PyValueError::new_err("max_duration_secs is too large"))?,
+            ),
+            None => None,
+        };
+        let identity = Arc::new(VoiceIdentityBinding::default());
+        let mixer = Arc::new(Mutex::new(mixer));
+        let queue = NotifyQueue::new(max_chunks, Overflow::DropOldest);
+        let handler = MixSinkHandler {
+            identity: identity.clone(),
+            builder: TickBuilder::new(format),
+            mixer: mixer.clone(),
+            queue: queue.clone(),
+        };
+        Ok((
+            Self { mixer, queue },
+            SinkBase::new(
+                Arc::new(handler),
+                identity,
+                vec![Event::Core(CoreEvent::VoiceTick)]
+                    .into_iter()
+                    .collect(),
+            )?,
+        ))
+    }
+
+    /// Stop mixing new ticks.
+    ///
+    /// Any partially filled chunk is emitted, and iteration ends once the
+    /// remaining chunks have been read.
+    ///
+    /// Notes
+    /// -----
+    /// This does not unregister the sink.
+    ///
+    /// Returns
+    /// -------
+    /// None
+    fn stop(&self) {
+        let mut mixer = self.mixer.lock().unwrap();
+        if self.queue.is_closed() {
+            return;
+        }
+        if let Some(chunk) = mixer.flush() {
+            self.queue.push(chunk);
+        }
+        self.queue.close();
+    }
+
+    /// Return an async iterator over mixed PCM chunks.
+    ///
+    /// Unlike `BufferSink`, iteration waits for new audio and only ends after
+    /// `stop()`.
+    ///
+    /// Returns
+    /// -------
+    /// PyAsyncIterator[pyarrow.Int16Array | pyarrow.FloatArray]
+    ///
+    /// Examples
+    /// --------
+    /// ```python
+    /// async for pcm in sink:
+    ///     recording.write(pcm)
+    /// ```
+    fn __aiter__<'py>(
+        slf: PyRef<'py, Self>,
+    ) -> Generic<'py, PyAsyncIterator, ArrowArray<'py, PcmArray>> {
+        let queue = slf.queue.clone();
+        let s = stream! {
+            while let Some(chunk) = queue.next().await {
+                yield Python::attach(|py| {
+                    PyArray::from_array_ref(chunk)
+                        .into_arro3(py)
+                        .and_then(|x| x.into_py_any(py))
+                });
+            }
+        };
+        Generic::new(PyAsyncIterator::new_in_raw(s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mixer(chunk_ms: u64, gains: &[(VoiceKey, f32)], exclude: &[VoiceKey]) -> Mixer {
+        let format = PcmFormat::new(8_000, 1, "int16").unwrap();
+        Mixer::new(
+            format,
+            gains.iter().cloned().collect(),
+            exclude.iter().cloned().collect(),
+            chunk_ms,
+        )
+        .unwrap()
+    }
+
+    fn values(chunk: ArrayRef) -> Vec<i16> {
+        chunk.as_primitive::<Int16Type>().values().to_vec()
+    }
+
+    #[test]
+    fn sums_speakers_with_gain_and_exclusion() {
+        let (a, b, c) = (VoiceKey::User(1), VoiceKey::User(2), VoiceKey::User(3));
+        let mut mixer = mixer(20, &[(b.clone(), 0.5)], std::slice::from_ref(&c));
+        let chunk = mixer
+            .push(&VoiceTickBatch::from_test_constants(
+                0,
+                160,
+                &[(a, 1_000), (b, 2_000), (c, 4_000)],
+            ))
+            .unwrap();
+        assert_eq!(values(chunk), vec![2_000; 160]);
+    }
+
+    #[test]
+    fn chunks_span_several_ticks_and_silence() {
+        let mut mixer = mixer(60, &[], &[]);
+        assert!(
+            mixer
+                .push(&VoiceTickBatch::from_test_constants(
+                    0,
+                    160,
+                    &[(VoiceKey::User(1), 100)]
+                ))
+                .is_none()
+        );
+        assert!(
+            mixer
+                .push(&VoiceTickBatch::from_test_constants(0, 160, &[]))
+                .is_none()
+        );
+        let chunk = values(
+            mixer
+                .push(&VoiceTickBatch::from_test_constants(
+                    0,
+                    160,
+                    &[(VoiceKey::User(1), 300)],
+                ))
+                .unwrap(),
+        );
+        assert_eq!(chunk.len(), 480);
+        assert_eq!((chunk[0], chunk[200], chunk[400]), (100, 0, 300));
+        assert!(mixer.flush().is_none());
+    }
+
+    #[test]
+    fn loud_mixes_are_soft_clipped() {
+        let mut mixer = mixer(20, &[], &[]);
+        let loud = i16::MAX - 1;
+        let chunk = mixer
+            .push(&VoiceTickBatch::from_test_constants(
+                0,
+                160,
+                &[(VoiceKey::User(1), loud), (VoiceKey::User(2), loud)],
+            ))
+            .unwrap();
+        assert_eq!(values(chunk)[0], i16::MAX);
+    }
+
+    #[test]
+    fn invalid_settings_are_rejected() {
+        let format = PcmFormat::default();
+        assert!(Mixer::new(format, HashMap::new(), HashSet::new(), 30).is_err());
+        let gains = HashMap::from([(VoiceKey::User(1), -1.0)]);
+        assert!(Mixer::new(format, gains, HashSet::new(), 20).is_err());
+    }
+}
@@ -0,0 +1,271 @@
+use crate::model::{ArrowRecordBatch, Generic, PyAsyncIterator};
+use crate::receive::identity::VoiceIdentityBinding;
+use crate::receive::packet::{opus_batch, rtcp_batch};
+use crate::receive::queue::{NotifyQueue, Overflow};
+use crate::receive::sink::SinkBase;
+use arrow::array::RecordBatch;
+use async_stream::stream;
+use async_trait::async_trait;
+use pyo3::exceptions::PyValueError;
+use pyo3::{IntoPyObjectExt, PyRef, PyResult, Python, pyclass, pymethods};
+use pyo3_arrow::PyRecordBatch;
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use songbird::{CoreEvent, Event, EventContext, EventHandler};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Yield batches as they arrive, ending once the queue is closed and drained.
+fn iter_batches<'py>(
+    queue: Arc<NotifyQueue<RecordBatch>>,
+) -> Generic<'py, PyAsyncIterator, ArrowRecordBatch<'py>> {
+    let s = stream! {
+        while let Some(batch) = queue.next().await {
+            yield Python::attach(|py| {
+                PyRecordBatch::new(batch)
+                    .into_arro3(py)
+                    .and_then(|x| x.into_py_any(py))
+            });
+        }
+    };
+    Generic::new(PyAsyncIterator::new_in_raw(s))
+}
+
+fn positive(name: &str, value: Option<usize>) -> PyResult<Option<usize>> {
+    match value {
+        Some(0) => Err(PyValueError::new_err(format!(
+            "{name} must be greater than zero"
+        ))),
+        value => Ok(value),
+    }
+}
+
+pub struct OpusSinkHandler {
+    identity: Arc<VoiceIdentityBinding>,
+    next_tick: AtomicU64,
+    queue: Arc<NotifyQueue<RecordBatch>>,
+}
+
+#[async_trait]
+impl EventHandler for OpusSinkHandler {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if self.queue.is_closed() {
+            return None;
+        }
+        if let EventContext::VoiceTick(tick) = ctx {
+            let index = self.next_tick.fetch_add(1, Ordering::Relaxed);
+            self.queue.push(opus_batch(tick, index, &*self.identity));
+        }
+        None
+    }
+}
+
+#[gen_stub_pyclass]
+#[pyclass(
+    extends = SinkBase,
+    module = "discord.ext.songbird.native.receive",
+    skip_from_py_object
+)]
+/// Sink that yields the undecoded Opus packets of each tick.
+///
+/// Each tick becomes a record batch with one row per source whose packet
+/// arrived, in playout order, with `key_kind`, `key_id`, `rtp_sequence`,
+/// `rtp_timestamp`, `opus`, `tick`, and `timestamp` columns. `opus` holds
+/// the Opus frame exactly as sent, so audio can be archived without decoding
+/// and re-encoding. Lost packets have no row.
+///
+/// Examples
+/// --------
+/// ```python
+/// from discord.ext import songbird
+/// from discord.ext.songbird import receive
+///
+/// vc = await channel.connect(cls=songbird.SongbirdClient)
+/// sink = receive.OpusSink()
+/// vc.listen(sink)
+///
+/// async for batch in sink:
+///     archive.write(batch)
+/// ```
+pub struct OpusSink {
+    queue: Arc<NotifyQueue<RecordBatch>>,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl OpusSink {
+    #[gen_stub(override_return_type(type_repr = "typing.Self", imports = ("typing")))]
+    #[new]
+    #[pyo3(signature = (*, max_duration_secs = None))]
+    /// Create a new OpusSink.
+    ///
+    /// Parameters
+    /// ----------
+    /// max_duration_secs : int | None, optional
+    ///     Maximum seconds of unread ticks to keep. The oldest ticks are
+    ///     dropped once full. If None, unbounded.
+    ///
+    /// Returns
+    /// -------
+    /// OpusSink
+    fn new(max_duration_secs: Option<usize>) -> PyResult<(Self, SinkBase)> {
+        let max_ticks = positive("max_duration_secs", max_duration_secs)?
+            .map(|secs| {
+                secs.checked_mul(50)
+                    .ok_or_else(|| PyValueError::new_err("max_duration_secs is too large"))
+            })
+            .transpose()?;
+        let queue = NotifyQueue::new(max_ticks, Overflow::DropOldest);
+        let identity = Arc::new(VoiceIdentityBinding::default());
+        let handler = OpusSinkHandler {
+            identity: identity.clone(),
+            next_tick: AtomicU64::new(0),
+            queue: queue.clone(),
+        };
+        Ok((
+            Self { queue },
+            SinkBase::new(
+                Arc::new(handler),
+                identity,
+                vec![Event::Core(CoreEvent::VoiceTick)]
+                    .into_iter()
+                    .collect(),
+            )?,
+        ))
+    }
+
+    /// Stop collecting packets.
+    ///
+    /// Iteration ends once the remaining batches have been read.
+    ///
+    /// Notes
+    /// -----
+    /// This does not unregister the sink.
+    ///
+    /// Returns
+    /// -------
+    /// None
+    fn stop(&self) {
+        self.queue.close();
+    }
+
+    /// Return an async iterator over per-tick Opus batches.
+    ///
+    /// Returns
+    /// -------
+    /// PyAsyncIterator[pyarrow.RecordBatch]
+    fn __aiter__<'py>(
+        slf: PyRef<'py, Self>,
+    ) -> Generic<'py, PyAsyncIterator, ArrowRecordBatch<'py>> {
+        iter_batches(slf.queue.clone())
+    }
+}
+
+pub struct RtcpSinkHandler {
+    identity: Arc<VoiceIdentityBinding>,
+    queue: Arc<NotifyQueue<RecordBatch>>,
+}
+
+#[async_trait]
+impl EventHandler for RtcpSinkHandler {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if self.queue.is_closed() {
+            return None;
+        }
+        if let EventContext::RtcpPacket(packet) = ctx
+            && let Some(batch) = rtcp_batch(packet, &*self.identity)
+        {
+            self.queue.push(batch);
+        }
+        None
+    }
+}
+
+#[gen_stub_pyclass]
+#[pyclass(
+    extends = SinkBase,
+    module = "discord.ext.songbird.native.receive",
+    skip_from_py_object
+)]
+/// Sink that yields received RTCP packets.
+///
+/// Each packet becomes a one-row record batch with `key_kind` and `key_id`
+/// for its sender, `packet_type` (200 for sender reports, 201 for receiver
+/// reports), the decrypted `payload` following the 8-byte header, and
+/// `timestamp`. Use it to inspect the network statistics reported by other
+/// participants.
+///
+/// Examples
+/// --------
+/// ```python
+/// sink = receive.RtcpSink()
+/// vc.listen(sink)
+///
+/// async for batch in sink:
+///     print(batch.to_pylist())
+/// ```
+pub struct RtcpSink {
+    queue: Arc<NotifyQueue<RecordBatch>>,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl RtcpSink {
+    #[gen_stub(override_return_type(type_repr = "typing.Self", imports = ("typing")))]
+    #[new]
+    #[pyo3(signature = (*, max_packets = None))]
+    /// Create a new RtcpSink.
+    ///
+    /// Parameters
+    /// ----------
+    /// max_packets : int | None, optional
+    ///     Maximum number of unread packets to keep. The oldest packets are
+    ///     dropped once full. If None, unbounded.
+    ///
+    /// Returns
+    /// -------
+    /// RtcpSink
+    fn new(max_packets: Option<usize>) -> PyResult<(Self, SinkBase)> {
+        let queue = NotifyQueue::new(positive("max_packets", max_packets)?, Overflow::DropOldest);
+        let identity = Arc::new(VoiceIdentityBinding::default());
+        let handler = RtcpSinkHandler {
+            identity: identity.clone(),
+            queue: queue.clone(),
+        };
+        Ok((
+            Self { queue },
+            SinkBase::new(
+                Arc::new(handler),
+                identity,
+                vec![Event::Core(CoreEvent::RtcpPacket)]
+                    .into_iter()
+                    .collect(),
+            )?,
+        ))
+    }
+
+    /// Stop collecting packets.
+    ///
+    /// Iteration ends once the remaining packets have been read.
+    ///
+    /// Notes
+    /// -----
+    /// This does not unregister the sink.
+    ///
+    /// Returns
+    /// -------
+    /// None
+    fn stop(&self) {
+        self.queue.close();
+    }
+
+    /// Return an async iterator over RTCP packets.
+    ///
+    /// Returns
+    /// -------
+    /// PyAsyncIterator[pyarrow.RecordBatch]
+    fn __aiter__<'py>(
+        slf: PyRef<'py, Self>,
+    ) -> Generic<'py, PyAsyncIterator, ArrowRecordBatch<'py>> {
+        iter_batches(slf.queue.clone())
+    }
+}
@@ -0,0 +1,123 @@
+use crate::receive::tick::VoiceTickBatch;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, TrySendError};
+use std::sync::{Arc, Mutex};
+
+/// Ticks a recorder thread may fall behind by, about 10 seconds, before new
+/// ticks are dropped.
+pub(super) const RECORDER_QUEUE_TICKS: usize = 500;
+
+/// Work for a thread that writes ticks to an output.
+pub(super) enum RecorderCommand {
+    Tick(Arc<VoiceTickBatch>),
+    Identify(u32, u64),
+    Stop,
+}
+
+/// Sending side of a recorder thread's bounded queue.
+///
+/// Nothing here blocks songbird's event task: ticks that find the queue full
+/// are dropped and counted, and SSRC mappings that find it full are held
+/// back and sent ahead of the next tick, so the recorder sees them in order.
+pub(super) struct RecorderQueue {
+    commands: mpsc::SyncSender<RecorderCommand>,
+    identities: Mutex<VecDeque<(u32, u64)>>,
+    dropped: AtomicU64,
+}
+
+impl RecorderQueue {
+    pub(super) fn new(capacity: usize) -> (Arc<Self>, mpsc::Receiver<RecorderCommand>) {
+        let (commands, receiver) = mpsc::sync_channel(capacity);
+        let queue = Arc::new(Self {
+            commands,
+            identities: Mutex::default(),
+            dropped: AtomicU64::new(0),
+        });
+        (queue, receiver)
+    }
+
+    pub(super) fn push_tick(&self, tick: Arc<VoiceTickBatch>) {
+        let mut identities = self.identities.lock().unwrap();
+        let sent = self.flush(&mut identities)
+            && !matches!(
+                self.commands.try_send(RecorderCommand::Tick(tick)),
+                Err(TrySendError::Full(_))
+            );
+        if !sent {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(super) fn identify(&self, ssrc: u32, user_id: u64) {
+        let mut identities = self.identities.lock().unwrap();
+        identities.push_back((ssrc, user_id));
+        self.flush(&mut identities);
+    }
+
+    /// Ask the recorder to finish, waiting for room in the queue. Call this
+    /// off the event task, without holding the GIL.
+    pub(super) fn stop(&self) {
+        let mut identities = self.identities.lock().unwrap();
+        for (ssrc, user_id) in identities.drain(..) {
+            let _ = self.commands.send(RecorderCommand::Identify(ssrc, user_id));
+        }
+        let _ = self.commands.send(RecorderCommand::Stop);
+    }
+
+    /// Number of ticks discarded because the queue was full.
+    pub(super) fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Send held-back mappings; false if the queue filled up first.
+    fn flush(&self, identities: &mut VecDeque<(u32, u64)>) -> bool {
+        while let Some(&(ssrc, user_id)) = identities.front() {
+            match self
+                .commands
+                .try_send(RecorderCommand::Identify(ssrc, user_id))
+            {
+                Ok(()) | Err(TrySendError::Disconnected(_)) => {
+                    identities.pop_front();
+                }
+                Err(TrySendError::Full(_)) => return false,
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn received(commands: &mpsc::Receiver<RecorderCommand>) -> Vec<String> {
+        commands
+            .try_iter()
+            .map(|command| match command {
+                RecorderCommand::Tick(tick) => format!("tick {}", tick.tick()),
+                RecorderCommand::Identify(ssrc, user_id) => format!("{ssrc} is {user_id}"),
+                RecorderCommand::Stop => "stop".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn mappings_wait_for_room_ahead_of_the_next_tick() {
+        let (queue, commands) = RecorderQueue::new(2);
+        queue.push_tick(VoiceTickBatch::from_test_rows(0, vec![]));
+        queue.push_tick(VoiceTickBatch::from_test_rows(1, vec![]));
+        queue.identify(9, 4);
+        queue.push_tick(VoiceTickBatch::from_test_rows(2, vec![]));
+        assert_eq!(queue.dropped(), 1);
+        assert_eq!(received(&commands), ["tick 0", "tick 1"]);
+
+        queue.push_tick(VoiceTickBatch::from_test_rows(3, vec![]));
+        assert_eq!(received(&commands), ["9 is 4", "tick 3"]);
+        assert_eq!(queue.dropped(), 1);
+
+        queue.identify(5, 6);
+        queue.stop();
+        assert_eq!(received(&commands), ["5 is 6", "stop"]);
+    }
+}
@@ -1,19 +1,27 @@
-use crate::model::{ArrowArray, ArrowRecordBatch, Generic, PyAsyncIterator, PyFuture};
+use crate::model::{ArrowArray, ArrowRecordBatch, Generic, PcmArray, PyAsyncIterator, PyFuture};
+use crate::receive::filter::KeyFilter;
+use crate::receive::format::{PcmFormat, TickBuilder};
 use crate::receive::identity::VoiceIdentityBinding;
 use crate::receive::sink::SinkBase;
 use crate::receive::tick::{VoiceKey, VoiceTickBatch};
-use arrow::array::Int16Array;
+use crate::receive::user_stream::{AudioChunk, UserChunker};
+use crate::receive::utterance::{Segmenter, UtteranceSettings, UtteranceTuple, require_vad};
+use async_stream::stream;
 use async_trait::async_trait;
-use futures::StreamExt;
+use futures::{Stream, StreamExt};
 use pyo3::exceptions::PyValueError;
 use pyo3::{Bound, IntoPyObjectExt, PyAny, PyRef, PyRefMut, PyResult, Python, pyclass, pymethods};
 use pyo3_async_runtimes::tokio::future_into_py;
 use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
 use songbird::{CoreEvent, Event, EventContext, EventHandler};
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
 use tokio::sync::{OwnedSemaphorePermit, Semaphore, broadcast};
 use tokio_stream::wrappers::BroadcastStream;
 
+/// Unread identity changes kept per `identity_changes()` iterator.
+const IDENTITY_CHANGES_CAPACITY: usize = 64;
+
 #[gen_stub_pyclass]
 #[pyclass(
     extends = SinkBase,
@@ -41,9 +49,13 @@ use tokio_stream::wrappers::BroadcastStream;
 ///         ...
 /// ```
 pub struct StreamSink {
+    format: PcmFormat,
+    filter: Arc<RwLock<KeyFilter>>,
+    identity_tx: broadcast::WeakSender<(u32, u64)>,
     _rx: broadcast::Receiver<Arc<VoiceTickBatch>>,
     weak_tx: broadcast::WeakSender<Arc<VoiceTickBatch>>,
     sem: Arc<Semaphore>,
+    vad: bool,
 }
 
 #[gen_stub_pyclass]
@@ -59,6 +71,7 @@ pub struct PyStream {
     acquire: Option<OwnedSemaphorePermit>,
     sem: Arc<Semaphore>,
     weak_tx: broadcast::WeakSender<Arc<VoiceTickBatch>>,
+    vad: bool,
 }
 
 pub struct StreamSinkHandler {
@@ -67,17 +80,31 @@ pub struct StreamSinkHandler {
     retain: bool,
     sem: Arc<Semaphore>,
     identity: Arc<VoiceIdentityBinding>,
+    builder: TickBuilder,
+    identity_tx: broadcast::Sender<(u32, u64)>,
+    mapped: Mutex<HashMap<u32, u64>>,
 }
 
 #[async_trait]
 impl EventHandler for StreamSinkHandler {
     async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
         match ctx {
-            EventContext::VoiceTick(tick)
-                if self.sem.available_permits() < self.max_concurrent || self.retain =>
-            {
-                let tick = VoiceTickBatch::from_parts(tick, &*self.identity);
-                drop(self.tx.send(tick))
+            EventContext::VoiceTick(tick) => {
+                if self.sem.available_permits() < self.max_concurrent || self.retain {
+                    if let Some(tick) = self.builder.build_filtered(tick, &*self.identity) {
+                        drop(self.tx.send(tick))
+                    }
+                } else {
+                    self.builder.skip();
+                }
+            }
+            EventContext::SpeakingStateUpdate(speaking) => {
+                if let Some(user_id) = speaking.user_id {
+                    let previous = self.mapped.lock().unwrap().insert(speaking.ssrc, user_id.0);
+                    if previous != Some(user_id.0) {
+                        drop(self.identity_tx.send((speaking.ssrc, user_id.0)));
+                    }
+                }
             }
             _ => {}
         }
@@ -90,7 +117,19 @@ impl EventHandler for StreamSinkHandler {
 impl StreamSink {
     #[gen_stub(override_return_type(type_repr = "typing.Self", imports = ("typing")))]
     #[new]
-    #[pyo3(signature = (*, retain = false, retain_secs = 15, max_concurrent = 50))]
+    #[pyo3(signature = (
+        *,
+        retain = false,
+        retain_secs = 15,
+        max_concurrent = 50,
+        sample_rate = 48_000,
+        channels = 2,
+        dtype = "int16",
+        keys = None,
+        exclude = None,
+        skip_silent = false,
+        vad = false
+    ))]
     /// Create a new StreamSink.
     ///
     /// Parameters
@@ -104,15 +143,47 @@ impl StreamSink {
     /// max_concurrent : int, optional
     ///     Maximum number of concurrent streams.
     ///     Must be greater than zero.
+    /// sample_rate : int, optional
+    ///     Sample rate of the `pcm` column, a multiple of 50 between 8000 and
+    ///     192000. Rates other than 48000 are resampled per speaker.
+    /// channels : int, optional
+    ///     1 downmixes to mono, 2 keeps stereo.
+    /// dtype : {"int16", "float32"}, optional
+    ///     Sample type of the `pcm` column. `float32` samples are in [-1, 1].
+    /// keys : list[VoiceKey] | None, optional
+    ///     Sources to stream. If None, every source is streamed. Unmapped
+    ///     `VoiceKey.Unknown` sources are streamed as well, since they may
+    ///     belong to a listed user.
+    /// exclude : list[VoiceKey] | None, optional
+    ///     Sources never to stream, even if listed in `keys`.
+    /// skip_silent : bool, optional
+    ///     If True, ticks in which no streamed source is speaking are dropped.
+    /// vad : bool, optional
+    ///     If True, score voice activity into the `vad` column, as required
+    ///     by `Stream.utterances()`. Otherwise the column is zero.
     ///
     /// Returns
     /// -------
     /// StreamSink
+    #[allow(clippy::too_many_arguments)]
     fn new(
         retain: bool,
         retain_secs: usize,
         max_concurrent: usize,
+        sample_rate: u32,
+        channels: usize,
+        #[gen_stub(override_type(
+            type_repr = "typing.Literal[\"int16\", \"float32\"]",
+            imports = ("typing")
+        ))]
+        dtype: &str,
+        keys: Option<Vec<VoiceKey>>,
+        exclude: Option<Vec<VoiceKey>>,
+        skip_silent: bool,
+        vad: bool,
     ) -> PyResult<(StreamSink, SinkBase)> {
+        let format = PcmFormat::new(sample_rate, channels, dtype)?;
+        let filter = Arc::new(RwLock::new(KeyFilter::new(keys, exclude, skip_silent)));
         if retain_secs == 0 {
             return Err(PyValueError::new_err(
                 "retain_secs must be greater than zero",
@@ -129,11 +200,16 @@ impl StreamSink {
         let (tx, rx) = broadcast::channel(retain_ticks);
         let sem = Arc::new(Semaphore::new(max_concurrent));
         let identity = Arc::new(VoiceIdentityBinding::default());
+        let (identity_tx, _) = broadcast::channel(IDENTITY_CHANGES_CAPACITY);
         Ok((
             StreamSink {
+                format,
+                filter: filter.clone(),
+                identity_tx: identity_tx.downgrade(),
                 _rx: rx,
                 sem: sem.clone(),
                 weak_tx: tx.downgrade(),
+                vad,
             },
             SinkBase {
                 subscriber: Arc::new(StreamSinkHandler {
@@ -142,11 +218,17 @@ impl StreamSink {
                     retain,
                     sem,
                     identity: identity.clone(),
+                    builder: TickBuilder::new(format).with_filter(filter).with_vad(vad),
+                    identity_tx,
+                    mapped: Mutex::new(HashMap::new()),
                 }),
                 identity,
-                receive_events: vec![Event::Core(CoreEvent::VoiceTick)]
-                    .into_iter()
-                    .collect(),
+                receive_events: vec![
+                    Event::Core(CoreEvent::VoiceTick),
+                    Event::Core(CoreEvent::SpeakingStateUpdate),
+                ]
+                .into_iter()
+                .collect(),
             },
         ))
     }
@@ -171,8 +253,64 @@ impl StreamSink {
             acquire: None,
             sem: self.sem.clone(),
             weak_tx: self.weak_tx.clone(),
+            vad: self.vad,
         })
     }
+
+    #[pyo3(signature = (*, keys = None, exclude = None, skip_silent = false))]
+    /// Replace the source filter for ticks received from now on.
+    ///
+    /// Parameters
+    /// ----------
+    /// keys : list[VoiceKey] | None, optional
+    ///     Sources to stream. If None, every source is streamed. Unmapped
+    ///     `VoiceKey.Unknown` sources are streamed as well, since they may
+    ///     belong to a listed user.
+    /// exclude : list[VoiceKey] | None, optional
+    ///     Sources never to stream, even if listed in `keys`.
+    /// skip_silent : bool, optional
+    ///     If True, ticks in which no streamed source is speaking are dropped.
+    ///
+    /// Returns
+    /// -------
+    /// None
+    fn set_filter(
+        &self,
+        keys: Option<Vec<VoiceKey>>,
+        exclude: Option<Vec<VoiceKey>>,
+        skip_silent: bool,
+    ) {
+        *self.filter.write().unwrap() = KeyFilter::new(keys, exclude, skip_silent);
+    }
+
+    /// Return an async iterator over newly learned SSRC to user mappings.
+    ///
+    /// Batches keep the key they were built with, so audio received before
+    /// Discord announced a speaker's SSRC stays under `VoiceKey.Unknown`.
+    /// Use these `(old, new)` pairs to re-key such audio yourself. Only
+    /// mappings learned after this call are yielded.
+    ///
+    /// Iteration ends, like the sink's streams, once the voice connection
+    /// and the sink have released its handler, typically after the sink is
+    /// garbage collected following a disconnect. Break out of the loop to
+    /// stop listening earlier.
+    ///
+    /// Returns
+    /// -------
+    /// PyAsyncIterator[tuple[VoiceKey, VoiceKey]]
+    ///
+    /// Examples
+    /// --------
+    /// ```python
+    /// async for old, new in sink.identity_changes():
+    ///     transcripts[new] = transcripts.pop(old, "") + transcripts.get(new, "")
+    /// ```
+    fn identity_changes(&self) -> Generic<'_, PyAsyncIterator, (VoiceKey, VoiceKey)> {
+        let stream = identity_stream(&self.identity_tx).map(|(ssrc, user_id)| {
+            Python::attach(|py| (VoiceKey::Unknown(ssrc), VoiceKey::User(user_id)).into_py_any(py))
+        });
+        Generic::new(PyAsyncIterator::new_in_raw(stream))
+    }
 }
 
 #[gen_stub_pymethods]
@@ -252,7 +390,7 @@ impl PyStream {
     ///
     /// Returns
     /// -------
-    /// PyAsyncIterator[pyarrow.Int16Array | None]
+    /// PyAsyncIterator[pyarrow.Int16Array | pyarrow.FloatArray | None]
     ///
     /// Examples
     /// --------
@@ -265,7 +403,7 @@ impl PyStream {
     fn __getitem__(
         &self,
         key: VoiceKey,
-    ) -> PyResult<Generic<'_, PyAsyncIterator, Option<ArrowArray<'_, Int16Array>>>> {
+    ) -> PyResult<Generic<'_, PyAsyncIterator, Option<ArrowArray<'_, PcmArray>>>> {
         let tx = self.try_tx()?;
         let rx = tx.subscribe();
         let stream = BroadcastStream::new(rx)
@@ -279,6 +417,172 @@ impl PyStream {
 
         Ok(Generic::new(PyAsyncIterator::new_in_raw(stream)))
     }
+
+    /// Return an async iterator over contiguous PCM for a specific key.
+    ///
+    /// Parameters
+    /// ----------
+    /// key : VoiceKey
+    ///     The user/ssrc key to follow.
+    /// fill_silence : bool, optional
+    ///     If True, ticks where the key sent no audio, and ticks the sink
+    ///     dropped, are zero-filled once the key has first spoken, giving a
+    ///     gapless timeline. If False, chunks only contain received audio and
+    ///     end early at each silence.
+    /// chunk_ms : int, optional
+    ///     Length of each chunk in milliseconds. Must be a positive multiple
+    ///     of 20.
+    ///
+    /// Returns
+    /// -------
+    /// PyAsyncIterator[AudioChunk]
+    ///
+    /// Examples
+    /// --------
+    /// ```python
+    /// key = receive.VoiceKey.User(user_id)
+    /// async with sink.stream() as stream:
+    ///     async for chunk in stream.user_stream(key, chunk_ms=200):
+    ///         transcriber.feed(chunk.pcm, chunk.timestamp)
+    /// ```
+    #[pyo3(signature = (key, *, fill_silence = true, chunk_ms = 20))]
+    fn user_stream(
+        &self,
+        key: VoiceKey,
+        fill_silence: bool,
+        chunk_ms: u64,
+    ) -> PyResult<Generic<'_, PyAsyncIterator, AudioChunk>> {
+        let mut chunker = UserChunker::new(key, fill_silence, chunk_ms)?;
+        let mut rx = BroadcastStream::new(self.try_tx()?.subscribe());
+        let s = stream! {
+            while let Some(tick) = rx.next().await {
+                // Lagged ticks show up as a jump in tick indices.
+                let Ok(tick) = tick else {
+                    continue;
+                };
+                for chunk in chunker.push(&tick) {
+                    yield Python::attach(|py| chunk.into_py_any(py));
+                }
+            }
+            if let Some(chunk) = chunker.finish() {
+                yield Python::attach(|py| chunk.into_py_any(py));
+            }
+        };
+        Ok(Generic::new(PyAsyncIterator::new_in_raw(s)))
+    }
+
+    /// Return an async iterator over speech segments from every source.
+    ///
+    /// Segments are cut from the `vad` column: one starts when a source's
+    /// speech probability reaches `threshold` and ends once it has stayed
+    /// below it for `post_padding_ms`. The sink must be created with
+    /// `vad=True`.
+    ///
+    /// Parameters
+    /// ----------
+    /// threshold : float, optional
+    ///     Speech probability, between 0 and 1, that counts as speech.
+    /// pre_padding_ms : int, optional
+    ///     Audio kept before the onset.
+    /// post_padding_ms : int, optional
+    ///     Quiet audio kept after the last speech; also how long a pause must
+    ///     last to end the segment.
+    /// min_duration_ms : int, optional
+    ///     Segments with less speech than this, excluding padding, are dropped.
+    ///
+    /// Returns
+    /// -------
+    /// PyAsyncIterator[tuple[VoiceKey, datetime.datetime, datetime.datetime, pyarrow.Int16Array | pyarrow.FloatArray]]
+    ///     `(key, start, end, pcm)` for each segment, in the sink's PCM format.
+    ///
+    /// Raises
+    /// ------
+    /// RuntimeError
+    ///     If the sink was created without `vad=True`.
+    ///
+    /// Examples
+    /// --------
+    /// ```python
+    /// sink = receive.StreamSink(vad=True)
+    /// ...
+    /// async with sink.stream() as stream:
+    ///     async for key, start, end, pcm in stream.utterances():
+    ///         await transcribe(key, pcm)
+    /// ```
+    #[pyo3(signature = (
+        *,
+        threshold = 0.5,
+        pre_padding_ms = 200,
+        post_padding_ms = 500,
+        min_duration_ms = 300
+    ))]
+    fn utterances(
+        &self,
+        threshold: f32,
+        pre_padding_ms: u64,
+        post_padding_ms: u64,
+        min_duration_ms: u64,
+    ) -> PyResult<Generic<'_, PyAsyncIterator, UtteranceTuple<'_>>> {
+        require_vad(self.vad)?;
+        let mut segmenter = Segmenter::new(UtteranceSettings::new(
+            threshold,
+            pre_padding_ms,
+            post_padding_ms,
+            min_duration_ms,
+        )?);
+        let mut rx = BroadcastStream::new(self.try_tx()?.subscribe());
+        let s = stream! {
+            while let Some(tick) = rx.next().await {
+                let Ok(tick) = tick else {
+                    continue;
+                };
+                for utterance in segmenter.push(&tick) {
+                    yield Python::attach(|py| utterance.into_py_any(py));
+                }
+            }
+            for utterance in segmenter.finish() {
+                yield Python::attach(|py| utterance.into_py_any(py));
+            }
+        };
+        Ok(Generic::new(PyAsyncIterator::new_in_raw(s)))
+    }
+}
+
+impl StreamSink {
+    /// Subscribe a native consumer, holding a stream permit while it lives.
+    pub(crate) fn subscribe(
+        &self,
+    ) -> PyResult<(
+        PcmFormat,
+        broadcast::Receiver<Arc<VoiceTickBatch>>,
+        OwnedSemaphorePermit,
+    )> {
+        let permit = self.sem.clone().try_acquire_owned().map_err(|_| {
+            pyo3::exceptions::PyRuntimeError::new_err("Failed to acquire stream permit")
+        })?;
+        let tx = self.weak_tx.upgrade().ok_or_else(|| {
+            pyo3::exceptions::PyRuntimeError::new_err("StreamSink has been closed")
+        })?;
+        Ok((self.format, tx.subscribe(), permit))
+    }
+
+    /// Whether the sink scores voice activity into the `vad` column.
+    pub(crate) fn scores_vad(&self) -> bool {
+        self.vad
+    }
+}
+
+/// Subscribe to identity changes. The stream ends once the handler, which
+/// holds the only strong sender, is dropped.
+fn identity_stream(
+    identity_tx: &broadcast::WeakSender<(u32, u64)>,
+) -> impl Stream<Item = (u32, u64)> + Send + 'static {
+    let rx = identity_tx
+        .upgrade()
+        .map(|tx| BroadcastStream::new(tx.subscribe()));
+    futures::stream::iter(rx)
+        .flatten()
+        .filter_map(|r| async move { r.ok() })
 }
 
 impl PyStream {
@@ -304,3 +608,21 @@ impl Drop for PyStream {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn identity_changes_end_when_the_handler_is_dropped() {
+        let (identity_tx, _) = broadcast::channel(IDENTITY_CHANGES_CAPACITY);
+        let weak = identity_tx.downgrade();
+        let mut changes = Box::pin(identity_stream(&weak));
+        identity_tx.send((9, 4)).unwrap();
+        drop(identity_tx);
+
+        assert_eq!(changes.next().await, Some((9, 4)));
+        assert_eq!(changes.next().await, None);
+        assert_eq!(Box::pin(identity_stream(&weak)).next().await, None);
+    }
+}
@@ -1,10 +1,16 @@
-use crate::model::{ArrowArray, ArrowRecordBatch};
+use crate::model::{ArrowArray, ArrowRecordBatch, PcmArray};
+use crate::receive::filter::KeyFilter;
+use crate::receive::format::{PcmConverter, PcmDtype, to_i16};
 use crate::receive::identity::VoiceIdentityResolver;
+use crate::receive::vad::VoiceActivityDetector;
+use arrow::array::AsArray;
 use arrow::array::{
-    ArrayRef, BooleanArray, Int16Array, Int16Builder, ListArray, ListBuilder, RecordBatch,
-    UInt8Array, UInt64Array,
+    ArrayBuilder, ArrayRef, BooleanArray, Float32Array, Float32Builder, Int16Builder, ListArray,
+    ListBuilder, RecordBatch, TimestampMicrosecondArray, UInt8Array, UInt16Array, UInt32Array,
+    UInt64Array,
 };
-use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::compute::take;
+use arrow::datatypes::{DataType, Field, Float32Type, Schema, SchemaRef, TimeUnit};
 use pyo3::types::PyInt;
 use pyo3::{Bound, PyResult, Python, pyclass, pymethods};
 use pyo3_arrow::{PyArray, PyRecordBatch};
@@ -12,9 +18,11 @@ use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pyclass_complex_enum, gen
 use songbird::events::context_data::VoiceTick as SongbirdVoiceTick;
 use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 const KEY_KIND_USER: u8 = 0;
 const KEY_KIND_UNKNOWN_SSRC: u8 = 1;
+pub(crate) const TIMESTAMP_TZ: &str = "UTC";
 
 #[gen_stub_pyclass_complex_enum]
 #[pyclass(module = "discord.ext.songbird.native.receive", frozen, from_py_object)]
@@ -35,18 +43,61 @@ pub enum VoiceKey {
     Unknown(u32),
 }
 
+/// RTP header fields of the packet clocked out for a row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RtpInfo {
+    pub sequence: u16,
+    pub timestamp: u32,
+}
+
 #[derive(Clone)]
 pub struct VoiceTickBatch {
+    /// Index of this tick since the sink was created.
+    tick: u64,
+    /// Wall-clock time the tick was received.
+    timestamp: SystemTime,
     batch: RecordBatch,
     key_kind: UInt8Array,
     key_id: UInt64Array,
     speaking: BooleanArray,
     pcm: ListArray,
+    vad: Float32Array,
+    rtp: Vec<Option<RtpInfo>>,
 }
 
 struct VoiceTickRow<'a> {
     key: VoiceKey,
     pcm: Option<&'a [i16]>,
+    rtp: Option<RtpInfo>,
+    vad: f32,
+    lost_packets: u64,
+}
+
+impl VoiceTickRow<'_> {
+    /// Audio without a packet was synthesised by the decoder to cover a loss.
+    fn concealed(&self) -> bool {
+        self.pcm.is_some() && self.rtp.is_none()
+    }
+}
+
+/// Running count of concealed packets for each source.
+#[derive(Default)]
+pub struct PacketLossCounter {
+    lost: HashMap<VoiceKey, u64>,
+}
+
+impl PacketLossCounter {
+    /// Count this tick for `key`, returning its total so far.
+    fn record(&mut self, key: &VoiceKey, concealed: bool) -> u64 {
+        let lost = self.lost.entry(key.clone()).or_default();
+        *lost += u64::from(concealed);
+        *lost
+    }
+
+    /// Drop counts for sources no longer present in the call.
+    fn retain(&mut self, mut present: impl FnMut(&VoiceKey) -> bool) {
+        self.lost.retain(|key, _| present(key));
+    }
 }
 
 #[gen_stub_pyclass]
@@ -145,13 +196,13 @@ impl VoiceTick {
     ///
     /// Returns
     /// -------
-    /// pyarrow.Int16Array | None
+    /// pyarrow.Int16Array | pyarrow.FloatArray | None
     ///     PCM when speaking, otherwise None.
     pub fn get<'py>(
         &self,
         py: Python<'py>,
         key: &VoiceKey,
-    ) -> PyResult<Option<ArrowArray<'py, Int16Array>>> {
+    ) -> PyResult<Option<ArrowArray<'py, PcmArray>>> {
         self.inner.get(py, key)
     }
 
@@ -213,26 +264,56 @@ impl VoiceTick {
 }
 
 impl VoiceTickBatch {
+    /// Build a batch from a songbird tick. Without a converter, PCM is kept
+    /// as decoded (48 kHz stereo `int16`); without a detector, `vad` is zero;
+    /// without a loss counter, `lost_packets` is zero; without a filter,
+    /// every source gets a row.
     pub fn from_parts(
         tick: &SongbirdVoiceTick,
+        index: u64,
         identities: &impl VoiceIdentityResolver,
+        converter: Option<&mut PcmConverter>,
+        vad: Option<&mut VoiceActivityDetector>,
+        loss: Option<&mut PacketLossCounter>,
+        filter: Option<&KeyFilter>,
     ) -> Arc<Self> {
+        let allows = |ssrc: &u32| {
+            filter.is_none_or(|filter| filter.allows(&key_for_ssrc(*ssrc, identities)))
+        };
         Self::from_ssrc_rows(
             tick.speaking
                 .iter()
-                .map(|(ssrc, data)| (*ssrc, data.decoded_voice.as_deref())),
-            tick.silent.iter().copied(),
+                .filter(|(ssrc, _)| allows(ssrc))
+                .map(|(ssrc, data)| {
+                    let rtp = data.packet.as_ref().map(|packet| {
+                        let packet = packet.rtp();
+                        RtpInfo {
+                            sequence: packet.get_sequence().into(),
+                            timestamp: packet.get_timestamp().into(),
+                        }
+                    });
+                    (*ssrc, data.decoded_voice.as_deref(), rtp)
+                }),
+            tick.silent.iter().copied().filter(|ssrc| allows(ssrc)),
+            index,
             identities,
+            converter,
+            vad,
+            loss,
         )
     }
 
-    fn from_ssrc_rows<'a, S, I>(
+    pub(crate) fn from_ssrc_rows<'a, S, I>(
         speaking: S,
         silent: I,
+        index: u64,
         identities: &impl VoiceIdentityResolver,
+        converter: Option<&mut PcmConverter>,
+        vad: Option<&mut VoiceActivityDetector>,
+        loss: Option<&mut PacketLossCounter>,
     ) -> Arc<Self>
     where
-        S: IntoIterator<Item = (u32, Option<&'a [i16]>)>,
+        S: IntoIterator<Item = (u32, Option<&'a [i16]>, Option<RtpInfo>)>,
         I: IntoIterator<Item = u32>,
     {
         let speaking = speaking.into_iter();
@@ -241,39 +322,61 @@ impl VoiceTickBatch {
         let silent_capacity = silent.size_hint().0;
         let mut rows_by_key = HashMap::with_capacity(speaking_capacity + silent_capacity);
 
-        for (ssrc, decoded) in speaking {
+        for (ssrc, decoded, rtp) in speaking {
             let key = key_for_ssrc(ssrc, identities);
             if let Some(decoded) = decoded {
-                let entry = rows_by_key.entry(key).or_insert(None);
-                if entry.is_none() {
-                    *entry = Some(decoded);
+                let entry = rows_by_key.entry(key).or_insert((None, None));
+                if entry.0.is_none() {
+                    *entry = (Some(decoded), rtp);
                 }
             } else {
-                rows_by_key.entry(key).or_insert(None);
+                rows_by_key.entry(key).or_insert((None, None));
             }
         }
 
         for ssrc in silent {
             let key = key_for_ssrc(ssrc, identities);
-            rows_by_key.entry(key).or_insert(None);
+            rows_by_key.entry(key).or_insert((None, None));
         }
 
-        let rows = rows_by_key
+        let mut rows: Vec<_> = rows_by_key
             .into_iter()
-            .map(|(key, pcm)| VoiceTickRow { key, pcm })
+            .map(|(key, (pcm, rtp))| VoiceTickRow {
+                key,
+                pcm,
+                rtp,
+                vad: 0.0,
+                lost_packets: 0,
+            })
             .collect();
 
-        Arc::new(Self::from_rows(rows))
+        if let Some(vad) = vad {
+            vad.retain(|key| rows.iter().any(|row| row.key == *key));
+            for row in &mut rows {
+                match row.pcm {
+                    Some(pcm) => row.vad = vad.detect(&row.key, pcm),
+                    None => vad.silence(&row.key),
+                }
+            }
+        }
+
+        if let Some(loss) = loss {
+            loss.retain(|key| rows.iter().any(|row| row.key == *key));
+            for row in &mut rows {
+                row.lost_packets = loss.record(&row.key, row.concealed());
+            }
+        }
+
+        Arc::new(Self::from_rows(rows, index, converter))
     }
 
-    fn from_rows(mut rows: Vec<VoiceTickRow<'_>>) -> Self {
+    fn from_rows(
+        mut rows: Vec<VoiceTickRow<'_>>,
+        index: u64,
+        converter: Option<&mut PcmConverter>,
+    ) -> Self {
         rows.sort_by_key(|row| (key_kind(&row.key), key_id(&row.key)));
-
-        let total_samples = rows
-            .iter()
-            .filter_map(|row| row.pcm)
-            .map(<[i16]>::len)
-            .sum();
+        let timestamp = SystemTime::now();
 
         let key_kind = UInt8Array::from(
             rows.iter()
@@ -283,38 +386,115 @@ impl VoiceTickBatch {
         let key_id = UInt64Array::from(rows.iter().map(|row| key_id(&row.key)).collect::<Vec<_>>());
         let speaking =
             BooleanArray::from(rows.iter().map(|row| row.pcm.is_some()).collect::<Vec<_>>());
+        let vad = Float32Array::from(rows.iter().map(|row| row.vad).collect::<Vec<_>>());
+        let rtp_sequence = UInt16Array::from(
+            rows.iter()
+                .map(|row| row.rtp.map(|rtp| rtp.sequence))
+                .collect::<Vec<_>>(),
+        );
+        let rtp_timestamp = UInt32Array::from(
+            rows.iter()
+                .map(|row| row.rtp.map(|rtp| rtp.timestamp))
+                .collect::<Vec<_>>(),
+        );
+        let concealed =
+            BooleanArray::from(rows.iter().map(VoiceTickRow::concealed).collect::<Vec<_>>());
+        let lost_packets =
+            UInt64Array::from(rows.iter().map(|row| row.lost_packets).collect::<Vec<_>>());
 
-        let values = Int16Builder::with_capacity(total_samples);
-        let item_field = Arc::new(Field::new_list_field(DataType::Int16, false));
-        let mut pcm_builder = ListBuilder::with_capacity(values, rows.len()).with_field(item_field);
-        for row in &rows {
-            if let Some(pcm) = row.pcm {
-                pcm_builder.values().append_slice(pcm);
+        let (pcm, dtype) = match converter {
+            Some(converter) if !converter.format().is_native() => {
+                converter.retain(|key| rows.iter().any(|row| row.pcm.is_some() && row.key == *key));
+                let converted: Vec<_> = rows
+                    .iter()
+                    .map(|row| row.pcm.map(|pcm| converter.convert(&row.key, pcm)))
+                    .collect();
+                let dtype = converter.format().dtype;
+                let pcm = match dtype {
+                    PcmDtype::Int16 => list_array(
+                        Int16Builder::new(),
+                        DataType::Int16,
+                        converted.iter().map(|pcm| pcm.as_ref()),
+                        |values, pcm| values.extend(pcm.iter().copied().map(to_i16).map(Some)),
+                    ),
+                    PcmDtype::Float32 => list_array(
+                        Float32Builder::new(),
+                        DataType::Float32,
+                        converted.iter().map(|pcm| pcm.as_ref()),
+                        |values, pcm| values.append_slice(pcm),
+                    ),
+                };
+                (pcm, dtype)
             }
-            pcm_builder.append(true);
-        }
-        let pcm = pcm_builder.finish();
+            _ => {
+                let total_samples = rows
+                    .iter()
+                    .filter_map(|row| row.pcm)
+                    .map(<[i16]>::len)
+                    .sum();
+                let pcm = list_array(
+                    Int16Builder::with_capacity(total_samples),
+                    DataType::Int16,
+                    rows.iter().map(|row| row.pcm),
+                    |values, pcm| values.append_slice(pcm),
+                );
+                (pcm, PcmDtype::Int16)
+            }
+        };
 
+        let micros = timestamp_micros(timestamp);
         let batch = RecordBatch::try_new(
-            voice_tick_schema(),
+            voice_tick_schema(dtype),
             vec![
                 Arc::new(key_kind.clone()) as ArrayRef,
                 Arc::new(key_id.clone()) as ArrayRef,
                 Arc::new(speaking.clone()) as ArrayRef,
                 Arc::new(pcm.clone()) as ArrayRef,
+                Arc::new(vad.clone()) as ArrayRef,
+                Arc::new(UInt64Array::from(vec![index; rows.len()])) as ArrayRef,
+                Arc::new(
+                    TimestampMicrosecondArray::from(vec![micros; rows.len()])
+                        .with_timezone(TIMESTAMP_TZ),
+                ) as ArrayRef,
+                Arc::new(rtp_sequence) as ArrayRef,
+                Arc::new(rtp_timestamp) as ArrayRef,
+                Arc::new(concealed) as ArrayRef,
+                Arc::new(lost_packets) as ArrayRef,
             ],
         )
         .expect("VoiceTickBatch columns must match the fixed schema");
 
         Self {
+            tick: index,
+            timestamp,
             batch,
             key_kind,
             key_id,
             speaking,
             pcm,
+            vad,
+            rtp: rows.iter().map(|row| row.rtp).collect(),
         }
     }
 
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+
+    /// Speech probability for `key`, if it has a row in this tick.
+    pub fn vad(&self, key: &VoiceKey) -> Option<f32> {
+        self.find_row(key).map(|row| self.vad.value(row))
+    }
+
+    /// RTP header of the packet clocked out for `key`, if one arrived.
+    pub fn rtp(&self, key: &VoiceKey) -> Option<RtpInfo> {
+        self.find_row(key).and_then(|row| self.rtp[row])
+    }
+
     pub fn record_batch(&self) -> RecordBatch {
         self.batch.clone()
     }
@@ -329,7 +509,7 @@ impl VoiceTickBatch {
         &self,
         py: Python<'py>,
         key: &VoiceKey,
-    ) -> PyResult<Option<ArrowArray<'py, Int16Array>>> {
+    ) -> PyResult<Option<ArrowArray<'py, PcmArray>>> {
         if let Some(data) = self.get_array_ref(key) {
             Ok(Some(PyArray::from_array_ref(data).into_arro3(py)?.into()))
         } else {
@@ -348,13 +528,13 @@ impl VoiceTickBatch {
             .is_some_and(|row| !self.speaking.value(row))
     }
 
-    fn all_keys(&self) -> HashSet<VoiceKey> {
+    pub(crate) fn all_keys(&self) -> HashSet<VoiceKey> {
         (0..self.batch.num_rows())
             .map(|row| self.key_at(row))
             .collect()
     }
 
-    fn speaking_keys(&self) -> HashSet<VoiceKey> {
+    pub(crate) fn speaking_keys(&self) -> HashSet<VoiceKey> {
         (0..self.batch.num_rows())
             .filter(|row| self.speaking.value(*row))
             .map(|row| self.key_at(row))
@@ -368,6 +548,75 @@ impl VoiceTickBatch {
             .collect()
     }
 
+    /// Attribute the `Unknown(ssrc)` row to `user_id`, keeping rows sorted by
+    /// key. Returns `None` if there is no such row, or if the user already
+    /// has a row of their own.
+    pub(crate) fn rekey(&self, ssrc: u32, user_id: u64) -> Option<Arc<Self>> {
+        let row = self.find_row(&VoiceKey::Unknown(ssrc))?;
+        if self.find_row(&VoiceKey::User(user_id)).is_some() {
+            return None;
+        }
+        let mut keys: Vec<_> = (0..self.batch.num_rows())
+            .map(|row| (self.key_kind.value(row), self.key_id.value(row)))
+            .collect();
+        keys[row] = (KEY_KIND_USER, user_id);
+        let mut order: Vec<u32> = (0..keys.len() as u32).collect();
+        order.sort_by_key(|row| keys[*row as usize]);
+        Some(self.select(&keys, order))
+    }
+
+    /// Drop the row of `key`. Returns `None` if there is no such row.
+    pub(crate) fn without(&self, key: &VoiceKey) -> Option<Arc<Self>> {
+        let row = self.find_row(key)?;
+        let keys: Vec<_> = (0..self.batch.num_rows())
+            .map(|row| (self.key_kind.value(row), self.key_id.value(row)))
+            .collect();
+        let order = (0..keys.len() as u32)
+            .filter(|other| *other as usize != row)
+            .collect();
+        Some(self.select(&keys, order))
+    }
+
+    /// Build a tick from the rows at `order`, with `keys` giving each
+    /// original row's key.
+    fn select(&self, keys: &[(u8, u64)], order: Vec<u32>) -> Arc<Self> {
+        let key_kind = UInt8Array::from_iter_values(order.iter().map(|row| keys[*row as usize].0));
+        let key_id = UInt64Array::from_iter_values(order.iter().map(|row| keys[*row as usize].1));
+        let order = UInt32Array::from(order);
+        let mut columns = Vec::with_capacity(self.batch.num_columns());
+        for (field, column) in self
+            .batch
+            .schema()
+            .fields()
+            .iter()
+            .zip(self.batch.columns())
+        {
+            columns.push(match field.name().as_str() {
+                "key_kind" => Arc::new(key_kind.clone()) as ArrayRef,
+                "key_id" => Arc::new(key_id.clone()) as ArrayRef,
+                _ => take(column, &order, None).expect("row indices are in bounds"),
+            });
+        }
+        let batch = RecordBatch::try_new(self.batch.schema(), columns)
+            .expect("selected columns must match the original schema");
+
+        Arc::new(Self {
+            tick: self.tick,
+            timestamp: self.timestamp,
+            key_kind,
+            key_id,
+            speaking: batch["speaking"].as_boolean().clone(),
+            pcm: batch["pcm"].as_list::<i32>().clone(),
+            vad: batch["vad"].as_primitive::<Float32Type>().clone(),
+            rtp: order
+                .values()
+                .iter()
+                .map(|row| self.rtp[*row as usize])
+                .collect(),
+            batch,
+        })
+    }
+
     fn find_row(&self, key: &VoiceKey) -> Option<usize> {
         let kind = key_kind(key);
         let id = key_id(key);
@@ -389,21 +638,130 @@ impl VoiceTickBatch {
     }
 }
 
-fn voice_tick_schema() -> SchemaRef {
-    static SCHEMA: OnceLock<SchemaRef> = OnceLock::new();
-    SCHEMA
+#[cfg(test)]
+impl VoiceTickBatch {
+    pub(crate) fn from_test_row(
+        tick: u64,
+        key: VoiceKey,
+        pcm: Option<&[i16]>,
+        rtp: Option<RtpInfo>,
+        vad: f32,
+    ) -> Arc<Self> {
+        Arc::new(Self::from_rows(
+            vec![VoiceTickRow {
+                key,
+                pcm,
+                rtp,
+                vad,
+                lost_packets: 0,
+            }],
+            tick,
+            None,
+        ))
+    }
+
+    pub(crate) fn from_test_rows(tick: u64, rows: Vec<(VoiceKey, Vec<i16>)>) -> Arc<Self> {
+        let rows = rows
+            .iter()
+            .map(|(key, pcm)| VoiceTickRow {
+                key: key.clone(),
+                pcm: Some(pcm),
+                rtp: None,
+                vad: 0.0,
+                lost_packets: 0,
+            })
+            .collect();
+        Arc::new(Self::from_rows(rows, tick, None))
+    }
+
+    /// A tick in which each source sends `samples` copies of one value.
+    pub(crate) fn from_test_constants(
+        tick: u64,
+        samples: usize,
+        rows: &[(VoiceKey, i16)],
+    ) -> Arc<Self> {
+        Self::from_test_rows(
+            tick,
+            rows.iter()
+                .map(|(key, sample)| (key.clone(), vec![*sample; samples]))
+                .collect(),
+        )
+    }
+}
+
+/// Build a list column with one entry per row; rows without PCM get an
+/// empty list.
+fn list_array<'a, B, T: ?Sized + 'a>(
+    values: B,
+    data_type: DataType,
+    rows: impl ExactSizeIterator<Item = Option<&'a T>>,
+    mut append: impl FnMut(&mut B, &T),
+) -> ListArray
+where
+    B: ArrayBuilder,
+{
+    let item_field = Arc::new(Field::new_list_field(data_type, false));
+    let mut builder = ListBuilder::with_capacity(values, rows.len()).with_field(item_field);
+    for pcm in rows {
+        if let Some(pcm) = pcm {
+            append(builder.values(), pcm);
+        }
+        builder.append(true);
+    }
+    builder.finish()
+}
+
+/// Re-key every tick holding an `Unknown(ssrc)` row once `ssrc` is known to
+/// belong to `user_id`.
+pub(crate) fn rekey_ticks<'a>(
+    ticks: impl IntoIterator<Item = &'a mut Arc<VoiceTickBatch>>,
+    ssrc: u32,
+    user_id: u64,
+) {
+    for tick in ticks {
+        if let Some(rekeyed) = tick.rekey(ssrc, user_id) {
+            *tick = rekeyed;
+        }
+    }
+}
+
+pub(crate) fn voice_tick_schema(dtype: PcmDtype) -> SchemaRef {
+    static INT16: OnceLock<SchemaRef> = OnceLock::new();
+    static FLOAT32: OnceLock<SchemaRef> = OnceLock::new();
+    let (schema, data_type) = match dtype {
+        PcmDtype::Int16 => (&INT16, DataType::Int16),
+        PcmDtype::Float32 => (&FLOAT32, DataType::Float32),
+    };
+    schema
         .get_or_init(|| {
             Arc::new(Schema::new(vec![
                 Field::new("key_kind", DataType::UInt8, false),
                 Field::new("key_id", DataType::UInt64, false),
                 Field::new("speaking", DataType::Boolean, false),
-                Field::new_list("pcm", Field::new_list_field(DataType::Int16, false), false),
+                Field::new_list("pcm", Field::new_list_field(data_type, false), false),
+                Field::new("vad", DataType::Float32, false),
+                Field::new("tick", DataType::UInt64, false),
+                Field::new(
+                    "timestamp",
+                    DataType::Timestamp(TimeUnit::Microsecond, Some(TIMESTAMP_TZ.into())),
+                    false,
+                ),
+                Field::new("rtp_sequence", DataType::UInt16, true),
+                Field::new("rtp_timestamp", DataType::UInt32, true),
+                Field::new("concealed", DataType::Boolean, false),
+                Field::new("lost_packets", DataType::UInt64, false),
             ]))
         })
         .clone()
 }
 
-fn key_for_ssrc(ssrc: u32, identities: &impl VoiceIdentityResolver) -> VoiceKey {
+/// Microseconds since the Unix epoch, for `timestamp` columns.
+pub(crate) fn timestamp_micros(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_micros() as i64)
+}
+
+pub(crate) fn key_for_ssrc(ssrc: u32, identities: &impl VoiceIdentityResolver) -> VoiceKey {
     if let Some(user_id) = identities.user_id_for_ssrc(ssrc) {
         VoiceKey::User(user_id)
     } else {
@@ -411,14 +769,14 @@ fn key_for_ssrc(ssrc: u32, identities: &impl VoiceIdentityResolver) -> VoiceKey
     }
 }
 
-fn key_kind(key: &VoiceKey) -> u8 {
+pub(crate) fn key_kind(key: &VoiceKey) -> u8 {
     match key {
         VoiceKey::User(_) => KEY_KIND_USER,
         VoiceKey::Unknown(_) => KEY_KIND_UNKNOWN_SSRC,
     }
 }
 
-fn key_id(key: &VoiceKey) -> u64 {
+pub(crate) fn key_id(key: &VoiceKey) -> u64 {
     match key {
         VoiceKey::User(user_id) => *user_id,
         VoiceKey::Unknown(ssrc) => u64::from(*ssrc),
@@ -428,8 +786,10 @@ fn key_id(key: &VoiceKey) -> u64 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use arrow::array::AsArray;
-    use arrow::datatypes::Int16Type;
+    use arrow::array::{Array, AsArray};
+    use arrow::datatypes::{
+        Int16Type, TimestampMicrosecondType, UInt16Type, UInt32Type, UInt64Type,
+    };
 
     fn int16_values(array: &ArrayRef) -> Vec<i16> {
         array
@@ -444,20 +804,33 @@ mod tests {
     fn builds_expected_columnar_batch() {
         let user_pcm = [1, 2, 3, 4];
         let unknown_pcm = [9, 8];
-        let batch = VoiceTickBatch::from_rows(vec![
-            VoiceTickRow {
-                key: VoiceKey::Unknown(42),
-                pcm: Some(&unknown_pcm),
-            },
-            VoiceTickRow {
-                key: VoiceKey::User(7),
-                pcm: Some(&user_pcm),
-            },
-            VoiceTickRow {
-                key: VoiceKey::User(9),
-                pcm: None,
-            },
-        ]);
+        let batch = VoiceTickBatch::from_rows(
+            vec![
+                VoiceTickRow {
+                    key: VoiceKey::Unknown(42),
+                    pcm: Some(&unknown_pcm),
+                    rtp: None,
+                    vad: 0.0,
+                    lost_packets: 0,
+                },
+                VoiceTickRow {
+                    key: VoiceKey::User(7),
+                    pcm: Some(&user_pcm),
+                    rtp: None,
+                    vad: 0.0,
+                    lost_packets: 0,
+                },
+                VoiceTickRow {
+                    key: VoiceKey::User(9),
+                    pcm: None,
+                    rtp: None,
+                    vad: 0.0,
+                    lost_packets: 0,
+                },
+            ],
+            12,
+            None,
+        );
 
         assert_eq!(
             batch
@@ -467,7 +840,19 @@ mod tests {
                 .iter()
                 .map(|field| field.name().as_str())
                 .collect::<Vec<_>>(),
-            ["key_kind", "key_id", "speaking", "pcm"]
+            [
+                "key_kind",
+                "key_id",
+                "speaking",
+                "pcm",
+                "vad",
+                "tick",
+                "timestamp",
+                "rtp_sequence",
+                "rtp_timestamp",
+                "concealed",
+                "lost_packets"
+            ]
         );
         assert_eq!(
             batch.key_kind.values(),
@@ -479,22 +864,37 @@ mod tests {
         assert!(batch.speaking.value(2));
         assert_eq!(batch.pcm.value_offsets(), &[0, 4, 4, 6]);
         assert_eq!(int16_values(batch.pcm.values()), vec![1, 2, 3, 4, 9, 8]);
+        let record_batch = batch.record_batch();
+        let ticks = record_batch["tick"].as_primitive::<UInt64Type>();
+        assert_eq!(ticks.values(), &[12, 12, 12]);
+        let timestamps = record_batch["timestamp"].as_primitive::<TimestampMicrosecondType>();
+        assert!(timestamps.values().iter().all(|micros| *micros > 0));
     }
 
     #[test]
     fn per_key_helper_returns_zero_copy_pcm_slice() {
         let user_pcm = [1, 2, 3, 4];
         let unknown_pcm = [9, 8];
-        let batch = VoiceTickBatch::from_rows(vec![
-            VoiceTickRow {
-                key: VoiceKey::User(7),
-                pcm: Some(&user_pcm),
-            },
-            VoiceTickRow {
-                key: VoiceKey::Unknown(42),
-                pcm: Some(&unknown_pcm),
-            },
-        ]);
+        let batch = VoiceTickBatch::from_rows(
+            vec![
+                VoiceTickRow {
+                    key: VoiceKey::User(7),
+                    pcm: Some(&user_pcm),
+                    rtp: None,
+                    vad: 0.0,
+                    lost_packets: 0,
+                },
+                VoiceTickRow {
+                    key: VoiceKey::Unknown(42),
+                    pcm: Some(&unknown_pcm),
+                    rtp: None,
+                    vad: 0.0,
+                    lost_packets: 0,
+                },
+            ],
+            0,
+            None,
+        );
 
         let base = batch.pcm.values().as_primitive::<Int16Type>();
         let base_ptr = base.values().inner().as_ptr();
@@ -519,10 +919,17 @@ mod tests {
     #[test]
     fn clone_keeps_payload_buffer_shared() {
         let pcm = [1, 2, 3, 4];
-        let batch = VoiceTickBatch::from_rows(vec![VoiceTickRow {
-            key: VoiceKey::User(7),
-            pcm: Some(&pcm),
-        }]);
+        let batch = VoiceTickBatch::from_rows(
+            vec![VoiceTickRow {
+                key: VoiceKey::User(7),
+                pcm: Some(&pcm),
+                rtp: None,
+                vad: 0.0,
+                lost_packets: 0,
+            }],
+            0,
+            None,
+        );
         let cloned = batch.clone();
 
         let original_values = batch.pcm.values().as_primitive::<Int16Type>();
@@ -538,7 +945,15 @@ mod tests {
         let identities = crate::receive::identity::VoiceIdentityMap::default();
         identities.insert(42, 7);
         let pcm = [1, 2, 3, 4];
-        let batch = VoiceTickBatch::from_ssrc_rows([(42, Some(pcm.as_slice()))], [99], &identities);
+        let batch = VoiceTickBatch::from_ssrc_rows(
+            [(42, Some(pcm.as_slice()), None)],
+            [99],
+            0,
+            &identities,
+            None,
+            None,
+            None,
+        );
 
         assert_eq!(batch.speaking_keys(), HashSet::from([VoiceKey::User(7)]));
         assert_eq!(batch.silent_keys(), HashSet::from([VoiceKey::Unknown(99)]));
@@ -552,14 +967,22 @@ mod tests {
         identities.insert(44, 7);
         let first_pcm = [1, 2, 3, 4];
         let second_pcm = [9, 8];
+        let first_rtp = RtpInfo {
+            sequence: 10,
+            timestamp: 960,
+        };
         let batch = VoiceTickBatch::from_ssrc_rows(
             [
-                (42, None),
-                (43, Some(first_pcm.as_slice())),
-                (44, Some(second_pcm.as_slice())),
+                (42, None, None),
+                (43, Some(first_pcm.as_slice()), Some(first_rtp)),
+                (44, Some(second_pcm.as_slice()), None),
             ],
             [42],
+            0,
             &identities,
+            None,
+            None,
+            None,
         );
 
         assert_eq!(batch.record_batch().num_rows(), 1);
@@ -569,6 +992,7 @@ mod tests {
             .get_array_ref(&VoiceKey::User(7))
             .expect("merged user PCM should be present");
         assert_eq!(int16_values(&pcm), first_pcm);
+        assert_eq!(batch.rtp(&VoiceKey::User(7)), Some(first_rtp));
     }
 
     #[test]
@@ -576,11 +1000,122 @@ mod tests {
         let identities = crate::receive::identity::VoiceIdentityMap::default();
         identities.insert(42, 7);
         identities.insert(43, 7);
-        let batch = VoiceTickBatch::from_ssrc_rows([(42, None)], [42, 43], &identities);
+        let batch = VoiceTickBatch::from_ssrc_rows(
+            [(42, None, None)],
+            [42, 43],
+            0,
+            &identities,
+            None,
+            None,
+            None,
+        );
 
         assert_eq!(batch.record_batch().num_rows(), 1);
         assert_eq!(batch.all_keys(), HashSet::from([VoiceKey::User(7)]));
         assert_eq!(batch.speaking_keys(), HashSet::new());
         assert_eq!(batch.silent_keys(), HashSet::from([VoiceKey::User(7)]));
     }
+
+    #[test]
+    fn ssrc_rows_expose_rtp_and_count_concealed_packets() {
+        let identities = crate::receive::identity::VoiceIdentityMap::default();
+        let pcm = [1, 2];
+        let rtp = RtpInfo {
+            sequence: 65_535,
+            timestamp: 48_000,
+        };
+        let mut loss = PacketLossCounter::default();
+        let mut build = |speaking: Option<RtpInfo>| {
+            VoiceTickBatch::from_ssrc_rows(
+                [(42, Some(pcm.as_slice()), speaking)],
+                [43],
+                0,
+                &identities,
+                None,
+                None,
+                Some(&mut loss),
+            )
+        };
+
+        build(None);
+        let received = build(Some(rtp));
+        let record_batch = received.record_batch();
+        let sequence = record_batch["rtp_sequence"].as_primitive::<UInt16Type>();
+        let timestamp = record_batch["rtp_timestamp"].as_primitive::<UInt32Type>();
+        assert_eq!(sequence.value(0), 65_535);
+        assert_eq!(timestamp.value(0), 48_000);
+        assert!(sequence.is_null(1) && timestamp.is_null(1));
+        assert!(!record_batch["concealed"].as_boolean().value(0));
+
+        let concealed = build(None);
+        let record_batch = concealed.record_batch();
+        let concealed_column = record_batch["concealed"].as_boolean();
+        assert!(concealed_column.value(0));
+        assert!(!concealed_column.value(1), "silent rows are not concealed");
+        assert_eq!(
+            record_batch["lost_packets"]
+                .as_primitive::<UInt64Type>()
+                .values(),
+            &[2, 0]
+        );
+    }
+
+    #[test]
+    fn rekey_attributes_unknown_rows_and_keeps_key_order() {
+        let identities = crate::receive::identity::VoiceIdentityMap::default();
+        identities.insert(10, 5);
+        let batch = VoiceTickBatch::from_ssrc_rows(
+            [
+                (10, Some([1, 1].as_slice()), None),
+                (3, Some([3, 3].as_slice()), None),
+            ],
+            [4],
+            2,
+            &identities,
+            None,
+            None,
+            None,
+        );
+        assert!(batch.rekey(99, 1).is_none());
+        assert!(batch.rekey(3, 5).is_none(), "user 5 already has a row");
+
+        let rekeyed = batch.rekey(3, 1).expect("ssrc 3 has an unknown row");
+        let record_batch = rekeyed.record_batch();
+        assert_eq!(
+            record_batch["key_kind"]
+                .as_primitive::<arrow::datatypes::UInt8Type>()
+                .values(),
+            &[0, 0, 1]
+        );
+        assert_eq!(
+            record_batch["key_id"].as_primitive::<UInt64Type>().values(),
+            &[1, 5, 4]
+        );
+        assert_eq!(
+            int16_values(&rekeyed.get_array_ref(&VoiceKey::User(1)).unwrap()),
+            vec![3, 3]
+        );
+        assert!(rekeyed.is_silent(&VoiceKey::Unknown(4)));
+        assert_eq!(rekeyed.tick(), 2);
+    }
+
+    #[test]
+    fn without_drops_only_the_given_row() {
+        let batch = VoiceTickBatch::from_test_rows(
+            3,
+            vec![
+                (VoiceKey::User(1), vec![1, 1]),
+                (VoiceKey::Unknown(4), vec![4, 4]),
+            ],
+        );
+        assert!(batch.without(&VoiceKey::User(2)).is_none());
+
+        let remaining = batch.without(&VoiceKey::Unknown(4)).unwrap();
+        assert_eq!(remaining.all_keys(), HashSet::from([VoiceKey::User(1)]));
+        assert_eq!(
+            int16_values(&remaining.get_array_ref(&VoiceKey::User(1)).unwrap()),
+            vec![1, 1]
+        );
+        assert_eq!(remaining.tick(), 3);
+    }
 }
@@ -0,0 +1,392 @@
+use crate::model::{ArrowArray, PcmArray};
+use crate::receive::format::{TICK_DURATION, concat_pcm, silence};
+use crate::receive::tick::{RtpInfo, VoiceKey, VoiceTickBatch};
+use arrow::array::{Array, ArrayRef};
+use arrow::datatypes::DataType;
+use pyo3::exceptions::PyValueError;
+use pyo3::{PyResult, Python, pyclass, pymethods};
+use pyo3_arrow::PyArray;
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use std::time::{Duration, SystemTime};
+
+/// RTP clock advance of one 20 ms Opus frame at 48 kHz.
+const RTP_TICK_SAMPLES: i64 = 960;
+
+#[gen_stub_pyclass]
+#[pyclass(
+    module = "discord.ext.songbird.native.receive",
+    frozen,
+    skip_from_py_object
+)]
+/// Contiguous PCM for one voice source, yielded by `user_stream()`.
+///
+/// Each chunk covers `tick_count` consecutive 20 ms ticks starting at `tick`.
+/// Ticks where the source sent no audio are zero-filled when the stream was
+/// created with `fill_silence=True`.
+pub struct AudioChunk {
+    key: VoiceKey,
+    pcm: ArrayRef,
+    tick: u64,
+    tick_count: u64,
+    timestamp: SystemTime,
+    filled_ticks: u64,
+    sequence_gaps: u64,
+    timestamp_gap: i64,
+    rtp: Option<RtpInfo>,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl AudioChunk {
+    /// Voice source this chunk belongs to.
+    ///
+    /// Returns
+    /// -------
+    /// VoiceKey
+    #[getter]
+    fn key(&self) -> VoiceKey {
+        self.key.clone()
+    }
+
+    /// Interleaved PCM in the sink's format.
+    ///
+    /// Returns
+    /// -------
+    /// pyarrow.Int16Array | pyarrow.FloatArray
+    #[getter]
+    fn pcm<'py>(&self, py: Python<'py>) -> PyResult<ArrowArray<'py, PcmArray>> {
+        Ok(PyArray::from_array_ref(self.pcm.clone())
+            .into_arro3(py)?
+            .into())
+    }
+
+    /// Index of the first tick in this chunk, counted from sink creation.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    #[getter]
+    fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Number of ticks in this chunk.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    #[getter]
+    fn tick_count(&self) -> u64 {
+        self.tick_count
+    }
+
+    /// Wall-clock time the first tick was received.
+    ///
+    /// Returns
+    /// -------
+    /// datetime.datetime
+    #[getter]
+    fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+
+    /// Duration of audio in this chunk.
+    ///
+    /// Returns
+    /// -------
+    /// datetime.timedelta
+    #[getter]
+    fn duration(&self) -> Duration {
+        TICK_DURATION * self.tick_count as u32
+    }
+
+    /// Number of ticks that were filled with silence.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    #[getter]
+    fn filled_ticks(&self) -> u64 {
+        self.filled_ticks
+    }
+
+    /// Number of RTP packets missing from the sequence within this chunk.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    #[getter]
+    fn sequence_gaps(&self) -> u64 {
+        self.sequence_gaps
+    }
+
+    /// RTP clock units (48 kHz samples) by which the sender's timestamps
+    /// diverged from the ticks they were played out on.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    ///
+    /// Notes
+    /// -----
+    /// Positive values mean the sender skipped time, for example after a
+    /// transmission pause; negative values mean audio was played out later
+    /// than it was captured.
+    #[getter]
+    fn timestamp_gap(&self) -> i64 {
+        self.timestamp_gap
+    }
+
+    /// RTP sequence number of the first packet in this chunk.
+    ///
+    /// Returns
+    /// -------
+    /// int | None
+    #[getter]
+    fn rtp_sequence(&self) -> Option<u16> {
+        self.rtp.map(|rtp| rtp.sequence)
+    }
+
+    /// RTP timestamp of the first packet in this chunk.
+    ///
+    /// Returns
+    /// -------
+    /// int | None
+    #[getter]
+    fn rtp_timestamp(&self) -> Option<u32> {
+        self.rtp.map(|rtp| rtp.timestamp)
+    }
+
+    /// Return a debug representation.
+    ///
+    /// Returns
+    /// -------
+    /// str
+    fn __repr__(&self) -> String {
+        format!(
+            "AudioChunk(key={:?}, tick={}, tick_count={}, filled_ticks={}, sequence_gaps={})",
+            self.key, self.tick, self.tick_count, self.filled_ticks, self.sequence_gaps
+        )
+    }
+}
+
+/// Chunk being assembled from consecutive ticks.
+struct Pending {
+    tick: u64,
+    timestamp: SystemTime,
+    pieces: Vec<ArrayRef>,
+    tick_count: u64,
+    filled_ticks: u64,
+    sequence_gaps: u64,
+    timestamp_gap: i64,
+    rtp: Option<RtpInfo>,
+}
+
+/// Reassembles one source's rows from a tick stream into contiguous chunks.
+pub(crate) struct UserChunker {
+    key: VoiceKey,
+    fill_silence: bool,
+    ticks_per_chunk: u64,
+    /// Samples per tick and sample type, learned from the first PCM seen.
+    layout: Option<(usize, DataType)>,
+    last_tick: Option<u64>,
+    last_rtp: Option<(u64, RtpInfo)>,
+    pending: Option<Pending>,
+}
+
+impl UserChunker {
+    pub(crate) fn new(key: VoiceKey, fill_silence: bool, chunk_ms: u64) -> PyResult<Self> {
+        let tick_ms = TICK_DURATION.as_millis() as u64;
+        if chunk_ms == 0 || !chunk_ms.is_multiple_of(tick_ms) {
+            return Err(PyValueError::new_err(format!(
+                "chunk_ms must be a positive multiple of {tick_ms}"
+            )));
+        }
+        Ok(Self {
+            key,
+            fill_silence,
+            ticks_per_chunk: chunk_ms / tick_ms,
+            layout: None,
+            last_tick: None,
+            last_rtp: None,
+            pending: None,
+        })
+    }
+
+    /// Feed the next tick, returning any chunks it completed.
+    pub(crate) fn push(&mut self, batch: &VoiceTickBatch) -> Vec<AudioChunk> {
+        let mut chunks = Vec::new();
+        let tick = batch.tick();
+
+        // Ticks the sink never delivered, e.g. dropped from a full buffer or
+        // skipped by a lagging stream.
+        if let Some(last) = self.last_tick {
+            for missing in last + 1..tick {
+                let timestamp = batch.timestamp() - TICK_DURATION * (tick - missing) as u32;
+                self.push_silence(missing, timestamp, &mut chunks);
+            }
+        }
+        self.last_tick = Some(tick);
+
+        match batch.get_array_ref(&self.key) {
+            Some(pcm) => {
+                self.layout
+                    .get_or_insert_with(|| (pcm.len(), pcm.data_type().clone()));
+                let rtp = batch.rtp(&self.key);
+                let (sequence_gaps, timestamp_gap) = self.track_rtp(tick, rtp);
+                let pending = self.pending(tick, batch.timestamp());
+                pending.pieces.push(pcm);
+                pending.sequence_gaps += sequence_gaps;
+                pending.timestamp_gap += timestamp_gap;
+                pending.rtp = pending.rtp.or(rtp);
+                self.advance(&mut chunks);
+            }
+            None => self.push_silence(tick, batch.timestamp(), &mut chunks),
+        }
+        chunks
+    }
+
+    /// Emit whatever has been assembled so far.
+    pub(crate) fn finish(&mut self) -> Option<AudioChunk> {
+        let pending = self.pending.take()?;
+        Some(AudioChunk {
+            key: self.key.clone(),
+            pcm: concat_pcm(&pending.pieces),
+            tick: pending.tick,
+            tick_count: pending.tick_count,
+            timestamp: pending.timestamp,
+            filled_ticks: pending.filled_ticks,
+            sequence_gaps: pending.sequence_gaps,
+            timestamp_gap: pending.timestamp_gap,
+            rtp: pending.rtp,
+        })
+    }
+
+    fn push_silence(&mut self, tick: u64, timestamp: SystemTime, chunks: &mut Vec<AudioChunk>) {
+        match &self.layout {
+            Some((samples, data_type)) if self.fill_silence => {
+                let silence = silence(data_type, *samples);
+                let pending = self.pending(tick, timestamp);
+                pending.pieces.push(silence);
+                pending.filled_ticks += 1;
+                self.advance(chunks);
+            }
+            // Without filling, a silent tick ends the contiguous run.
+            _ => chunks.extend(self.finish()),
+        }
+    }
+
+    fn pending(&mut self, tick: u64, timestamp: SystemTime) -> &mut Pending {
+        self.pending.get_or_insert_with(|| Pending {
+            tick,
+            timestamp,
+            pieces: Vec::new(),
+            tick_count: 0,
+            filled_ticks: 0,
+            sequence_gaps: 0,
+            timestamp_gap: 0,
+            rtp: None,
+        })
+    }
+
+    fn advance(&mut self, chunks: &mut Vec<AudioChunk>) {
+        let pending = self.pending.as_mut().expect("a tick was just pushed");
+        pending.tick_count += 1;
+        if pending.tick_count == self.ticks_per_chunk {
+            chunks.extend(self.finish());
+        }
+    }
+
+    /// Compare `rtp` with the previous packet from this source, returning
+    /// the number of missing sequence numbers and the RTP clock divergence.
+    fn track_rtp(&mut self, tick: u64, rtp: Option<RtpInfo>) -> (u64, i64) {
+        let Some(rtp) = rtp else {
+            return (0, 0);
+        };
+        let Some((last_tick, last)) = self.last_rtp.replace((tick, rtp)) else {
+            return (0, 0);
+        };
+        let step = rtp.sequence.wrapping_sub(last.sequence);
+        // Larger steps are reordering or a stream reset rather than loss.
+        if step == 0 || step >= u16::MAX / 2 {
+            return (0, 0);
+        }
+        let elapsed = rtp.timestamp.wrapping_sub(last.timestamp) as i32 as i64;
+        let expected = (tick - last_tick) as i64 * RTP_TICK_SAMPLES;
+        (u64::from(step - 1), elapsed - expected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::AsArray;
+    use arrow::datatypes::Int16Type;
+    use std::sync::Arc;
+
+    const KEY: VoiceKey = VoiceKey::User(7);
+
+    fn batch(tick: u64, pcm: Option<&[i16]>, rtp: Option<(u16, u32)>) -> Arc<VoiceTickBatch> {
+        VoiceTickBatch::from_test_row(
+            tick,
+            KEY,
+            pcm,
+            rtp.map(|(sequence, timestamp)| RtpInfo {
+                sequence,
+                timestamp,
+            }),
+            0.0,
+        )
+    }
+
+    fn values(chunk: &AudioChunk) -> Vec<i16> {
+        chunk.pcm.as_primitive::<Int16Type>().values().to_vec()
+    }
+
+    #[test]
+    fn fills_silent_and_missing_ticks() {
+        let mut chunker = UserChunker::new(KEY, true, 80).unwrap();
+        assert!(chunker.push(&batch(0, None, None)).is_empty());
+        assert!(chunker.push(&batch(1, Some(&[1, 1]), None)).is_empty());
+        assert!(chunker.push(&batch(2, None, None)).is_empty());
+        // Tick 3 was never delivered.
+        let chunks = chunker.push(&batch(4, Some(&[2, 2]), None));
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].tick, 1);
+        assert_eq!(chunks[0].tick_count, 4);
+        assert_eq!(chunks[0].filled_ticks, 2);
+        assert_eq!(values(&chunks[0]), [1, 1, 0, 0, 0, 0, 2, 2]);
+        assert!(chunker.finish().is_none());
+    }
+
+    #[test]
+    fn without_filling_silence_splits_chunks() {
+        let mut chunker = UserChunker::new(KEY, false, 100).unwrap();
+        chunker.push(&batch(0, Some(&[1, 1]), None));
+        chunker.push(&batch(1, Some(&[2, 2]), None));
+        let chunks = chunker.push(&batch(2, None, None));
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(values(&chunks[0]), [1, 1, 2, 2]);
+        chunker.push(&batch(5, Some(&[3, 3]), None));
+        let last = chunker.finish().unwrap();
+        assert_eq!((last.tick, last.tick_count), (5, 1));
+    }
+
+    #[test]
+    fn reports_rtp_sequence_and_timestamp_gaps() {
+        let mut chunker = UserChunker::new(KEY, true, 60).unwrap();
+        chunker.push(&batch(0, Some(&[1]), Some((100, 0))));
+        chunker.push(&batch(1, Some(&[1]), Some((103, 960))));
+        let chunks = chunker.push(&batch(2, Some(&[1]), Some((104, 960 * 5))));
+        assert_eq!(chunks[0].sequence_gaps, 2);
+        assert_eq!(chunks[0].timestamp_gap, 960 * 3);
+        assert_eq!(chunks[0].rtp.map(|rtp| rtp.sequence), Some(100));
+    }
+
+    #[test]
+    fn chunk_length_must_be_whole_ticks() {
+        assert!(UserChunker::new(KEY, true, 0).is_err());
+        assert!(UserChunker::new(KEY, true, 30).is_err());
+        assert!(UserChunker::new(KEY, true, 40).is_ok());
+    }
+}
@@ -0,0 +1,295 @@
+use crate::model::{ArrowArray, PcmArray};
+use crate::receive::format::{TICK_DURATION, concat_pcm, silence};
+use crate::receive::tick::{VoiceKey, VoiceTickBatch};
+use arrow::array::{Array, ArrayRef};
+use arrow::datatypes::DataType;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::{IntoPyObjectExt, Py, PyAny, PyResult, Python};
+use pyo3_arrow::PyArray;
+use std::collections::{HashMap, VecDeque};
+use std::time::SystemTime;
+
+/// `(key, start, end, pcm)` as yielded by `utterances()`.
+pub(crate) type UtteranceTuple<'py> = (VoiceKey, SystemTime, SystemTime, ArrowArray<'py, PcmArray>);
+
+/// A finished speech segment from one source.
+pub(crate) struct Utterance {
+    pub key: VoiceKey,
+    pub start: SystemTime,
+    pub end: SystemTime,
+    pub pcm: ArrayRef,
+}
+
+impl Utterance {
+    /// Convert to the `(key, start, end, pcm)` tuple yielded to Python.
+    pub(crate) fn into_py_any(self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let pcm = PyArray::from_array_ref(self.pcm).into_arro3(py)?;
+        (self.key, self.start, self.end, pcm).into_py_any(py)
+    }
+}
+
+/// Reject `utterances()` on a sink that does not score voice activity.
+pub(crate) fn require_vad(enabled: bool) -> PyResult<()> {
+    if enabled {
+        Ok(())
+    } else {
+        Err(PyRuntimeError::new_err(
+            "utterances() requires a sink created with vad=True",
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct UtteranceSettings {
+    threshold: f32,
+    pre_ticks: usize,
+    post_ticks: u64,
+    min_ticks: u64,
+}
+
+impl UtteranceSettings {
+    pub(crate) fn new(
+        threshold: f32,
+        pre_padding_ms: u64,
+        post_padding_ms: u64,
+        min_duration_ms: u64,
+    ) -> PyResult<Self> {
+        if !(0.0..=1.0).contains(&threshold) {
+            return Err(PyValueError::new_err("threshold must be between 0 and 1"));
+        }
+        let tick_ms = TICK_DURATION.as_millis() as u64;
+        Ok(Self {
+            threshold,
+            pre_ticks: pre_padding_ms.div_ceil(tick_ms) as usize,
+            // At least one quiet tick is needed to notice speech has stopped.
+            post_ticks: post_padding_ms.div_ceil(tick_ms).max(1),
+            min_ticks: min_duration_ms.div_ceil(tick_ms),
+        })
+    }
+}
+
+/// Splits every source in a tick stream into utterances using the `vad`
+/// column.
+pub(crate) struct Segmenter {
+    settings: UtteranceSettings,
+    sources: HashMap<VoiceKey, Source>,
+    last_tick: Option<u64>,
+}
+
+#[derive(Default)]
+struct Source {
+    /// Samples per tick and sample type, learned from the first PCM seen.
+    layout: Option<(usize, DataType)>,
+    /// Recent quiet ticks, kept as pre-padding for the next utterance.
+    history: VecDeque<(SystemTime, ArrayRef)>,
+    active: Option<Active>,
+}
+
+struct Active {
+    start: SystemTime,
+    end: SystemTime,
+    pieces: Vec<ArrayRef>,
+    /// Ticks from the onset to the latest voiced tick.
+    voiced_ticks: u64,
+    /// Quiet ticks since the latest voiced tick.
+    quiet_ticks: u64,
+}
+
+impl Segmenter {
+    pub(crate) fn new(settings: UtteranceSettings) -> Self {
+        Self {
+            settings,
+            sources: HashMap::new(),
+            last_tick: None,
+        }
+    }
+
+    /// Feed the next tick, returning any utterances it completed.
+    pub(crate) fn push(&mut self, batch: &VoiceTickBatch) -> Vec<Utterance> {
+        let mut utterances = Vec::new();
+        let tick = batch.tick();
+
+        // Ticks the sink never delivered count as quiet for every source.
+        if let Some(last) = self.last_tick {
+            for missing in last + 1..tick {
+                let timestamp = batch.timestamp() - TICK_DURATION * (tick - missing) as u32;
+                for (key, source) in self.sources.iter_mut() {
+                    utterances.extend(source.push(key, &self.settings, timestamp, None, 0.0));
+                }
+            }
+        }
+        self.last_tick = Some(tick);
+
+        for key in batch.speaking_keys() {
+            self.sources.entry(key).or_default();
+        }
+        for (key, source) in self.sources.iter_mut() {
+            let pcm = batch.get_array_ref(key);
+            let vad = batch.vad(key).unwrap_or(0.0);
+            utterances.extend(source.push(key, &self.settings, batch.timestamp(), pcm, vad));
+        }
+        // Forget sources that left the call while quiet.
+        self.sources
+            .retain(|key, source| source.active.is_some() || batch.vad(key).is_some());
+        utterances
+    }
+
+    /// End every utterance in progress.
+    pub(crate) fn finish(&mut self) -> Vec<Utterance> {
+        let settings = self.settings;
+        self.sources
+            .iter_mut()
+            .filter_map(|(key, source)| source.finish(key, &settings))
+            .collect()
+    }
+}
+
+impl Source {
+    fn push(
+        &mut self,
+        key: &VoiceKey,
+        settings: &UtteranceSettings,
+        timestamp: SystemTime,
+        pcm: Option<ArrayRef>,
+        vad: f32,
+    ) -> Option<Utterance> {
+        if let Some(pcm) = &pcm {
+            self.layout
+                .get_or_insert_with(|| (pcm.len(), pcm.data_type().clone()));
+        }
+        let voiced = pcm.is_some() && vad >= settings.threshold;
+        let frame = pcm.or_else(|| {
+            let (samples, data_type) = self.layout.as_ref()?;
+            Some(silence(data_type, *samples))
+        })?;
+
+        let Some(active) = &mut self.active else {
+            if voiced {
+                let start = self.history.front().map_or(timestamp, |(start, _)| *start);
+                let mut pieces: Vec<_> = self.history.drain(..).map(|(_, pcm)| pcm).collect();
+                pieces.push(frame);
+                self.active = Some(Active {
+                    start,
+                    end: timestamp + TICK_DURATION,
+                    pieces,
+                    voiced_ticks: 1,
+                    quiet_ticks: 0,
+                });
+            } else if settings.pre_ticks > 0 {
+                if self.history.len() == settings.pre_ticks {
+                    self.history.pop_front();
+                }
+                self.history.push_back((timestamp, frame));
+            }
+            return None;
+        };
+
+        active.pieces.push(frame);
+        active.end = timestamp + TICK_DURATION;
+        if voiced {
+            active.voiced_ticks += active.quiet_ticks + 1;
+            active.quiet_ticks = 0;
+            None
+        } else {
+            active.quiet_ticks += 1;
+            if active.quiet_ticks >= settings.post_ticks {
+                self.finish(key, settings)
+            } else {
+                None
+            }
+        }
+    }
+
+    fn finish(&mut self, key: &VoiceKey, settings: &UtteranceSettings) -> Option<Utterance> {
+        let active = self.active.take()?;
+        (active.voiced_ticks >= settings.min_ticks).then(|| Utterance {
+            key: key.clone(),
+            start: active.start,
+            end: active.end,
+            pcm: concat_pcm(&active.pieces),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::AsArray;
+    use arrow::datatypes::Int16Type;
+    use std::sync::Arc;
+
+    #[test]
+    fn utterances_require_vad() {
+        assert!(require_vad(true).is_ok());
+        assert!(require_vad(false).is_err());
+    }
+
+    const KEY: VoiceKey = VoiceKey::User(7);
+
+    fn feed(segmenter: &mut Segmenter, ticks: &[(u64, Option<i16>, f32)]) -> Vec<Utterance> {
+        let mut utterances = Vec::new();
+        for &(tick, sample, vad) in ticks {
+            let pcm = sample.map(|sample| [sample; 2]);
+            let batch: Arc<VoiceTickBatch> = VoiceTickBatch::from_test_row(
+                tick,
+                KEY,
+                pcm.as_ref().map(|pcm| &pcm[..]),
+                None,
+                vad,
+            );
+            utterances.extend(segmenter.push(&batch));
+        }
+        utterances
+    }
+
+    fn values(utterance: &Utterance) -> Vec<i16> {
+        utterance.pcm.as_primitive::<Int16Type>().values().to_vec()
+    }
+
+    #[test]
+    fn pads_utterances_on_both_sides() {
+        let settings = UtteranceSettings::new(0.5, 40, 40, 0).unwrap();
+        let mut segmenter = Segmenter::new(settings);
+        let utterances = feed(
+            &mut segmenter,
+            &[
+                (0, Some(1), 0.1),
+                (1, Some(2), 0.1),
+                (2, Some(3), 0.1),
+                (3, Some(4), 0.9),
+                (4, Some(5), 0.2),
+                (5, None, 0.0),
+                (6, Some(6), 0.1),
+            ],
+        );
+        assert_eq!(utterances.len(), 1);
+        assert_eq!(values(&utterances[0]), [2, 2, 3, 3, 4, 4, 5, 5, 0, 0]);
+        assert!(utterances[0].end > utterances[0].start);
+    }
+
+    #[test]
+    fn short_bursts_are_dropped() {
+        let settings = UtteranceSettings::new(0.5, 0, 40, 60).unwrap();
+        let mut segmenter = Segmenter::new(settings);
+        let ticks = [
+            (0, Some(1), 0.9),
+            (1, Some(1), 0.9),
+            (2, Some(1), 0.0),
+            (3, Some(1), 0.0),
+            // A one-tick pause does not split the next utterance.
+            (4, Some(1), 0.9),
+            (5, Some(1), 0.0),
+            (6, Some(1), 0.9),
+        ];
+        assert!(feed(&mut segmenter, &ticks).is_empty());
+        let utterances = segmenter.finish();
+        assert_eq!(utterances.len(), 1);
+        assert_eq!(utterances[0].key, KEY);
+    }
+
+    #[test]
+    fn settings_are_validated() {
+        assert!(UtteranceSettings::new(1.5, 0, 0, 0).is_err());
+        assert_eq!(UtteranceSettings::new(0.5, 30, 0, 10).unwrap().pre_ticks, 2);
+    }
+}
@@ -0,0 +1,205 @@
+use crate::receive::format::DECODED_CHANNELS;
+use crate::receive::tick::VoiceKey;
+use realfft::num_complex::Complex;
+use realfft::{RealFftPlanner, RealToComplex};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+/// One 20 ms tick of decoded audio.
+const FRAME_LEN: usize = 960;
+const BIN_HZ: f32 = 48_000.0 / FRAME_LEN as f32;
+/// Band holding most of the energy of voiced speech.
+const SPEECH_BAND_HZ: (f32, f32) = (250.0, 4_000.0);
+/// Frames quieter than this are never treated as speech.
+const ABSOLUTE_FLOOR_DB: f32 = -55.0;
+const INITIAL_NOISE_FLOOR_DB: f32 = -60.0;
+/// Noise floor creeps up by this much per frame and drops immediately, so it
+/// follows the quietest recent frames.
+const NOISE_FLOOR_RISE_DB: f32 = 0.02;
+/// Signal-to-noise ratio beyond which louder frames are no more speech-like.
+const MAX_SNR_DB: f32 = 20.0;
+/// Weight of the previous frame's probability.
+const SMOOTHING: f32 = 0.3;
+
+/// Energy and spectral-shape voice activity detector.
+///
+/// Each source keeps its own adaptive noise floor. A frame scores highly when
+/// it is loud relative to that floor, concentrates its energy in the speech
+/// band, and has a peaky (harmonic) rather than flat (noise-like) spectrum.
+pub struct VoiceActivityDetector {
+    fft: Arc<dyn RealToComplex<f32>>,
+    window: Vec<f32>,
+    input: Vec<f32>,
+    spectrum: Vec<Complex<f32>>,
+    states: HashMap<VoiceKey, SourceState>,
+}
+
+struct SourceState {
+    noise_floor_db: f32,
+    probability: f32,
+}
+
+impl Default for VoiceActivityDetector {
+    fn default() -> Self {
+        let fft = RealFftPlanner::<f32>::new().plan_fft_forward(FRAME_LEN);
+        let window = (0..FRAME_LEN)
+            .map(|n| 0.5 - 0.5 * (2.0 * PI * n as f32 / FRAME_LEN as f32).cos())
+            .collect();
+        Self {
+            input: fft.make_input_vec(),
+            spectrum: fft.make_output_vec(),
+            fft,
+            window,
+            states: HashMap::new(),
+        }
+    }
+}
+
+impl VoiceActivityDetector {
+    /// Speech probability of one tick of decoded stereo PCM from `key`.
+    pub fn detect(&mut self, key: &VoiceKey, pcm: &[i16]) -> f32 {
+        let frames = pcm.chunks_exact(DECODED_CHANNELS).take(FRAME_LEN);
+        let frame_count = frames.len();
+        self.input.fill(0.0);
+        for (sample, frame) in self.input.iter_mut().zip(frames) {
+            *sample =
+                frame.iter().map(|&s| s as f32).sum::<f32>() / (DECODED_CHANNELS as f32 * 32_768.0);
+        }
+        let energy = self.input[..frame_count].iter().map(|s| s * s).sum::<f32>()
+            / frame_count.max(1) as f32;
+        let energy_db = 10.0 * (energy + 1e-10).log10();
+
+        for (sample, weight) in self.input.iter_mut().zip(&self.window) {
+            *sample *= weight;
+        }
+        self.fft
+            .process(&mut self.input, &mut self.spectrum)
+            .expect("buffers come from the planned FFT");
+        let (band_ratio, flatness) = spectral_shape(&self.spectrum);
+
+        let state = self.states.entry(key.clone()).or_insert(SourceState {
+            noise_floor_db: INITIAL_NOISE_FLOOR_DB,
+            probability: 0.0,
+        });
+        state.noise_floor_db = if energy_db < state.noise_floor_db {
+            energy_db
+        } else {
+            state.noise_floor_db + NOISE_FLOOR_RISE_DB
+        };
+        let snr_db = (energy_db - state.noise_floor_db).min(MAX_SNR_DB);
+
+        let raw = if energy_db < ABSOLUTE_FLOOR_DB {
+            0.0
+        } else {
+            sigmoid(0.25 * (snr_db - 10.0) + 8.0 * (band_ratio - 0.5) + 6.0 * (0.3 - flatness))
+        };
+        state.probability = SMOOTHING * state.probability + (1.0 - SMOOTHING) * raw;
+        state.probability
+    }
+
+    /// Note that `key` sent no audio this tick.
+    pub fn silence(&mut self, key: &VoiceKey) {
+        if let Some(state) = self.states.get_mut(key) {
+            state.probability = 0.0;
+        }
+    }
+
+    /// Drop state for sources no longer present in the call.
+    pub fn retain(&mut self, mut present: impl FnMut(&VoiceKey) -> bool) {
+        self.states.retain(|key, _| present(key));
+    }
+}
+
+/// Share of power inside the speech band, and the spectral flatness
+/// (geometric over arithmetic mean) within it.
+fn spectral_shape(spectrum: &[Complex<f32>]) -> (f32, f32) {
+    let low = (SPEECH_BAND_HZ.0 / BIN_HZ) as usize;
+    let high = (SPEECH_BAND_HZ.1 / BIN_HZ) as usize;
+    let power = |bin: &Complex<f32>| bin.norm_sqr() + 1e-12;
+    let total: f32 = spectrum.iter().skip(1).map(power).sum();
+    let band = &spectrum[low..=high];
+    let band_power: f32 = band.iter().map(power).sum();
+    let log_mean = band.iter().map(|bin| power(bin).ln()).sum::<f32>() / band.len() as f32;
+    let flatness = log_mean.exp() / (band_power / band.len() as f32);
+    (band_power / total, flatness)
+}
+
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+#[cfg(test)]
+fn stereo(samples: impl Iterator<Item = f32>) -> Vec<i16> {
+    samples
+        .take(FRAME_LEN)
+        .flat_map(|s| {
+            let s = (s * 32_768.0) as i16;
+            [s, s]
+        })
+        .collect()
+}
+
+/// One tick of a harmonic series on a 150 Hz fundamental, like a voiced
+/// vowel, starting `offset` frames in.
+#[cfg(test)]
+pub(crate) fn test_voiced(amplitude: f32, offset: usize) -> Vec<i16> {
+    stereo((offset..).map(|n| {
+        let t = n as f32 / 48_000.0;
+        (1..=12)
+            .map(|h| (2.0 * PI * 150.0 * h as f32 * t).sin() / h as f32)
+            .sum::<f32>()
+            * amplitude
+            / 3.0
+    }))
+}
+
+/// One tick of white noise.
+#[cfg(test)]
+pub(crate) fn test_noise(amplitude: f32, seed: &mut u32) -> Vec<i16> {
+    stereo(std::iter::repeat_with(|| {
+        *seed ^= *seed << 13;
+        *seed ^= *seed >> 17;
+        *seed ^= *seed << 5;
+        (*seed as f32 / u32::MAX as f32 * 2.0 - 1.0) * amplitude
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: VoiceKey = VoiceKey::User(1);
+
+    #[test]
+    fn voiced_audio_scores_as_speech() {
+        let mut vad = VoiceActivityDetector::default();
+        let mut probability = 0.0;
+        for tick in 0..5 {
+            probability = vad.detect(&KEY, &test_voiced(0.1, tick * FRAME_LEN));
+        }
+        assert!(probability > 0.9, "{probability}");
+    }
+
+    #[test]
+    fn noise_and_silence_do_not() {
+        let mut vad = VoiceActivityDetector::default();
+        let mut seed = 7;
+        for _ in 0..5 {
+            assert!(vad.detect(&KEY, &test_noise(0.1, &mut seed)) < 0.5);
+        }
+        let silent = VoiceKey::User(2);
+        assert_eq!(vad.detect(&silent, &[0; FRAME_LEN * 2]), 0.0);
+    }
+
+    #[test]
+    fn sources_are_tracked_independently() {
+        let mut vad = VoiceActivityDetector::default();
+        vad.detect(&KEY, &test_voiced(0.1, 0));
+        vad.detect(&VoiceKey::User(2), &test_voiced(0.1, 0));
+        vad.retain(|key| *key == KEY);
+        assert_eq!(vad.states.len(), 1);
+        vad.silence(&KEY);
+        assert_eq!(vad.states[&KEY].probability, 0.0);
+    }
+}
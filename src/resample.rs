@@ -0,0 +1,238 @@
+use pyo3::PyResult;
+use pyo3::exceptions::PyValueError;
+use rubato::{FftFixedIn, Resampler};
+
+const CHUNK_FRAMES: usize = 1024;
+const SUB_CHUNKS: usize = 2;
+
+/// Resample interleaved f32 PCM from `from` Hz to `to` Hz.
+///
+/// The output has `ceil(frames * to / from)` frames and the resampler's
+/// group delay is trimmed, so the result lines up with the input.
+pub fn resample_interleaved(
+    samples: &[f32],
+    channels: usize,
+    from: u32,
+    to: u32,
+) -> PyResult<Vec<f32>> {
+    if channels == 0 {
+        return Err(PyValueError::new_err("channels must be greater than 0"));
+    }
+    if from == 0 || to == 0 {
+        return Err(PyValueError::new_err("sample_rate must be greater than 0"));
+    }
+    if !samples.len().is_multiple_of(channels) {
+        return Err(PyValueError::new_err(
+            "Sample count must be a multiple of the channel count",
+        ));
+    }
+    if from == to || samples.is_empty() {
+        return Ok(samples.to_vec());
+    }
+
+    let frames = samples.len() / channels;
+    let expected = (frames as u64 * to as u64).div_ceil(from as u64) as usize;
+    let input = deinterleave(samples, channels);
+
+    let mut resampler = FftFixedIn::<f32>::new(
+        from as usize,
+        to as usize,
+        CHUNK_FRAMES,
+        SUB_CHUNKS,
+        channels,
+    )
+    .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let delay = resampler.output_delay();
+    let mut output = vec![Vec::with_capacity(expected + delay); channels];
+    let mut buffer = resampler.output_buffer_allocate(true);
+
+    let mut offset = 0;
+    while offset < frames || output[0].len() < expected + delay {
+        let needed = resampler.input_frames_next();
+        let (_, written) = if offset + needed <= frames {
+            let chunk: Vec<&[f32]> = input.iter().map(|c| &c[offset..offset + needed]).collect();
+            resampler.process_into_buffer(&chunk, &mut buffer, None)
+        } else if offset < frames {
+            let chunk: Vec<&[f32]> = input.iter().map(|c| &c[offset..]).collect();
+            resampler.process_partial_into_buffer(Some(&chunk), &mut buffer, None)
+        } else {
+            resampler.process_partial_into_buffer(None::<&[&[f32]]>, &mut buffer, None)
+        }
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        offset += needed;
+        for (out, chunk) in output.iter_mut().zip(buffer.iter()) {
+            out.extend_from_slice(&chunk[..written]);
+        }
+    }
+
+    for channel in output.iter_mut() {
+        channel.drain(..delay);
+        channel.truncate(expected);
+    }
+    Ok(interleave(&output))
+}
+
+/// Incremental resampler for interleaved f32 PCM.
+///
+/// Input can be pushed in arbitrarily sized pieces. Output is delayed by at
+/// most one internal chunk; call `flush` at end of input to drain the tail.
+pub struct StreamResampler {
+    inner: FftFixedIn<f32>,
+    channels: usize,
+    ratio: (u64, u64),
+    pending: Vec<Vec<f32>>,
+    buffer: Vec<Vec<f32>>,
+    skip: usize,
+    frames_in: u64,
+    frames_out: u64,
+}
+
+impl StreamResampler {
+    pub fn new(channels: usize, from: u32, to: u32) -> PyResult<Self> {
+        if channels == 0 {
+            return Err(PyValueError::new_err("channels must be greater than 0"));
+        }
+        if from == 0 || to == 0 {
+            return Err(PyValueError::new_err("sample_rate must be greater than 0"));
+        }
+        let inner = FftFixedIn::<f32>::new(
+            from as usize,
+            to as usize,
+            CHUNK_FRAMES,
+            SUB_CHUNKS,
+            channels,
+        )
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Self {
+            skip: inner.output_delay(),
+            buffer: inner.output_buffer_allocate(true),
+            inner,
+            channels,
+            ratio: (from as u64, to as u64),
+            pending: vec![Vec::new(); channels],
+            frames_in: 0,
+            frames_out: 0,
+        })
+    }
+
+    /// Push interleaved samples and return any resampled output.
+    pub fn push(&mut self, samples: &[f32]) -> Vec<f32> {
+        for (index, sample) in samples.iter().enumerate() {
+            self.pending[index % self.channels].push(*sample);
+        }
+        self.frames_in += (samples.len() / self.channels) as u64;
+
+        let mut output = vec![Vec::new(); self.channels];
+        while self.pending[0].len() >= self.inner.input_frames_next() {
+            let needed = self.inner.input_frames_next();
+            let chunk: Vec<&[f32]> = self.pending.iter().map(|c| &c[..needed]).collect();
+            let (_, written) = self
+                .inner
+                .process_into_buffer(&chunk, &mut self.buffer, None)
+                .expect("resampler buffers are sized by input_frames_next");
+            for channel in self.pending.iter_mut() {
+                channel.drain(..needed);
+            }
+            self.collect(written, &mut output);
+        }
+        interleave(&output)
+    }
+
+    /// Drain buffered input, returning the remaining resampled output.
+    pub fn flush(&mut self) -> Vec<f32> {
+        let expected = (self.frames_in * self.ratio.1).div_ceil(self.ratio.0);
+        let mut output = vec![Vec::new(); self.channels];
+        while self.frames_out < expected {
+            let pending = std::mem::replace(&mut self.pending, vec![Vec::new(); self.channels]);
+            let (_, written) = if pending[0].is_empty() {
+                self.inner
+                    .process_partial_into_buffer(None::<&[&[f32]]>, &mut self.buffer, None)
+            } else {
+                self.inner
+                    .process_partial_into_buffer(Some(&pending), &mut self.buffer, None)
+            }
+            .expect("resampler buffers are sized by input_frames_next");
+            let written = written.min(((expected - self.frames_out) as usize) + self.skip);
+            self.collect(written, &mut output);
+        }
+        interleave(&output)
+    }
+
+    fn collect(&mut self, written: usize, output: &mut [Vec<f32>]) {
+        let skipped = self.skip.min(written);
+        self.skip -= skipped;
+        for (out, chunk) in output.iter_mut().zip(self.buffer.iter()) {
+            out.extend_from_slice(&chunk[skipped..written]);
+        }
+        self.frames_out += (written - skipped) as u64;
+    }
+}
+
+fn deinterleave(samples: &[f32], channels: usize) -> Vec<Vec<f32>> {
+    (0..channels)
+        .map(|c| samples.iter().skip(c).step_by(channels).copied().collect())
+        .collect()
+}
+
+fn interleave(channels: &[Vec<f32>]) -> Vec<f32> {
+    let frames = channels.first().map_or(0, Vec::len);
+    let mut samples = Vec::with_capacity(frames * channels.len());
+    for frame in 0..frames {
+        samples.extend(channels.iter().map(|c| c[frame]));
+    }
+    samples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matching_rates_pass_samples_through() {
+        let samples = vec![0.1, -0.1, 0.2, -0.2];
+        assert_eq!(
+            resample_interleaved(&samples, 2, 48_000, 48_000).unwrap(),
+            samples
+        );
+    }
+
+    #[test]
+    fn output_length_follows_rate_ratio() {
+        let samples = vec![0.0_f32; 44_100 * 2];
+        let output = resample_interleaved(&samples, 2, 44_100, 48_000).unwrap();
+        assert_eq!(output.len(), 48_000 * 2);
+
+        let samples = vec![0.0_f32; 1_000];
+        let output = resample_interleaved(&samples, 1, 48_000, 16_000).unwrap();
+        assert_eq!(output.len(), 334);
+    }
+
+    #[test]
+    fn constant_signal_survives_resampling() {
+        let samples = vec![0.5_f32; 22_050];
+        let output = resample_interleaved(&samples, 1, 22_050, 48_000).unwrap();
+        let middle = &output[output.len() / 4..output.len() * 3 / 4];
+        assert!(middle.iter().all(|s| (s - 0.5).abs() < 0.01));
+    }
+
+    #[test]
+    fn streaming_output_matches_one_shot_length() {
+        let samples = vec![0.25_f32; 44_100 * 2];
+        let mut resampler = StreamResampler::new(2, 44_100, 48_000).unwrap();
+        let mut output = Vec::new();
+        for chunk in samples.chunks(1_764) {
+            output.extend(resampler.push(chunk));
+        }
+        output.extend(resampler.flush());
+        assert_eq!(output.len(), 48_000 * 2);
+        let middle = &output[output.len() / 4..output.len() * 3 / 4];
+        assert!(middle.iter().all(|s| (s - 0.25).abs() < 0.01));
+    }
+
+    #[test]
+    fn invalid_layouts_are_rejected() {
+        assert!(resample_interleaved(&[0.0; 3], 2, 44_100, 48_000).is_err());
+        assert!(resample_interleaved(&[0.0; 2], 0, 44_100, 48_000).is_err());
+        assert!(resample_interleaved(&[0.0; 2], 1, 0, 48_000).is_err());
+    }
+}